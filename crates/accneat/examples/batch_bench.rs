@@ -0,0 +1,166 @@
+//! Times `evaluate_batch` against `evaluate_grouped` on populations like those
+//! of prune phases and weight mutation: a few topologies, each shared by many
+//! networks with their own weights. The topologies are random, or those of the
+//! champions of `-s phased` runs, one run per topology.
+//!
+//!     cargo run --release --example batch_bench [networks] [topologies]
+
+use std::time::{Duration, Instant};
+
+use accneat::batch::{evaluate_batch, evaluate_grouped, phenotypes};
+use accneat::experiments::sequence::SequenceExperiment;
+use accneat::experiments::xor;
+use accneat::genome::{parse_fittest_file, ParsedOrganism};
+use accneat::network::{ActivationFunction, CpuNetwork, NetDims, NetLink, NetNode, OrganismEvaluation};
+use accneat::rng::Rng;
+use accneat::static_evaluator::{Scoring, Test};
+use accneat::{execute_in, AccNeatArgs, SearchType};
+
+const NHIDDEN: usize = 4;
+const REPEATS: usize = 5;
+const RUN_POP_SIZE: usize = 1000;
+const RUN_MAX_GENS: usize = 20;
+
+/// Links of a random recurrent topology, as (in, out) node indices.
+fn random_topology(rng: &mut Rng, dims: &NetDims) -> Vec<(usize, usize)> {
+    let mut links = vec![];
+    for out in dims.nnodes.input..dims.nnodes.all {
+        for inp in 0..dims.nnodes.all {
+            if rng.boolean() {
+                links.push((inp, out));
+            }
+        }
+    }
+    links
+}
+
+fn population(tests: &[Test], nnets: usize, ntopologies: usize) -> Vec<CpuNetwork> {
+    let mut rng = Rng::new(1);
+    let mut dims = NetDims::default();
+    dims.nnodes.bias = 1;
+    dims.nnodes.sensor = tests[0].steps[0].input.len();
+    dims.nnodes.output = tests[0].steps[0].output.len();
+    dims.nnodes.hidden = NHIDDEN;
+    dims.nnodes.input = dims.nnodes.bias + dims.nnodes.sensor;
+    dims.nnodes.noninput = dims.nnodes.output + dims.nnodes.hidden;
+    dims.nnodes.all = dims.nnodes.input + dims.nnodes.noninput;
+
+    let topologies: Vec<Vec<(usize, usize)>> = (0..ntopologies).map(|_| random_topology(&mut rng, &dims)).collect();
+    (0..nnets)
+        .map(|i| {
+            let topology = &topologies[i % ntopologies];
+            let mut nodes = vec![];
+            let mut links = vec![];
+            for out in 0..dims.nnodes.all {
+                let incoming_start = links.len();
+                for &(in_node_index, _) in topology.iter().filter(|l| l.1 == out) {
+                    links.push(NetLink {
+                        weight: 6.0 * rng.prob() - 3.0,
+                        in_node_index,
+                        out_node_index: out,
+                        hebb_rate: 0.0,
+                        pre_rate: 0.0,
+                        post_rate: 0.0,
+                    });
+                }
+                nodes.push(NetNode { incoming_start, incoming_end: links.len(), function: ActivationFunction::Sigmoid });
+            }
+            CpuNetwork::configure(NetDims { nlinks: links.len(), ..dims }, nodes, links)
+        })
+        .collect()
+}
+
+/// Champions of `ntopologies` phased runs of `experiment`, with perturbed
+/// weights. Runs that stop early leave unconverged champions too.
+fn fittest_population(experiment: &str, nnets: usize, ntopologies: usize) -> Vec<CpuNetwork> {
+    let dir = std::env::temp_dir().join(format!("accneat-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let args = AccNeatArgs {
+        num_experiments: ntopologies,
+        force_delete: true,
+        pop_size: RUN_POP_SIZE,
+        maxgens: RUN_MAX_GENS,
+        search_type: SearchType::Phased,
+        experiment: experiment.to_string(),
+        ..Default::default()
+    };
+    execute_in(args, &dir).expect("cannot run accneat");
+
+    let mut champions = vec![];
+    for run in std::fs::read_dir(dir.join("experiments")).unwrap() {
+        for entry in std::fs::read_dir(run.unwrap().path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.file_name().unwrap().to_str().unwrap().starts_with("fittest_") {
+                champions.push(parse_fittest_file(&path).unwrap());
+            }
+        }
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(!champions.is_empty(), "no fittest files from {}", experiment);
+
+    let mut rng = Rng::new(1);
+    let genomes: Vec<ParsedOrganism> = (0..nnets)
+        .map(|i| {
+            let mut genome = champions[i % champions.len()].clone();
+            for gene in &mut genome.genes.0 {
+                gene.weight += 2.0 * rng.prob() - 1.0;
+            }
+            genome
+        })
+        .collect();
+    phenotypes(&genomes)
+}
+
+fn time<F>(mut f: F) -> (Duration, Vec<OrganismEvaluation>)
+where
+    F: FnMut() -> Vec<OrganismEvaluation>,
+{
+    let mut best = Duration::MAX;
+    let mut result = vec![];
+    for _ in 0..REPEATS {
+        let start = Instant::now();
+        result = f();
+        best = best.min(start.elapsed());
+    }
+    (best, result)
+}
+
+fn main() {
+    let args: Vec<usize> = std::env::args().skip(1).map(|a| a.parse().expect("usage: batch_bench [networks] [topologies]")).collect();
+    let nnets = args.first().copied().unwrap_or(1000);
+    let ntopologies = args.get(1).copied().unwrap_or(10);
+    let scoring = Scoring::default();
+    let experiments = [
+        ("xor", xor()),
+        ("seq-1bit-4el", SequenceExperiment { len: 4, ..Default::default() }.tests().unwrap()),
+    ];
+
+    let mut nthreads_list = vec![1, rayon::current_num_threads()];
+    nthreads_list.dedup();
+
+    println!("{} networks, {} topologies", nnets, ntopologies);
+    for (name, tests) in &experiments {
+        let populations = [
+            ("random", population(tests, nnets, ntopologies)),
+            ("fittest", fittest_population(name, nnets, ntopologies)),
+        ];
+        for ((kind, nets), &nthreads) in populations.iter().flat_map(|p| nthreads_list.iter().map(move |n| (p, n))) {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(nthreads).build().unwrap();
+            let (per_net, expected) = time(|| pool.install(|| evaluate_batch(&mut nets.clone(), tests, &scoring)));
+            let (grouped, actual) = time(|| pool.install(|| evaluate_grouped(&mut nets.clone(), tests, &scoring)));
+            assert!(
+                expected.iter().zip(&actual).all(|(e, a)| e.fitness.to_bits() == a.fitness.to_bits()),
+                "grouped evaluations differ"
+            );
+            println!(
+                "{:>13} {:>7} {:>2} threads: per-network {:>9.3?}, grouped {:>9.3?}, speedup {:.2}x",
+                name,
+                kind,
+                nthreads,
+                per_net,
+                grouped,
+                per_net.as_secs_f64() / grouped.as_secs_f64()
+            );
+        }
+    }
+}
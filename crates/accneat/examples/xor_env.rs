@@ -0,0 +1,22 @@
+//! Stand-in external environment: serves XOR over the `ext:` protocol on
+//! stdio, or on a TCP port with `--tcp <addr>`.
+//!
+//!     accneat 'ext:cmd:cargo run -q --example xor_env'
+//!     cargo run --example xor_env -- --tcp 127.0.0.1:4000 & accneat ext:tcp:127.0.0.1:4000
+
+use std::io::{stdin, stdout};
+use std::net::TcpListener;
+
+use accneat::external_env::{serve, serve_tcp, XorEnv, XOR_SPEC};
+
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => serve(&XOR_SPEC, XorEnv::default, stdin().lock(), stdout().lock()),
+        [flag, addr] if flag == "--tcp" => serve_tcp(&XOR_SPEC, XorEnv::default, &TcpListener::bind(addr)?),
+        _ => {
+            eprintln!("usage: xor_env [--tcp <addr>]");
+            std::process::exit(2);
+        }
+    }
+}
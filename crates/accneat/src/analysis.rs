@@ -0,0 +1,486 @@
+//! Aggregate statistics over many runs, e.g. different `rng_seed`s, search
+//! types or parameter sets, and significance tests between configurations.
+//!
+//! `EvaluatorExperiment::run` only prints n/min/max/mean; this reads each run's
+//! generation log and fittest file back and reports medians, percentiles,
+//! success-rate confidence intervals and a Mann–Whitney U test.
+
+use std::fmt::Formatter;
+use std::path::Path;
+
+use crate::generation_log::{self, read_generation_log, GenerationLogFormat};
+use crate::genome::{self, parse_fittest_file};
+use crate::AccNeatArgs;
+
+/// Default of the `success_error` parameter, the error at or below which
+/// `EvaluatorExperiment::is_success` counts a run as solved.
+pub const SUCCESS_ERROR: f64 = 0.0000001;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    GenerationLog(generation_log::Error),
+    Genome(genome::Error),
+    /// The run directory has no generation log or no fittest file.
+    MissingOutput(String),
+    /// A `-P name=value` parameter that isn't a number.
+    BadParam { name: String, value: String },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::GenerationLog(e) => write!(f, "{}", e),
+            Error::Genome(e) => write!(f, "{}", e),
+            Error::MissingOutput(dir) => write!(f, "{}: no generation log or fittest file", dir),
+            Error::BadParam { name, value } => write!(f, "invalid value '{}' for parameter {}", value, name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Linear interpolation between closest ranks, `p` in [0, 100].
+pub fn percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    if sorted[lo] == sorted[hi] {
+        // Also keeps infinities, which are used for failed runs, from turning into NaN.
+        return sorted[lo];
+    }
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+pub fn median(values: &[f64]) -> f64 {
+    percentile(values, 50.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub n: usize,
+    pub min: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl Summary {
+    pub fn new(values: &[f64]) -> Self {
+        Self {
+            n: values.len(),
+            min: percentile(values, 0.0),
+            p25: percentile(values, 25.0),
+            median: median(values),
+            p75: percentile(values, 75.0),
+            max: percentile(values, 100.0),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "n={}, min={:.4}, p25={:.4}, median={:.4}, p75={:.4}, max={:.4}, mean={:.4}",
+            self.n, self.min, self.p25, self.median, self.p75, self.max, self.mean
+        )
+    }
+}
+
+/// Wilson score interval for a binomial proportion. `z` is the standard
+/// normal quantile, e.g. 1.96 for 95%.
+pub fn wilson_interval(successes: usize, n: usize, z: f64) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    let n = n as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+/// Complementary error function, with fractional error below 1.2e-7
+/// (Numerical Recipes `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let ans = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        ans
+    } else {
+        2.0 - ans
+    }
+}
+
+/// Standard normal cumulative distribution function.
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitney {
+    /// U statistic of the first sample.
+    pub u: f64,
+    /// Normal approximation with tie and continuity corrections.
+    pub z: f64,
+    /// Two-sided.
+    pub p_value: f64,
+}
+
+/// Midranks (1-based) of `values`, ties sharing the average of their ranks.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut result = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &k in &order[i..=j] {
+            result[k] = rank;
+        }
+        i = j + 1;
+    }
+    result
+}
+
+/// Mann–Whitney U test of whether `a` and `b` come from the same distribution.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> MannWhitney {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let all: Vec<f64> = a.iter().chain(b).copied().collect();
+    let r = ranks(&all);
+    let r1: f64 = r[..a.len()].iter().sum();
+    let u = r1 - n1 * (n1 + 1.0) / 2.0;
+
+    let n = n1 + n2;
+    let mut tie_term = 0.0;
+    let mut sorted = r.clone();
+    sorted.sort_by(f64::total_cmp);
+    for group in sorted.chunk_by(|x, y| x == y) {
+        let t = group.len() as f64;
+        tie_term += t * t * t - t;
+    }
+    let mean = n1 * n2 / 2.0;
+    let sigma = (n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)))).sqrt();
+
+    if sigma.is_nan() || sigma <= 0.0 {
+        return MannWhitney { u, z: 0.0, p_value: 1.0 };
+    }
+    let diff = u - mean;
+    let z = (diff.abs() - 0.5).max(0.0) / sigma * diff.signum();
+    MannWhitney {
+        u,
+        z,
+        p_value: (2.0 * (1.0 - normal_cdf(z.abs()))).min(1.0),
+    }
+}
+
+/// The `success_error` that `args` runs with: its last `-P success_error`, or
+/// `SUCCESS_ERROR`.
+pub fn success_error(args: &AccNeatArgs) -> Result<f64, Error> {
+    match args.params.iter().rev().find(|(name, _)| name == "success_error") {
+        Some((name, value)) => value.parse().map_err(|_| Error::BadParam {
+            name: name.clone(),
+            value: value.clone(),
+        }),
+        None => Ok(SUCCESS_ERROR),
+    }
+}
+
+/// Outcome of a single run, i.e. one experiments/experiment_N directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub success: bool,
+    /// Generation at which the run was solved, or the last generation.
+    pub generations: usize,
+    /// The rest describe the champion, from the run's last fittest file.
+    pub fitness: f64,
+    pub error: f64,
+    pub nnodes: usize,
+    pub nlinks: usize,
+}
+
+impl RunResult {
+    /// `success_error` is the threshold the run was made with, see
+    /// `success_error`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P, success_error: f64) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let missing = || Error::MissingOutput(dir.display().to_string());
+
+        let log = [GenerationLogFormat::Csv, GenerationLogFormat::Jsonl]
+            .iter()
+            .map(|f| dir.join(f.filename().unwrap()))
+            .find(|p| p.exists())
+            .ok_or_else(missing)?;
+        let last = read_generation_log(log)
+            .map_err(Error::GenerationLog)?
+            .pop()
+            .ok_or_else(missing)?;
+
+        // The fittest file of the last generation holds the final champion.
+        let mut fittest = None;
+        for entry in std::fs::read_dir(dir).map_err(Error::Io)? {
+            let path = entry.map_err(Error::Io)?.path();
+            let gen = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix("fittest_"))
+                .and_then(|n| n.parse::<usize>().ok());
+            if let Some(gen) = gen {
+                if fittest.as_ref().is_none_or(|(g, _)| gen > *g) {
+                    fittest = Some((gen, path));
+                }
+            }
+        }
+        let (_, path) = fittest.ok_or_else(missing)?;
+        let organism = parse_fittest_file(path).map_err(Error::Genome)?;
+
+        let error = organism.info.error as f64;
+        Ok(Self {
+            success: error <= success_error,
+            generations: last.generation,
+            fitness: organism.info.fitness as f64,
+            error,
+            nnodes: organism.nodes.0.len(),
+            nlinks: organism.genes.0.iter().filter(|g| g.enable).count(),
+        })
+    }
+
+    /// Sort key for generations-to-solve: failures rank behind every success.
+    pub fn generations_to_solve(&self) -> f64 {
+        if self.success {
+            self.generations as f64
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Reads every run under `experiments_dir` (normally ./experiments), made
+/// with `success_error`.
+pub fn read_runs<P: AsRef<Path>>(experiments_dir: P, success_error: f64) -> Result<Vec<RunResult>, Error> {
+    let mut dirs = vec![];
+    for entry in std::fs::read_dir(experiments_dir).map_err(Error::Io)? {
+        let path = entry.map_err(Error::Io)?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    dirs.iter().map(|dir| RunResult::from_dir(dir, success_error)).collect()
+}
+
+/// The runs of one configuration, e.g. "phased" or "pop=500".
+#[derive(Debug, Clone)]
+pub struct RunSet {
+    pub name: String,
+    pub runs: Vec<RunResult>,
+}
+
+impl RunSet {
+    pub fn new(name: &str, runs: Vec<RunResult>) -> Self {
+        Self { name: name.to_string(), runs }
+    }
+
+    pub fn nsuccesses(&self) -> usize {
+        self.runs.iter().filter(|r| r.success).count()
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        self.nsuccesses() as f64 / self.runs.len() as f64
+    }
+
+    /// 95% Wilson interval of the success rate.
+    pub fn success_interval(&self) -> (f64, f64) {
+        wilson_interval(self.nsuccesses(), self.runs.len(), 1.96)
+    }
+
+    fn summary<F: Fn(&RunResult) -> f64>(&self, f: F) -> Summary {
+        Summary::new(&self.runs.iter().map(f).collect::<Vec<_>>())
+    }
+
+    /// Over successful runs only.
+    pub fn success_generations(&self) -> Summary {
+        let gens: Vec<f64> = self.runs.iter().filter(|r| r.success).map(|r| r.generations as f64).collect();
+        Summary::new(&gens)
+    }
+
+    pub fn fitness(&self) -> Summary {
+        self.summary(|r| r.fitness)
+    }
+
+    pub fn nnodes(&self) -> Summary {
+        self.summary(|r| r.nnodes as f64)
+    }
+
+    pub fn nlinks(&self) -> Summary {
+        self.summary(|r| r.nlinks as f64)
+    }
+
+    /// Tests generations-to-solve (failures ranked last) between two sets.
+    pub fn compare(&self, other: &RunSet) -> MannWhitney {
+        let gens = |s: &RunSet| s.runs.iter().map(RunResult::generations_to_solve).collect::<Vec<_>>();
+        mann_whitney_u(&gens(self), &gens(other))
+    }
+}
+
+impl std::fmt::Display for RunSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (lo, hi) = self.success_interval();
+        writeln!(f, "{}:", self.name)?;
+        writeln!(
+            f,
+            "  successes: {} of {} ({:.1}%, 95% CI {:.1}%..{:.1}%)",
+            self.nsuccesses(),
+            self.runs.len(),
+            self.success_rate() * 100.0,
+            lo * 100.0,
+            hi * 100.0
+        )?;
+        if self.nsuccesses() > 0 {
+            writeln!(f, "  success generations: {}", self.success_generations())?;
+        }
+        writeln!(f, "  fitness: {}", self.fitness())?;
+        writeln!(f, "  nnodes: {}", self.nnodes())?;
+        writeln!(f, "  nlinks: {}", self.nlinks())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{execute, AccNeatArgs, SearchType};
+    use serial_test::serial;
+
+    fn close(a: f64, b: f64, tol: f64) -> bool {
+        (a - b).abs() <= tol
+    }
+
+    #[test]
+    fn test_percentile() {
+        let v = [3.0, 1.0, 4.0, 1.0, 5.0];
+        assert_eq!(median(&v), 3.0);
+        assert_eq!(percentile(&v, 0.0), 1.0);
+        assert_eq!(percentile(&v, 100.0), 5.0);
+        assert_eq!(percentile(&v, 75.0), 4.0);
+        assert_eq!(median(&[1.0, 2.0]), 1.5);
+        assert!(median(&[]).is_nan());
+        assert_eq!(median(&[1.0, f64::INFINITY, f64::INFINITY]), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        let (lo, hi) = wilson_interval(8, 10, 1.96);
+        assert!(close(lo, 0.4902, 1e-4) && close(hi, 0.9433, 1e-4), "{} {}", lo, hi);
+        assert_eq!(wilson_interval(0, 10, 1.96).0, 0.0);
+        assert_eq!(wilson_interval(10, 10, 1.96).1, 1.0);
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!(close(normal_cdf(0.0), 0.5, 1e-7));
+        assert!(close(normal_cdf(1.96), 0.9750021, 1e-6));
+        assert!(close(normal_cdf(-1.0), 0.1586553, 1e-6));
+    }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+        let r = mann_whitney_u(&a, &b);
+        assert_eq!(r.u, 0.0);
+        // scipy.stats.mannwhitneyu(a, b, method="asymptotic") gives p=0.01219
+        assert!(close(r.p_value, 0.01219, 1e-4), "{}", r.p_value);
+        assert!(r.z < 0.0);
+
+        // With ties and a failed run on each side.
+        let a = [3.0, 4.0, 4.0, f64::INFINITY];
+        let b = [4.0, 6.0, 7.0, f64::INFINITY, f64::INFINITY];
+        let r = mann_whitney_u(&a, &b);
+        assert_eq!(r.u, 5.0);
+        assert_eq!(mann_whitney_u(&b, &a).u, 15.0);
+        assert!(r.p_value > 0.05 && r.p_value < 1.0);
+
+        assert_eq!(mann_whitney_u(&[1.0, 1.0], &[1.0]).p_value, 1.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_success_error_from_args() {
+        let a = AccNeatArgs {
+            force_delete: true,
+            num_experiments: 2,
+            pop_size: 150,
+            maxgens: 5,
+            params: vec![("success_error".to_string(), "100".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(success_error(&AccNeatArgs::default()).unwrap(), SUCCESS_ERROR);
+        assert_eq!(success_error(&a).unwrap(), 100.0);
+        execute(a).unwrap();
+        // Every champion is below the threshold the runs were made with.
+        for r in read_runs("experiments", 100.0).unwrap() {
+            assert!(r.success && r.generations == 1, "{:?}", r);
+        }
+        assert!(read_runs("experiments", SUCCESS_ERROR).unwrap().iter().all(|r| !r.success));
+        std::fs::remove_dir_all("experiments").unwrap();
+
+        let bad = AccNeatArgs {
+            params: vec![("success_error".to_string(), "small".to_string())],
+            ..Default::default()
+        };
+        assert!(matches!(success_error(&bad), Err(Error::BadParam { .. })));
+    }
+
+    #[test]
+    #[serial]
+    fn test_compare_search_types() {
+        let mut sets = vec![];
+        for (name, search_type) in [("phased", SearchType::Phased), ("complexify", SearchType::Complexify)] {
+            let a = AccNeatArgs {
+                force_delete: true,
+                num_experiments: 4,
+                pop_size: 150,
+                maxgens: 20,
+                search_type,
+                ..Default::default()
+            };
+            let success_error = success_error(&a).unwrap();
+            execute(a).unwrap();
+            let runs = read_runs("experiments", success_error).unwrap();
+            assert_eq!(runs.len(), 4);
+            for r in &runs {
+                assert!(r.generations >= 1 && r.generations <= 20);
+                assert!(r.nnodes >= 4);
+            }
+            sets.push(RunSet::new(name, runs));
+        }
+        std::fs::remove_dir_all("experiments").unwrap();
+
+        assert!(sets[0].to_string().starts_with("phased:\n  successes: "));
+        let r = sets[0].compare(&sets[1]);
+        assert!(r.p_value >= 0.0 && r.p_value <= 1.0);
+        assert!(r.u >= 0.0 && r.u <= 16.0);
+    }
+}
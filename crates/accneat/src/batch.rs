@@ -0,0 +1,330 @@
+//! Evaluation of a whole population at once, spread over threads with rayon
+//! the way `CpuNetworkExecutor::execute` spreads it with OpenMP.
+//!
+//! Each network keeps `CpuNetwork`'s flat layout, nodes holding
+//! `incoming_start..incoming_end` ranges into one array of links grouped by
+//! target, and is evaluated by the same code as the serial path, so results
+//! are bit-identical whatever the number of threads.
+//!
+//! `evaluate_grouped` also activates networks that differ only in their
+//! weights, as the offspring of weight mutation and prune phases often do,
+//! `LANES` at a time: each node's sum is computed for every lane in the same
+//! order as `CpuNetwork::activate` would, in loops the compiler vectorizes.
+
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::genome::ParsedOrganism;
+use crate::network::{ActivationSchedule, CpuNetwork, NetDims, NetNode, OrganismEvaluation, Real};
+use crate::static_evaluator::{Scoring, Test};
+
+/// Networks activated together by `evaluate_grouped`.
+pub const LANES: usize = 8;
+
+/// Fewest networks `evaluate_grouped` activates together. Unused lanes cost as
+/// much as used ones, so smaller groups are faster one by one.
+pub const MIN_LANES: usize = 6;
+
+/// The networks of a population, built in parallel.
+pub fn phenotypes(genomes: &[ParsedOrganism]) -> Vec<CpuNetwork> {
+    genomes.par_iter().map(CpuNetwork::from_genome).collect()
+}
+
+/// Evaluates every network with `evaluate`, in parallel. Evaluations are in
+/// the order of `nets`.
+pub fn execute<F>(nets: &mut [CpuNetwork], evaluate: F) -> Vec<OrganismEvaluation>
+where
+    F: Fn(&mut CpuNetwork) -> OrganismEvaluation + Sync,
+{
+    nets.par_iter_mut().map(&evaluate).collect()
+}
+
+/// Runs `tests` through every network, in parallel, as `Scoring::evaluate`
+/// does for one.
+pub fn evaluate_batch(nets: &mut [CpuNetwork], tests: &[Test], scoring: &Scoring) -> Vec<OrganismEvaluation> {
+    execute(nets, |net| scoring.evaluate(net, tests))
+}
+
+/// How a network activates, when the same for every input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Lockstep {
+    Cycles(usize),
+    FeedForward,
+}
+
+/// Everything networks must share to be activated together: all but their
+/// weights.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct Topology {
+    dims: NetDims,
+    nodes: Vec<NetNode>,
+    links: Vec<(usize, usize)>,
+    lockstep: Lockstep,
+}
+
+impl Topology {
+    /// None for networks whose activation depends on more than the topology:
+    /// plastic ones, and those on a `Settle` schedule.
+    fn of(net: &CpuNetwork) -> Option<Self> {
+        if net.is_plastic() {
+            return None;
+        }
+        let lockstep = match net.schedule() {
+            ActivationSchedule::Cycles(n) => Lockstep::Cycles(n),
+            ActivationSchedule::FeedForward { .. } if net.feedforward_order().is_some() => Lockstep::FeedForward,
+            ActivationSchedule::FeedForward { fallback_cycles } => Lockstep::Cycles(fallback_cycles),
+            ActivationSchedule::Settle { .. } => return None,
+        };
+        Some(Self {
+            dims: net.dims(),
+            nodes: net.nodes().to_vec(),
+            links: net.links().iter().map(|l| (l.in_node_index, l.out_node_index)).collect(),
+            lockstep,
+        })
+    }
+}
+
+/// Up to `LANES` networks of the same topology, activated as one. Unused lanes
+/// repeat the first network.
+struct LaneGroup<'a> {
+    template: &'a CpuNetwork,
+    lockstep: Lockstep,
+    weights: Vec<[Real; LANES]>,
+    activations: Vec<[Real; LANES]>,
+    act_other: Vec<[Real; LANES]>,
+}
+
+impl<'a> LaneGroup<'a> {
+    fn new(nets: &[&'a CpuNetwork], lockstep: Lockstep) -> Self {
+        let template = nets[0];
+        let weights = (0..template.links().len())
+            .map(|j| std::array::from_fn(|l| nets.get(l).unwrap_or(&template).links()[j].weight))
+            .collect();
+        let activations = template.activations().iter().map(|&a| [a; LANES]).collect();
+        let act_other = template.activations().iter().map(|&a| [a; LANES]).collect();
+        Self { template, lockstep, weights, activations, act_other }
+    }
+
+    fn clear_noninput(&mut self) {
+        for a in &mut self.activations[self.template.dims().nnodes.input..] {
+            *a = [0.0; LANES];
+        }
+    }
+
+    fn load_sensor(&mut self, isensor: usize, activation: Real) {
+        self.activations[self.template.dims().nnodes.bias + isensor] = [activation; LANES];
+    }
+
+    fn activate(&mut self) {
+        let input = self.template.dims().nnodes.input;
+        let nodes = self.template.nodes();
+        let links = self.template.links();
+        let weights = &self.weights;
+        let activate_node = |i: usize, act: &[[Real; LANES]]| -> [Real; LANES] {
+            let node = &nodes[i];
+            let mut sum = [0.0; LANES];
+            for j in node.incoming_start..node.incoming_end {
+                let (w, a) = (&weights[j], &act[links[j].in_node_index]);
+                for l in 0..LANES {
+                    sum[l] += w[l] * a[l];
+                }
+            }
+            sum.map(|s| node.function.apply(s))
+        };
+
+        match self.lockstep {
+            Lockstep::FeedForward => {
+                for &i in self.template.feedforward_order().unwrap() {
+                    self.activations[i] = activate_node(i, &self.activations);
+                }
+            }
+            Lockstep::Cycles(ncycles) => {
+                self.act_other[..input].copy_from_slice(&self.activations[..input]);
+                for _ in 0..ncycles {
+                    for i in input..self.activations.len() {
+                        self.act_other[i] = activate_node(i, &self.activations);
+                    }
+                    std::mem::swap(&mut self.activations, &mut self.act_other);
+                }
+            }
+        }
+    }
+
+    /// Evaluations and final activations of each lane, as `run_tests` would
+    /// leave them.
+    fn evaluate(mut self, tests: &[Test], scoring: &Scoring) -> Vec<(OrganismEvaluation, Vec<Real>)> {
+        let outputs_start = self.template.dims().nnodes.input;
+        let mut outputs = vec![0.0; self.template.dims().nnodes.output];
+        let mut errorsums = [0.0; LANES];
+        for test in tests {
+            for (j, step) in test.steps.iter().enumerate() {
+                if j == 0 {
+                    self.clear_noninput();
+                }
+                for (isensor, &x) in step.input.iter().enumerate() {
+                    self.load_sensor(isensor, x);
+                }
+                self.activate();
+                for (l, errorsum) in errorsums.iter_mut().enumerate() {
+                    for (o, a) in outputs.iter_mut().zip(&self.activations[outputs_start..]) {
+                        *o = a[l];
+                    }
+                    *errorsum += scoring.step_error(&outputs, &step.output) * step.weight;
+                }
+            }
+        }
+        (0..LANES)
+            .map(|l| (scoring.evaluation(errorsums[l], tests), self.activations.iter().map(|a| a[l]).collect()))
+            .collect()
+    }
+}
+
+/// Like `evaluate_batch`, but networks sharing a topology and schedule are
+/// activated `LANES` at a time. Others, and the rest of a group when fewer
+/// than `MIN_LANES`, are evaluated one by one. Results are bit-identical to
+/// `evaluate_batch`'s.
+pub fn evaluate_grouped(nets: &mut [CpuNetwork], tests: &[Test], scoring: &Scoring) -> Vec<OrganismEvaluation> {
+    let mut groups: HashMap<Topology, Vec<usize>> = HashMap::new();
+    let mut singles = vec![];
+    for (i, net) in nets.iter().enumerate() {
+        match Topology::of(net) {
+            Some(topology) => groups.entry(topology).or_default().push(i),
+            None => singles.push(i),
+        }
+    }
+    let mut chunks: Vec<(Lockstep, &[usize])> = vec![];
+    for (topology, members) in &groups {
+        for chunk in members.chunks(LANES) {
+            if chunk.len() < MIN_LANES {
+                singles.extend_from_slice(chunk);
+            } else {
+                chunks.push((topology.lockstep, chunk));
+            }
+        }
+    }
+
+    let shared: &[CpuNetwork] = nets;
+    let lanes: Vec<(usize, OrganismEvaluation, Vec<Real>)> = chunks
+        .par_iter()
+        .flat_map_iter(|&(lockstep, chunk)| {
+            let members: Vec<&CpuNetwork> = chunk.iter().map(|&i| &shared[i]).collect();
+            let results = LaneGroup::new(&members, lockstep).evaluate(tests, scoring);
+            chunk.iter().zip(results).map(|(&i, (eval, acts))| (i, eval, acts))
+        })
+        .collect();
+
+    let mut result = vec![OrganismEvaluation::default(); nets.len()];
+    for (i, eval, acts) in lanes {
+        nets[i].set_activations(&acts);
+        result[i] = eval;
+    }
+    let mut is_single = vec![false; nets.len()];
+    for &i in &singles {
+        is_single[i] = true;
+    }
+    let evals: Vec<(usize, OrganismEvaluation)> = nets
+        .par_iter_mut()
+        .enumerate()
+        .filter(|(i, _)| is_single[*i])
+        .map(|(i, net)| (i, scoring.evaluate(net, tests)))
+        .collect();
+    for (i, eval) in evals {
+        result[i] = eval;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::sequence::SequenceExperiment;
+    use crate::genome::tests::{XOR_CHAMPION, XOR_FEEDFORWARD_CHAMPION};
+    use crate::network::ActivationSchedule;
+    use crate::static_evaluator::Loss;
+    use crate::rng::Rng;
+
+    /// Champions with perturbed weights, some plastic or on other schedules.
+    fn population(n: usize) -> Vec<CpuNetwork> {
+        let champions: Vec<ParsedOrganism> = [XOR_CHAMPION, XOR_FEEDFORWARD_CHAMPION]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let mut rng = Rng::new(1);
+        let genomes: Vec<ParsedOrganism> = (0..n)
+            .map(|i| {
+                let mut genome = champions[i % champions.len()].clone();
+                for gene in &mut genome.genes.0 {
+                    gene.weight += 2.0 * rng.prob() - 1.0;
+                }
+                genome
+            })
+            .collect();
+
+        let mut nets = phenotypes(&genomes);
+        for (i, net) in nets.iter_mut().enumerate() {
+            match i % 4 {
+                1 => net.set_schedule(ActivationSchedule::FeedForward { fallback_cycles: 10 }),
+                2 => net.set_schedule(ActivationSchedule::Settle { epsilon: 1e-3, max_cycles: 20 }),
+                3 => net.set_plastic(i % 8 == 3),
+                _ => {}
+            }
+        }
+        nets
+    }
+
+    #[test]
+    fn test_batch_matches_serial() {
+        let tests = SequenceExperiment { len: 3, ..Default::default() }.tests().unwrap();
+        let scoring = Scoring { loss: Loss::Squared, tolerance: 0.0 };
+        let mut serial_nets = population(200);
+        let serial: Vec<OrganismEvaluation> =
+            serial_nets.iter_mut().map(|net| scoring.evaluate(net, &tests)).collect();
+
+        for nthreads in [1, 4] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(nthreads).build().unwrap();
+            let mut nets = population(200);
+            let batch = pool.install(|| evaluate_batch(&mut nets, &tests, &scoring));
+            assert_eq!(batch.len(), serial.len());
+            for (b, s) in batch.iter().zip(&serial) {
+                assert_eq!(b.fitness.to_bits(), s.fitness.to_bits());
+                assert_eq!(b.error.to_bits(), s.error.to_bits());
+            }
+            // Plastic networks learned the same.
+            for (b, s) in nets.iter().zip(&serial_nets) {
+                assert_eq!(b.links(), s.links());
+            }
+        }
+        assert!(serial.iter().any(|e| e.fitness != serial[0].fitness));
+    }
+
+    #[test]
+    fn test_grouped_matches_serial() {
+        let tests = SequenceExperiment { len: 3, ..Default::default() }.tests().unwrap();
+        let scoring = Scoring::default();
+        let mut serial_nets = population(197);
+        let serial = evaluate_batch(&mut serial_nets, &tests, &scoring);
+        let mut nets = population(197);
+        let grouped = evaluate_grouped(&mut nets, &tests, &scoring);
+        for (g, s) in grouped.iter().zip(&serial) {
+            assert_eq!(g.fitness.to_bits(), s.fitness.to_bits());
+            assert_eq!(g.error.to_bits(), s.error.to_bits());
+        }
+        for (g, s) in nets.iter().zip(&serial_nets) {
+            assert_eq!(g.activations(), s.activations());
+            assert_eq!(g.links(), s.links());
+        }
+
+        let groups: Vec<Option<Topology>> = nets.iter().map(Topology::of).collect();
+        assert!(groups[0].is_some() && groups[0] == groups[4] && groups[0] != groups[1]);
+        assert!(groups[2].is_none() && groups[3].is_none());
+
+        // Groups left fewer than MIN_LANES networks over, evaluated one by one.
+        let mut sizes: HashMap<&Topology, usize> = HashMap::new();
+        for topology in groups.iter().flatten() {
+            *sizes.entry(topology).or_default() += 1;
+        }
+        let leftovers: usize = sizes.values().map(|n| n % LANES).filter(|&n| n < MIN_LANES).sum();
+        assert!(leftovers > 0);
+    }
+}
@@ -0,0 +1,460 @@
+//! Turns a parsed champion into standalone Rust or C source that reproduces
+//! `CpuNetwork::activate(NACTIVATES_PER_INPUT)`, recurrent state included.
+//!
+//! The generated code has no dependencies. The caller owns the activation
+//! state array (one entry per node, bias nodes first, see `CpuNetwork`) and
+//! is expected to start from `INITIAL_STATE` and call `clear_noninput` at the
+//! start of every sequence, like the static evaluator does at the first step
+//! of each `Test`.
+
+use std::fmt::Write;
+
+use crate::genome::ParsedOrganism;
+use crate::network::{
+    init_phenotype, ActivationFunction, CpuNetwork, NetDims, NetLink, NetNode, Real, NACTIVATES_PER_INPUT,
+    SIGMOID_SLOPE,
+};
+use crate::static_evaluator::{run_tests, Test};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar {
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn rust(self) -> &'static str {
+        match self {
+            Scalar::F32 => "f32",
+            Scalar::F64 => "f64",
+        }
+    }
+
+    fn c(self) -> &'static str {
+        match self {
+            Scalar::F32 => "float",
+            Scalar::F64 => "double",
+        }
+    }
+
+    /// A literal that parses back to exactly `x` rounded to this type.
+    fn literal(self, x: Real) -> String {
+        let s = match self {
+            Scalar::F32 => format!("{:?}", x as f32),
+            Scalar::F64 => format!("{:?}", x),
+        };
+        if s.contains(['.', 'e']) {
+            s
+        } else {
+            format!("{}.0", s)
+        }
+    }
+
+    fn c_literal(self, x: Real) -> String {
+        match self {
+            Scalar::F32 => format!("{}f", self.literal(x)),
+            Scalar::F64 => self.literal(x),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    pub scalar: Scalar,
+    pub ncycles: usize,
+    /// Prefix for the C symbols, e.g. `accneat_` gives `accneat_activate`.
+    pub c_prefix: String,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        Self {
+            scalar: Scalar::F32,
+            ncycles: NACTIVATES_PER_INPUT,
+            c_prefix: "accneat_".to_string(),
+        }
+    }
+}
+
+struct Layout {
+    dims: NetDims,
+    nodes: Vec<NetNode>,
+    links: Vec<NetLink>,
+}
+
+impl Layout {
+    fn new(genome: &ParsedOrganism) -> Self {
+        let (dims, nodes, links) = init_phenotype(genome);
+        Self { dims, nodes, links }
+    }
+
+    fn initial_state(&self) -> Vec<Real> {
+        (0..self.dims.nnodes.all)
+            .map(|i| if i < self.dims.nnodes.bias { 1.0 } else { 0.0 })
+            .collect()
+    }
+
+    fn uses_sigmoid(&self) -> bool {
+        self.nodes[self.dims.nnodes.input..].iter().any(|n| n.function == ActivationFunction::Sigmoid)
+    }
+}
+
+/// Rust expression applying `function` to `sum`.
+fn rust_activation(function: ActivationFunction, scalar: Scalar) -> String {
+    let (zero, one) = (scalar.literal(0.0), scalar.literal(1.0));
+    match function {
+        ActivationFunction::Sigmoid => "fsigmoid(sum)".to_string(),
+        ActivationFunction::Tanh => "sum.tanh()".to_string(),
+        ActivationFunction::Relu => format!("if sum > {0} {{ sum }} else {{ {0} }}", zero),
+        ActivationFunction::Gaussian => "(-(sum * sum)).exp()".to_string(),
+        ActivationFunction::Sine => "sum.sin()".to_string(),
+        ActivationFunction::Step => format!("if sum > {} {{ {} }} else {{ {} }}", zero, one, zero),
+        ActivationFunction::Identity => "sum".to_string(),
+        ActivationFunction::Abs => "sum.abs()".to_string(),
+    }
+}
+
+/// C expression applying `function` to `sum`, with `p` the symbol prefix.
+fn c_activation(function: ActivationFunction, scalar: Scalar, p: &str) -> String {
+    let suffix = match scalar {
+        Scalar::F32 => "f",
+        Scalar::F64 => "",
+    };
+    match function {
+        ActivationFunction::Sigmoid => format!("{}fsigmoid(sum)", p),
+        ActivationFunction::Tanh => format!("tanh{}(sum)", suffix),
+        ActivationFunction::Relu => "(sum > 0 ? sum : 0)".to_string(),
+        ActivationFunction::Gaussian => format!("exp{}(-(sum * sum))", suffix),
+        ActivationFunction::Sine => format!("sin{}(sum)", suffix),
+        ActivationFunction::Step => "(sum > 0 ? 1 : 0)".to_string(),
+        ActivationFunction::Identity => "sum".to_string(),
+        ActivationFunction::Abs => format!("fabs{}(sum)", suffix),
+    }
+}
+
+/// Emits a Rust module exposing
+/// `fn activate(state: &mut [T; NNODES], inputs: &[T; NINPUTS]) -> [T; NOUTPUTS]`.
+pub fn generate_rust(genome: &ParsedOrganism, opts: &CodegenOptions) -> String {
+    let layout = Layout::new(genome);
+    let n = layout.dims.nnodes;
+    let t = opts.scalar.rust();
+    let mut s = String::new();
+
+    writeln!(s, "// Generated by accneat::codegen from organism #{}. Do not edit.", genome.info.id).unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "pub const NNODES: usize = {};", n.all).unwrap();
+    writeln!(s, "pub const NINPUTS: usize = {};", n.sensor).unwrap();
+    writeln!(s, "pub const NOUTPUTS: usize = {};", n.output).unwrap();
+    writeln!(s, "pub const NCYCLES: usize = {};", opts.ncycles).unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "/// Bias nodes at 1, everything else at 0.").unwrap();
+    let init: Vec<String> = layout.initial_state().iter().map(|&x| opts.scalar.literal(x)).collect();
+    writeln!(s, "pub const INITIAL_STATE: [{}; NNODES] = [{}];", t, init.join(", ")).unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "/// Resets all non-input nodes, like `CpuNetwork::clear_noninput`.").unwrap();
+    writeln!(s, "pub fn clear_noninput(state: &mut [{}; NNODES]) {{", t).unwrap();
+    writeln!(s, "    for a in state[{}..].iter_mut() {{", n.input).unwrap();
+    writeln!(s, "        *a = 0.0;").unwrap();
+    writeln!(s, "    }}").unwrap();
+    writeln!(s, "}}").unwrap();
+    writeln!(s).unwrap();
+    if layout.uses_sigmoid() {
+        writeln!(s, "#[inline]").unwrap();
+        writeln!(s, "fn fsigmoid(activesum: {}) -> {} {{", t, t).unwrap();
+        writeln!(s, "    1.0 / (1.0 + (-({} * activesum)).exp())", opts.scalar.literal(SIGMOID_SLOPE)).unwrap();
+        writeln!(s, "}}").unwrap();
+        writeln!(s).unwrap();
+    }
+    writeln!(s, "/// Loads the sensors and runs NCYCLES activation cycles.").unwrap();
+    writeln!(s, "pub fn activate(state: &mut [{0}; NNODES], inputs: &[{0}; NINPUTS]) -> [{0}; NOUTPUTS] {{", t).unwrap();
+    for i in 0..n.sensor {
+        writeln!(s, "    state[{}] = inputs[{}];", n.bias + i, i).unwrap();
+    }
+    writeln!(s, "    for _ in 0..NCYCLES {{").unwrap();
+    writeln!(s, "        let curr = *state;").unwrap();
+    for i in n.input..n.all {
+        let node = layout.nodes[i];
+        writeln!(s, "        let mut sum = 0.0;").unwrap();
+        for link in &layout.links[node.incoming_start..node.incoming_end] {
+            writeln!(s, "        sum += {} * curr[{}];", opts.scalar.literal(link.weight), link.in_node_index).unwrap();
+        }
+        writeln!(s, "        state[{}] = {};", i, rust_activation(node.function, opts.scalar)).unwrap();
+    }
+    writeln!(s, "    }}").unwrap();
+    let outputs: Vec<String> = (n.input..n.input + n.output).map(|i| format!("state[{}]", i)).collect();
+    writeln!(s, "    [{}]", outputs.join(", ")).unwrap();
+    writeln!(s, "}}").unwrap();
+    s
+}
+
+/// Emits a C translation unit exposing
+/// `void <prefix>activate(T state[NNODES], const T inputs[NINPUTS], T outputs[NOUTPUTS])`.
+pub fn generate_c(genome: &ParsedOrganism, opts: &CodegenOptions) -> String {
+    let layout = Layout::new(genome);
+    let n = layout.dims.nnodes;
+    let t = opts.scalar.c();
+    let p = &opts.c_prefix;
+    let upper = p.to_uppercase();
+    let exp = match opts.scalar {
+        Scalar::F32 => "expf",
+        Scalar::F64 => "exp",
+    };
+    let mut s = String::new();
+
+    writeln!(s, "/* Generated by accneat::codegen from organism #{}. Do not edit. */", genome.info.id).unwrap();
+    writeln!(s, "#include <math.h>").unwrap();
+    writeln!(s, "#include <string.h>").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "#define {}NNODES {}", upper, n.all).unwrap();
+    writeln!(s, "#define {}NINPUTS {}", upper, n.sensor).unwrap();
+    writeln!(s, "#define {}NOUTPUTS {}", upper, n.output).unwrap();
+    writeln!(s, "#define {}NCYCLES {}", upper, opts.ncycles).unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "/* Bias nodes at 1, everything else at 0. */").unwrap();
+    let init: Vec<String> = layout.initial_state().iter().map(|&x| opts.scalar.c_literal(x)).collect();
+    writeln!(s, "static const {} {}initial_state[{}NNODES] = {{{}}};", t, p, upper, init.join(", ")).unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "void {}reset({} state[{}NNODES]) {{", p, t, upper).unwrap();
+    writeln!(s, "    memcpy(state, {}initial_state, sizeof({}initial_state));", p, p).unwrap();
+    writeln!(s, "}}").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "/* Resets all non-input nodes, like CpuNetwork::clear_noninput(). */").unwrap();
+    writeln!(s, "void {}clear_noninput({} state[{}NNODES]) {{", p, t, upper).unwrap();
+    writeln!(s, "    for(int i = {}; i < {}NNODES; i++) state[i] = 0;", n.input, upper).unwrap();
+    writeln!(s, "}}").unwrap();
+    writeln!(s).unwrap();
+    if layout.uses_sigmoid() {
+        writeln!(s, "static {} {}fsigmoid({} activesum) {{", t, p, t).unwrap();
+        writeln!(s, "    return 1 / (1 + {}(-({} * activesum)));", exp, opts.scalar.c_literal(SIGMOID_SLOPE)).unwrap();
+        writeln!(s, "}}").unwrap();
+        writeln!(s).unwrap();
+    }
+    writeln!(s, "/* Loads the sensors and runs NCYCLES activation cycles. */").unwrap();
+    writeln!(s, "void {0}activate({1} state[{2}NNODES], const {1} inputs[{2}NINPUTS], {1} outputs[{2}NOUTPUTS]) {{", p, t, upper).unwrap();
+    writeln!(s, "    {} curr[{}NNODES];", t, upper).unwrap();
+    writeln!(s, "    {} sum;", t).unwrap();
+    for i in 0..n.sensor {
+        writeln!(s, "    state[{}] = inputs[{}];", n.bias + i, i).unwrap();
+    }
+    writeln!(s, "    for(int icycle = 0; icycle < {}NCYCLES; icycle++) {{", upper).unwrap();
+    writeln!(s, "        memcpy(curr, state, sizeof(curr));").unwrap();
+    for i in n.input..n.all {
+        let node = layout.nodes[i];
+        writeln!(s, "        sum = 0;").unwrap();
+        for link in &layout.links[node.incoming_start..node.incoming_end] {
+            writeln!(s, "        sum += {} * curr[{}];", opts.scalar.c_literal(link.weight), link.in_node_index).unwrap();
+        }
+        writeln!(s, "        state[{}] = {};", i, c_activation(node.function, opts.scalar, p)).unwrap();
+    }
+    writeln!(s, "    }}").unwrap();
+    for i in 0..n.output {
+        writeln!(s, "    outputs[{}] = state[{}];", i, n.input + i).unwrap();
+    }
+    writeln!(s, "}}").unwrap();
+    s
+}
+
+/// Outputs of the interpreter for every step of `tests`, in order.
+fn expected_outputs(genome: &ParsedOrganism, tests: &[Test]) -> Vec<Vec<Real>> {
+    let mut net = CpuNetwork::from_genome(genome);
+    let mut result = vec![];
+    run_tests(&mut net, tests, |_, outputs| result.push(outputs.to_vec()));
+    result
+}
+
+/// Tolerance used by the generated tests. `F64` code must match the
+/// interpreter bit for bit; `F32` code can only match it approximately.
+fn tolerance(scalar: Scalar) -> &'static str {
+    match scalar {
+        Scalar::F32 => "1e-4",
+        Scalar::F64 => "0.0",
+    }
+}
+
+/// Emits a `#[test]` that runs the code from `generate_rust` over `tests` and
+/// compares it with the outputs of the interpreter.
+pub fn generate_rust_test(genome: &ParsedOrganism, tests: &[Test], opts: &CodegenOptions) -> String {
+    let expected = expected_outputs(genome, tests);
+    let mut s = String::new();
+
+    writeln!(s, "#[cfg(test)]").unwrap();
+    writeln!(s, "mod generated_tests {{").unwrap();
+    writeln!(s, "    use super::*;").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "    #[test]").unwrap();
+    writeln!(s, "    fn test_matches_interpreter() {{").unwrap();
+    writeln!(s, "        let mut state = INITIAL_STATE;").unwrap();
+    let mut istep = 0;
+    for test in tests {
+        writeln!(s, "        // {}", if test.name.is_empty() { "test" } else { &test.name }).unwrap();
+        writeln!(s, "        clear_noninput(&mut state);").unwrap();
+        for step in &test.steps {
+            let inputs: Vec<String> = step.input.iter().map(|&x| opts.scalar.literal(x)).collect();
+            // Expected values are kept as f64 so F32 code is compared against the real thing.
+            let outputs: Vec<String> = expected[istep].iter().map(|&x| Scalar::F64.literal(x)).collect();
+            writeln!(s, "        let actual = activate(&mut state, &[{}]);", inputs.join(", ")).unwrap();
+            writeln!(s, "        let expected: [f64; NOUTPUTS] = [{}];", outputs.join(", ")).unwrap();
+            writeln!(s, "        for (a, e) in actual.iter().zip(expected.iter()) {{").unwrap();
+            writeln!(s, "            assert!((*a as f64 - e).abs() <= {}, \"{{}} != {{}}\", a, e);", tolerance(opts.scalar)).unwrap();
+            writeln!(s, "        }}").unwrap();
+            istep += 1;
+        }
+    }
+    writeln!(s, "    }}").unwrap();
+    writeln!(s, "}}").unwrap();
+    s
+}
+
+/// Emits a C `main()` that runs the code from `generate_c` over `tests`,
+/// compares it with the outputs of the interpreter and exits non-zero on a
+/// mismatch.
+pub fn generate_c_test(genome: &ParsedOrganism, tests: &[Test], opts: &CodegenOptions) -> String {
+    let t = opts.scalar.c();
+    let p = &opts.c_prefix;
+    let upper = p.to_uppercase();
+    let expected = expected_outputs(genome, tests);
+    let mut s = String::new();
+
+    writeln!(s, "#include <stdio.h>").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "static int check(const {} *actual, const double *expected) {{", t).unwrap();
+    writeln!(s, "    int failures = 0;").unwrap();
+    writeln!(s, "    for(int i = 0; i < {}NOUTPUTS; i++) {{", upper).unwrap();
+    writeln!(s, "        if(fabs((double)actual[i] - expected[i]) > {}) {{", tolerance(opts.scalar)).unwrap();
+    writeln!(s, "            printf(\"%.17g != %.17g\\n\", (double)actual[i], expected[i]);").unwrap();
+    writeln!(s, "            failures++;").unwrap();
+    writeln!(s, "        }}").unwrap();
+    writeln!(s, "    }}").unwrap();
+    writeln!(s, "    return failures;").unwrap();
+    writeln!(s, "}}").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "int main() {{").unwrap();
+    writeln!(s, "    {0} state[{1}NNODES], outputs[{1}NOUTPUTS];", t, upper).unwrap();
+    writeln!(s, "    int failures = 0;").unwrap();
+    writeln!(s, "    {}reset(state);", p).unwrap();
+    let mut istep = 0;
+    for test in tests {
+        writeln!(s, "    {}clear_noninput(state);", p).unwrap();
+        for step in &test.steps {
+            let inputs: Vec<String> = step.input.iter().map(|&x| opts.scalar.c_literal(x)).collect();
+            let outputs: Vec<String> = expected[istep].iter().map(|&x| Scalar::F64.literal(x)).collect();
+            writeln!(s, "    {{").unwrap();
+            writeln!(s, "        const {} inputs[] = {{{}}};", t, inputs.join(", ")).unwrap();
+            writeln!(s, "        const double expected[] = {{{}}};", outputs.join(", ")).unwrap();
+            writeln!(s, "        {}activate(state, inputs, outputs);", p).unwrap();
+            writeln!(s, "        failures += check(outputs, expected);").unwrap();
+            writeln!(s, "    }}").unwrap();
+            istep += 1;
+        }
+    }
+    writeln!(s, "    return failures != 0;").unwrap();
+    writeln!(s, "}}").unwrap();
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments;
+    use crate::genome::tests::XOR_CHAMPION;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("accneat-codegen-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// XOR_CHAMPION with every function but sigmoid and step, whose
+    /// threshold f32 code can't be expected to match.
+    fn mixed_activation_champion() -> String {
+        XOR_CHAMPION
+            .replace("node 4 1 2\n", "node 4 1 2 7\n")
+            .replace("node 5 1 3\n", "node 5 1 3 1\n")
+            .replace("node 6 1 3\n", "node 6 1 3 3\n")
+            .replace("node 17 1 3\n", "node 17 1 3 2\n")
+    }
+
+    fn compile_and_test_rust(genome: &str, scalar: Scalar) {
+        let genome: ParsedOrganism = genome.parse().unwrap();
+        let opts = CodegenOptions { scalar, ..Default::default() };
+        let src = generate_rust(&genome, &opts) + "\n" + &generate_rust_test(&genome, &experiments::xor(), &opts);
+
+        let dir = scratch_dir(&format!("rust-{}-{:?}", genome.info.id, scalar));
+        let path = dir.join("champion.rs");
+        std::fs::write(&path, src).unwrap();
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let o = Command::new(rustc)
+            .args(["--edition", "2021", "--test", "-o"])
+            .arg(dir.join("champion"))
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(o.status.success(), "{}", String::from_utf8_lossy(&o.stderr));
+
+        let o = Command::new(dir.join("champion")).output().unwrap();
+        assert!(o.status.success(), "{}", String::from_utf8_lossy(&o.stdout));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn compile_and_test_c(genome: &str, scalar: Scalar) {
+        let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        assert!(Command::new(&cc).arg("--version").output().is_ok(), "no C compiler '{}', set CC", cc);
+
+        let genome: ParsedOrganism = genome.parse().unwrap();
+        let opts = CodegenOptions { scalar, ..Default::default() };
+        let src = generate_c(&genome, &opts) + "\n" + &generate_c_test(&genome, &experiments::xor(), &opts);
+
+        let dir = scratch_dir(&format!("c-{}-{:?}", genome.info.id, scalar));
+        let path = dir.join("champion.c");
+        std::fs::write(&path, src).unwrap();
+        let o = Command::new(cc)
+            .arg("-o")
+            .arg(dir.join("champion"))
+            .arg(&path)
+            .arg("-lm")
+            .output()
+            .unwrap();
+        assert!(o.status.success(), "{}", String::from_utf8_lossy(&o.stderr));
+
+        let o = Command::new(dir.join("champion")).output().unwrap();
+        assert!(o.status.success(), "{}", String::from_utf8_lossy(&o.stdout));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_generated_rust_f64_matches_interpreter() {
+        compile_and_test_rust(XOR_CHAMPION, Scalar::F64);
+    }
+
+    #[test]
+    fn test_generated_rust_f32_matches_interpreter() {
+        compile_and_test_rust(XOR_CHAMPION, Scalar::F32);
+    }
+
+    #[test]
+    fn test_generated_c_matches_interpreter() {
+        compile_and_test_c(XOR_CHAMPION, Scalar::F64);
+        compile_and_test_c(XOR_CHAMPION, Scalar::F32);
+    }
+
+    #[test]
+    fn test_generated_code_honors_activation_functions() {
+        let genome = mixed_activation_champion();
+        let src = generate_rust(&genome.parse().unwrap(), &CodegenOptions::default());
+        assert!(src.contains("sum.tanh()") && src.contains("sum.abs()"));
+        compile_and_test_rust(&genome, Scalar::F64);
+        compile_and_test_rust(&genome, Scalar::F32);
+        compile_and_test_c(&genome, Scalar::F64);
+        compile_and_test_c(&genome, Scalar::F32);
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(Scalar::F64.literal(1.0), "1.0");
+        assert_eq!(Scalar::F64.literal(-1.31462), "-1.31462");
+        assert_eq!(Scalar::F32.literal(0.1), "0.1");
+        assert_eq!(Scalar::F32.c_literal(2.0), "2.0f");
+    }
+}
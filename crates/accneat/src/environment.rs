@@ -0,0 +1,180 @@
+//! Gym-style episodic environments, and a port of the evaluator of
+//! src/experiments/environment that scores networks on them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::network::{CpuNetwork, OrganismEvaluation, Real};
+
+/// Result of one action in an `Environment`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvStep {
+    pub observation: Vec<Real>,
+    pub reward: Real,
+    pub done: bool,
+}
+
+/// An episodic task. A network is shown each observation on its sensors and
+/// its outputs are the action. Episodes are seeded so that every organism
+/// faces the same ones.
+pub trait Environment {
+    /// Starts an episode, returning the first observation.
+    fn reset(&mut self, seed: u64) -> Vec<Real>;
+
+    fn step(&mut self, action: &[Real]) -> EnvStep;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentSpec {
+    pub nsensors: usize,
+    pub noutputs: usize,
+    /// Episodes per organism, seeded `0..nepisodes`.
+    pub nepisodes: usize,
+    /// Episode return that counts as solving it.
+    pub max_return: Real,
+}
+
+/// Runs one episode, returning its return.
+pub fn run_episode<E: Environment>(net: &mut CpuNetwork, env: &mut E, spec: &EnvironmentSpec, seed: u64) -> Real {
+    let mut observation = env.reset(seed);
+    net.clear_noninput();
+    let mut episode_return = 0.0;
+    loop {
+        assert_eq!(observation.len(), spec.nsensors, "environment observation size");
+        for (i, &s) in observation.iter().enumerate() {
+            net.load_sensor(i, s);
+        }
+        net.activate_scheduled();
+        let step = env.step(&net.outputs()[..spec.noutputs]);
+        episode_return += step.reward;
+        if step.done {
+            return episode_return;
+        }
+        observation = step.observation;
+    }
+}
+
+/// Same as the C++ `EnvironmentEvaluator`: fitness is the mean return, and
+/// error the mean fraction of `max_return` an episode fell short by.
+pub fn evaluate<E: Environment>(net: &mut CpuNetwork, env: &mut E, spec: &EnvironmentSpec) -> OrganismEvaluation {
+    let mut total_return = 0.0;
+    let mut shortfall = 0.0;
+    for episode in 0..spec.nepisodes {
+        let episode_return = run_episode(net, env, spec, episode as u64);
+        total_return += episode_return;
+        if episode_return < spec.max_return {
+            shortfall += 1.0 - episode_return / spec.max_return;
+        }
+    }
+    OrganismEvaluation {
+        fitness: total_return / spec.nepisodes as Real,
+        error: shortfall / spec.nepisodes as Real,
+    }
+}
+
+/// Evaluates every network, each with its own environment from `create_env`,
+/// spread over the available cores.
+pub fn evaluate_all<E, F>(nets: &mut [CpuNetwork], create_env: F, spec: &EnvironmentSpec) -> Vec<OrganismEvaluation>
+where
+    E: Environment,
+    F: Fn() -> E + Sync,
+{
+    let nthreads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = nets.len().div_ceil(nthreads).max(1);
+    let mut results = vec![OrganismEvaluation::default(); nets.len()];
+    std::thread::scope(|scope| {
+        for (nets, results) in nets.chunks_mut(chunk_size).zip(results.chunks_mut(chunk_size)) {
+            let create_env = &create_env;
+            scope.spawn(move || {
+                for (net, result) in nets.iter_mut().zip(results) {
+                    *result = evaluate(net, &mut create_env(), spec);
+                }
+            });
+        }
+    });
+    results
+}
+
+/// Next value of a SplitMix64 sequence, as `splitmix64` in environment.cpp.
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Uniform value in `[lo, hi)` from a SplitMix64 sequence.
+pub fn splitmix64_uniform(state: &mut u64, lo: Real, hi: Real) -> Real {
+    let unit = (splitmix64(state) >> 11) as Real / (1u64 << 53) as Real;
+    lo + unit * (hi - lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{NetDims, NetNode, NodeCounts};
+
+    /// Pays the first output each step, for `len` steps.
+    struct Echo {
+        len: usize,
+        nsteps: usize,
+    }
+
+    impl Environment for Echo {
+        fn reset(&mut self, seed: u64) -> Vec<Real> {
+            self.nsteps = 0;
+            vec![seed as Real]
+        }
+
+        fn step(&mut self, action: &[Real]) -> EnvStep {
+            self.nsteps += 1;
+            EnvStep {
+                observation: vec![0.0],
+                reward: action[0],
+                done: self.nsteps == self.len,
+            }
+        }
+    }
+
+    #[test]
+    fn test_splitmix64() {
+        // First outputs of the reference implementation seeded with 0.
+        let mut state = 0;
+        assert_eq!(splitmix64(&mut state), 0xe220a8397b1dcdaf);
+        assert_eq!(splitmix64(&mut state), 0x6e789e6aa1b965f4);
+        let mut state = 7;
+        let x = splitmix64_uniform(&mut state, -0.05, 0.05);
+        assert!((-0.05..0.05).contains(&x));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // No links: the output is sigmoid(0) = 0.5 each step.
+        let dims = NetDims {
+            nnodes: NodeCounts {
+                bias: 1,
+                sensor: 1,
+                output: 1,
+                hidden: 0,
+                all: 3,
+                input: 2,
+                noninput: 1,
+            },
+            nlinks: 0,
+        };
+        let nodes = vec![NetNode::default(); 3];
+        let spec = EnvironmentSpec {
+            nsensors: 1,
+            noutputs: 1,
+            nepisodes: 4,
+            max_return: 2.0,
+        };
+        let mut nets = vec![CpuNetwork::configure(dims, nodes, vec![]); 5];
+        let results = evaluate_all(&mut nets, || Echo { len: 3, nsteps: 0 }, &spec);
+        assert_eq!(results.len(), 5);
+        for eval in results {
+            assert!((eval.fitness - 1.5).abs() < 1e-9);
+            assert!((eval.error - 0.25).abs() < 1e-9);
+        }
+    }
+}
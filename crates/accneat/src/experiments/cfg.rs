@@ -0,0 +1,357 @@
+//! The `cfg:<productions>` experiment family of src/experiments/static/cfg.cpp.
+//!
+//! Productions are written one per line, or separated by `;`, as
+//! `S -> a S b | a b`. Upper-case letters are nonterminals and any other
+//! character besides whitespace, `|`, `;` and `#` is a terminal. An empty
+//! alternative derives the empty sentence, `#` starts a comment, and the first
+//! production's left side is the start symbol. Sentences are over the
+//! terminals; see `sentences` for how they are chosen and encoded.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::sentences::{self, create_sentences, sentence_tests};
+use crate::static_evaluator::Test;
+use crate::AccNeatArgs;
+
+/// Balanced parentheses.
+pub const DYCK1: &str = "S -> ( S ) S |";
+/// Balanced parentheses and brackets.
+pub const DYCK2: &str = "S -> ( S ) S | [ S ] S |";
+/// a^n b^n, n >= 1.
+pub const ANBN: &str = "S -> a S b | a b";
+/// Even-length palindromes over {a, b}, like cfg-XSX.
+pub const PALINDROMES: &str = "S -> a S a | b S b |";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A line isn't `X -> ...`.
+    Syntax { line: usize, text: String },
+    NoProductions,
+    Undefined(char),
+    Sentences(sentences::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Syntax { line, text } => write!(f, "grammar line {}: expected 'X -> ...', got '{}'", line, text),
+            Error::NoProductions => write!(f, "grammar has no productions"),
+            Error::Undefined(c) => write!(f, "grammar has no productions for {}", c),
+            Error::Sentences(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+    pub lhs: char,
+    pub rhs: Vec<char>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    pub start: char,
+    pub productions: Vec<Production>,
+    /// Sorted.
+    pub terminals: Vec<char>,
+    nullable: HashSet<char>,
+}
+
+fn is_nonterminal(c: char) -> bool {
+    c.is_ascii_uppercase()
+}
+
+impl FromStr for Grammar {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut productions = vec![];
+        let mut terminals = BTreeSet::new();
+        let mut defined = HashSet::new();
+
+        for (i, line) in s.split(['\n', ';']).enumerate() {
+            let line: String = line.split('#').next().unwrap().chars().filter(|c| !c.is_whitespace()).collect();
+            if line.is_empty() {
+                continue;
+            }
+            let lhs = line.chars().next().unwrap();
+            let Some(alts) = line.strip_prefix(lhs).and_then(|l| l.strip_prefix("->")) else {
+                return Err(Error::Syntax { line: i + 1, text: line });
+            };
+            if !is_nonterminal(lhs) {
+                return Err(Error::Syntax { line: i + 1, text: line });
+            }
+            defined.insert(lhs);
+            for rhs in alts.split('|') {
+                terminals.extend(rhs.chars().filter(|&c| !is_nonterminal(c)));
+                productions.push(Production {
+                    lhs,
+                    rhs: rhs.chars().collect(),
+                });
+            }
+        }
+
+        let start = productions.first().ok_or(Error::NoProductions)?.lhs;
+        for p in &productions {
+            if let Some(&c) = p.rhs.iter().find(|&&c| is_nonterminal(c) && !defined.contains(&c)) {
+                return Err(Error::Undefined(c));
+            }
+        }
+
+        let mut nullable = HashSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for p in &productions {
+                if !nullable.contains(&p.lhs) && p.rhs.iter().all(|c| nullable.contains(c)) {
+                    nullable.insert(p.lhs);
+                    changed = true;
+                }
+            }
+        }
+
+        Ok(Self {
+            start,
+            productions,
+            terminals: terminals.into_iter().collect(),
+            nullable,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    production: usize,
+    dot: usize,
+    origin: usize,
+}
+
+impl Grammar {
+    /// Earley recognizer, with Aycock and Horspool's handling of nullable
+    /// nonterminals.
+    pub fn accepts(&self, sentence: &str) -> bool {
+        let sentence: Vec<char> = sentence.chars().collect();
+        let n = sentence.len();
+        let mut chart: Vec<Vec<Item>> = vec![vec![]; n + 1];
+        let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); n + 1];
+        let mut add = |chart: &mut Vec<Vec<Item>>, i: usize, item: Item| {
+            if seen[i].insert(item) {
+                chart[i].push(item);
+            }
+        };
+
+        for (p, prod) in self.productions.iter().enumerate() {
+            if prod.lhs == self.start {
+                add(&mut chart, 0, Item { production: p, dot: 0, origin: 0 });
+            }
+        }
+
+        for i in 0..=n {
+            let mut j = 0;
+            while j < chart[i].len() {
+                let item = chart[i][j];
+                let prod = &self.productions[item.production];
+                let advanced = Item { dot: item.dot + 1, ..item };
+                match prod.rhs.get(item.dot) {
+                    Some(&sym) if is_nonterminal(sym) => {
+                        for (p, q) in self.productions.iter().enumerate() {
+                            if q.lhs == sym {
+                                add(&mut chart, i, Item { production: p, dot: 0, origin: i });
+                            }
+                        }
+                        if self.nullable.contains(&sym) {
+                            add(&mut chart, i, advanced);
+                        }
+                    }
+                    Some(&sym) => {
+                        if i < n && sentence[i] == sym {
+                            add(&mut chart, i + 1, advanced);
+                        }
+                    }
+                    None => {
+                        let mut k = 0;
+                        while k < chart[item.origin].len() {
+                            let parent = chart[item.origin][k];
+                            if self.productions[parent.production].rhs.get(parent.dot) == Some(&prod.lhs) {
+                                add(&mut chart, i, Item { dot: parent.dot + 1, ..parent });
+                            }
+                            k += 1;
+                        }
+                    }
+                }
+                j += 1;
+            }
+        }
+
+        chart[n].iter().any(|item| {
+            let prod = &self.productions[item.production];
+            prod.lhs == self.start && item.origin == 0 && item.dot == prod.rhs.len()
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CfgExperiment {
+    /// The productions, or the path of a file of them, as passed to the binary.
+    pub grammar: String,
+    /// `cfg_max_len`.
+    pub max_len: usize,
+    /// `cfg_max_sentences`.
+    pub max_sentences: usize,
+}
+
+impl CfgExperiment {
+    pub fn new(grammar: &str) -> Self {
+        Self {
+            grammar: grammar.to_string(),
+            max_len: 8,
+            max_sentences: 256,
+        }
+    }
+
+    /// The experiment name understood by the `accneat` binary.
+    pub fn name(&self) -> String {
+        format!("cfg:{}", self.grammar)
+    }
+
+    /// `base` set up to run this experiment.
+    pub fn args(&self, base: &AccNeatArgs) -> AccNeatArgs {
+        let mut args = base.clone();
+        args.experiment = self.name();
+        args.params.push(("cfg_max_len".to_string(), self.max_len.to_string()));
+        args.params.push(("cfg_max_sentences".to_string(), self.max_sentences.to_string()));
+        args
+    }
+
+    /// Parses `grammar`, reading it from a file first if it names one.
+    pub fn parse_grammar(&self) -> Result<Grammar, Error> {
+        match std::fs::read_to_string(&self.grammar) {
+            Ok(text) => text.parse(),
+            Err(_) => self.grammar.parse(),
+        }
+    }
+
+    /// The grammatical and ungrammatical sentences, in that order.
+    pub fn sentences(&self) -> Result<(Vec<String>, Vec<String>), Error> {
+        let grammar = self.parse_grammar()?;
+        create_sentences(&grammar.terminals, self.max_len, self.max_sentences, |s| grammar.accepts(s)).map_err(Error::Sentences)
+    }
+
+    /// Same tests as `create_tests_cfg`.
+    pub fn tests(&self) -> Result<Vec<Test>, Error> {
+        let grammar = self.parse_grammar()?;
+        let (correct, incorrect) = self.sentences()?;
+        Ok(sentence_tests(&grammar.terminals, &correct, &incorrect))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{CpuNetwork, Real};
+    use crate::static_evaluator::evaluate;
+    use crate::{cmd_line, for_each_fittest, ExperimentType};
+    use serial_test::serial;
+
+    #[test]
+    fn test_parse() {
+        let g: Grammar = "S -> a S b | a b # comment\n\n".parse().unwrap();
+        assert_eq!(g.start, 'S');
+        assert_eq!(g.terminals, vec!['a', 'b']);
+        assert_eq!(g.productions.len(), 2);
+        assert_eq!(g.productions[0].rhs, vec!['a', 'S', 'b']);
+
+        let g: Grammar = "S -> A B; A -> a A |; B -> b".parse().unwrap();
+        assert_eq!(g.productions.len(), 4);
+        assert!(g.nullable.contains(&'A'));
+        assert!(!g.nullable.contains(&'S'));
+
+        assert_eq!("".parse::<Grammar>(), Err(Error::NoProductions));
+        assert_eq!("S -> a X".parse::<Grammar>(), Err(Error::Undefined('X')));
+        assert_eq!(
+            "S -> a\nx -> b".parse::<Grammar>(),
+            Err(Error::Syntax { line: 2, text: "x->b".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_accepts() {
+        let g: Grammar = DYCK1.parse().unwrap();
+        for s in ["", "()", "(())", "()()", "(()())()"] {
+            assert!(g.accepts(s), "{}", s);
+        }
+        for s in ["(", ")(", "(()", "())(", "a"] {
+            assert!(!g.accepts(s), "{}", s);
+        }
+
+        let g: Grammar = ANBN.parse().unwrap();
+        assert!(g.accepts("aaabbb"));
+        assert!(!g.accepts("aabbb"));
+        assert!(!g.accepts("abab"));
+
+        let g: Grammar = PALINDROMES.parse().unwrap();
+        assert!(g.accepts("abba"));
+        assert!(!g.accepts("aba"));
+
+        // Left recursion and chains of nullable nonterminals.
+        let g: Grammar = "S -> S a | B; B -> C C; C -> | b".parse().unwrap();
+        for s in ["", "a", "b", "bb", "bbaa", "baa"] {
+            assert!(g.accepts(s), "{}", s);
+        }
+        assert!(!g.accepts("bbb"));
+        assert!(!g.accepts("ab"));
+    }
+
+    #[test]
+    fn test_tests() {
+        let mut exp = CfgExperiment::new(ANBN);
+        exp.max_len = 6;
+        let (correct, incorrect) = exp.sentences().unwrap();
+        assert_eq!(correct, vec!["ab", "aabb", "aaabbb"]);
+        // 2 + 4 + ... + 64 sentences in all.
+        assert_eq!(incorrect.len(), 126 - 3);
+
+        let tests = exp.tests().unwrap();
+        assert_eq!(tests.len(), 126);
+        assert_eq!(tests[0].name, "ab");
+        assert_eq!(tests[0].steps[0].input, vec![1.0, 0.0, 0.0]);
+        assert_eq!(tests[0].steps[2].input, vec![1.0, 1.0, 0.0]);
+        assert_eq!(tests[0].steps[4].output, vec![1.0]);
+        assert_eq!(tests[0].steps[4].weight, 1.0 / 3.0);
+
+        exp.max_len = 1;
+        assert_eq!(exp.sentences(), Err(Error::Sentences(sentences::Error::OneClass)));
+
+        match "cfg:S->ab".parse::<ExperimentType>() {
+            Ok(ExperimentType::Cfg(grammar)) => assert_eq!(grammar, "S->ab"),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_matches_cpp_tests() {
+        let grammar_path = std::env::temp_dir().join(format!("accneat-dyck2-{}.cfg", std::process::id()));
+        std::fs::write(&grammar_path, "# Dyck-2\nS -> ( S ) S\nS -> [ S ] S\nS ->\n").unwrap();
+
+        let mut exp = CfgExperiment::new(grammar_path.to_str().unwrap());
+        exp.max_len = 6;
+        exp.max_sentences = 64;
+        let base = AccNeatArgs {
+            force_delete: true,
+            pop_size: 100,
+            maxgens: 5,
+            ..Default::default()
+        };
+        let tests = exp.tests().unwrap();
+        for_each_fittest("cfg", |_| cmd_line(exp.args(&base)), |genome| {
+            let eval = evaluate(&mut CpuNetwork::from_genome(genome), &tests);
+            assert!((eval.fitness - genome.info.fitness as Real).abs() < 1e-4);
+        });
+        std::fs::remove_file(grammar_path).unwrap();
+    }
+}
@@ -0,0 +1,444 @@
+//! Procedural T-mazes for the maze experiment.
+//!
+//! Mazes are H-trees: from the agent, corridors branch left and right, and
+//! every corridor ends in another left/right junction until `depth` turns have
+//! been made. Each corridor gets a food whose `seq` is the turns leading to it,
+//! so a maze of depth `d` has `2^(d+1) - 2` trials, like res/maze.map does for
+//! depth 3.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Formatter;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use super::{distance_map, turn, Direction, MAX_TRIALS, UNREACHABLE};
+use crate::map::{Glyph, Location, Map, DEFAULT_LABELS};
+use crate::rng::Rng;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    NoAgent,
+    BadAgentDir(String),
+    TooLarge { width: usize, height: usize, max: usize },
+    NoFood,
+    TooManyFoods { count: usize, max: usize },
+    BadSeq { row: char, col: char, seq: String },
+    SeqTooLong { row: char, col: char, seq: String, max: usize },
+    DuplicateSeq(String),
+    Unreachable { row: char, col: char, seq: String },
+    /// The distance doesn't fit the evaluator's 8-bit distance maps.
+    TooFar { row: char, col: char, seq: String },
+    /// A cell reachable by the agent is on the edge of the map, so it could
+    /// look or walk off the grid.
+    OpenBorder { row: char, col: char },
+    /// The `MazeSpec` field is zero.
+    BadSpec(&'static str),
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::NoAgent => write!(f, "map has no agent"),
+            ValidationError::BadAgentDir(d) => write!(f, "invalid agent dir '{}'", d),
+            ValidationError::TooLarge { width, height, max } => {
+                write!(f, "map is {}x{}, larger than {}x{}", width, height, max, max)
+            }
+            ValidationError::NoFood => write!(f, "map has no food with a seq attribute"),
+            ValidationError::TooManyFoods { count, max } => write!(f, "map has {} foods, more than {}", count, max),
+            ValidationError::BadSeq { row, col, seq } => write!(f, "{},{}: seq '{}' is not made of l and r", row, col, seq),
+            ValidationError::SeqTooLong { row, col, seq, max } => {
+                write!(f, "{},{}: seq '{}' is longer than {}", row, col, seq, max)
+            }
+            ValidationError::DuplicateSeq(seq) => write!(f, "more than one food has seq '{}'", seq),
+            ValidationError::Unreachable { row, col, seq } => {
+                write!(f, "{},{}: food '{}' is unreachable from the agent", row, col, seq)
+            }
+            ValidationError::TooFar { row, col, seq } => write!(f, "{},{}: food '{}' is too far from the agent", row, col, seq),
+            ValidationError::OpenBorder { row, col } => write!(f, "{},{}: reachable cell on the map border", row, col),
+            ValidationError::BadSpec(field) => write!(f, "maze spec {} must be at least 1", field),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks that `map` can be run by the maze experiment: one agent with a
+/// valid direction, food with distinct `l`/`r` sequences of at most
+/// `max_seq_len`, at most `MAX_TRIALS` of them, and every food reachable from
+/// the agent.
+pub fn validate(map: &Map, max_seq_len: usize, max_maze_len: usize) -> Result<(), ValidationError> {
+    if map.width > max_maze_len || map.height > max_maze_len {
+        return Err(ValidationError::TooLarge {
+            width: map.width,
+            height: map.height,
+            max: max_maze_len,
+        });
+    }
+    let agent = map.agent().ok_or(ValidationError::NoAgent)?;
+    let dir = agent.glyph.attrs.get("dir").map(String::as_str).unwrap_or("");
+    if Direction::parse(dir).is_none() {
+        return Err(ValidationError::BadAgentDir(dir.to_string()));
+    }
+
+    let from_agent = distance_map(map, agent.loc);
+    for (i, d) in from_agent.iter().enumerate() {
+        let loc = Location::new(i / map.width, i % map.width);
+        if *d != UNREACHABLE && (loc.row == 0 || loc.col == 0 || loc.row == map.height - 1 || loc.col == map.width - 1) {
+            let (row, col) = map.loc_trans.labels(loc);
+            return Err(ValidationError::OpenBorder { row, col });
+        }
+    }
+
+    let mut seqs = HashSet::new();
+    for food in map.objects_of_type("food") {
+        let Some(seq) = food.attrs.get("seq") else { continue };
+        let (row, col) = map.loc_trans.labels(food.loc);
+        let err_seq = seq.clone();
+        if seq.is_empty() || !seq.chars().all(|c| turn(c).is_some()) {
+            return Err(ValidationError::BadSeq { row, col, seq: err_seq });
+        }
+        if seq.len() > max_seq_len {
+            return Err(ValidationError::SeqTooLong {
+                row,
+                col,
+                seq: err_seq,
+                max: max_seq_len,
+            });
+        }
+        if !seqs.insert(seq.clone()) {
+            return Err(ValidationError::DuplicateSeq(err_seq));
+        }
+        if from_agent[food.loc.row * map.width + food.loc.col] == UNREACHABLE {
+            return Err(ValidationError::Unreachable { row, col, seq: err_seq });
+        }
+        // The evaluator keeps distances to the food in 8-bit maps, 0xff
+        // meaning unreachable.
+        let from_food = distance_map(map, food.loc);
+        if from_food.iter().any(|&d| d != UNREACHABLE && d >= u8::MAX as usize) {
+            return Err(ValidationError::TooFar { row, col, seq: err_seq });
+        }
+    }
+    if seqs.is_empty() {
+        return Err(ValidationError::NoFood);
+    }
+    if seqs.len() > MAX_TRIALS {
+        return Err(ValidationError::TooManyFoods {
+            count: seqs.len(),
+            max: MAX_TRIALS,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MazeSpec {
+    /// Number of turns to the deepest food.
+    pub depth: usize,
+    /// Length of the last corridors, which have the food at their end.
+    pub leaf_len: usize,
+    /// Extra cells added to every other corridor.
+    pub spacing: usize,
+    /// Up to this many extra cells are added to the corridors of each level,
+    /// chosen with `seed`.
+    pub jitter: usize,
+    pub seed: u64,
+    pub agent_dir: Direction,
+    /// Empty cells around the outer walls.
+    pub margin: usize,
+}
+
+impl Default for MazeSpec {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            leaf_len: 2,
+            spacing: 1,
+            jitter: 0,
+            seed: 1,
+            agent_dir: Direction::East,
+            margin: 1,
+        }
+    }
+}
+
+impl MazeSpec {
+    pub fn check(&self) -> Result<(), ValidationError> {
+        if self.depth == 0 {
+            return Err(ValidationError::BadSpec("depth"));
+        }
+        if self.leaf_len == 0 {
+            return Err(ValidationError::BadSpec("leaf_len"));
+        }
+        Ok(())
+    }
+
+    /// Corridor length for each level, starting with the corridors next to the
+    /// agent. A corridor must be longer than everything that branches off its
+    /// grandchildren back toward it, so that subtrees never touch.
+    ///
+    /// Panics unless `check` passes.
+    pub fn corridor_lengths(&self) -> Vec<usize> {
+        self.check().unwrap();
+        let mut rng = Rng::new(self.seed);
+        let mut lens = vec![0; self.depth];
+        for k in (0..self.depth).rev() {
+            let extra = rng.below(self.jitter as u64 + 1) as usize;
+            lens[k] = if k == self.depth - 1 {
+                self.leaf_len + extra
+            } else {
+                2 + self.spacing + extra + lens[k + 2..].iter().step_by(2).sum::<usize>()
+            };
+        }
+        lens
+    }
+}
+
+struct Layout {
+    lens: Vec<usize>,
+    corridor: HashSet<(isize, isize)>,
+    foods: Vec<((isize, isize), String)>,
+}
+
+impl Layout {
+    fn branch(&mut self, start: (isize, isize), heading: Direction, seq: String) {
+        let level = seq.len();
+        let (dr, dc) = heading.rel_pos();
+        let mut pos = start;
+        for _ in 0..self.lens[level - 1] {
+            pos = (pos.0 + dr, pos.1 + dc);
+            self.corridor.insert(pos);
+        }
+
+        if level == self.lens.len() {
+            self.foods.push((pos, seq));
+        } else {
+            self.foods.push(((pos.0 - dr, pos.1 - dc), seq.clone()));
+            for c in ['l', 'r'] {
+                self.branch(pos, heading.rotate(turn(c).unwrap()), format!("{}{}", seq, c));
+            }
+        }
+    }
+}
+
+fn glyphs() -> BTreeMap<char, Glyph> {
+    let mut glyphs = BTreeMap::new();
+    let mut add = |type_: &str, c: char, dir: Option<Direction>| {
+        let mut attrs = BTreeMap::new();
+        if let Some(dir) = dir {
+            attrs.insert("dir".to_string(), dir.name().to_string());
+        }
+        glyphs.insert(
+            c,
+            Glyph {
+                type_: type_.to_string(),
+                character: c,
+                attrs,
+            },
+        );
+    };
+    add("agent", '>', Some(Direction::East));
+    add("agent", '^', Some(Direction::North));
+    add("agent", '<', Some(Direction::West));
+    add("agent", 'v', Some(Direction::South));
+    add("food", 'f', None);
+    add("wall", '|', None);
+    add("wall", '~', None);
+    glyphs
+}
+
+fn agent_glyph(dir: Direction) -> char {
+    match dir {
+        Direction::East => '>',
+        Direction::North => '^',
+        Direction::West => '<',
+        Direction::South => 'v',
+    }
+}
+
+/// Generates a maze, checking it with `validate` for its own depth.
+pub fn generate(spec: &MazeSpec) -> Result<Map, ValidationError> {
+    spec.check()?;
+    let mut layout = Layout {
+        lens: spec.corridor_lengths(),
+        corridor: HashSet::new(),
+        foods: vec![],
+    };
+    layout.corridor.insert((0, 0));
+    for c in ['l', 'r'] {
+        layout.branch((0, 0), spec.agent_dir.rotate(turn(c).unwrap()), c.to_string());
+    }
+
+    let pad = 1 + spec.margin as isize;
+    let min_row = layout.corridor.iter().map(|p| p.0).min().unwrap() - pad;
+    let max_row = layout.corridor.iter().map(|p| p.0).max().unwrap() + pad;
+    let min_col = layout.corridor.iter().map(|p| p.1).min().unwrap() - pad;
+    let max_col = layout.corridor.iter().map(|p| p.1).max().unwrap() + pad;
+    let height = (max_row - min_row + 1) as usize;
+    let width = (max_col - min_col + 1) as usize;
+    if width > DEFAULT_LABELS.len() || height > DEFAULT_LABELS.len() {
+        return Err(ValidationError::TooLarge {
+            width,
+            height,
+            max: DEFAULT_LABELS.len(),
+        });
+    }
+    let loc = |p: (isize, isize)| Location::new((p.0 - min_row) as usize, (p.1 - min_col) as usize);
+
+    let mut map = Map::new(glyphs(), height, width);
+    let is_corridor = |r: isize, c: isize| layout.corridor.contains(&(r, c));
+    for &(r, c) in &layout.corridor {
+        for dr in -1..=1 {
+            for dc in -1..=1 {
+                let (wr, wc) = (r + dr, c + dc);
+                if is_corridor(wr, wc) {
+                    continue;
+                }
+                let glyph = if is_corridor(wr, wc - 1) || is_corridor(wr, wc + 1) { '|' } else { '~' };
+                map.set_object(loc((wr, wc)), glyph);
+            }
+        }
+    }
+    map.set_object(loc((0, 0)), agent_glyph(spec.agent_dir));
+    for (pos, seq) in &layout.foods {
+        map.set_object(loc(*pos), 'f').attrs.insert("seq".to_string(), seq.clone());
+    }
+
+    validate(&map, spec.depth, DEFAULT_LABELS.len())?;
+    Ok(map)
+}
+
+/// Mazes of increasing depth, otherwise generated from `spec`.
+pub fn curriculum(spec: &MazeSpec, depths: RangeInclusive<usize>) -> Result<Vec<Map>, ValidationError> {
+    depths
+        .map(|depth| generate(&MazeSpec { depth, ..spec.clone() }))
+        .collect()
+}
+
+/// Writes `maze_depth_N.map` files to `dir`, returning their paths.
+pub fn write_curriculum<P: AsRef<Path>>(dir: P, maps: &[Map]) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(&dir)?;
+    let mut paths = vec![];
+    for map in maps {
+        let depth = map.objects_of_type("food").filter_map(|o| o.attrs.get("seq")).map(|s| s.len()).max().unwrap_or(0);
+        let path = dir.as_ref().join(format!("maze_depth_{}.map", depth));
+        std::fs::write(&path, map.to_string())?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::maze::{is_wall, MAX_MAZE_LEN, MAX_SEQ_LEN};
+    use crate::map::{parse_map, tests::maze_map_path};
+
+    /// Follows the turns of `seq` from the agent, walking to the end of each
+    /// corridor, and returns the cells of the last corridor.
+    fn walk(map: &Map, seq: &str) -> Vec<Location> {
+        let agent = map.agent().unwrap();
+        let mut dir = Direction::parse(&agent.glyph.attrs["dir"]).unwrap();
+        let mut pos = agent.loc;
+        let mut cells = vec![];
+        for c in seq.chars() {
+            dir = dir.rotate(turn(c).unwrap());
+            cells.clear();
+            let (dr, dc) = dir.rel_pos();
+            loop {
+                let next = Location::new((pos.row as isize + dr) as usize, (pos.col as isize + dc) as usize);
+                if is_wall(map, next) {
+                    break;
+                }
+                pos = next;
+                cells.push(pos);
+            }
+            assert!(!cells.is_empty(), "no corridor for {}", seq);
+        }
+        cells
+    }
+
+    #[test]
+    fn test_validate_maze_map() {
+        let map = parse_map(maze_map_path()).unwrap();
+        validate(&map, MAX_SEQ_LEN, MAX_MAZE_LEN).unwrap();
+        assert_eq!(
+            validate(&map, 2, MAX_MAZE_LEN),
+            Err(ValidationError::SeqTooLong {
+                row: 'C',
+                col: 'H',
+                seq: "llr".to_string(),
+                max: 2
+            })
+        );
+
+        let mut blocked = map.clone();
+        blocked.set_object(Location::new(11, 12), '~');
+        blocked.set_object(Location::new(13, 12), '~');
+        assert!(matches!(
+            validate(&blocked, MAX_SEQ_LEN, MAX_MAZE_LEN),
+            Err(ValidationError::Unreachable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_generate() {
+        let map = generate(&MazeSpec::default()).unwrap();
+        validate(&map, MAX_SEQ_LEN, MAX_MAZE_LEN).unwrap();
+        assert_eq!(map, map.to_string().parse().unwrap());
+
+        let foods: Vec<_> = map.objects_of_type("food").collect();
+        assert_eq!(foods.len(), 14);
+        for food in foods {
+            let seq = &food.attrs["seq"];
+            assert!(walk(&map, seq).contains(&food.loc), "food {} is not where its turns lead", seq);
+        }
+    }
+
+    #[test]
+    fn test_generate_variants() {
+        for agent_dir in [Direction::North, Direction::South, Direction::West] {
+            for seed in 1..4 {
+                let spec = MazeSpec {
+                    depth: 4,
+                    jitter: 2,
+                    seed,
+                    agent_dir,
+                    ..Default::default()
+                };
+                let map = generate(&spec).unwrap();
+                for food in map.objects_of_type("food") {
+                    assert!(walk(&map, &food.attrs["seq"]).contains(&food.loc));
+                }
+            }
+        }
+        assert!(matches!(
+            generate(&MazeSpec { depth: 9, ..Default::default() }),
+            Err(ValidationError::TooLarge { .. })
+        ));
+        assert_eq!(
+            generate(&MazeSpec { depth: 0, ..Default::default() }),
+            Err(ValidationError::BadSpec("depth"))
+        );
+        assert_eq!(
+            generate(&MazeSpec { leaf_len: 0, ..Default::default() }),
+            Err(ValidationError::BadSpec("leaf_len"))
+        );
+    }
+
+    #[test]
+    fn test_curriculum() {
+        let maps = curriculum(&MazeSpec::default(), 1..=4).unwrap();
+        let nfoods: Vec<usize> = maps.iter().map(|m| m.objects_of_type("food").count()).collect();
+        assert_eq!(nfoods, vec![2, 6, 14, 30]);
+        for map in &maps {
+            validate(map, MAX_SEQ_LEN, MAX_MAZE_LEN).unwrap();
+        }
+        assert!(validate(&maps[3], 3, MAX_MAZE_LEN).is_err());
+
+        let dir = std::env::temp_dir().join(format!("accneat-mazes-{}", std::process::id()));
+        let paths = write_curriculum(&dir, &maps).unwrap();
+        assert!(paths[3].ends_with("maze_depth_4.map"));
+        assert_eq!(parse_map(&paths[3]).unwrap(), maps[3]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
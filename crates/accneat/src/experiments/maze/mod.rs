@@ -0,0 +1,107 @@
+//! The maze (T-maze sequence memory) experiment of src/experiments/maze.
+//!
+//! The agent hears a sequence of left/right turns and must then walk to the
+//! food at the end of that sequence. Food objects carry a `seq` attribute of
+//! `l`/`r` characters, with turns relative to the agent's heading.
+
+pub mod generator;
+pub mod simulator;
+
+use crate::map::{Location, Map};
+
+/// Same limits as `Max_Seq_Len`, `Max_Maze_Len` and `Max_Trials` in
+/// mazeevaluator.cxx.
+pub const MAX_SEQ_LEN: usize = 255;
+pub const MAX_MAZE_LEN: usize = 127;
+pub const MAX_TRIALS: usize = 254;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    East = 0,
+    North = 1,
+    West = 2,
+    South = 3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Clockwise = -1,
+    None = 0,
+    Counter = 1,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [Direction::East, Direction::North, Direction::West, Direction::South];
+
+    pub fn rotate(self, rot: Rotation) -> Direction {
+        Self::ALL[((self as i32 + rot as i32 + 4) % 4) as usize]
+    }
+
+    /// (row, col) offset of one step in this direction.
+    pub fn rel_pos(self) -> (isize, isize) {
+        match self {
+            Direction::East => (0, 1),
+            Direction::North => (-1, 0),
+            Direction::West => (0, -1),
+            Direction::South => (1, 0),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::East => "east",
+            Direction::North => "north",
+            Direction::West => "west",
+            Direction::South => "south",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Direction> {
+        Self::ALL.into_iter().find(|d| d.name() == s)
+    }
+}
+
+/// The turn taken for a `seq` character.
+pub fn turn(c: char) -> Option<Rotation> {
+    match c {
+        'l' => Some(Rotation::Counter),
+        'r' => Some(Rotation::Clockwise),
+        _ => None,
+    }
+}
+
+pub const UNREACHABLE: usize = usize::MAX;
+
+pub fn is_wall(map: &Map, loc: Location) -> bool {
+    map.objects.get(&loc).is_some_and(|o| o.glyph.type_ == "wall")
+}
+
+/// Number of steps from `from` to every cell, indexed by `row * width + col`,
+/// or `UNREACHABLE`. Same as `create_distance_map` in mazeevaluator.cxx, but
+/// without its 8-bit limit.
+pub fn distance_map(map: &Map, from: Location) -> Vec<usize> {
+    let mut dist = vec![UNREACHABLE; map.width * map.height];
+    if is_wall(map, from) {
+        return dist;
+    }
+    let mut queue = std::collections::VecDeque::new();
+    dist[from.row * map.width + from.col] = 0;
+    queue.push_back(from);
+    while let Some(loc) = queue.pop_front() {
+        let d = dist[loc.row * map.width + loc.col];
+        for dir in Direction::ALL {
+            let (dr, dc) = dir.rel_pos();
+            let (row, col) = (loc.row as isize + dr, loc.col as isize + dc);
+            if row < 0 || col < 0 || row >= map.height as isize || col >= map.width as isize {
+                continue;
+            }
+            let next = Location::new(row as usize, col as usize);
+            let i = next.row * map.width + next.col;
+            if dist[i] == UNREACHABLE && !is_wall(map, next) {
+                dist[i] = d + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
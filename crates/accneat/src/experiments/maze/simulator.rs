@@ -0,0 +1,657 @@
+//! A port of the maze evaluator (src/experiments/maze/mazeevaluator.cxx) that
+//! also records what the agent does, step by step, and renders it over the map.
+
+use std::fmt::Write;
+
+use super::generator::{validate, ValidationError};
+use super::{is_wall, Direction, Rotation, UNREACHABLE};
+use crate::map::{Location, Map};
+use crate::network::{CpuNetwork, OrganismEvaluation, Real};
+
+pub const SENSOR_RIGHT: usize = 0;
+pub const SENSOR_FWD: usize = 1;
+pub const SENSOR_LEFT: usize = 2;
+pub const SENSOR_SOUND: usize = 3;
+pub const SENSOR_FREQ: usize = 4;
+pub const SENSOR_GO: usize = 5;
+pub const NSENSORS: usize = 6;
+
+pub const OUTPUT_RIGHT: usize = 0;
+pub const OUTPUT_LEFT: usize = 1;
+pub const OUTPUT_FWD: usize = 2;
+pub const NOUTPUTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trial {
+    pub food_pos: Location,
+    pub seq: String,
+    pub max_steps: usize,
+    pub max_dist: usize,
+    pub dist_map: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MazeConfig {
+    pub width: usize,
+    pub height: usize,
+    pub agent_pos: Location,
+    pub agent_dir: Direction,
+    pub wall: Vec<bool>,
+    pub trials: Vec<Trial>,
+}
+
+impl MazeConfig {
+    /// Same as `create_config`, without the evaluator's size limits. Trials
+    /// are in map order, i.e. by row then column.
+    pub fn from_map(map: &Map) -> Result<Self, ValidationError> {
+        validate(map, usize::MAX, usize::MAX)?;
+        let agent = map.agent().unwrap();
+
+        let mut wall = vec![false; map.width * map.height];
+        for obj in map.objects_of_type("wall") {
+            wall[obj.loc.row * map.width + obj.loc.col] = true;
+        }
+
+        let mut trials = vec![];
+        for food in map.objects_of_type("food") {
+            if let Some(seq) = food.attrs.get("seq") {
+                let (dist_map, max_dist) = create_distance_map(map.width, map.height, &wall, food.loc);
+                let dist = dist_map[agent.loc.row * map.width + agent.loc.col];
+                trials.push(Trial {
+                    food_pos: food.loc,
+                    seq: seq.clone(),
+                    max_steps: dist + 3 * seq.len(),
+                    max_dist,
+                    dist_map,
+                });
+            }
+        }
+
+        Ok(Self {
+            width: map.width,
+            height: map.height,
+            agent_pos: agent.loc,
+            agent_dir: Direction::parse(&agent.glyph.attrs["dir"]).unwrap(),
+            wall,
+            trials,
+        })
+    }
+
+    fn is_wall(&self, pos: Location) -> bool {
+        self.wall[pos.row * self.width + pos.col]
+    }
+}
+
+/// Same as `create_distance_map` in mazeevaluator.cxx. The distances match
+/// `distance_map`, but `max_dist` is the largest distance ever written by its
+/// depth-first search, which can exceed the largest final distance.
+fn create_distance_map(width: usize, height: usize, wall: &[bool], from: Location) -> (Vec<usize>, usize) {
+    struct Search<'a> {
+        width: usize,
+        height: usize,
+        wall: &'a [bool],
+        dist_map: Vec<usize>,
+        max_dist: usize,
+    }
+
+    impl Search<'_> {
+        fn visit(&mut self, row: isize, col: isize, dist: usize) {
+            if row < 0 || col < 0 || row as usize >= self.height || col as usize >= self.width {
+                return;
+            }
+            let offset = self.width * row as usize + col as usize;
+            if dist >= self.dist_map[offset] || self.wall[offset] {
+                return;
+            }
+            self.dist_map[offset] = dist;
+            self.max_dist = self.max_dist.max(dist);
+            self.visit(row - 1, col, dist + 1);
+            self.visit(row + 1, col, dist + 1);
+            self.visit(row, col - 1, dist + 1);
+            self.visit(row, col + 1, dist + 1);
+        }
+    }
+
+    let mut search = Search {
+        width,
+        height,
+        wall,
+        dist_map: vec![UNREACHABLE; width * height],
+        max_dist: 0,
+    };
+    search.visit(from.row as isize, from.col as isize, 0);
+    (search.dist_map, search.max_dist)
+}
+
+fn step(pos: Location, dir: Direction) -> Location {
+    let (dr, dc) = dir.rel_pos();
+    Location::new((pos.row as isize + dr) as usize, (pos.col as isize + dc) as usize)
+}
+
+/// What the agent is hearing during a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Element `i` of the sequence is playing.
+    Sound(usize),
+    Silence,
+    Go,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryStep {
+    pub step: usize,
+    pub phase: Phase,
+    pub sensors: [Real; NSENSORS],
+    pub outputs: [Real; NOUTPUTS],
+    /// Position and direction after the outputs were applied.
+    pub pos: Location,
+    pub dir: Direction,
+    pub dist: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrialTrajectory {
+    pub trial: usize,
+    pub seq: String,
+    pub food_pos: Location,
+    pub steps: Vec<TrajectoryStep>,
+    pub reached_food: bool,
+    pub best_dist: usize,
+}
+
+/// State of a single organism's run through every trial, mirroring the
+/// `Evaluator` struct of mazeevaluator.cxx.
+struct Evaluator<'a> {
+    config: &'a MazeConfig,
+    trial: usize,
+    trial_step: usize,
+    agent_pos: Location,
+    agent_dir: Direction,
+    max_trial_steps: usize,
+    best_dist: usize,
+    /// Index of the sequence element being played, -1 for silence or -2 for go.
+    iseq: isize,
+    eval: OrganismEvaluation,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(config: &'a MazeConfig) -> Self {
+        Self {
+            config,
+            trial: 0,
+            trial_step: 0,
+            agent_pos: Location::new(0, 0),
+            agent_dir: Direction::East,
+            max_trial_steps: 1,
+            best_dist: 0xff,
+            iseq: 0,
+            eval: OrganismEvaluation::default(),
+        }
+    }
+
+    fn trial(&self) -> &'a Trial {
+        &self.config.trials[self.trial]
+    }
+
+    fn dist(&self) -> usize {
+        self.trial().dist_map[self.config.width * self.agent_pos.row + self.agent_pos.col]
+    }
+
+    fn next_step(&mut self) -> bool {
+        let trial_complete = self.best_dist == 0 || self.trial_step == self.max_trial_steps;
+        if trial_complete {
+            if self.trial == self.config.trials.len() - 1 {
+                return false;
+            }
+            self.trial += 1;
+            self.trial_step = 1;
+        } else {
+            self.trial_step += 1;
+        }
+
+        if self.trial_step == 1 {
+            self.agent_pos = self.config.agent_pos;
+            self.agent_dir = self.config.agent_dir;
+            self.max_trial_steps = self.trial().max_steps;
+            self.best_dist = self.dist();
+            self.iseq = 0;
+        } else if self.trial_step <= self.trial().seq.len() * 2 {
+            self.iseq = if self.trial_step.is_multiple_of(2) { -1 } else { (self.trial_step / 2) as isize };
+        } else {
+            self.iseq = -2;
+        }
+        true
+    }
+
+    fn clear_noninput(&self) -> bool {
+        self.trial_step == 1
+    }
+
+    fn phase(&self) -> Phase {
+        match self.iseq {
+            -2 => Phase::Go,
+            -1 => Phase::Silence,
+            i => Phase::Sound(i as usize),
+        }
+    }
+
+    fn obj_sensor(&self, rot: Rotation) -> Real {
+        if self.config.is_wall(step(self.agent_pos, self.agent_dir.rotate(rot))) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn sensors(&self) -> [Real; NSENSORS] {
+        let mut s = [0.0; NSENSORS];
+        s[SENSOR_RIGHT] = self.obj_sensor(Rotation::Clockwise);
+        s[SENSOR_FWD] = self.obj_sensor(Rotation::None);
+        s[SENSOR_LEFT] = self.obj_sensor(Rotation::Counter);
+        if let Phase::Sound(i) = self.phase() {
+            s[SENSOR_SOUND] = 1.0;
+            s[SENSOR_FREQ] = match self.trial().seq.as_bytes()[i] {
+                b'l' => 0.0,
+                _ => 1.0,
+            };
+        }
+        if self.phase() == Phase::Go {
+            s[SENSOR_GO] = 1.0;
+        }
+        s
+    }
+
+    fn evaluate(&mut self, output: &[Real]) {
+        if self.iseq != -2 {
+            return;
+        }
+        if output[OUTPUT_RIGHT] > 0.5 {
+            self.agent_dir = self.agent_dir.rotate(Rotation::Clockwise);
+        }
+        if output[OUTPUT_LEFT] > 0.5 {
+            self.agent_dir = self.agent_dir.rotate(Rotation::Counter);
+        }
+        if output[OUTPUT_FWD] > 0.5 {
+            let newpos = step(self.agent_pos, self.agent_dir);
+            if !self.config.is_wall(newpos) {
+                self.agent_pos = newpos;
+            }
+        }
+        let curr_dist = self.dist();
+        if curr_dist < self.best_dist {
+            self.best_dist = curr_dist;
+        }
+
+        let max_steps = self.max_trial_steps as Real;
+        if self.best_dist == 0 {
+            self.eval.fitness += 10.0 + (max_steps - self.trial_step as Real) / max_steps;
+        } else if self.trial_step == self.max_trial_steps {
+            self.eval.error += 1.0;
+            let max_dist = self.trial().max_dist as Real;
+            self.eval.fitness += (max_dist - self.best_dist as Real) / max_dist;
+            self.eval.fitness += (max_dist - curr_dist as Real) / max_dist;
+        }
+    }
+}
+
+/// What a controller is shown each step. A network only gets `sensors`, and
+/// has its non-input activations cleared when `clear_noninput` is set, i.e. at
+/// the start of each trial.
+#[derive(Debug, Clone, Copy)]
+pub struct Observation {
+    pub trial: usize,
+    pub clear_noninput: bool,
+    pub pos: Location,
+    pub dir: Direction,
+    pub sensors: [Real; NSENSORS],
+}
+
+/// Runs every trial, asking `controller` for the outputs of each step.
+pub fn run_with<F>(config: &MazeConfig, mut controller: F) -> (OrganismEvaluation, Vec<TrialTrajectory>)
+where
+    F: FnMut(&Observation) -> [Real; NOUTPUTS],
+{
+    let mut ev = Evaluator::new(config);
+    let mut trajectories: Vec<TrialTrajectory> = vec![];
+    while ev.next_step() {
+        if ev.clear_noninput() {
+            trajectories.push(TrialTrajectory {
+                trial: ev.trial,
+                seq: ev.trial().seq.clone(),
+                food_pos: ev.trial().food_pos,
+                steps: vec![],
+                reached_food: false,
+                best_dist: ev.best_dist,
+            });
+        }
+        let sensors = ev.sensors();
+        let phase = ev.phase();
+        let outputs = controller(&Observation {
+            trial: ev.trial,
+            clear_noninput: ev.clear_noninput(),
+            pos: ev.agent_pos,
+            dir: ev.agent_dir,
+            sensors,
+        });
+        ev.evaluate(&outputs);
+
+        let t = trajectories.last_mut().unwrap();
+        t.steps.push(TrajectoryStep {
+            step: ev.trial_step,
+            phase,
+            sensors,
+            outputs,
+            pos: ev.agent_pos,
+            dir: ev.agent_dir,
+            dist: ev.dist(),
+        });
+        t.best_dist = ev.best_dist;
+        t.reached_food = ev.best_dist == 0;
+    }
+    (ev.eval, trajectories)
+}
+
+/// Runs the network the way `CpuNetworkExecutor::execute` does.
+pub fn run(net: &mut CpuNetwork, config: &MazeConfig) -> (OrganismEvaluation, Vec<TrialTrajectory>) {
+    run_with(config, |obs| {
+        if obs.clear_noninput {
+            net.clear_noninput();
+        }
+        for (i, &s) in obs.sensors.iter().enumerate() {
+            net.load_sensor(i, s);
+        }
+        net.activate_scheduled();
+        let mut outputs = [0.0; NOUTPUTS];
+        outputs.copy_from_slice(&net.outputs()[..NOUTPUTS]);
+        outputs
+    })
+}
+
+pub fn evaluate(net: &mut CpuNetwork, config: &MazeConfig) -> OrganismEvaluation {
+    run(net, config).0
+}
+
+/// Where the agent ended each trial, as fractions of the maze's height and
+/// width: the behavior characterization novelty search uses for the maze.
+pub fn behavior(config: &MazeConfig, trajectories: &[TrialTrajectory]) -> Vec<Real> {
+    trajectories
+        .iter()
+        .flat_map(|t| {
+            let pos = t.steps.last().map_or(config.agent_pos, |s| s.pos);
+            [pos.row as Real / config.height as Real, pos.col as Real / config.width as Real]
+        })
+        .collect()
+}
+
+fn dir_char(dir: Direction) -> char {
+    match dir {
+        Direction::East => '>',
+        Direction::North => '^',
+        Direction::West => '<',
+        Direction::South => 'v',
+    }
+}
+
+/// Draws the map with the trial's path: `#` walls, `F` the trial's food, `f`
+/// other food, `S` the start, `*` visited cells and the agent's final heading.
+pub fn render(map: &Map, trajectory: &TrialTrajectory) -> String {
+    let mut grid = vec![vec![' '; map.width]; map.height];
+    for obj in map.objects.values() {
+        grid[obj.loc.row][obj.loc.col] = if is_wall(map, obj.loc) {
+            '#'
+        } else if obj.glyph.type_ == "food" {
+            'f'
+        } else {
+            ' '
+        };
+    }
+    if let Some(agent) = map.agent() {
+        grid[agent.loc.row][agent.loc.col] = 'S';
+    }
+    for s in &trajectory.steps {
+        if grid[s.pos.row][s.pos.col] == ' ' {
+            grid[s.pos.row][s.pos.col] = '*';
+        }
+    }
+    grid[trajectory.food_pos.row][trajectory.food_pos.col] = 'F';
+    if let Some(last) = trajectory.steps.last() {
+        grid[last.pos.row][last.pos.col] = dir_char(last.dir);
+    }
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "trial {} seq={} {}",
+        trajectory.trial,
+        trajectory.seq,
+        if trajectory.reached_food {
+            "reached food".to_string()
+        } else {
+            format!("best distance {}", trajectory.best_dist)
+        }
+    )
+    .unwrap();
+    for row in grid {
+        writeln!(out, "{}", row.into_iter().collect::<String>().trim_end()).unwrap();
+    }
+    out
+}
+
+/// One line per step: step, phase, position, direction, sensors and outputs.
+pub fn format_trajectory(map: &Map, trajectory: &TrialTrajectory) -> String {
+    let mut out = String::new();
+    writeln!(out, "step  phase     pos  dir    dist  sensors(r f l snd frq go)  outputs(r l f)").unwrap();
+    for s in &trajectory.steps {
+        let (row, col) = map.loc_trans.labels(s.pos);
+        let phase = match s.phase {
+            Phase::Sound(i) => format!("sound {}", i),
+            Phase::Silence => "silence".to_string(),
+            Phase::Go => "go".to_string(),
+        };
+        let sensors: Vec<String> = s.sensors.iter().map(|x| format!("{:.0}", x)).collect();
+        let outputs: Vec<String> = s.outputs.iter().map(|x| format!("{:.2}", x)).collect();
+        writeln!(
+            out,
+            "{:>4}  {:<8}  {},{}  {:<5}  {:>4}  {:<25}  {}",
+            s.step,
+            phase,
+            row,
+            col,
+            s.dir.name(),
+            s.dist,
+            sensors.join(" "),
+            outputs.join(" ")
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Outputs of an agent that knows where the food is: it steps to whichever
+/// neighbour ahead or to the side is closer to it, or turns around.
+pub fn oracle(config: &MazeConfig, obs: &Observation) -> [Real; NOUTPUTS] {
+    let (pos, dir) = (obs.pos, obs.dir);
+    let trial = &config.trials[obs.trial];
+    let dist = |p: Location| trial.dist_map[p.row * config.width + p.col];
+    let mut best = (dist(pos), None);
+    for rot in [Rotation::None, Rotation::Counter, Rotation::Clockwise] {
+        let next = step(pos, dir.rotate(rot));
+        if !config.is_wall(next) && dist(next) < best.0 {
+            best = (dist(next), Some(rot));
+        }
+    }
+    let mut out = [0.0; NOUTPUTS];
+    match best.1 {
+        Some(Rotation::Clockwise) => out[OUTPUT_RIGHT] = 1.0,
+        Some(Rotation::Counter) => out[OUTPUT_LEFT] = 1.0,
+        Some(Rotation::None) => {}
+        // Dead end behind us: turn around over two steps.
+        None => {
+            out[OUTPUT_RIGHT] = 1.0;
+            return out;
+        }
+    }
+    out[OUTPUT_FWD] = 1.0;
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::maze::generator::{generate, MazeSpec};
+    use crate::for_each_fittest;
+    use crate::map::{parse_map, tests::maze_map_path};
+    use serial_test::serial;
+    use std::path::Path;
+
+    fn maze() -> (Map, MazeConfig) {
+        let map = parse_map(maze_map_path()).unwrap();
+        let config = MazeConfig::from_map(&map).unwrap();
+        (map, config)
+    }
+
+    #[test]
+    fn test_config() {
+        let (map, config) = maze();
+        assert_eq!(config.trials.len(), 14);
+        assert_eq!(config.agent_pos, map.loc_trans.find('M', 'M').unwrap());
+        assert_eq!(config.agent_dir, Direction::East);
+        // Trials are in map order; the first food is C,H.
+        assert_eq!(config.trials[0].seq, "llr");
+        for t in &config.trials {
+            let dist = t.dist_map[config.agent_pos.row * config.width + config.agent_pos.col];
+            assert_eq!(t.max_steps, dist + 3 * t.seq.len());
+        }
+    }
+
+    #[test]
+    fn test_distance_map() {
+        let (map, config) = maze();
+        for t in &config.trials {
+            assert_eq!(t.dist_map, super::super::distance_map(&map, t.food_pos));
+            assert!(t.max_dist >= t.dist_map.iter().copied().filter(|&d| d != UNREACHABLE).max().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_oracle_solves_maze() {
+        let (map, config) = maze();
+        let (eval, trajectories) = run_with(&config, |obs| oracle(&config, obs));
+        assert_eq!(eval.error, 0.0);
+        assert!(eval.fitness > 10.0 * config.trials.len() as Real);
+        for t in &trajectories {
+            assert!(t.reached_food);
+            assert_eq!(t.steps.last().unwrap().pos, t.food_pos);
+        }
+
+        let drawn = render(&map, &trajectories[0]);
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(lines[0], "trial 0 seq=llr reached food");
+        assert_eq!(lines.len(), map.height + 1);
+        // C,H is the food, reached heading north.
+        assert_eq!(lines[1 + 2].chars().nth(7), Some('^'));
+        assert_eq!(lines[1 + 12].chars().nth(12), Some('S'));
+        assert_eq!(lines[1 + 11].chars().nth(12), Some('*'));
+        assert_eq!(lines[1 + 2].chars().nth(17), Some('f'));
+
+        let table = format_trajectory(&map, &trajectories[0]);
+        assert_eq!(table.lines().count(), trajectories[0].steps.len() + 1);
+        assert!(table.lines().nth(1).unwrap().starts_with("   1  sound 0   M,M  east"));
+    }
+
+    #[test]
+    fn test_sensors_and_phases() {
+        let (_, config) = maze();
+        let (eval, trajectories) = run_with(&config, |_| [0.0; NOUTPUTS]);
+        assert_eq!(eval.error, 14.0);
+        assert_eq!(trajectories.len(), 14);
+
+        let t = &trajectories[0];
+        assert_eq!(t.seq, "llr");
+        assert_eq!(t.steps.len(), config.trials[0].max_steps);
+        let phases: Vec<Phase> = t.steps.iter().take(7).map(|s| s.phase).collect();
+        assert_eq!(
+            phases,
+            vec![Phase::Sound(0), Phase::Silence, Phase::Sound(1), Phase::Silence, Phase::Sound(2), Phase::Silence, Phase::Go]
+        );
+        // Facing east in a north-south corridor: walls ahead, none to the sides.
+        assert_eq!(t.steps[0].sensors, [0.0, 1.0, 0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(t.steps[4].sensors[SENSOR_FREQ], 1.0);
+        assert_eq!(t.steps[6].sensors[SENSOR_GO], 1.0);
+        assert!(t.steps.iter().all(|s| s.pos == config.agent_pos));
+
+        let start = [
+            config.agent_pos.row as Real / config.height as Real,
+            config.agent_pos.col as Real / config.width as Real,
+        ];
+        assert_eq!(behavior(&config, &trajectories), start.repeat(14));
+    }
+
+    /// Runs the C++ maze experiment on `map` in `name` and checks the
+    /// fitness of its fittest organisms against `evaluate`.
+    fn check_matches_cpp(name: &str, map: &Map) {
+        let config = MazeConfig::from_map(map).unwrap();
+        let args = |dir: &Path| {
+            let map_path = dir.join("maze.map");
+            std::fs::write(&map_path, map.to_string()).unwrap();
+            ["-f", "-x", "5", "-n", "100", "-m", map_path.to_str().unwrap(), "maze"].map(String::from).to_vec()
+        };
+        for_each_fittest(name, args, |genome| {
+            let eval = evaluate(&mut CpuNetwork::from_genome(genome), &config);
+            let expected = genome.info.fitness as Real;
+            assert!((eval.fitness - expected).abs() <= 1e-4 * expected.abs().max(1.0), "{} != {}", eval.fitness, expected);
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_matches_cpp_evaluator() {
+        let (map, _) = maze();
+        check_matches_cpp("maze", &map);
+    }
+
+    #[test]
+    #[serial]
+    fn test_matches_cpp_evaluator_depth_4() {
+        let map = generate(&MazeSpec { depth: 4, ..Default::default() }).unwrap();
+        check_matches_cpp("maze-depth-4", &map);
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_rejects_unreachable_food() {
+        let (mut map, _) = maze();
+        map.set_object(Location::new(11, 12), '~');
+        map.set_object(Location::new(13, 12), '~');
+        let dir = std::env::temp_dir().join(format!("accneat-maze-unreachable-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let map_path = dir.join("maze.map");
+        std::fs::write(&map_path, map.to_string()).unwrap();
+        let args = ["-f", "-x", "1", "-n", "10", "-m", map_path.to_str().unwrap(), "maze"].map(String::from);
+        let (_, err) = crate::execute_cmd_line(&args, &dir).unwrap();
+        assert!(err.contains("is unreachable or more than 254 steps from the agent"), "{}", err);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_rejects_bad_maps() {
+        let dir = std::env::temp_dir().join(format!("accneat-maze-bad-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let map = std::fs::read_to_string(maze_map_path()).unwrap();
+        for (from, to, expected) in [
+            ("H,M f seq = l\n", "H,M f seq = x\n", "expected only 'l' and 'r'"),
+            ("> dir = east", "> dir = up", "Invalid agent dir 'up'"),
+        ] {
+            assert!(map.contains(from));
+            let map_path = dir.join("maze.map");
+            std::fs::write(&map_path, map.replace(from, to)).unwrap();
+            let args = ["-f", "-x", "1", "-n", "10", "-m", map_path.to_str().unwrap(), "maze"].map(String::from);
+            let o = crate::build_cmd(&args, &dir).unwrap();
+            assert!(!o.status.success());
+            let err = String::from_utf8_lossy(&o.stderr);
+            assert!(err.contains(expected), "{}", err);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,12 @@
+//! Test sets mirroring the static experiments registered by the C++ engine
+//! under src/experiments/static, and support code for the other experiments.
+
+pub mod cfg;
+pub mod maze;
+pub mod pole;
+pub mod regex;
+pub mod sentences;
+pub mod sequence;
+mod xor;
+
+pub use xor::xor;
@@ -0,0 +1,516 @@
+//! The pole balancing experiments of src/experiments/polebalance.
+//!
+//! A cart on a 4.8m track carries one or two poles, and the network pushes it
+//! left or right each step. The Markovian variants (`pole1`, `pole2`) show the
+//! network every velocity and score the fraction of `max_steps` balanced. The
+//! non-Markovian ones (`pole1-nv`, `pole2-nv`) only show positions, and score
+//! a 1000 step run with Gruau's anti-jiggle fitness; organisms that balance
+//! all of it go on to the long run, which is what success is judged by.
+//!
+//! `cartpole` is single pole balancing as an `Environment`, see `CartPoleEnv`.
+
+use crate::environment::{splitmix64_uniform, EnvStep, Environment, EnvironmentSpec};
+use crate::network::{CpuNetwork, OrganismEvaluation, Real};
+use crate::AccNeatArgs;
+
+/// Length of the anti-jiggle run.
+pub const GRUAU_STEPS: usize = 1000;
+/// Steps at the end of the anti-jiggle run whose jiggle is summed.
+pub const JIGGLE_WINDOW: usize = 100;
+
+const GRAVITY: Real = -9.8;
+const MASSCART: Real = 1.0;
+const MASSPOLE_1: Real = 0.1;
+const LENGTH_1: Real = 0.5; // Half the pole's length
+const MASSPOLE_2: Real = 0.01;
+const LENGTH_2: Real = 0.05;
+const FORCE_MAG: Real = 10.0;
+const TAU: Real = 0.01;
+const MUP: Real = 0.000002; // Friction of the hinges
+const TRACK_LIMIT: Real = 2.4;
+const ONE_DEGREE: Real = 0.0174532;
+const INITIAL_ANGLE: Real = 0.07;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoleVariant {
+    pub npoles: usize,
+    /// Whether the network is shown velocities.
+    pub velocities: bool,
+    /// Whether fitness is Gruau's anti-jiggle fitness.
+    pub anti_jiggle: bool,
+}
+
+impl PoleVariant {
+    pub const POLE1: Self = Self::new(1, true, false);
+    pub const POLE1_NV: Self = Self::new(1, false, true);
+    pub const POLE2: Self = Self::new(2, true, false);
+    pub const POLE2_NV: Self = Self::new(2, false, true);
+
+    const fn new(npoles: usize, velocities: bool, anti_jiggle: bool) -> Self {
+        Self {
+            npoles,
+            velocities,
+            anti_jiggle,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pole1" => Some(Self::POLE1),
+            "pole1-nv" => Some(Self::POLE1_NV),
+            "pole2" => Some(Self::POLE2),
+            "pole2-nv" => Some(Self::POLE2_NV),
+            _ => None,
+        }
+    }
+
+    /// The experiment name understood by the `accneat` binary.
+    pub fn name(&self) -> &'static str {
+        match (self.npoles, self.velocities) {
+            (1, true) => "pole1",
+            (1, false) => "pole1-nv",
+            (_, true) => "pole2",
+            (_, false) => "pole2-nv",
+        }
+    }
+
+    /// Same as `pole_balance_nsensors`.
+    pub fn nsensors(&self) -> usize {
+        (if self.velocities { 2 } else { 1 }) * (1 + self.npoles)
+    }
+}
+
+/// Cart with one or two poles hinged side by side, as in Wieland's double
+/// pole benchmark. Integrated with fourth-order Runge-Kutta, two `TAU` steps
+/// per action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CartPole {
+    pub npoles: usize,
+    /// x, x_dot, theta_1, theta_1_dot, theta_2, theta_2_dot.
+    pub state: [Real; 6],
+}
+
+impl CartPole {
+    /// At rest in the middle of the track, the long pole leaning about 4 degrees.
+    pub fn new(npoles: usize) -> Self {
+        let mut state = [0.0; 6];
+        state[2] = INITIAL_ANGLE;
+        Self { npoles, state }
+    }
+
+    pub fn failure_angle(&self) -> Real {
+        (if self.npoles == 1 { 12.0 } else { 36.0 }) * ONE_DEGREE
+    }
+
+    pub fn outside_bounds(&self) -> bool {
+        let fa = self.failure_angle();
+        let s = &self.state;
+        s[0] < -TRACK_LIMIT || s[0] > TRACK_LIMIT || s[2] < -fa || s[2] > fa || s[4] < -fa || s[4] > fa
+    }
+
+    fn derivs(&self, action: Real, st: &[Real; 6]) -> [Real; 6] {
+        let force = (action - 0.5) * FORCE_MAG * 2.0;
+
+        let costheta_1 = st[2].cos();
+        let gsintheta_1 = GRAVITY * st[2].sin();
+        let ml_1 = LENGTH_1 * MASSPOLE_1;
+        let temp_1 = MUP * st[3] / ml_1;
+        let fi_1 = (ml_1 * st[3] * st[3] * st[2].sin()) + (0.75 * MASSPOLE_1 * costheta_1 * (temp_1 + gsintheta_1));
+        let mi_1 = MASSPOLE_1 * (1.0 - (0.75 * costheta_1 * costheta_1));
+
+        let (mut costheta_2, mut gsintheta_2, mut temp_2, mut fi_2, mut mi_2) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        if self.npoles == 2 {
+            costheta_2 = st[4].cos();
+            gsintheta_2 = GRAVITY * st[4].sin();
+            let ml_2 = LENGTH_2 * MASSPOLE_2;
+            temp_2 = MUP * st[5] / ml_2;
+            fi_2 = (ml_2 * st[5] * st[5] * st[4].sin()) + (0.75 * MASSPOLE_2 * costheta_2 * (temp_2 + gsintheta_2));
+            mi_2 = MASSPOLE_2 * (1.0 - (0.75 * costheta_2 * costheta_2));
+        }
+
+        let mut d = [0.0; 6];
+        d[0] = st[1];
+        d[1] = (force + fi_1 + fi_2) / (mi_1 + mi_2 + MASSCART);
+        d[2] = st[3];
+        d[3] = -0.75 * (d[1] * costheta_1 + gsintheta_1 + temp_1) / LENGTH_1;
+        if self.npoles == 2 {
+            d[4] = st[5];
+            d[5] = -0.75 * (d[1] * costheta_2 + gsintheta_2 + temp_2) / LENGTH_2;
+        }
+        d
+    }
+
+    fn rk4(&mut self, action: Real) {
+        let hh = TAU * 0.5;
+        let h6 = TAU / 6.0;
+        let mut yt = [0.0; 6];
+
+        let dydx = self.derivs(action, &self.state);
+        for i in 0..6 {
+            yt[i] = self.state[i] + hh * dydx[i];
+        }
+        let dyt = self.derivs(action, &yt);
+        for i in 0..6 {
+            yt[i] = self.state[i] + hh * dyt[i];
+        }
+        let mut dym = self.derivs(action, &yt);
+        for i in 0..6 {
+            yt[i] = self.state[i] + TAU * dym[i];
+            dym[i] += dyt[i];
+        }
+        let dyt = self.derivs(action, &yt);
+        for i in 0..6 {
+            self.state[i] += h6 * (dydx[i] + dyt[i] + 2.0 * dym[i]);
+        }
+    }
+
+    /// `action` is in [0, 1]: 0 pushes left at full force, 1 right.
+    pub fn step(&mut self, action: Real) {
+        for _ in 0..2 {
+            self.rk4(action);
+        }
+    }
+
+    /// What a network of `variant` is shown.
+    pub fn sensors(&self, variant: &PoleVariant) -> Vec<Real> {
+        let s = &self.state;
+        let sensors = if variant.velocities {
+            vec![s[0] / 4.8, s[1] / 2.0, s[2] / 0.52, s[3] / 2.0, s[4] / 0.52, s[5] / 2.0]
+        } else {
+            vec![s[0] / 4.8, s[2] / 0.52, s[4] / 0.52]
+        };
+        sensors[..variant.nsensors()].to_vec()
+    }
+
+    fn jiggle(&self) -> Real {
+        self.state[..4].iter().map(|v| v.abs()).sum()
+    }
+}
+
+/// Steps in an episode of `CartPoleEnv`.
+pub const CARTPOLE_MAX_STEPS: usize = 500;
+
+/// The spec the `cartpole` experiment is registered with.
+pub const CARTPOLE_SPEC: EnvironmentSpec = EnvironmentSpec {
+    nsensors: 4,
+    noutputs: 1,
+    nepisodes: 10,
+    max_return: CARTPOLE_MAX_STEPS as Real,
+};
+
+/// Same as `CartPoleEnvironment` of cartpoleenvironment.cpp: each episode
+/// starts the cart and pole in a random state within 0.05 of rest, and pays 1
+/// for each step the pole stays up, up to `CARTPOLE_MAX_STEPS`.
+#[derive(Debug, Clone)]
+pub struct CartPoleEnv {
+    pub cart: CartPole,
+    nsteps: usize,
+}
+
+impl Default for CartPoleEnv {
+    fn default() -> Self {
+        Self {
+            cart: CartPole::new(1),
+            nsteps: 0,
+        }
+    }
+}
+
+impl Environment for CartPoleEnv {
+    fn reset(&mut self, mut seed: u64) -> Vec<Real> {
+        self.cart = CartPole::new(1);
+        for i in 0..4 {
+            self.cart.state[i] = splitmix64_uniform(&mut seed, -0.05, 0.05);
+        }
+        self.nsteps = 0;
+        self.cart.sensors(&PoleVariant::POLE1)
+    }
+
+    fn step(&mut self, action: &[Real]) -> EnvStep {
+        self.cart.step(action[0]);
+        let observation = self.cart.sensors(&PoleVariant::POLE1);
+        if self.cart.outside_bounds() {
+            return EnvStep {
+                observation,
+                reward: 0.0,
+                done: true,
+            };
+        }
+        self.nsteps += 1;
+        EnvStep {
+            observation,
+            reward: 1.0,
+            done: self.nsteps == CARTPOLE_MAX_STEPS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoleExperiment {
+    pub variant: PoleVariant,
+    /// `pole_max_steps`.
+    pub max_steps: usize,
+}
+
+impl PoleExperiment {
+    pub fn new(variant: PoleVariant) -> Self {
+        Self {
+            variant,
+            max_steps: 100000,
+        }
+    }
+
+    /// `base` set up to run this experiment.
+    pub fn args(&self, base: &AccNeatArgs) -> AccNeatArgs {
+        let mut args = base.clone();
+        args.experiment = self.variant.name().to_string();
+        args.params.push(("pole_max_steps".to_string(), self.max_steps.to_string()));
+        args
+    }
+}
+
+/// What a controller is shown each step.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    /// Set on the first step of each run, when a network's non-input
+    /// activations are cleared.
+    pub clear_noninput: bool,
+    pub cart: CartPole,
+    pub sensors: Vec<Real>,
+}
+
+/// Balances from `cart` for at most `max_steps`, returning the steps survived
+/// and the jiggle of the last `JIGGLE_WINDOW` of them.
+fn balance<F>(variant: &PoleVariant, mut cart: CartPole, max_steps: usize, controller: &mut F) -> (usize, Real)
+where
+    F: FnMut(&Observation) -> Real,
+{
+    let mut jiggle = [0.0; JIGGLE_WINDOW];
+    let mut step = 0;
+    while step < max_steps {
+        let action = controller(&Observation {
+            clear_noninput: step == 0,
+            cart,
+            sensors: cart.sensors(variant),
+        });
+        cart.step(action);
+        if cart.outside_bounds() {
+            break;
+        }
+        jiggle[step % JIGGLE_WINDOW] = cart.jiggle();
+        step += 1;
+    }
+    (step, jiggle.iter().sum())
+}
+
+/// Scores `controller` the way the C++ evaluator does.
+pub fn run_with<F>(exp: &PoleExperiment, mut controller: F) -> OrganismEvaluation
+where
+    F: FnMut(&Observation) -> Real,
+{
+    let variant = &exp.variant;
+    let mut eval = OrganismEvaluation {
+        fitness: 0.0,
+        error: 1.0,
+    };
+    if variant.anti_jiggle {
+        let (steps, jiggle) = balance(variant, CartPole::new(variant.npoles), GRUAU_STEPS, &mut controller);
+        let f1 = steps as Real / GRUAU_STEPS as Real;
+        let f2 = if steps >= JIGGLE_WINDOW { 0.75 / jiggle } else { 0.0 };
+        eval.fitness = 0.1 * f1 + 0.9 * f2;
+        if steps < GRUAU_STEPS {
+            return eval;
+        }
+    }
+    let (steps, _) = balance(variant, CartPole::new(variant.npoles), exp.max_steps, &mut controller);
+    let balanced = steps as Real / exp.max_steps as Real;
+    if !variant.anti_jiggle {
+        eval.fitness = balanced;
+    }
+    eval.error = 1.0 - balanced;
+    eval
+}
+
+fn activate(net: &mut CpuNetwork, obs: &Observation) -> Real {
+    if obs.clear_noninput {
+        net.clear_noninput();
+    }
+    for (i, &s) in obs.sensors.iter().enumerate() {
+        net.load_sensor(i, s);
+    }
+    net.activate_scheduled();
+    net.outputs()[0]
+}
+
+/// Runs the network the way `CpuNetworkExecutor::execute` does.
+pub fn evaluate(net: &mut CpuNetwork, exp: &PoleExperiment) -> OrganismEvaluation {
+    run_with(exp, |obs| activate(net, obs))
+}
+
+/// Gruau's generalization test: the number of 625 starts, spread over the
+/// cart's position and velocity and the long pole's angle and angular
+/// velocity, from which the network balances for 1000 steps. 200 is the usual
+/// bar for a controller that generalizes.
+pub fn generalization(net: &mut CpuNetwork, variant: &PoleVariant) -> usize {
+    const POINTS: [Real; 5] = [0.05, 0.25, 0.5, 0.75, 0.95];
+    let range = |v: Real, limit: Real| v * 2.0 * limit - limit;
+    let mut nbalanced = 0;
+    for &x in &POINTS {
+        for &x_dot in &POINTS {
+            for &theta in &POINTS {
+                for &theta_dot in &POINTS {
+                    let mut cart = CartPole::new(variant.npoles);
+                    cart.state[0] = range(x, 2.16);
+                    cart.state[1] = range(x_dot, 1.35);
+                    cart.state[2] = range(theta, 3.6 * ONE_DEGREE);
+                    cart.state[3] = range(theta_dot, 8.6 * ONE_DEGREE);
+                    let (steps, _) = balance(variant, cart, GRUAU_STEPS, &mut |obs| activate(net, obs));
+                    if steps == GRUAU_STEPS {
+                        nbalanced += 1;
+                    }
+                }
+            }
+        }
+    }
+    nbalanced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_cmd, cmd_line, for_each_fittest};
+    use serial_test::serial;
+
+    #[test]
+    fn test_variants() {
+        for name in ["pole1", "pole1-nv", "pole2", "pole2-nv"] {
+            assert_eq!(PoleVariant::from_name(name).unwrap().name(), name);
+        }
+        assert_eq!(PoleVariant::from_name("pole3"), None);
+        assert_eq!(PoleVariant::POLE1.nsensors(), 4);
+        assert_eq!(PoleVariant::POLE2.nsensors(), 6);
+        assert_eq!(PoleVariant::POLE2_NV.nsensors(), 3);
+        assert_eq!(CartPole::new(2).sensors(&PoleVariant::POLE2_NV), vec![0.0, 0.07 / 0.52, 0.0]);
+        assert!(matches!("pole2-nv".parse(), Ok(crate::ExperimentType::Pole2Nv)));
+    }
+
+    #[test]
+    fn test_physics() {
+        // Unpushed, the leaning pole falls further and pulls the cart back.
+        let mut cart = CartPole::new(1);
+        cart.step(0.5);
+        assert!(cart.state[2] > INITIAL_ANGLE && cart.state[3] > 0.0);
+        assert!(cart.state[1] < 0.0);
+        let mut steps = 1;
+        while !cart.outside_bounds() {
+            cart.step(0.5);
+            steps += 1;
+        }
+        assert!(steps < 100);
+        assert!(cart.state[2] > cart.failure_angle());
+
+        // Pushing right moves the cart right and pushes the pole left.
+        let mut cart = CartPole::new(2);
+        cart.step(1.0);
+        assert!(cart.state[1] > 0.0 && cart.state[3] < 0.0 && cart.state[5] < 0.0);
+    }
+
+    #[test]
+    fn test_run_with() {
+        let mut exp = PoleExperiment::new(PoleVariant::POLE1);
+        exp.max_steps = 1000;
+        let eval = run_with(&exp, |_| 0.5);
+        assert!(eval.fitness > 0.0 && eval.fitness < 0.1);
+        assert_eq!(eval.error, 1.0 - eval.fitness);
+
+        // Proportional-derivative control is enough for one pole.
+        let pd = |obs: &Observation| {
+            let s = &obs.cart.state;
+            (0.5 + s[0] + s[1] + 10.0 * s[2] + 2.0 * s[3]).clamp(0.0, 1.0)
+        };
+        let eval = run_with(&exp, pd);
+        assert_eq!(eval.fitness, 1.0);
+        assert_eq!(eval.error, 0.0);
+
+        // Anti-jiggle fitness goes up the less the cart and pole move at the end.
+        exp.variant = PoleVariant::POLE1_NV;
+        let eval = run_with(&exp, pd);
+        assert!(eval.fitness > 0.1);
+        assert_eq!(eval.error, 0.0);
+        let eval = run_with(&exp, |_| 0.5);
+        assert!(eval.fitness < 0.01);
+        assert_eq!(eval.error, 1.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_matches_cpp_evaluator() {
+        for variant in [PoleVariant::POLE1, PoleVariant::POLE1_NV, PoleVariant::POLE2, PoleVariant::POLE2_NV] {
+            let mut exp = PoleExperiment::new(variant);
+            exp.max_steps = 2000;
+            let base = AccNeatArgs {
+                force_delete: true,
+                pop_size: 100,
+                maxgens: 5,
+                ..Default::default()
+            };
+            for_each_fittest("pole", |_| cmd_line(exp.args(&base)), |genome| {
+                let eval = evaluate(&mut CpuNetwork::from_genome(genome), &exp);
+                let expected = genome.info.fitness as Real;
+                assert!((eval.fitness - expected).abs() <= 1e-4 * expected.max(1.0), "{} != {}", eval.fitness, expected);
+            });
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_runs_complete() {
+        let dir = std::env::temp_dir().join(format!("accneat-pole-runs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for variant in [PoleVariant::POLE1, PoleVariant::POLE1_NV, PoleVariant::POLE2, PoleVariant::POLE2_NV] {
+            let mut exp = PoleExperiment::new(variant);
+            exp.max_steps = 2000;
+            for rng_seed in [1, 2] {
+                let base = AccNeatArgs {
+                    force_delete: true,
+                    rng_seed,
+                    pop_size: 100,
+                    maxgens: 5,
+                    ..Default::default()
+                };
+                let o = build_cmd(&cmd_line(exp.args(&base)), &dir).unwrap();
+                assert!(o.status.success(), "{} -r {}: {}", variant.name(), rng_seed, String::from_utf8_lossy(&o.stderr));
+                let log = std::fs::read_to_string(dir.join("experiments/experiment_1/generations.csv")).unwrap();
+                assert!(log.lines().count() > 1);
+            }
+
+            exp.max_steps = 0;
+            let o = build_cmd(&cmd_line(exp.args(&AccNeatArgs { force_delete: true, ..Default::default() })), &dir).unwrap();
+            assert!(!o.status.success());
+            assert!(String::from_utf8_lossy(&o.stderr).contains("pole_max_steps must be positive"));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_cartpole_matches_cpp_evaluator() {
+        let args = AccNeatArgs {
+            force_delete: true,
+            pop_size: 100,
+            maxgens: 5,
+            experiment: "cartpole".to_string(),
+            ..Default::default()
+        };
+        let mut nets = vec![];
+        let mut expected = vec![];
+        for_each_fittest("cartpole", |_| cmd_line(args), |genome| {
+            nets.push(CpuNetwork::from_genome(genome));
+            expected.push(genome.info.fitness as Real);
+        });
+        let results = crate::environment::evaluate_all(&mut nets, CartPoleEnv::default, &CARTPOLE_SPEC);
+        for (eval, expected) in results.iter().zip(expected) {
+            assert!((eval.fitness - expected).abs() <= 1e-4 * expected.max(1.0));
+        }
+    }
+}
@@ -0,0 +1,357 @@
+//! The `regex:<pattern>` experiment family of src/experiments/static/regex.cpp.
+//!
+//! A sentence is grammatical if the pattern matches all of it. See
+//! `sentences` for how sentences are chosen and encoded.
+//!
+//! The `regex` crate and C++'s ECMAScript `std::regex` are different
+//! dialects, so patterns are limited to what both read the same way, see
+//! `check_pattern`.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use super::sentences::{self, create_sentences, sentence_tests};
+use crate::static_evaluator::Test;
+use crate::AccNeatArgs;
+
+#[derive(Debug)]
+pub enum Error {
+    Escape,
+    /// Syntax outside the subset shared with `std::regex`, at a char offset.
+    Unsupported(String, usize),
+    NoSymbols,
+    Regex(regex::Error),
+    Sentences(sentences::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Escape => write!(f, "escapes aren't supported in regex experiments"),
+            Error::Unsupported(pattern, i) => write!(f, "unsupported regex syntax at {} of '{}'", i, pattern),
+            Error::NoSymbols => write!(f, "no symbols in regex"),
+            Error::Regex(e) => write!(f, "invalid regex: {}", e),
+            Error::Sentences(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegexExperiment {
+    pub pattern: String,
+    /// `regex_alphabet`; `None` for the letters and digits of the pattern.
+    pub alphabet: Option<String>,
+    /// `regex_max_len`.
+    pub max_len: usize,
+    /// `regex_max_sentences`.
+    pub max_sentences: usize,
+}
+
+impl RegexExperiment {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            alphabet: None,
+            max_len: 6,
+            max_sentences: 256,
+        }
+    }
+
+    /// The experiment name understood by the `accneat` binary.
+    pub fn name(&self) -> String {
+        format!("regex:{}", self.pattern)
+    }
+
+    /// `base` set up to run this experiment.
+    pub fn args(&self, base: &AccNeatArgs) -> AccNeatArgs {
+        let mut args = base.clone();
+        args.experiment = self.name();
+        args.params.push(("regex_max_len".to_string(), self.max_len.to_string()));
+        args.params.push(("regex_max_sentences".to_string(), self.max_sentences.to_string()));
+        if let Some(alphabet) = &self.alphabet {
+            args.params.push(("regex_alphabet".to_string(), alphabet.clone()));
+        }
+        args
+    }
+
+    /// Sorted symbols of the sentences.
+    pub fn alphabet(&self) -> Result<Vec<char>, Error> {
+        let alphabet: Vec<char> = match &self.alphabet {
+            Some(a) => a.chars().collect(),
+            None => pattern_alphabet(&self.pattern)?,
+        };
+        if alphabet.is_empty() {
+            return Err(Error::NoSymbols);
+        }
+        Ok(alphabet)
+    }
+
+    /// Fewest bits that give each symbol its own code.
+    pub fn nbits(&self) -> Result<usize, Error> {
+        Ok(sentences::nbits(self.alphabet()?.len()))
+    }
+
+    /// The matching and non-matching sentences, in that order.
+    pub fn sentences(&self) -> Result<(Vec<String>, Vec<String>), Error> {
+        check_pattern(&self.pattern)?;
+        let alphabet = self.alphabet()?;
+        let re = regex::Regex::new(&format!("^(?:{})$", self.pattern)).map_err(Error::Regex)?;
+        create_sentences(&alphabet, self.max_len, self.max_sentences, |s| re.is_match(s)).map_err(Error::Sentences)
+    }
+
+    /// Same tests as `create_tests_regex`.
+    pub fn tests(&self) -> Result<Vec<Test>, Error> {
+        let (correct, incorrect) = self.sentences()?;
+        Ok(sentence_tests(&self.alphabet()?, &correct, &incorrect))
+    }
+}
+
+/// Checks that `pattern` only uses syntax `regex` and `std::regex` agree on,
+/// like `check_regex_pattern` in regex.cpp: letters and digits, `.`, `|`,
+/// `^`, `$`, groups and `(?:)`, the quantifiers `*`, `+`, `?` and `{n}`,
+/// `{n,}`, `{n,m}`, and classes like `[^a-cx]` of letters, digits and ranges.
+/// That leaves out escapes and backreferences, lookaround, flags, named
+/// groups, and nested, POSIX or set-operation classes.
+pub fn check_pattern(pattern: &str) -> Result<(), Error> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let unsupported = |i: usize| Err(Error::Unsupported(pattern.to_string(), i));
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => return Err(Error::Escape),
+            '(' if chars.get(i + 1) == Some(&'?') => {
+                if chars.get(i + 2) != Some(&':') {
+                    return unsupported(i);
+                }
+                i += 2;
+            }
+            '{' => {
+                let start = i;
+                i += 1;
+                let digits = |i: &mut usize| {
+                    let from = *i;
+                    while *i < chars.len() && chars[*i].is_ascii_digit() {
+                        *i += 1;
+                    }
+                    *i > from
+                };
+                if !digits(&mut i) {
+                    return unsupported(start);
+                }
+                if chars.get(i) == Some(&',') {
+                    i += 1;
+                    digits(&mut i);
+                }
+                if chars.get(i) != Some(&'}') {
+                    return unsupported(start);
+                }
+            }
+            '[' => {
+                let start = i;
+                i += 1;
+                if chars.get(i) == Some(&'^') {
+                    i += 1;
+                }
+                let mut nitems = 0;
+                while chars.get(i) != Some(&']') {
+                    match chars.get(i) {
+                        Some(c) if c.is_ascii_alphanumeric() => {
+                            if chars.get(i + 1) == Some(&'-') {
+                                match chars.get(i + 2) {
+                                    Some(hi) if hi.is_ascii_alphanumeric() && hi >= c => i += 2,
+                                    _ => return unsupported(i + 1),
+                                }
+                            }
+                        }
+                        Some('\\') => return Err(Error::Escape),
+                        Some(_) => return unsupported(i),
+                        None => return unsupported(start),
+                    }
+                    nitems += 1;
+                    i += 1;
+                }
+                if nitems == 0 {
+                    return unsupported(start);
+                }
+            }
+            c if c.is_ascii_alphanumeric() || "().|*+?^$".contains(c) => {}
+            _ => return unsupported(i),
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Letters and digits in the pattern, with ranges like `[a-d]` expanded.
+/// Counts such as `{2,3}` are skipped.
+pub fn pattern_alphabet(pattern: &str) -> Result<Vec<char>, Error> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut letters = BTreeSet::new();
+    let mut in_class = false;
+    let mut in_count = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' {
+            return Err(Error::Escape);
+        }
+        if in_count {
+            if c == '}' {
+                in_count = false;
+            }
+        } else if c == '{' && !in_class {
+            in_count = true;
+        } else if c == '[' {
+            in_class = true;
+        } else if c == ']' {
+            in_class = false;
+        } else if c.is_ascii_alphanumeric() {
+            if in_class && i + 2 < chars.len() && chars[i + 1] == '-' && chars[i + 2].is_ascii_alphanumeric() {
+                letters.extend(c..=chars[i + 2]);
+                i += 2;
+            } else {
+                letters.insert(c);
+            }
+        }
+        i += 1;
+    }
+    Ok(letters.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{CpuNetwork, Real};
+    use crate::static_evaluator::{evaluate, max_err};
+    use crate::{cmd_line, for_each_fittest, ExperimentType};
+    use serial_test::serial;
+
+    #[test]
+    fn test_alphabet() {
+        assert_eq!(pattern_alphabet("a+b+a+").unwrap(), vec!['a', 'b']);
+        assert_eq!(pattern_alphabet("[ad][bc]").unwrap(), vec!['a', 'b', 'c', 'd']);
+        assert_eq!(pattern_alphabet("[a-c]x{2,3}").unwrap(), vec!['a', 'b', 'c', 'x']);
+        assert!(matches!(pattern_alphabet(r"\d"), Err(Error::Escape)));
+        assert!(matches!(RegexExperiment::new("()").tests(), Err(Error::NoSymbols)));
+
+        assert_eq!(RegexExperiment::new("a+b+a+").nbits().unwrap(), 1);
+        assert_eq!(RegexExperiment::new("[a-c]").nbits().unwrap(), 2);
+        assert_eq!(RegexExperiment::new("[a-e]").nbits().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_check_pattern() {
+        for pattern in ["(ab|ba)+c", "a{2}b{1,}c{1,3}", "(?:a|b)*?c", "[^a-cx]+.d?", "^a$"] {
+            check_pattern(pattern).unwrap();
+        }
+        let unsupported = |pattern: &str| matches!(check_pattern(pattern), Err(Error::Unsupported(..)));
+        // Backreference
+        assert!(matches!(check_pattern(r"(a)\1"), Err(Error::Escape)));
+        // Lookahead, negative lookahead and lookbehind
+        assert!(unsupported("a(?=b)"));
+        assert!(unsupported("a(?!b)"));
+        assert!(unsupported("(?<=a)b"));
+        // Flags and named groups
+        assert!(unsupported("(?i)a"));
+        assert!(unsupported("(?P<x>a)"));
+        // Nested, POSIX and set-operation classes
+        assert!(unsupported("[a[bc]]"));
+        assert!(unsupported("[[:alpha:]]"));
+        assert!(unsupported("[a-c&&b]"));
+        assert!(unsupported("[a-c--b]"));
+        assert!(unsupported("[c-a]"));
+        // Empty class, which std::regex reads as matching nothing and regex
+        // as the start of a class containing ]
+        assert!(unsupported("[]a]"));
+        // Counts without a lower bound
+        assert!(unsupported("a{,2}"));
+        assert!(unsupported("a{x}"));
+        assert!(unsupported("a b"));
+        assert!(matches!(RegexExperiment::new("a(?=b)").tests(), Err(Error::Unsupported(..))));
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_rejects_unsupported() {
+        let dir = std::env::temp_dir().join(format!("accneat-regex-unsupported-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for pattern in ["a(?=b)", "(?<=a)b", "[a[bc]]", "[[:alpha:]]", "[a-c&&b]", "[]a]", "a{,2}"] {
+            let args = ["-f", "-x", "1", "-n", "10", &format!("regex:{}", pattern)].map(String::from);
+            let (_, stderr) = crate::execute_cmd_line(&args, &dir).unwrap();
+            assert!(stderr.contains("Unsupported regex syntax"), "{}: {}", pattern, stderr);
+        }
+        let args = ["-f", "-x", "1", "-n", "10", r"regex:(a)\1"].map(String::from);
+        let (_, stderr) = crate::execute_cmd_line(&args, &dir).unwrap();
+        assert!(stderr.contains("Escapes aren't supported"), "{}", stderr);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_sentences() {
+        let mut exp = RegexExperiment::new("a+b+a+");
+        exp.max_len = 4;
+        let (correct, incorrect) = exp.sentences().unwrap();
+        assert_eq!(correct, vec!["aba", "aaba", "abaa", "abba"]);
+        // 2 + 4 + 8 + 16 sentences in all.
+        assert_eq!(incorrect.len(), 30 - 4);
+
+        exp.max_sentences = 10;
+        let (correct, incorrect) = exp.sentences().unwrap();
+        assert_eq!(correct.len(), 4);
+        assert_eq!(incorrect.len(), 6);
+        assert_eq!(incorrect[0], "a");
+
+        exp.max_len = 2;
+        assert!(matches!(exp.sentences(), Err(Error::Sentences(sentences::Error::OneClass))));
+        exp.max_len = 20;
+        assert!(matches!(exp.sentences(), Err(Error::Sentences(sentences::Error::TooManySentences))));
+    }
+
+    #[test]
+    fn test_tests() {
+        let exp = RegexExperiment::new("[ad][bc][ad][bc]");
+        let tests = exp.tests().unwrap();
+        let (correct, incorrect) = exp.sentences().unwrap();
+        assert_eq!(correct.len(), 16);
+        assert_eq!(tests.len(), 16 + incorrect.len());
+        assert!((max_err(&tests) - 2.0).abs() < 1e-9);
+
+        let t = tests.iter().find(|t| t.name == "dbab").unwrap();
+        assert_eq!(t.steps.len(), 9);
+        assert_eq!(t.steps[0].input, vec![1.0, 1.0, 1.0, 0.0]);
+        assert_eq!(t.steps[2].input, vec![1.0, 0.0, 1.0, 0.0]);
+        assert_eq!(t.steps[1].input, vec![0.0; 4]);
+        assert_eq!(t.steps[8].input, vec![0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(t.steps[8].output, vec![1.0]);
+        assert_eq!(t.steps[8].weight, 1.0 / 16.0);
+    }
+
+    #[test]
+    fn test_experiment_type() {
+        match "regex:a+b+".parse::<ExperimentType>() {
+            Ok(ExperimentType::Regex(pattern)) => assert_eq!(pattern, "a+b+"),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_matches_cpp_tests() {
+        let mut exp = RegexExperiment::new("(ab|ba)+c");
+        exp.max_len = 5;
+        exp.max_sentences = 40;
+        let base = AccNeatArgs {
+            force_delete: true,
+            pop_size: 100,
+            maxgens: 5,
+            ..Default::default()
+        };
+        let tests = exp.tests().unwrap();
+        for_each_fittest("regex", |_| cmd_line(exp.args(&base)), |genome| {
+            let eval = evaluate(&mut CpuNetwork::from_genome(genome), &tests);
+            assert!((eval.fitness - genome.info.fitness as Real).abs() < 1e-4);
+        });
+    }
+}
@@ -0,0 +1,136 @@
+//! Sentence enumeration and the signal/delay/query test layout shared by the
+//! grammar experiments, as in src/experiments/static/sentences.cpp.
+
+use std::fmt::{Display, Formatter};
+
+use crate::network::Real;
+use crate::static_evaluator::{Step, Test};
+
+/// Same as `Max_Sentences_Enumerated` in sentences.h.
+pub const MAX_ENUMERATED: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    TooManySentences,
+    OneClass,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::TooManySentences => write!(f, "would enumerate more than {} sentences", MAX_ENUMERATED),
+            Error::OneClass => write!(f, "needs both grammatical and ungrammatical sentences"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Every sentence over `alphabet` of 1 to `max_len` symbols, split by
+/// `is_grammatical` and subsampled down to `max_sentences` with as close to
+/// half grammatical as possible. Returns the grammatical sentences first.
+pub fn create_sentences<F>(
+    alphabet: &[char],
+    max_len: usize,
+    max_sentences: usize,
+    mut is_grammatical: F,
+) -> Result<(Vec<String>, Vec<String>), Error>
+where
+    F: FnMut(&str) -> bool,
+{
+    let mut nenumerated = 0usize;
+    let mut n = 1usize;
+    for _ in 0..max_len {
+        n = n.saturating_mul(alphabet.len());
+        nenumerated = nenumerated.saturating_add(n);
+        if nenumerated > MAX_ENUMERATED {
+            return Err(Error::TooManySentences);
+        }
+    }
+
+    let mut correct = vec![];
+    let mut incorrect = vec![];
+    for len in 1..=max_len {
+        for sentence in permute_repeat(alphabet, len) {
+            if is_grammatical(&sentence) {
+                correct.push(sentence);
+            } else {
+                incorrect.push(sentence);
+            }
+        }
+    }
+    if correct.is_empty() || incorrect.is_empty() {
+        return Err(Error::OneClass);
+    }
+
+    let ncorrect = correct.len().min(max_sentences / 2);
+    let nincorrect = incorrect.len().min(max_sentences - ncorrect);
+    let ncorrect = correct.len().min(max_sentences - nincorrect);
+    Ok((subsample(correct, ncorrect), subsample(incorrect, nincorrect)))
+}
+
+/// Fewest bits that give each of `nsymbols` its own code.
+pub fn nbits(nsymbols: usize) -> usize {
+    let mut nbits = 1;
+    while (1 << nbits) < nsymbols {
+        nbits += 1;
+    }
+    nbits
+}
+
+/// Same tests as `create_sentence_tests`: inputs are signal, the symbol's
+/// bits and query; the single output is 1 for a grammatical sentence.
+pub fn sentence_tests(alphabet: &[char], correct: &[String], incorrect: &[String]) -> Vec<Test> {
+    const S: Real = 1.0;
+    const Q: Real = 1.0;
+    const N: Real = 0.0;
+    let weight_seq = 0.0;
+    let weight_delay = 0.0;
+    let weight_query_correct = 1.0 / correct.len() as Real;
+    let weight_query_incorrect = 1.0 / incorrect.len() as Real;
+
+    let nbits = nbits(alphabet.len());
+    let ninputs = nbits + 2;
+
+    let mut tests = vec![];
+    for (sentences, expected, weight) in [(correct, 1.0, weight_query_correct), (incorrect, 0.0, weight_query_incorrect)] {
+        for sentence in sentences {
+            let mut steps = vec![];
+            for c in sentence.chars() {
+                let symbol = alphabet.iter().position(|&a| a == c).unwrap();
+                let mut input = vec![N; ninputs];
+                input[0] = S;
+                for bit in 0..nbits {
+                    input[1 + bit] = ((symbol >> (nbits - 1 - bit)) & 1) as Real;
+                }
+                steps.push(Step::new(input, vec![N], weight_seq));
+                steps.push(Step::new(vec![N; ninputs], vec![N], weight_delay));
+            }
+            let mut query = vec![N; ninputs];
+            query[ninputs - 1] = Q;
+            steps.push(Step::new(query, vec![expected], weight));
+            tests.push(Test::new(sentence, steps));
+        }
+    }
+    tests
+}
+
+/// Every string of `len` symbols, in the order of `permute_repeat` in util.cpp.
+pub fn permute_repeat(alphabet: &[char], len: usize) -> Vec<String> {
+    let mut result = vec![String::new()];
+    for _ in 0..len {
+        result = result
+            .into_iter()
+            .flat_map(|prefix| alphabet.iter().map(move |&c| format!("{}{}", prefix, c)))
+            .collect();
+    }
+    result
+}
+
+/// Evenly spaced elements of `v`, at most `n` of them.
+fn subsample(v: Vec<String>, n: usize) -> Vec<String> {
+    if n >= v.len() {
+        return v;
+    }
+    (0..n).map(|i| v[i * v.len() / n].clone()).collect()
+}
@@ -0,0 +1,349 @@
+//! The `seq:<spec>` experiment family of src/experiments/static/sequence.cpp.
+//!
+//! Each symbol of a sequence is presented followed by a silent step, then
+//! after `delay` more silent steps the network is asked for the sequence back:
+//! all at once in a single query step (parallel), or one symbol per query step
+//! (forward or reverse). With the defaults and `len=N` these are the same
+//! tests as `seq-1bit-Nel`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::sentences::{nbits, permute_repeat, MAX_ENUMERATED};
+use crate::network::Real;
+use crate::static_evaluator::{Step, Test};
+use crate::AccNeatArgs;
+
+/// Longest `delay`, as `Max_Seq_Delay` in sequence.cpp.
+pub const MAX_DELAY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecallOrder {
+    Parallel,
+    Forward,
+    Reverse,
+}
+
+impl RecallOrder {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            RecallOrder::Parallel => "parallel",
+            RecallOrder::Forward => "forward",
+            RecallOrder::Reverse => "reverse",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A parameter isn't `key=value`.
+    Syntax(String),
+    UnknownKey(String),
+    InvalidValue { key: String, value: String },
+    AlphabetSize(usize),
+    ZeroLen,
+    DelayTooLong(usize),
+    TooManySequences,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Syntax(s) => write!(f, "invalid seq parameter '{}', expected key=value", s),
+            Error::UnknownKey(k) => write!(f, "unknown seq parameter '{}'", k),
+            Error::InvalidValue { key, value } => write!(f, "invalid value for seq parameter {}: '{}'", key, value),
+            Error::AlphabetSize(n) => write!(f, "seq alphabet must be from 2 to 26 symbols, got {}", n),
+            Error::ZeroLen => write!(f, "seq len must be at least 1"),
+            Error::DelayTooLong(n) => write!(f, "seq delay must be at most {}, got {}", MAX_DELAY, n),
+            Error::TooManySequences => write!(f, "would enumerate more than {} sequences", MAX_ENUMERATED),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceExperiment {
+    /// Number of symbols.
+    pub alphabet: usize,
+    /// Symbols per sequence.
+    pub len: usize,
+    /// Silent steps between the sequence and its recall.
+    pub delay: usize,
+    pub order: RecallOrder,
+    /// Evenly spaced sequences to keep; `None` for all of them.
+    pub samples: Option<usize>,
+}
+
+impl Default for SequenceExperiment {
+    fn default() -> Self {
+        Self {
+            alphabet: 2,
+            len: 2,
+            delay: 0,
+            order: RecallOrder::Parallel,
+            samples: None,
+        }
+    }
+}
+
+impl FromStr for SequenceExperiment {
+    type Err = Error;
+
+    /// Parses a spec such as `alphabet=4,len=6,delay=3,order=reverse`. Accepts
+    /// the same specs as `SequenceSpec` in sequence.cpp.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut exp = Self::default();
+        if s.is_empty() {
+            return Ok(exp);
+        }
+        for pair in s.split(',') {
+            let (key, value) = pair.split_once('=').ok_or_else(|| Error::Syntax(pair.to_string()))?;
+            let invalid = || Error::InvalidValue {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            // Digits only, as usize would also take a '+'.
+            let size = || {
+                if value.bytes().all(|b| b.is_ascii_digit()) {
+                    value.parse::<usize>().map_err(|_| invalid())
+                } else {
+                    Err(invalid())
+                }
+            };
+            match key {
+                "alphabet" => exp.alphabet = size()?,
+                "len" => exp.len = size()?,
+                "delay" => exp.delay = size()?,
+                "samples" => exp.samples = Some(size()?).filter(|&n| n > 0),
+                "order" => {
+                    exp.order = match value {
+                        "parallel" => RecallOrder::Parallel,
+                        "forward" => RecallOrder::Forward,
+                        "reverse" => RecallOrder::Reverse,
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => return Err(Error::UnknownKey(key.to_string())),
+            }
+        }
+        if !(2..=26).contains(&exp.alphabet) {
+            return Err(Error::AlphabetSize(exp.alphabet));
+        }
+        if exp.len == 0 {
+            return Err(Error::ZeroLen);
+        }
+        if exp.delay > MAX_DELAY {
+            return Err(Error::DelayTooLong(exp.delay));
+        }
+        Ok(exp)
+    }
+}
+
+impl Display for SequenceExperiment {
+    /// The spec, with every key.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "alphabet={},len={},delay={},order={}",
+            self.alphabet,
+            self.len,
+            self.delay,
+            self.order.as_arg()
+        )?;
+        if let Some(samples) = self.samples {
+            write!(f, ",samples={}", samples)?;
+        }
+        Ok(())
+    }
+}
+
+impl SequenceExperiment {
+    /// The experiment name understood by the `accneat` binary.
+    pub fn name(&self) -> String {
+        format!("seq:{}", self)
+    }
+
+    /// `base` set up to run this experiment.
+    pub fn args(&self, base: &AccNeatArgs) -> AccNeatArgs {
+        let mut args = base.clone();
+        args.experiment = self.name();
+        args
+    }
+
+    /// The symbols, `a` onward.
+    pub fn symbols(&self) -> Vec<char> {
+        (0..self.alphabet as u8).map(|i| (b'a' + i) as char).collect()
+    }
+
+    pub fn sequences(&self) -> Result<Vec<String>, Error> {
+        let mut n = 1usize;
+        for _ in 0..self.len {
+            n = n.saturating_mul(self.alphabet);
+            if n > MAX_ENUMERATED {
+                return Err(Error::TooManySequences);
+            }
+        }
+        let all = permute_repeat(&self.symbols(), self.len);
+        Ok(match self.samples {
+            Some(k) if k < all.len() => (0..k).map(|i| all[i * all.len() / k].clone()).collect(),
+            _ => all,
+        })
+    }
+
+    /// Same tests as `create_seq_tests`.
+    pub fn tests(&self) -> Result<Vec<Test>, Error> {
+        const N: Real = 0.0;
+        const X: Real = 1.0;
+        let weight_seq = 5.0;
+        let weight_query = 50.0;
+
+        let nbits = nbits(self.alphabet);
+        let encode = |sym: char, v: &mut Vec<Real>| {
+            let sym = (sym as u8 - b'a') as usize;
+            v.extend((0..nbits).rev().map(|bit| ((sym >> bit) & 1) as Real));
+        };
+        let (noutputs, weight_recall) = match self.order {
+            RecallOrder::Parallel => (self.len * nbits, weight_query),
+            _ => (nbits, weight_query / self.len as Real),
+        };
+        let silence = vec![N; nbits + 2];
+        let empty_output = vec![N; noutputs];
+        let mut query = vec![N, X];
+        query.resize(nbits + 2, N);
+
+        let mut tests = vec![];
+        for sequence in self.sequences()? {
+            let mut steps = vec![];
+            for sym in sequence.chars() {
+                let mut input = vec![X, N];
+                encode(sym, &mut input);
+                steps.push(Step::new(input, empty_output.clone(), weight_seq));
+                steps.push(Step::new(silence.clone(), empty_output.clone(), weight_seq));
+            }
+            for _ in 0..self.delay {
+                steps.push(Step::new(silence.clone(), empty_output.clone(), weight_seq));
+            }
+
+            let recalled: Vec<char> = match self.order {
+                RecallOrder::Reverse => sequence.chars().rev().collect(),
+                _ => sequence.chars().collect(),
+            };
+            if self.order == RecallOrder::Parallel {
+                let mut output = vec![];
+                for &sym in &recalled {
+                    encode(sym, &mut output);
+                }
+                steps.push(Step::new(query.clone(), output, weight_recall));
+            } else {
+                for &sym in &recalled {
+                    let mut output = vec![];
+                    encode(sym, &mut output);
+                    steps.push(Step::new(query.clone(), output, weight_recall));
+                }
+            }
+            tests.push(Test::new(&sequence, steps));
+        }
+        Ok(tests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::CpuNetwork;
+    use crate::static_evaluator::evaluate;
+    use crate::{build_cmd, cmd_line, for_each_fittest, ExperimentType};
+    use serial_test::serial;
+
+    #[test]
+    fn test_parse() {
+        let exp: SequenceExperiment = "alphabet=4,len=6,delay=3".parse().unwrap();
+        assert_eq!(exp.alphabet, 4);
+        assert_eq!(exp.len, 6);
+        assert_eq!(exp.delay, 3);
+        assert_eq!(exp.order, RecallOrder::Parallel);
+        assert_eq!(exp.to_string(), "alphabet=4,len=6,delay=3,order=parallel");
+        assert_eq!(exp.to_string().parse::<SequenceExperiment>().unwrap(), exp);
+        assert_eq!("".parse::<SequenceExperiment>().unwrap(), SequenceExperiment::default());
+
+        assert_eq!("len".parse::<SequenceExperiment>(), Err(Error::Syntax("len".to_string())));
+        assert_eq!("foo=1".parse::<SequenceExperiment>(), Err(Error::UnknownKey("foo".to_string())));
+        assert!(matches!("order=sideways".parse::<SequenceExperiment>(), Err(Error::InvalidValue { .. })));
+        assert_eq!("alphabet=1".parse::<SequenceExperiment>(), Err(Error::AlphabetSize(1)));
+        assert_eq!("len=0".parse::<SequenceExperiment>(), Err(Error::ZeroLen));
+        assert_eq!("delay=1001".parse::<SequenceExperiment>(), Err(Error::DelayTooLong(1001)));
+        for spec in ["delay=-1", "len=+3", "alphabet=", "len=99999999999999999999"] {
+            assert!(matches!(spec.parse::<SequenceExperiment>(), Err(Error::InvalidValue { .. })), "{}", spec);
+        }
+        for spec in [",len=3", "len=3,", "len=3,,delay=1"] {
+            assert_eq!(spec.parse::<SequenceExperiment>(), Err(Error::Syntax(String::new())), "{}", spec);
+        }
+
+        match "seq:len=3".parse::<ExperimentType>() {
+            Ok(ExperimentType::Seq(spec)) => assert_eq!(spec, "len=3"),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_tests() {
+        let exp: SequenceExperiment = "alphabet=3,len=2,delay=1,order=reverse".parse().unwrap();
+        let tests = exp.tests().unwrap();
+        assert_eq!(tests.len(), 9);
+        let t = tests.iter().find(|t| t.name == "cb").unwrap();
+        // Two symbols with silence, one delay step, two recall steps.
+        assert_eq!(t.steps.len(), 7);
+        assert_eq!(t.steps[0].input, vec![1.0, 0.0, 1.0, 0.0]);
+        assert_eq!(t.steps[2].input, vec![1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(t.steps[4].input, vec![0.0; 4]);
+        assert_eq!(t.steps[5].input, vec![0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(t.steps[5].output, vec![0.0, 1.0]);
+        assert_eq!(t.steps[6].output, vec![1.0, 0.0]);
+        assert_eq!(t.steps[6].weight, 25.0);
+
+        let exp: SequenceExperiment = "len=3".parse().unwrap();
+        let t = &exp.tests().unwrap()[3];
+        assert_eq!(t.name, "abb");
+        assert_eq!(t.steps.len(), 7);
+        assert_eq!(t.steps[6].output, vec![0.0, 1.0, 1.0]);
+        assert_eq!(t.steps[6].weight, 50.0);
+
+        let exp: SequenceExperiment = "alphabet=4,len=4,samples=10".parse().unwrap();
+        assert_eq!(exp.sequences().unwrap().len(), 10);
+        let exp: SequenceExperiment = "alphabet=26,len=6".parse().unwrap();
+        assert_eq!(exp.sequences(), Err(Error::TooManySequences));
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_rejects_bad_specs() {
+        let dir = std::env::temp_dir().join(format!("accneat-seq-bad-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for spec in ["delay=-1", "len=-1", "alphabet=-1", "len=+3", ",len=3", "len=3,", "delay=1001"] {
+            assert!(spec.parse::<SequenceExperiment>().is_err(), "{}", spec);
+            let args = ["-f", "-x", "1", "-n", "10", &format!("seq:{}", spec)].map(String::from);
+            let o = build_cmd(&args, &dir).unwrap();
+            assert!(!o.status.success(), "{}", spec);
+            assert!(String::from_utf8_lossy(&o.stderr).contains("seq"), "{}", spec);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_matches_cpp_tests() {
+        let exp: SequenceExperiment = "alphabet=4,len=3,delay=2,order=forward,samples=20".parse().unwrap();
+        let base = AccNeatArgs {
+            force_delete: true,
+            pop_size: 100,
+            maxgens: 5,
+            ..Default::default()
+        };
+        let tests = exp.tests().unwrap();
+        for_each_fittest("seq", |_| cmd_line(exp.args(&base)), |genome| {
+            let eval = evaluate(&mut CpuNetwork::from_genome(genome), &tests);
+            assert!((eval.fitness - genome.info.fitness as Real).abs() < 1e-4);
+        });
+    }
+}
@@ -0,0 +1,15 @@
+use crate::static_evaluator::{Step, Test};
+
+/// Same tests as src/experiments/static/xor.cpp.
+pub fn xor() -> Vec<Test> {
+    const T: f64 = 1.0;
+    const F: f64 = 0.0;
+    let weight = 1.0;
+
+    vec![
+        vec![Step::new(vec![F, F], vec![F], weight)].into(),
+        vec![Step::new(vec![F, T], vec![T], weight)].into(),
+        vec![Step::new(vec![T, F], vec![T], weight)].into(),
+        vec![Step::new(vec![T, T], vec![F], weight)].into(),
+    ]
+}
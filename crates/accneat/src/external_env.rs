@@ -0,0 +1,306 @@
+//! Serves an `Environment` to the `ext:<address>` experiments of
+//! src/experiments/environment/externalenvironment.cpp, which evolve networks
+//! for environments hosted outside the engine.
+//!
+//! The protocol is line-delimited JSON: the engine sends one request per line
+//! and reads one reply line for each.
+//!
+//! ```text
+//! {"op":"hello"}
+//!   -> {"nsensors":N,"noutputs":N,"nepisodes":N,"max_return":R}
+//! {"op":"reset","episodes":[{"id":I,"seed":S},...]}
+//!   -> {"observations":[[...],...]}
+//! {"op":"step","actions":[{"id":I,"action":[...]},...]}
+//!   -> {"results":[{"observation":[...],"reward":R,"done":B},...]}
+//! {"op":"close"}
+//!   -> {}
+//! ```
+//!
+//! Replies list episodes in the order of the request. Each organism being
+//! evaluated has its own id, and runs episode k with seed k: one reset for
+//! every organism, then one step request per step carrying the actions of
+//! the episodes that aren't done yet. A reply of `{"error":"..."}` stops the
+//! run with that message.
+//!
+//! With `ext:tcp:<host>:<port>` the engine connects to a server such as
+//! `serve_tcp`; with `ext:cmd:<command>` it runs the command and speaks the
+//! protocol over its stdin and stdout, as `serve` on stdio does. The
+//! `xor_env` example is a stand-in environment for both.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::environment::{EnvStep, Environment, EnvironmentSpec};
+use crate::network::Real;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeStart {
+    pub id: u64,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeAction {
+    pub id: u64,
+    pub action: Vec<Real>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Request {
+    Hello,
+    Reset { episodes: Vec<EpisodeStart> },
+    Step { actions: Vec<EpisodeAction> },
+    Close,
+}
+
+/// Answers requests for `spec`, giving each episode id its own environment
+/// from `create_env`. Returns after a close request or at end of input.
+pub fn serve<E, F, R, W>(spec: &EnvironmentSpec, create_env: F, reader: R, mut writer: W) -> std::io::Result<()>
+where
+    E: Environment,
+    F: Fn() -> E,
+    R: BufRead,
+    W: Write,
+{
+    let mut envs: HashMap<u64, E> = HashMap::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (reply, close) = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Hello) => (json!(spec), false),
+            Ok(Request::Reset { episodes }) => {
+                let observations: Vec<Vec<Real>> = episodes
+                    .iter()
+                    .map(|e| envs.entry(e.id).or_insert_with(&create_env).reset(e.seed))
+                    .collect();
+                (json!({ "observations": observations }), false)
+            }
+            Ok(Request::Step { actions }) => match check_actions(spec, &envs, &actions) {
+                Some(e) => (json!({ "error": e }), false),
+                None => {
+                    let results: Vec<EnvStep> = actions
+                        .iter()
+                        .map(|a| envs.get_mut(&a.id).unwrap().step(&a.action))
+                        .collect();
+                    (json!({ "results": results }), false)
+                }
+            },
+            Ok(Request::Close) => (json!({}), true),
+            Err(e) => (json!({ "error": format!("bad request: {}", e) }), false),
+        };
+        writeln!(writer, "{}", reply)?;
+        writer.flush()?;
+        if close {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Why `actions` can't be stepped, if so. Checked before stepping any, so an
+/// error leaves every episode as it was.
+fn check_actions<E>(spec: &EnvironmentSpec, envs: &HashMap<u64, E>, actions: &[EpisodeAction]) -> Option<String> {
+    actions.iter().find_map(|a| {
+        if !envs.contains_key(&a.id) {
+            Some(format!("step of episode {} before its reset", a.id))
+        } else if a.action.len() != spec.noutputs {
+            Some(format!("action of episode {} has {} values, expected {}", a.id, a.action.len(), spec.noutputs))
+        } else {
+            None
+        }
+    })
+}
+
+/// Accepts one connection to `listener` and serves it until it closes.
+pub fn serve_tcp<E, F>(spec: &EnvironmentSpec, create_env: F, listener: &TcpListener) -> std::io::Result<()>
+where
+    E: Environment,
+    F: Fn() -> E,
+{
+    let (stream, _) = listener.accept()?;
+    serve(spec, &create_env, BufReader::new(stream.try_clone()?), stream)
+}
+
+/// The stand-in environment of the `xor_env` example and the tests. Episode
+/// `seed` shows the inputs of XOR case `seed % 4` and pays 1 minus the
+/// distance of the first output from the answer, so `XOR_SPEC` counts an
+/// episode solved when the output is on the right side of 0.5.
+#[derive(Debug, Clone, Default)]
+pub struct XorEnv {
+    target: Real,
+}
+
+pub const XOR_SPEC: EnvironmentSpec = EnvironmentSpec {
+    nsensors: 2,
+    noutputs: 1,
+    nepisodes: 4,
+    max_return: 0.5,
+};
+
+impl Environment for XorEnv {
+    fn reset(&mut self, seed: u64) -> Vec<Real> {
+        let a = (seed >> 1) & 1;
+        let b = seed & 1;
+        self.target = (a ^ b) as Real;
+        vec![a as Real, b as Real]
+    }
+
+    fn step(&mut self, action: &[Real]) -> EnvStep {
+        EnvStep {
+            observation: vec![0.0, 0.0],
+            reward: 1.0 - (action[0] - self.target).abs(),
+            done: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::evaluate;
+    use crate::network::CpuNetwork;
+    use crate::{cmd_line, for_each_fittest, AccNeatArgs};
+    use serial_test::serial;
+
+    #[test]
+    fn test_serve() {
+        let input = [
+            r#"{"op":"hello"}"#,
+            r#"{"op":"reset","episodes":[{"id":0,"seed":1},{"id":7,"seed":3}]}"#,
+            r#"{"op":"step","actions":[{"id":7,"action":[0.25]},{"id":0,"action":[0.75]}]}"#,
+            r#"{"op":"step","actions":[{"id":3,"action":[0.5]}]}"#,
+            r#"{"op":"jump"}"#,
+            r#"{"op":"close"}"#,
+            r#"{"op":"hello"}"#,
+        ]
+        .join("\n");
+        let mut output = vec![];
+        serve(&XOR_SPEC, XorEnv::default, input.as_bytes(), &mut output).unwrap();
+
+        let replies: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 6);
+        assert_eq!(
+            replies[0],
+            json!({"nsensors": 2, "noutputs": 1, "nepisodes": 4, "max_return": 0.5})
+        );
+        assert_eq!(replies[1], json!({"observations": [[0.0, 1.0], [1.0, 1.0]]}));
+        assert_eq!(replies[2]["results"][0]["reward"], json!(0.75));
+        assert_eq!(replies[2]["results"][1]["reward"], json!(0.75));
+        assert_eq!(replies[2]["results"][1]["done"], json!(true));
+        assert!(replies[3]["error"].as_str().unwrap().contains("episode 3"));
+        assert!(replies[4]["error"].as_str().unwrap().starts_with("bad request"));
+        assert_eq!(replies[5], json!({}));
+    }
+
+    /// Pays the number of steps taken since reset.
+    #[derive(Default)]
+    struct CountingEnv(usize);
+
+    impl Environment for CountingEnv {
+        fn reset(&mut self, _seed: u64) -> Vec<Real> {
+            self.0 = 0;
+            vec![0.0, 0.0]
+        }
+
+        fn step(&mut self, _action: &[Real]) -> EnvStep {
+            self.0 += 1;
+            EnvStep {
+                observation: vec![0.0, 0.0],
+                reward: self.0 as Real,
+                done: false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_serve_step_of_unknown_episode() {
+        let input = [
+            r#"{"op":"reset","episodes":[{"id":0,"seed":0}]}"#,
+            r#"{"op":"step","actions":[{"id":0,"action":[0.0]},{"id":3,"action":[0.0]}]}"#,
+            r#"{"op":"step","actions":[{"id":0,"action":[0.0]}]}"#,
+            r#"{"op":"step","actions":[{"id":0,"action":[]}]}"#,
+        ]
+        .join("\n");
+        let mut output = vec![];
+        serve(&XOR_SPEC, CountingEnv::default, input.as_bytes(), &mut output).unwrap();
+
+        let replies: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert!(replies[1]["error"].as_str().unwrap().contains("episode 3"));
+        // The failed request didn't step episode 0.
+        assert_eq!(replies[2]["results"][0]["reward"], json!(1.0));
+        assert!(replies[3]["error"].as_str().unwrap().contains("has 0 values, expected 1"));
+    }
+
+    /// Evolves XOR with `experiment` in `name` and checks that the champions
+    /// score the same against `XorEnv` in-process.
+    fn check_xor_run(name: &str, experiment: String) {
+        let args = AccNeatArgs {
+            force_delete: true,
+            pop_size: 100,
+            maxgens: 20,
+            experiment,
+            ..Default::default()
+        };
+        let (stdout, stderr) = for_each_fittest(name, |_| cmd_line(args), |genome| {
+            let eval = evaluate(&mut CpuNetwork::from_genome(genome), &mut XorEnv::default(), &XOR_SPEC);
+            assert!((eval.fitness - genome.info.fitness as Real).abs() < 1e-4);
+        });
+        assert!(stdout.contains("Failures: 0 out of 1 runs"), "{}{}", stdout, stderr);
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || serve_tcp(&XOR_SPEC, XorEnv::default, &listener));
+        check_xor_run("ext-tcp", format!("ext:tcp:127.0.0.1:{}", port));
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_rejects_bad_hello() {
+        let dir = std::env::temp_dir().join(format!("accneat-ext-hello-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (spec, key) in [
+            (EnvironmentSpec { noutputs: 0, ..XOR_SPEC }, "noutputs is 0"),
+            (EnvironmentSpec { nsensors: 100_000, ..XOR_SPEC }, "nsensors is 100000"),
+        ] {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = std::thread::spawn(move || serve_tcp(&spec, XorEnv::default, &listener));
+            let args = ["-f", "-x", "1", "-n", "10", &format!("ext:tcp:127.0.0.1:{}", port)].map(String::from);
+            let o = crate::build_cmd(&args, &dir).unwrap();
+            assert!(!o.status.success());
+            assert!(String::from_utf8_lossy(&o.stderr).contains(key), "{}", String::from_utf8_lossy(&o.stderr));
+            server.join().unwrap().unwrap();
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_over_stdio() {
+        // cargo test builds the examples next to the test binary's deps dir.
+        let exe = std::env::current_exe().unwrap();
+        let xor_env = exe.parent().unwrap().parent().unwrap().join("examples").join("xor_env");
+        assert!(xor_env.exists(), "{} not built", xor_env.display());
+        check_xor_run("ext-cmd", format!("ext:cmd:{}", xor_env.display()));
+    }
+}
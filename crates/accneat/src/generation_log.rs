@@ -0,0 +1,248 @@
+//! Reader for the per-generation statistics that runs write next to the
+//! `fittest_*` files, as `generations.csv` or `generations.jsonl` depending on
+//! the `-l` option (see src/util/generationlog.h).
+
+use std::fmt::Formatter;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationLogFormat {
+    None,
+    Csv,
+    Jsonl,
+}
+
+impl GenerationLogFormat {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            GenerationLogFormat::None => "none",
+            GenerationLogFormat::Csv => "csv",
+            GenerationLogFormat::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn filename(&self) -> Option<&'static str> {
+        match self {
+            GenerationLogFormat::None => None,
+            GenerationLogFormat::Csv => Some("generations.csv"),
+            GenerationLogFormat::Jsonl => Some("generations.jsonl"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json { line: usize, error: serde_json::Error },
+    Csv { line: usize, message: String },
+    UnknownFormat(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Json { line, error } => write!(f, "line {}: {}", line, error),
+            Error::Csv { line, message } => write!(f, "line {}: {}", line, message),
+            Error::UnknownFormat(path) => write!(f, "{}: not a generations.csv or generations.jsonl file", path),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Population statistics for one generation.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GenerationRecord {
+    pub generation: usize,
+    pub best_fitness: f64,
+    pub mean_fitness: f64,
+    pub median_fitness: f64,
+    pub best_error: f64,
+    pub mean_error: f64,
+    pub median_error: f64,
+    pub nspecies: usize,
+    pub species_sizes: Vec<usize>,
+    pub mean_nnodes: f64,
+    pub mean_nlinks: f64,
+    /// COMPLEXIFY or PRUNE for phased searches, BLENDED for blended ones.
+    pub search_phase: String,
+    /// Seconds.
+    pub epoch_time: f64,
+    /// Seconds.
+    pub evaluate_time: f64,
+}
+
+const CSV_HEADER: &str = "generation,best_fitness,mean_fitness,median_fitness,\
+best_error,mean_error,median_error,nspecies,species_sizes,mean_nnodes,mean_nlinks,\
+search_phase,epoch_time,evaluate_time";
+
+fn csv_field<T: FromStr>(line: usize, name: &str, s: &str) -> Result<T, Error> {
+    s.parse().map_err(|_| Error::Csv {
+        line,
+        message: format!("bad {} '{}'", name, s),
+    })
+}
+
+impl GenerationRecord {
+    fn from_csv(line: usize, s: &str) -> Result<Self, Error> {
+        let f: Vec<&str> = s.split(',').collect();
+        if f.len() != 14 {
+            return Err(Error::Csv {
+                line,
+                message: format!("expected 14 fields, found {}", f.len()),
+            });
+        }
+        let species_sizes = if f[8].is_empty() {
+            vec![]
+        } else {
+            f[8].split(';')
+                .map(|x| csv_field(line, "species size", x))
+                .collect::<Result<_, _>>()?
+        };
+        Ok(Self {
+            generation: csv_field(line, "generation", f[0])?,
+            best_fitness: csv_field(line, "best_fitness", f[1])?,
+            mean_fitness: csv_field(line, "mean_fitness", f[2])?,
+            median_fitness: csv_field(line, "median_fitness", f[3])?,
+            best_error: csv_field(line, "best_error", f[4])?,
+            mean_error: csv_field(line, "mean_error", f[5])?,
+            median_error: csv_field(line, "median_error", f[6])?,
+            nspecies: csv_field(line, "nspecies", f[7])?,
+            species_sizes,
+            mean_nnodes: csv_field(line, "mean_nnodes", f[9])?,
+            mean_nlinks: csv_field(line, "mean_nlinks", f[10])?,
+            search_phase: f[11].to_string(),
+            epoch_time: csv_field(line, "epoch_time", f[12])?,
+            evaluate_time: csv_field(line, "evaluate_time", f[13])?,
+        })
+    }
+}
+
+pub fn parse_csv<R: BufRead>(reader: R) -> Result<Vec<GenerationRecord>, Error> {
+    let mut records = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(Error::Io)?;
+        if i == 0 {
+            if line != CSV_HEADER {
+                return Err(Error::Csv {
+                    line: 1,
+                    message: "unexpected header".to_string(),
+                });
+            }
+        } else if !line.is_empty() {
+            records.push(GenerationRecord::from_csv(i + 1, &line)?);
+        }
+    }
+    Ok(records)
+}
+
+pub fn parse_jsonl<R: BufRead>(reader: R) -> Result<Vec<GenerationRecord>, Error> {
+    let mut records = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(Error::Io)?;
+        if !line.is_empty() {
+            let record = serde_json::from_str(&line).map_err(|error| Error::Json { line: i + 1, error })?;
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// Reads a generations.csv or generations.jsonl file, picking the format from
+/// the extension.
+pub fn read_generation_log<P: AsRef<Path>>(path: P) -> Result<Vec<GenerationRecord>, Error> {
+    let path = path.as_ref();
+    let ext = path.extension().and_then(|e| e.to_str());
+    if ext != Some("csv") && ext != Some("jsonl") {
+        return Err(Error::UnknownFormat(path.display().to_string()));
+    }
+    let reader = BufReader::new(std::fs::File::open(path).map_err(Error::Io)?);
+    match ext {
+        Some("csv") => parse_csv(reader),
+        _ => parse_jsonl(reader),
+    }
+}
+
+/// Paths of the generation logs of every run under ./experiments.
+pub fn find_generation_logs() -> Result<Vec<String>, std::io::Error> {
+    let mut logs = vec![];
+    for d in crate::find_experiment_result_dirs()? {
+        for format in [GenerationLogFormat::Csv, GenerationLogFormat::Jsonl] {
+            let path = Path::new(&d).join(format.filename().unwrap());
+            if path.exists() {
+                logs.push(path.to_str().unwrap().to_string());
+            }
+        }
+    }
+    logs.sort();
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{execute, AccNeatArgs};
+    use serial_test::serial;
+
+    const CSV: &str = "generation,best_fitness,mean_fitness,median_fitness,best_error,mean_error,median_error,nspecies,species_sizes,mean_nnodes,mean_nlinks,search_phase,epoch_time,evaluate_time
+1,0.64402976672743995,0.50271784776446282,0.50221093595813793,1.4238809330902402,1.9891286089421494,1.9911562561674487,1,200,6,8,BLENDED,0.00056099891662597656,0.000514984130859375
+2,0.75068597000773329,0.51486012301769957,0.50570561093899113,0.99725611996906693,1.9405595079292013,1.9771775562440355,3,120;50;30,6.0099999999999998,8.2100000000000009,PRUNE,0.0012190341949462891,0.00066804885864257812
+";
+
+    const JSONL: &str = r#"{"generation":1,"best_fitness":0.64402976672743995,"mean_fitness":0.50271784776446282,"median_fitness":0.50221093595813793,"best_error":1.4238809330902402,"mean_error":1.9891286089421494,"median_error":1.9911562561674487,"nspecies":1,"species_sizes":[200],"mean_nnodes":6,"mean_nlinks":8,"search_phase":"BLENDED","epoch_time":0.00056099891662597656,"evaluate_time":0.000514984130859375}
+{"generation":2,"best_fitness":0.75068597000773329,"mean_fitness":0.51486012301769957,"median_fitness":0.50570561093899113,"best_error":0.99725611996906693,"mean_error":1.9405595079292013,"median_error":1.9771775562440355,"nspecies":3,"species_sizes":[120,50,30],"mean_nnodes":6.0099999999999998,"mean_nlinks":8.2100000000000009,"search_phase":"PRUNE","epoch_time":0.0012190341949462891,"evaluate_time":0.00066804885864257812}
+"#;
+
+    #[test]
+    fn test_parse_csv_matches_jsonl() {
+        let csv = parse_csv(CSV.as_bytes()).unwrap();
+        let jsonl = parse_jsonl(JSONL.as_bytes()).unwrap();
+        assert_eq!(csv.len(), 2);
+        assert_eq!(csv, jsonl);
+        assert_eq!(csv[1].species_sizes, vec![120, 50, 30]);
+        assert_eq!(csv[1].search_phase, "PRUNE");
+    }
+
+    #[test]
+    fn test_parse_csv_errors() {
+        let bad = CSV.replace("120;50;30", "120;x;30");
+        match parse_csv(bad.as_bytes()) {
+            Err(Error::Csv { line: 3, .. }) => {}
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert!(matches!(parse_csv("generation\n".as_bytes()), Err(Error::Csv { line: 1, .. })));
+    }
+
+    #[test]
+    #[serial]
+    fn test_read_generation_log() {
+        for format in [GenerationLogFormat::Csv, GenerationLogFormat::Jsonl] {
+            let a = AccNeatArgs {
+                force_delete: true,
+                pop_size: 100,
+                maxgens: 5,
+                generation_log: format,
+                ..Default::default()
+            };
+            execute(a).unwrap();
+            let logs = find_generation_logs().unwrap();
+            assert_eq!(logs.len(), 1);
+            assert!(logs[0].ends_with(format.filename().unwrap()));
+            let records = read_generation_log(&logs[0]).unwrap();
+            assert!(!records.is_empty() && records.len() <= 5);
+            for (i, r) in records.iter().enumerate() {
+                assert_eq!(r.generation, i + 1);
+                assert_eq!(r.nspecies, r.species_sizes.len());
+                assert_eq!(r.species_sizes.iter().sum::<usize>(), 100);
+                assert!(r.best_fitness >= r.mean_fitness);
+                assert!(r.best_error <= r.median_error);
+            }
+        }
+        std::fs::remove_dir_all("experiments").unwrap();
+    }
+}
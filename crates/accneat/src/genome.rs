@@ -0,0 +1,450 @@
+use std::fmt::Formatter;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::network::{ActivationFunction, Real};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    ParseFloat(std::num::ParseFloatError),
+    ParseInt(std::num::ParseIntError),
+    /// A node line with the wrong number of fields, or an unknown node type
+    /// or activation function.
+    BadNode(String),
+    /// Any other line that isn't part of a genome as `Organism::write()`
+    /// writes it.
+    BadLine(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::ParseFloat(e) => write!(f, "{}", e),
+            Error::ParseInt(e) => write!(f, "{}", e),
+            Error::BadNode(msg) => write!(f, "bad node: {}", msg),
+            Error::BadLine(msg) => write!(f, "bad line: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OrganismInfo {
+    pub id: usize,
+    pub fitness: f32,
+    pub error: f32,
+}
+
+impl Default for OrganismInfo {
+    fn default() -> Self {
+        Self {
+            id: usize::MAX,
+            fitness: -1.0,
+            error: 100.0,
+        }
+    }
+}
+
+impl std::fmt::Display for OrganismInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Organism #{}    Fitness: {:.2}%    Error: {:.4}", self.id, self.fitness*100.0, self.error)
+    }
+}
+
+pub const NUM_TRAIT_PARAMS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct TraitInfo {
+    pub id: usize,
+    pub params: Vec<Real>,
+}
+
+impl TraitInfo {
+    fn new(a: &[String]) -> Result<Self, Error> {
+        if a.len() != NUM_TRAIT_PARAMS + 1 {
+            return Err(Error::BadLine(format!("trait with {} fields, expected {}", a.len(), NUM_TRAIT_PARAMS + 1)));
+        }
+        let id = usize::from_str(&a[0]).map_err(Error::ParseInt)?;
+        let params = a[1..]
+            .iter()
+            .map(|x| Real::from_str(x))
+            .collect::<Result<_, _>>()
+            .map_err(Error::ParseFloat)?;
+        Ok(Self { id, params })
+    }
+}
+
+impl std::fmt::Display for TraitInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let p = &self.params;
+        assert_eq!(p.len(), NUM_TRAIT_PARAMS);
+        writeln!(f, "Trait {}: [{:.4}, {:.4}, {:.4}, {:.4}, {:.4}, {:.4}, {:.4}, {:.4}]",
+                 self.id, p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeType {
+    Bias = 0,
+    Sensor = 1,
+    Output = 2,
+    Hidden = 3,
+}
+
+impl NodeType {
+    pub fn is_input(self) -> bool {
+        matches!(self, NodeType::Bias | NodeType::Sensor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeInfo {
+    pub id: usize,
+    pub trait_id: usize,
+    pub type_: NodeType,
+    pub function: ActivationFunction,
+}
+
+impl NodeInfo {
+    /// The function column is optional, sigmoid when missing.
+    fn new(a: Vec<usize>) -> Result<Self, Error> {
+        if a.len() != 3 && a.len() != 4 {
+            return Err(Error::BadNode(format!("{} fields, expected 3 or 4", a.len())));
+        }
+        let id = a[0];
+        let trait_id = a[1];
+        let type_ = match a[2] {
+            0 => NodeType::Bias,
+            1 => NodeType::Sensor,
+            2 => NodeType::Output,
+            3 => NodeType::Hidden,
+            t => return Err(Error::BadNode(format!("{}: unknown type {}", id, t))),
+        };
+        let function = match a.get(3) {
+            None => ActivationFunction::Sigmoid,
+            Some(&f) => ActivationFunction::from_index(f)
+                .ok_or_else(|| Error::BadNode(format!("{}: unknown activation function {}", id, f)))?,
+        };
+        Ok(Self { id, trait_id, type_, function })
+    }
+}
+
+impl std::fmt::Display for NodeInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}: {2:?}] => {1}", self.id, self.trait_id, self.type_)?;
+        if self.function != ActivationFunction::Sigmoid {
+            write!(f, " ({})", self.function.name())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GeneInfo {
+    pub trait_id: usize,
+    pub in_node_id: usize,
+    pub out_node_id: usize,
+    pub weight: f64,
+    pub is_recurrent: bool,
+    pub innovation_num: usize,
+    pub mutation_num: f64,
+    pub enable: bool,
+}
+
+impl std::fmt::Display for GeneInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{5}<-[{1}=>{2}] {6} we: {3:.4} mu: {4:.4} {7} ({0})", self.innovation_num,
+               self.in_node_id, self.out_node_id, self.weight, self.mutation_num, self.trait_id,
+               if self.enable {"e"} else {"d"}, if self.is_recurrent {"rc"} else {""})
+    }
+}
+
+impl GeneInfo {
+    fn new(a: &[String]) -> Result<Self, Error> {
+        if a.len() != 8 {
+            return Err(Error::BadLine(format!("gene with {} fields, expected 8", a.len())));
+        }
+        let int = |i: usize| usize::from_str(&a[i]).map_err(Error::ParseInt);
+        let float = |i: usize| f64::from_str(&a[i]).map_err(Error::ParseFloat);
+        Ok(Self {
+            trait_id: int(0)?,
+            in_node_id: int(1)?,
+            out_node_id: int(2)?,
+            weight: float(3)?,
+            is_recurrent: float(4)? > 0.5,
+            innovation_num: int(5)?,
+            mutation_num: float(6)?,
+            enable: float(7)? > 0.5,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Nodes(pub Vec<NodeInfo>);
+
+impl std::fmt::Display for Nodes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} nodes: ", self.0.len() )?;
+        for n in &self.0 {
+            write!(f, "{}; ", n)?;
+        }
+        writeln!(f)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Traits(pub Vec<TraitInfo>);
+
+impl std::fmt::Display for Traits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} traits:", self.0.len() )?;
+        for n in &self.0 {
+            write!(f, "  {}", n)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Genes(pub Vec<GeneInfo>);
+
+impl std::fmt::Display for Genes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} genes:", self.0.len() )?;
+        for n in &self.0 {
+            writeln!(f, "  {}", n)?;
+        }
+        Ok(())
+    }
+}
+
+/// A genome as written by `Organism::write()` into the `fittest_*` files.
+///
+/// Traits, nodes and genes are kept in file order, which for nodes is the
+/// order `InnovGenome` keeps them in (sorted by id, and therefore by type).
+#[derive(Debug, Clone)]
+pub struct ParsedOrganism {
+    pub info: OrganismInfo,
+    pub traits: Traits,
+    pub nodes: Nodes,
+    pub genes: Genes,
+}
+
+impl ParsedOrganism {
+    pub fn new(info: OrganismInfo, traits: Vec<TraitInfo>, nodes: Vec<NodeInfo>, genes: Vec<GeneInfo>) -> Self {
+        let traits = Traits(traits);
+        let nodes = Nodes(nodes);
+        let genes = Genes(genes);
+        Self { info, traits, nodes, genes }
+    }
+
+    pub fn node_index(&self, id: usize) -> Option<usize> {
+        self.nodes.0.iter().position(|n| n.id == id)
+    }
+
+    pub fn count_nodes(&self, type_: NodeType) -> usize {
+        self.nodes.0.iter().filter(|n| n.type_ == type_).count()
+    }
+}
+
+impl std::fmt::Display for ParsedOrganism {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}{}", self.info, self.traits)?;
+        writeln!(f, "{}{}", self.nodes, self.genes)
+    }
+}
+
+impl FromStr for ParsedOrganism {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_fittest_reader(s.as_bytes())
+    }
+}
+
+pub fn parse_fittest_file<P: AsRef<Path>>(path: P) -> Result<ParsedOrganism, Error> {
+    let reader = BufReader::new(File::open(path).map_err(Error::Io)?);
+    parse_fittest_reader(reader)
+}
+
+fn parse_fittest_reader<R: std::io::BufRead>(reader: R) -> Result<ParsedOrganism, Error> {
+    let mut reader = whiteread::Reader::new(reader);
+    let mut org = OrganismInfo::default();
+
+    let mut traits = vec![];
+    let mut nodes = vec![];
+    let mut genes = vec![];
+    while let Ok(v) = reader.line::<Vec<String>>() {
+        let Some(keyword) = v.first() else {
+            continue;
+        };
+        match keyword.as_str() {
+            "/*" => {
+                // /* Organism #6 Fitness: 0.542682 Error: 1.82927 */
+                let id = match v.get(2).and_then(|s| s.strip_prefix('#')) {
+                    Some(id) if v.len() == 8 => id,
+                    _ => return Err(Error::BadLine(v.join(" "))),
+                };
+                org.id = usize::from_str(id).map_err(Error::ParseInt)?;
+                org.error = f32::from_str(&v[6]).map_err(Error::ParseFloat)?;
+                org.fitness = f32::from_str(&v[4]).map_err(Error::ParseFloat)?;
+            },
+            "trait" => traits.push(TraitInfo::new(&v[1..])?),
+            "node" => {
+                let data = v[1..]
+                    .iter()
+                    .map(|x| usize::from_str(x))
+                    .collect::<Result<_, _>>()
+                    .map_err(Error::ParseInt)?;
+                nodes.push(NodeInfo::new(data)?);
+            },
+            "gene" => genes.push(GeneInfo::new(&v[1..])?),
+            "genomestart" | "genomeend" => {
+                if v.len() != 2 {
+                    return Err(Error::BadLine(v.join(" ")));
+                }
+                let id = usize::from_str(&v[1]).map_err(Error::ParseInt)?;
+                if id != org.id {
+                    return Err(Error::BadLine(format!("{} of organism {}", v.join(" "), org.id)));
+                }
+            },
+            _ => return Err(Error::BadLine(v.join(" "))),
+        }
+    }
+    Ok(ParsedOrganism::new(org, traits, nodes, genes))
+    /*
+
+    /* Organism #6 Fitness: 0.542682 Error: 1.82927 */
+    genomestart 6
+    trait 1 0.568373 0.112194 0.400981 0.918777 0.59396 0.239687 0.331848 0
+    node 1 1 0
+    node 2 1 1
+    node 3 1 1
+    node 4 1 2
+    node 5 1 3
+    node 6 1 3
+    gene 1 1 5 0.595938 0 1 0.595938 1
+    gene 1 1 6 0.442134 0 2 0.442134 1
+    gene 1 2 5 -0.440328 0 3 -0.440328 1
+    gene 1 2 6 -0.427128 0 4 -0.427128 1
+    gene 1 3 5 0.878225 0 5 0.878225 1
+    gene 1 3 6 0.60156 0 6 0.60156 1
+    gene 1 5 4 0.107134 0 7 0.107134 1
+    gene 1 6 4 -0.840043 0 8 -0.840043 1
+    genomeend 6
+
+         */
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// An xor champion produced by `accneat -f -x 50 -n 200 xor`. It has a
+    /// recurrent self-link on the output node.
+    pub(crate) const XOR_CHAMPION: &str = "\
+/* Organism #56 Fitness: 1 Error: 0 */
+genomestart 56
+trait 1 0.129007 0.0657708 0.224567 0.157518 0.503491 0.379023 0.211874 0.151529
+node 1 1 0
+node 2 1 1
+node 3 1 1
+node 4 1 2
+node 5 1 3
+node 6 1 3
+node 17 1 3
+gene 1 1 5 0.256968 0 1 0.256968 1
+gene 1 2 5 -1.31462 0 3 -1.31462 1
+gene 1 2 6 1.29034 0 4 1.29034 1
+gene 1 3 5 -1.11355 0 5 -1.11355 1
+gene 1 5 4 -2.6782 0 7 -2.6782 1
+gene 1 6 4 -1.59918 0 8 -1.59918 1
+gene 1 6 5 0.0517426 0 38 0.0517426 1
+gene 1 1 6 -1.39172 0 69 -1.39172 1
+gene 1 3 17 -0.743892 0 77 -0.743892 1
+gene 1 17 6 -1.52613 0 78 -1.52613 1
+gene 1 4 4 0.673448 1 143 0.673448 1
+gene 1 1 4 0.149914 0 172 0.149914 1
+gene 1 3 6 0.563544 0 197 0.563544 1
+genomeend 56
+";
+
+    /// An xor champion without recurrent genes, from `accneat -f -r 5 -x 100 -n 200 xor`.
+    pub(crate) const XOR_FEEDFORWARD_CHAMPION: &str = "\
+/* Organism #85 Fitness: 1 Error: 0 */
+genomestart 85
+trait 1 0.344696 0.439586 0.997756 0.811074 0.466468 0.0311939 1 0
+node 1 1 0
+node 2 1 1
+node 3 1 1
+node 4 1 2
+node 5 1 3
+node 6 1 3
+gene 1 1 5 -2.61931 0 1 -2.61931 1
+gene 1 1 6 1.07983 0 2 1.07983 1
+gene 1 2 5 2.89166 0 3 2.89166 1
+gene 1 2 6 -3.2461 0 4 -3.2461 1
+gene 1 3 5 -3.72834 0 5 -3.72834 1
+gene 1 3 6 -4.25331 0 6 -4.25331 1
+gene 1 5 4 5.18983 0 7 5.18983 1
+gene 1 6 4 -2.28753 0 8 -2.28753 1
+gene 1 5 6 -0.821738 0 27 -0.821738 1
+gene 1 2 4 -2.1091 0 64 -2.1091 1
+gene 1 1 4 0.852218 0 81 0.852218 1
+genomeend 85
+";
+
+    #[test]
+    fn test_parse_champion() {
+        let org: ParsedOrganism = XOR_CHAMPION.parse().unwrap();
+        assert_eq!(org.info.id, 56);
+        assert_eq!(org.info.fitness, 1.0);
+        assert_eq!(org.traits.0.len(), 1);
+        assert_eq!(org.nodes.0.len(), 7);
+        assert_eq!(org.genes.0.len(), 13);
+        assert_eq!(org.count_nodes(NodeType::Sensor), 2);
+        assert_eq!(org.node_index(17), Some(6));
+        assert!(org.nodes.0.iter().all(|n| n.function == ActivationFunction::Sigmoid));
+    }
+
+    #[test]
+    fn test_parse_activation_column() {
+        let genome = XOR_FEEDFORWARD_CHAMPION.replace("node 5 1 3\n", "node 5 1 3 1\n").replace("node 4 1 2\n", "node 4 1 2 7\n");
+        let org: ParsedOrganism = genome.parse().unwrap();
+        let functions: Vec<ActivationFunction> = org.nodes.0.iter().map(|n| n.function).collect();
+        use ActivationFunction::*;
+        assert_eq!(functions, vec![Sigmoid, Sigmoid, Sigmoid, Abs, Tanh, Sigmoid]);
+    }
+
+    #[test]
+    fn test_parse_bad_node() {
+        let bad_function = XOR_FEEDFORWARD_CHAMPION.replace("node 5 1 3\n", "node 5 1 3 99\n");
+        assert!(matches!(bad_function.parse::<ParsedOrganism>(), Err(Error::BadNode(_))));
+        let bad_type = XOR_FEEDFORWARD_CHAMPION.replace("node 5 1 3\n", "node 5 1 7\n");
+        assert!(matches!(bad_type.parse::<ParsedOrganism>(), Err(Error::BadNode(_))));
+        let bad_id = XOR_FEEDFORWARD_CHAMPION.replace("node 5 1 3\n", "node x 1 3\n");
+        assert!(matches!(bad_id.parse::<ParsedOrganism>(), Err(Error::ParseInt(_))));
+        assert!(matches!(NodeInfo::new(vec![1, 1]), Err(Error::BadNode(_))));
+    }
+
+    #[test]
+    fn test_parse_bad_lines() {
+        let bad = |from: &str, to: &str| {
+            assert!(XOR_FEEDFORWARD_CHAMPION.contains(from));
+            XOR_FEEDFORWARD_CHAMPION.replacen(from, to, 1).parse::<ParsedOrganism>()
+        };
+        assert!(matches!(bad("gene 1 1 5", "gene 1 1 x"), Err(Error::ParseInt(_))));
+        assert!(matches!(bad("gene 1 1 5 ", "gene 1 1 5 x"), Err(Error::ParseFloat(_))));
+        assert!(matches!(bad("gene 1 1 5 ", "gene 1 5 "), Err(Error::BadLine(_))));
+        assert!(matches!(bad("trait 1 ", "trait 1 x"), Err(Error::ParseFloat(_))));
+        assert!(matches!(bad("trait 1 ", "trait 1 0.5 "), Err(Error::BadLine(_))));
+        assert!(matches!(bad("gene", "gen"), Err(Error::BadLine(_))));
+        assert!(matches!(bad("genomeend", "genomeend 1\ngenomeend"), Err(Error::BadLine(_))));
+        assert!(matches!(bad("/* Organism #", "/* Organism "), Err(Error::BadLine(_))));
+    }
+}
@@ -0,0 +1,391 @@
+//! A port of the HyperNEAT substrate (src/hyperneat/substrate.cpp), which
+//! turns the CPPNs evolved by `-H <substrate>` into the networks they encode.
+//!
+//! A substrate file has lines:
+//!
+//! * `layer <name> <input|hidden|output> <columns> <rows> <z>`: a grid
+//!   spanning [-1, 1] in x and y at depth z, with its nodes in row-major order
+//! * `connect <from layer> <to layer>`: a potential link between every pair
+//! * `threshold <t>`: |CPPN output| at or below which there's no link (0.2)
+//! * `max_weight <w>`: weight of a CPPN output of 1 (3.0)
+//!
+//! Text after `#` is ignored. The CPPN takes `x1 y1 z1 x2 y2 z2` and outputs
+//! the weight of the link between the two points, then the weight of the bias
+//! link of node 2, queried with the origin as node 1.
+
+use std::fmt::Formatter;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::genome::{NodeType, ParsedOrganism};
+use crate::network::{ActivationFunction, CpuNetwork, NetDims, NetLink, NetNode, Real, NACTIVATES_PER_INPUT};
+
+pub const CPPN_NINPUTS: usize = 6;
+pub const CPPN_NOUTPUTS: usize = 2;
+
+/// Same as `NODES_MAX` and `LINKS_MAX` in src/neattypes.h.
+const NODES_MAX: usize = u16::MAX as usize;
+const LINKS_MAX: usize = u16::MAX as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    LayerSyntax,
+    LayerType(String),
+    EmptyLayer(String),
+    DuplicateLayer(String),
+    ConnectSyntax,
+    UnknownLayer(String),
+    ConnectToInput(String),
+    Threshold,
+    MaxWeight,
+    UnknownCommand(String),
+    Unexpected(String),
+    TooManyNodes,
+    TooManyLinks(usize),
+    NoInputOrOutput,
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::LayerSyntax => write!(f, "expecting: layer <name> <input|hidden|output> <columns> <rows> <z>"),
+            ErrorKind::LayerType(t) => write!(f, "invalid layer type: {}", t),
+            ErrorKind::EmptyLayer(l) => write!(f, "empty layer: {}", l),
+            ErrorKind::DuplicateLayer(l) => write!(f, "duplicate layer: {}", l),
+            ErrorKind::ConnectSyntax => write!(f, "expecting: connect <from layer> <to layer>"),
+            ErrorKind::UnknownLayer(l) => write!(f, "no such layer: {}", l),
+            ErrorKind::ConnectToInput(l) => write!(f, "can't connect into input layer {}", l),
+            ErrorKind::Threshold => write!(f, "expecting: threshold <t>, with 0 <= t < 1"),
+            ErrorKind::MaxWeight => write!(f, "expecting: max_weight <w>"),
+            ErrorKind::UnknownCommand(c) => write!(f, "unknown command: {}", c),
+            ErrorKind::Unexpected(s) => write!(f, "unexpected: {}", s),
+            ErrorKind::TooManyNodes => write!(f, "more than {} nodes", NODES_MAX),
+            ErrorKind::TooManyLinks(n) => write!(f, "connections have up to {} links, max is {}", n, LINKS_MAX),
+            ErrorKind::NoInputOrOutput => write!(f, "needs an input and an output layer"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// `line` is 0 for errors about the substrate as a whole.
+    Parse { line: usize, kind: ErrorKind },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse { line: 0, kind } => write!(f, "{}", kind),
+            Error::Parse { line, kind } => write!(f, "line {}: {}", line, kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    pub type_: NodeType,
+    pub columns: usize,
+    pub rows: usize,
+    pub z: Real,
+    /// Index of its first node in the network.
+    pub first: usize,
+}
+
+impl Layer {
+    pub fn len(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substrate {
+    pub layers: Vec<Layer>,
+    /// Pairs of indices into `layers`.
+    pub connections: Vec<(usize, usize)>,
+    /// Of each node, by index in the network: bias, sensors, outputs, hidden.
+    pub points: Vec<Point>,
+    pub dims: NetDims,
+    pub threshold: Real,
+    pub max_weight: Real,
+}
+
+/// Position of the i-th of n points spread evenly over [-1, 1].
+fn grid_coord(i: usize, n: usize) -> Real {
+    if n == 1 {
+        0.0
+    } else {
+        -1.0 + 2.0 * i as Real / (n - 1) as Real
+    }
+}
+
+impl FromStr for Substrate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layers: Vec<Layer> = vec![];
+        let mut connections = vec![];
+        let mut threshold = 0.2;
+        let mut max_weight = 3.0;
+
+        for (i, line) in s.lines().enumerate() {
+            let err = |kind| Error::Parse { line: i + 1, kind };
+            let line = line.split('#').next().unwrap();
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let Some(&cmd) = tokens.first() else {
+                continue;
+            };
+            let nargs = match cmd {
+                "layer" => 5,
+                "connect" => 2,
+                "threshold" | "max_weight" => 1,
+                _ => return Err(err(ErrorKind::UnknownCommand(cmd.to_string()))),
+            };
+            if let Some(extra) = tokens.get(nargs + 1) {
+                return Err(err(ErrorKind::Unexpected(extra.to_string())));
+            }
+            let args = &tokens[1..];
+
+            match cmd {
+                "layer" => {
+                    let syntax = || err(ErrorKind::LayerSyntax);
+                    if args.len() < 5 {
+                        return Err(syntax());
+                    }
+                    let name = args[0].to_string();
+                    let type_ = match args[1] {
+                        "input" => NodeType::Sensor,
+                        "hidden" => NodeType::Hidden,
+                        "output" => NodeType::Output,
+                        t => return Err(err(ErrorKind::LayerType(t.to_string()))),
+                    };
+                    let columns = args[2].parse().map_err(|_| syntax())?;
+                    let rows = args[3].parse().map_err(|_| syntax())?;
+                    let z = args[4].parse().map_err(|_| syntax())?;
+                    let layer = Layer { name, type_, columns, rows, z, first: 0 };
+                    if layer.is_empty() {
+                        return Err(err(ErrorKind::EmptyLayer(layer.name)));
+                    }
+                    if layers.iter().any(|l| l.name == layer.name) {
+                        return Err(err(ErrorKind::DuplicateLayer(layer.name)));
+                    }
+                    layers.push(layer);
+                }
+                "connect" => {
+                    if args.len() < 2 {
+                        return Err(err(ErrorKind::ConnectSyntax));
+                    }
+                    let find = |name: &str| {
+                        layers
+                            .iter()
+                            .position(|l| l.name == name)
+                            .ok_or_else(|| err(ErrorKind::UnknownLayer(name.to_string())))
+                    };
+                    let (from, to) = (find(args[0])?, find(args[1])?);
+                    if layers[to].type_ == NodeType::Sensor {
+                        return Err(err(ErrorKind::ConnectToInput(args[1].to_string())));
+                    }
+                    connections.push((from, to));
+                }
+                "threshold" => {
+                    threshold = args
+                        .first()
+                        .and_then(|t| t.parse().ok())
+                        .filter(|t| (0.0..1.0).contains(t))
+                        .ok_or_else(|| err(ErrorKind::Threshold))?;
+                }
+                _ => {
+                    max_weight = args.first().and_then(|w| w.parse().ok()).ok_or_else(|| err(ErrorKind::MaxWeight))?;
+                }
+            }
+        }
+
+        let err = |kind| Error::Parse { line: 0, kind };
+        let mut dims = NetDims::default();
+        let mut points = vec![Point { x: 0.0, y: 0.0, z: 0.0 }];
+        dims.nnodes.bias = 1;
+        for type_ in [NodeType::Sensor, NodeType::Output, NodeType::Hidden] {
+            for layer in layers.iter_mut().filter(|l| l.type_ == type_) {
+                layer.first = points.len();
+                for row in 0..layer.rows {
+                    for col in 0..layer.columns {
+                        points.push(Point {
+                            x: grid_coord(col, layer.columns),
+                            y: grid_coord(row, layer.rows),
+                            z: layer.z,
+                        });
+                    }
+                }
+                if points.len() > NODES_MAX {
+                    return Err(err(ErrorKind::TooManyNodes));
+                }
+                match type_ {
+                    NodeType::Sensor => dims.nnodes.sensor += layer.len(),
+                    NodeType::Output => dims.nnodes.output += layer.len(),
+                    _ => dims.nnodes.hidden += layer.len(),
+                }
+            }
+        }
+        dims.nnodes.all = points.len();
+        dims.nnodes.input = dims.nnodes.bias + dims.nnodes.sensor;
+        dims.nnodes.noninput = dims.nnodes.output + dims.nnodes.hidden;
+        if dims.nnodes.sensor == 0 || dims.nnodes.output == 0 {
+            return Err(err(ErrorKind::NoInputOrOutput));
+        }
+
+        let max_links = dims.nnodes.noninput
+            + connections.iter().map(|&(from, to)| layers[from].len() * layers[to].len()).sum::<usize>();
+        if max_links > LINKS_MAX {
+            return Err(err(ErrorKind::TooManyLinks(max_links)));
+        }
+
+        Ok(Self { layers, connections, points, dims, threshold, max_weight })
+    }
+}
+
+pub fn parse_substrate<P: AsRef<Path>>(path: P) -> Result<Substrate, Error> {
+    std::fs::read_to_string(path).map_err(Error::Io)?.parse()
+}
+
+impl Substrate {
+    fn query(&self, cppn: &mut CpuNetwork, from: Point, to: Point, ioutput: usize) -> Real {
+        cppn.clear_noninput();
+        for (i, x) in [from.x, from.y, from.z, to.x, to.y, to.z].into_iter().enumerate() {
+            cppn.load_sensor(i, x);
+        }
+        cppn.activate(NACTIVATES_PER_INPUT);
+        cppn.outputs()[ioutput]
+    }
+
+    /// The weight of a link for a CPPN output, or None for no link.
+    pub fn paint(&self, output: Real) -> Option<Real> {
+        let magnitude = output.abs().min(1.0);
+        if magnitude <= self.threshold {
+            return None;
+        }
+        let sign = if output < 0.0 { -1.0 } else { 1.0 };
+        Some(sign * self.max_weight * (magnitude - self.threshold) / (1.0 - self.threshold))
+    }
+
+    /// The network `cppn` paints over the substrate, as `Substrate::develop`
+    /// configures it.
+    pub fn develop(&self, cppn: &ParsedOrganism) -> CpuNetwork {
+        let mut cppn = CpuNetwork::from_genome(cppn);
+        let mut incoming: Vec<Vec<NetLink>> = vec![vec![]; self.dims.nnodes.all];
+        let mut add_link = |in_node_index: usize, out_node_index: usize, weight: Real| {
+            incoming[out_node_index].push(NetLink {
+                weight,
+                in_node_index,
+                out_node_index,
+                hebb_rate: 0.0,
+                pre_rate: 0.0,
+                post_rate: 0.0,
+            });
+        };
+
+        for i in self.dims.nnodes.input..self.dims.nnodes.all {
+            if let Some(w) = self.paint(self.query(&mut cppn, self.points[0], self.points[i], 1)) {
+                add_link(0, i, w);
+            }
+        }
+        for &(from, to) in &self.connections {
+            let (from, to) = (&self.layers[from], &self.layers[to]);
+            for j in to.first..to.first + to.len() {
+                for i in from.first..from.first + from.len() {
+                    if let Some(w) = self.paint(self.query(&mut cppn, self.points[i], self.points[j], 0)) {
+                        add_link(i, j, w);
+                    }
+                }
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(self.dims.nnodes.all);
+        let mut links = vec![];
+        for node_links in incoming {
+            let incoming_start = links.len();
+            links.extend(node_links);
+            nodes.push(NetNode { incoming_start, incoming_end: links.len(), function: ActivationFunction::Sigmoid });
+        }
+        let dims = NetDims { nlinks: links.len(), ..self.dims };
+        CpuNetwork::configure(dims, nodes, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::xor;
+    use crate::static_evaluator::evaluate;
+    use crate::{cmd_line, for_each_fittest, AccNeatArgs};
+    use serial_test::serial;
+
+    fn xor_substrate_path() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../res/xor.substrate")
+    }
+
+    #[test]
+    fn test_parse() {
+        let substrate = parse_substrate(xor_substrate_path()).unwrap();
+        let n = substrate.dims.nnodes;
+        assert_eq!((n.bias, n.sensor, n.output, n.hidden), (1, 2, 1, 3));
+        assert_eq!(substrate.layers.iter().map(|l| l.first).collect::<Vec<_>>(), vec![1, 4, 3]);
+        assert_eq!(substrate.points[1], Point { x: -1.0, y: 0.0, z: -1.0 });
+        assert_eq!(substrate.points[5], Point { x: 0.0, y: 0.0, z: 0.0 });
+        assert_eq!(substrate.connections, vec![(0, 1), (1, 2), (0, 2)]);
+
+        assert_eq!(substrate.paint(0.1), None);
+        assert!((substrate.paint(-1.0).unwrap() + 3.0).abs() < 1e-12);
+        assert!((substrate.paint(0.6).unwrap() - 1.5).abs() < 1e-12);
+
+        let err = |text: &str| match text.parse::<Substrate>() {
+            Err(Error::Parse { line, kind }) => (line, kind),
+            r => panic!("unexpected result: {:?}", r),
+        };
+        assert_eq!(err("layer a input 2 1 0\nconnect a b\n"), (2, ErrorKind::UnknownLayer("b".to_string())));
+        assert_eq!(err("layer a input 2 1 0\nlayer b output 1 1 1\nconnect b a\n"), (3, ErrorKind::ConnectToInput("a".to_string())));
+        assert_eq!(err("layer a input 2\n"), (1, ErrorKind::LayerSyntax));
+        assert_eq!(err("threshold 1\n"), (1, ErrorKind::Threshold));
+        assert_eq!(err("layer a hidden 2 2 0\n"), (0, ErrorKind::NoInputOrOutput));
+        assert_eq!(
+            err("layer a input 16 16 0\nlayer b hidden 16 16 1\nlayer c output 1 1 2\nconnect a b\nconnect b c\n"),
+            (0, ErrorKind::TooManyLinks(256 * 256 + 256 + 257))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_hyperneat_xor() {
+        let substrate_path = xor_substrate_path().canonicalize().unwrap();
+        let args = AccNeatArgs {
+            force_delete: true,
+            pop_size: 100,
+            maxgens: 10,
+            substrate: Some(substrate_path.clone()),
+            ..Default::default()
+        };
+        let substrate = parse_substrate(&substrate_path).unwrap();
+        for_each_fittest("hyperneat", |_| cmd_line(args), |cppn| {
+            assert_eq!(cppn.count_nodes(NodeType::Sensor), CPPN_NINPUTS);
+            assert_eq!(cppn.count_nodes(NodeType::Output), CPPN_NOUTPUTS);
+            let mut net = substrate.develop(cppn);
+            assert_eq!(net.dims().nnodes, substrate.dims.nnodes);
+            let eval = evaluate(&mut net, &xor());
+            assert!((eval.fitness - cppn.info.fitness as Real).abs() < 1e-4, "{} != {}", eval.fitness, cppn.info.fitness);
+        });
+    }
+}
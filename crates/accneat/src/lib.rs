@@ -1,13 +1,31 @@
-use std::collections::HashSet;
-use std::fmt::Formatter;
-use std::fs::File;
-use std::hash::{Hash, Hasher};
-use std::io::{BufReader, Stderr};
 // use std::env::{args, Args};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Output, Stdio};
 use std::str::FromStr;
 
-#[derive(Debug)]
+pub mod analysis;
+pub mod batch;
+pub mod codegen;
+pub mod environment;
+pub mod experiments;
+pub mod external_env;
+pub mod generation_log;
+pub mod genome;
+pub mod hyperneat;
+pub mod lineage;
+pub mod map;
+pub mod network;
+pub mod novelty;
+pub mod onnx;
+pub mod pareto;
+pub mod rng;
+pub mod static_evaluator;
+pub mod sweep;
+
+use generation_log::GenerationLogFormat;
+use genome::{parse_fittest_file, ParsedOrganism};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchType {
     Complexify,
     Phased,
@@ -16,6 +34,16 @@ pub enum SearchType {
 
 pub struct SearchTypeParseError;
 
+impl SearchType {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            SearchType::Phased => "phased",
+            SearchType::Blended => "blended",
+            SearchType::Complexify => "complexify",
+        }
+    }
+}
+
 impl FromStr for SearchType {
     type Err = SearchTypeParseError;
 
@@ -29,16 +57,63 @@ impl FromStr for SearchType {
     }
 }
 
+/// What organisms are selected on, passed as `-S`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionType {
+    Fitness,
+    Novelty,
+    Blended,
+    Pareto,
+}
+
+pub struct SelectionTypeParseError;
+
+impl SelectionType {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            SelectionType::Fitness => "fitness",
+            SelectionType::Novelty => "novelty",
+            SelectionType::Blended => "blended",
+            SelectionType::Pareto => "pareto",
+        }
+    }
+}
+
+impl FromStr for SelectionType {
+    type Err = SelectionTypeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fitness" => Ok(SelectionType::Fitness),
+            "novelty" => Ok(SelectionType::Novelty),
+            "blended" => Ok(SelectionType::Blended),
+            "pareto" => Ok(SelectionType::Pareto),
+            _ => Err(SelectionTypeParseError {}),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ExperimentType {
+    CartPole,
     CfgXsx,
+    /// `cfg:<productions>`, see `experiments::cfg`.
+    Cfg(String),
     Maze,
+    Pole1,
+    Pole1Nv,
+    Pole2,
+    Pole2Nv,
     RegexXyxy,
     RegexAba,
+    /// `regex:<pattern>`, see `experiments::regex`.
+    Regex(String),
     Seq1bit2el,
     Seq1bit3el,
     Seq1bit4el,
     Seq1bit5el,
+    /// `seq:<spec>`, see `experiments::sequence`.
+    Seq(String),
     Xor,
 }
 
@@ -50,20 +125,35 @@ impl FromStr for ExperimentType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "xor" => Ok(ExperimentType::Xor),
+            "cartpole" => Ok(ExperimentType::CartPole),
             "cfg-XSX" => Ok(ExperimentType::CfgXsx),
             "maze" => Ok(ExperimentType::Maze),
+            "pole1" => Ok(ExperimentType::Pole1),
+            "pole1-nv" => Ok(ExperimentType::Pole1Nv),
+            "pole2" => Ok(ExperimentType::Pole2),
+            "pole2-nv" => Ok(ExperimentType::Pole2Nv),
             "regex-XYXY" => Ok(ExperimentType::RegexXyxy),
             "regex-aba" => Ok(ExperimentType::RegexAba),
             "seq-1bit-2el" => Ok(ExperimentType::Seq1bit2el),
             "seq-1bit-3el" => Ok(ExperimentType::Seq1bit3el),
             "seq-1bit-4el" => Ok(ExperimentType::Seq1bit4el),
             "seq-1bit-5el" => Ok(ExperimentType::Seq1bit5el),
-            _ => Err(ExperimentNotSupported {}),
+            _ => {
+                if let Some(pattern) = s.strip_prefix("regex:") {
+                    Ok(ExperimentType::Regex(pattern.to_string()))
+                } else if let Some(grammar) = s.strip_prefix("cfg:") {
+                    Ok(ExperimentType::Cfg(grammar.to_string()))
+                } else if let Some(spec) = s.strip_prefix("seq:") {
+                    Ok(ExperimentType::Seq(spec.to_string()))
+                } else {
+                    Err(ExperimentNotSupported {})
+                }
+            }
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AccNeatArgs {
     pub num_experiments: usize,
     pub force_delete: bool,
@@ -71,31 +161,39 @@ pub struct AccNeatArgs {
     pub pop_size: usize,
     pub maxgens: usize,
     pub search_type: SearchType,
+    pub selection: SelectionType,
+    pub generation_log: GenerationLogFormat,
+    /// NeatEnv parameters passed as `-P name=value`.
+    pub params: Vec<(String, String)>,
+    /// Evolves HyperNEAT CPPNs for this substrate, as `-H substrate`.
+    pub substrate: Option<PathBuf>,
     pub experiment: String, // TODO: use ExperimentType
 }
 
-pub(crate) fn build_cmd(args: &[String]) -> std::io::Result<Output> {
+pub(crate) fn build_cmd(args: &[String], dir: &Path) -> std::io::Result<Output> {
     // println!("build_cmd({:?})", &args);
     if cfg!(target_os = "windows") {
         Command::new(".\\vendor\\accneat\\cmake-build-debug\\accneat.exe")
         // Command::new("..\\..\\cmake-build-debug\\accneat.exe")
             .args(args)
+            .current_dir(dir)
             .output()
     } else {
         Command::new("accneat")
             .args(args)
+            .current_dir(dir)
             .stdout(Stdio::piped())
             .output()
     }
 }
 
-pub(crate) fn execute_cmd_line(args: &[String]) -> Result<(String, String), std::io::Error> {
-    let o = build_cmd(args)?;
+pub(crate) fn execute_cmd_line(args: &[String], dir: &Path) -> Result<(String, String), std::io::Error> {
+    let o = build_cmd(args, dir)?;
     // println!("Got result: {:?}", o);
     // assert!(o.status.success());
     let result = String::from_utf8(o.stdout)
         .unwrap_or("[ERROR] String::from_utf8(o.stdout).unwrap() failed".to_string());
-    let errs = String::from_utf8(o.stderr).unwrap_or(String::default());
+    let errs = String::from_utf8(o.stderr).unwrap_or_default();
     // println!("result -- {}", &result);
     // println!("errors -- {}", &errs);
     Ok((result, errs))
@@ -106,7 +204,9 @@ const DEFAULT_RNG_SEED: usize = 1;
 const DEFAULT_POP_SIZE: usize = 1000;
 const DEFAULT_MAX_GENS: usize = 10000;
 const DEFAULT_SEARCHTYPE: SearchType = SearchType::Phased;
+const DEFAULT_SELECTION: SelectionType = SelectionType::Fitness;
 const DEFAULT_FORCE_DELETE: bool = false;
+const DEFAULT_GENERATION_LOG: GenerationLogFormat = GenerationLogFormat::Csv;
 
 impl Default for AccNeatArgs {
     fn default() -> Self {
@@ -117,12 +217,26 @@ impl Default for AccNeatArgs {
             pop_size: DEFAULT_POP_SIZE,
             maxgens: DEFAULT_MAX_GENS,
             search_type: DEFAULT_SEARCHTYPE,
+            selection: DEFAULT_SELECTION,
+            generation_log: DEFAULT_GENERATION_LOG,
+            params: vec![],
+            substrate: None,
             experiment: "xor".to_string(),
         }
     }
 }
 
 pub fn execute(args: AccNeatArgs) -> Result<(String, String), std::io::Error> {
+    execute_in(args, ".")
+}
+
+/// Like `execute`, but runs in `dir`, so results are written to `dir/experiments`.
+pub fn execute_in<P: AsRef<Path>>(args: AccNeatArgs, dir: P) -> Result<(String, String), std::io::Error> {
+    execute_cmd_line(&cmd_line(args), dir.as_ref())
+}
+
+/// The accneat command line for `args`.
+pub(crate) fn cmd_line(args: AccNeatArgs) -> Vec<String> {
     let mut a = vec![];
     if args.force_delete {
         a.push("-f".to_string());
@@ -136,231 +250,52 @@ pub fn execute(args: AccNeatArgs) -> Result<(String, String), std::io::Error> {
     a.push("-x".to_string());
     a.push(args.maxgens.to_string());
     a.push("-s".to_string());
-    a.push(
-        match args.search_type {
-            SearchType::Phased => "phased",
-            SearchType::Blended => "blended",
-            SearchType::Complexify => "complexify",
-        }
-        .to_string(),
-    );
-    a.push(args.experiment);
-
-    execute_cmd_line(a.as_slice())
-}
-
-#[derive(Debug)]
-enum Error {
-    IoError(std::io::Error),
-    ParseFloatError(std::num::ParseFloatError),
-    ParseIntError(std::num::ParseIntError),
-}
-
-#[derive(Clone, Copy)]
-struct OrganismInfo {
-    id: usize,
-    fitness: f32,
-    error: f32,
-}
-
-impl Default for OrganismInfo {
-    fn default() -> Self {
-        Self {
-            id: usize::MAX,
-            fitness: -1.0,
-            error: 100.0,
-        }
-    }
-}
-
-impl std::fmt::Display for OrganismInfo {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "Organism #{}    Fitness: {:.2}%    Error: {:.4}", self.id, self.fitness*100.0, self.error)
-    }
-}
-
-#[derive(Clone)]
-struct TraitInfo {
-    id: usize,
-    params: Vec<f32>,
-}
-
-impl Eq for TraitInfo {}
-impl PartialEq for TraitInfo {
-    fn eq(&self, other: &Self) -> bool {
-        self.id.eq(&other.id)
-    }
-}
-impl Hash for TraitInfo {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.id.hash(state);
-    }
-}
-
-impl TraitInfo {
-    fn new(a: Vec<f32>) -> Self {
-        assert_eq!(a.len(), 9);
-        let id = a[0] as usize;
-        let params = a[1..].iter().cloned().collect();
-        Self { id, params }
-    }
-}
-
-impl std::fmt::Display for TraitInfo {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let p = &self.params;
-        assert_eq!(p.len(), 8);
-        writeln!(f, "Trait {}: [{:.4}, {:.4}, {:.4}, {:.4}, {:.4}, {:.4}, {:.4}, {:.4}]",
-                 self.id, p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7])
-    }
-}
-
-#[derive(Debug, Clone, Hash)]
-enum NodeType {
-    Bias = 0,
-    Sensor = 1,
-    Output = 2,
-    Hidden = 3,
-}
-
-#[derive(Clone, Hash)]
-struct NodeInfo {
-    id: usize,
-    trait_id: usize,
-    type_: NodeType,
-}
-
-impl Eq for NodeInfo {}
-impl PartialEq for NodeInfo {
-    fn eq(&self, other: &Self) -> bool {
-        self.id.eq(&other.id) && self.trait_id.eq(&other.trait_id)
-    }
-}
-
-impl NodeInfo {
-    fn new(a: Vec<usize>) -> Self {
-        assert_eq!(a.len(), 3);
-        let id = a[0] as usize;
-        let trait_id = a[1] as usize;
-        let type_ = match a[2] {
-            0 => NodeType::Bias,
-            1 => NodeType::Sensor,
-            2 => NodeType::Output,
-            3 => NodeType::Hidden,
-            _ => unreachable!(),
-        };
-        Self { id, trait_id, type_ }
-    }
-}
-
-impl std::fmt::Display for NodeInfo {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}: {2:?}] => {1}", self.id, self.trait_id, self.type_)
-    }
-}
-
-#[derive(Clone, Copy)]
-struct GeneInfo {
-    trait_id: usize,
-    in_node_id: usize,
-    out_node_id: usize,
-    weight: f32,
-    is_recurrent: bool,
-    innovation_num: usize,
-    mutation_num: f32,
-    enable: bool,
-}
-
-impl std::fmt::Display for GeneInfo {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{5}<-[{1}=>{2}] {6} we: {3:.4} mu: {4:.4} {7} ({0})", self.innovation_num,
-               self.in_node_id, self.out_node_id, self.weight, self.mutation_num, self.trait_id,
-               if self.enable {"e"} else {"d"}, if self.is_recurrent {"rc"} else {""})
-    }
-}
-
-impl GeneInfo {
-    fn new(a: Vec<f32>) -> Self {
-        assert_eq!(a.len(), 8);
-        Self {
-            trait_id: a[0] as usize,
-            in_node_id: a[1] as usize,
-            out_node_id: a[2] as usize,
-            weight: a[3],
-            is_recurrent: a[4] > 0.5,
-            innovation_num: a[5] as usize,
-            mutation_num: a[6],
-            enable: a[7] > 0.5,
-        }
-    }
-}
-
-#[derive(Clone)]
-struct Nodes(pub HashSet<NodeInfo>);
-
-impl std::fmt::Display for Nodes {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} nodes: ", self.0.len() )?;
-        for n in &self.0 {
-            write!(f, "{}; ", n)?;
-        }
-        writeln!(f, "")
-    }
-}
-
-#[derive(Clone)]
-struct Traits(pub HashSet<TraitInfo>);
-
-impl std::fmt::Display for Traits {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{} traits:", self.0.len() )?;
-        for n in &self.0 {
-            write!(f, "  {}", n)?;
-        }
-        // writeln!(f, "")
-        Ok(())
+    a.push(args.search_type.as_arg().to_string());
+    a.push("-S".to_string());
+    a.push(args.selection.as_arg().to_string());
+    a.push("-l".to_string());
+    a.push(args.generation_log.as_arg().to_string());
+    for (name, value) in &args.params {
+        a.push("-P".to_string());
+        a.push(format!("{}={}", name, value));
+    }
+    if let Some(substrate) = &args.substrate {
+        a.push("-H".to_string());
+        a.push(substrate.display().to_string());
     }
+    a.push(args.experiment);
+    a
 }
 
-#[derive(Clone)]
-struct Genes(pub Vec<GeneInfo>);
-
-impl std::fmt::Display for Genes {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{} genes:", self.0.len() )?;
-        for n in &self.0 {
-            writeln!(f, "  {}", n)?;
+/// Runs accneat in a fresh temp dir named after `name`, with the command line
+/// `args` returns for that dir, and calls `check` on each fittest organism of
+/// the run. Returns accneat's stdout and stderr.
+#[cfg(test)]
+pub(crate) fn for_each_fittest<A, F>(name: &str, args: A, mut check: F) -> (String, String)
+where
+    A: FnOnce(&Path) -> Vec<String>,
+    F: FnMut(&ParsedOrganism),
+{
+    let dir = std::env::temp_dir().join(format!("accneat-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = execute_cmd_line(&args(&dir), &dir).unwrap();
+
+    let run_dir = dir.join("experiments").join("experiment_1");
+    let mut nchecked = 0;
+    for entry in std::fs::read_dir(run_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if !path.file_name().unwrap().to_str().unwrap().starts_with("fittest_") {
+            continue;
         }
-        // writeln!(f, "")
-        Ok(())
-    }
-}
-
-#[derive(Clone)]
-struct ParsedOrganism {
-    info: OrganismInfo,
-    traits: Traits,
-    nodes: Nodes,
-    genes: Genes,
-}
-
-impl ParsedOrganism {
-    fn new(info: OrganismInfo, traits: Vec<TraitInfo>, nodes: Vec<NodeInfo>, genes: Vec<GeneInfo>) -> Self {
-        let traits = Traits(traits.iter().cloned().collect());
-        let nodes = Nodes(nodes.iter().cloned().collect());
-        let genes = Genes(genes.iter().cloned().collect());
-        Self { info, traits, nodes, genes }
+        check(&parse_fittest_file(&path).unwrap());
+        nchecked += 1;
     }
+    assert!(nchecked > 0, "no fittest files: {}{}", output.0, output.1);
+    std::fs::remove_dir_all(dir).unwrap();
+    output
 }
 
-impl std::fmt::Display for ParsedOrganism {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}{}", self.info, self.traits)?;
-        writeln!(f, "{}{}", self.nodes, self.genes)
-    }
-}
-
-fn parse_fittest() -> Option<ParsedOrganism> {
+pub fn parse_fittest() -> Option<ParsedOrganism> {
     let f = find_fittest_files().unwrap();
     // println!("{:?}", f);
     assert!(!f.is_empty());
@@ -370,7 +305,7 @@ fn parse_fittest() -> Option<ParsedOrganism> {
         println!("{}:", &path);
         if let Ok(f) = parse_fittest_file(path) {
             println!("{}", f);
-            if (&fittest).is_none() {
+            if fittest.is_none() {
                 fittest = Some(f);
             } else {
                 let replace = if let Some(fittest) = &fittest {
@@ -385,114 +320,24 @@ fn parse_fittest() -> Option<ParsedOrganism> {
     fittest
 }
 
-fn parse_fittest_file(path: String) -> Result<ParsedOrganism, Error> {
-    // let raws = std::fs::read_to_string(&path).map_err(Error::IoError)?;
-    // println!("{}", raws);
-    let reader = BufReader::new(File::open(path).map_err(Error::IoError)?);
-    let mut reader = whiteread::Reader::new(reader);
-    let mut org = OrganismInfo::default();
-
-    let mut traits = vec![];
-    let mut nodes = vec![];
-    let mut genes = vec![];
-    while let Ok(v) = reader.line::<Vec<String>>() {
-        match v.len() {
-            8 => {
-                org.id = usize::from_str(&v[2][1..]).map_err(Error::ParseIntError)?;
-                org.error = f32::from_str(&v[6]).map_err(Error::ParseFloatError)?;
-                org.fitness = f32::from_str(&v[4]).map_err(Error::ParseFloatError)?;
-                // println!("Found Organism (# {}) with fitness, {} and error, {}", org.id, org.fitness, org.error);
-            },
-            10 => {
-                assert_eq!("trait", &v[0]);
-                let t = &v[1..];
-                let data = t.iter().filter_map(|x| {
-                    match f32::from_str(x).map_err(Error::ParseFloatError) {
-                        Ok(x) => Some(x),
-                        Err(_) => None,
-                    }
-                }).collect();
-                traits.push(TraitInfo::new(data));
-            },
-            4 => {
-                assert_eq!("node", &v[0]);
-                let data = (&v[1..]).iter().filter_map(|x|{
-                    match usize::from_str(x).map_err(Error::ParseIntError) {
-                        Ok(x) => Some(x),
-                        Err(_) => None,
-                    }
-                }).collect();
-                nodes.push(NodeInfo::new(data));
-            },
-            9 => {
-                assert_eq!("gene", &v[0]);
-                let g = &v[1..];
-                let data = g.iter().filter_map(|x| {
-                    match f32::from_str(x).map_err(Error::ParseFloatError) {
-                        Ok(x) => Some(x),
-                        Err(_) => None,
-                    }
-                }).collect();
-                genes.push(GeneInfo::new(data));
-            },
-            2 => {
-                let cmd = &v[0];
-                let id = usize::from_str(&v[1]).map_err(Error::ParseIntError)?;
-                assert_eq!(id, org.id);
-                match cmd.as_str() {
-                    "genomestart" => {
-                        // println!("Found start of Genome");
-                    },
-                    "genomeend" => {
-                        // println!("Found end of Genome");
-                    },
-                    _ => unreachable!(),
-                }
-                assert!(cmd == "genomestart" || cmd == "genomeend");
-            },
-            _ => unreachable!(),
-        }
-    }
-    Ok(ParsedOrganism::new(org, traits, nodes, genes))
-    /*
-
-    /* Organism #6 Fitness: 0.542682 Error: 1.82927 */
-    genomestart 6
-    trait 1 0.568373 0.112194 0.400981 0.918777 0.59396 0.239687 0.331848 0
-    node 1 1 0
-    node 2 1 1
-    node 3 1 1
-    node 4 1 2
-    node 5 1 3
-    node 6 1 3
-    gene 1 1 5 0.595938 0 1 0.595938 1
-    gene 1 1 6 0.442134 0 2 0.442134 1
-    gene 1 2 5 -0.440328 0 3 -0.440328 1
-    gene 1 2 6 -0.427128 0 4 -0.427128 1
-    gene 1 3 5 0.878225 0 5 0.878225 1
-    gene 1 3 6 0.60156 0 6 0.60156 1
-    gene 1 5 4 0.107134 0 7 0.107134 1
-    gene 1 6 4 -0.840043 0 8 -0.840043 1
-    genomeend 6
-
-         */
-    // todo!();
-}
-
-fn find_fittest_files() -> Result<Vec<String>, std::io::Error> {
+pub fn find_fittest_files() -> Result<Vec<String>, std::io::Error> {
     let resdirs = find_experiment_result_dirs()?;
     let mut ff = vec![];
 
     for d in resdirs.iter() {
         // println!("processing {}", d);
         if let Ok(dir) = std::fs::read_dir(d) {
-            ff = dir.map(|x| x.unwrap().path().to_str().unwrap().to_string()).collect();
+            ff.extend(
+                dir.map(|x| x.unwrap().path())
+                    .filter(|p| p.file_name().unwrap().to_str().unwrap().starts_with("fittest_"))
+                    .map(|p| p.to_str().unwrap().to_string()),
+            );
         }
     }
     Ok(ff)
 }
 
-fn find_experiment_result_dirs() -> Result<Vec<String>, std::io::Error> {
+pub fn find_experiment_result_dirs() -> Result<Vec<String>, std::io::Error> {
     let dir = std::fs::read_dir("experiments");
     if let Ok(dir) = dir {
         Ok(dir
@@ -505,9 +350,10 @@ fn find_experiment_result_dirs() -> Result<Vec<String>, std::io::Error> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{execute, find_experiment_result_dirs, find_fittest_files, AccNeatArgs, parse_fittest_file, parse_fittest};
+    use crate::{execute, find_experiment_result_dirs, find_fittest_files, AccNeatArgs, parse_fittest_file};
     use serial_test::serial;
 
+    #[allow(clippy::field_reassign_with_default)]
     fn exec_default() -> (String, String) {
         let mut a = AccNeatArgs::default();
         a.force_delete = true;
@@ -523,21 +369,6 @@ mod tests {
         (r, errstr)
     }
 
-    fn exec(pop_size: usize) -> (String, String) {
-        let mut a = AccNeatArgs::default();
-        a.force_delete = true;
-        a.pop_size = pop_size;
-        a.rng_seed = (std::time::Instant::now().elapsed().as_nanos() % 0xffffffff) as usize;
-        let r = execute(a);
-        assert!(r.is_ok());
-        let (r, errstr) = r.unwrap();
-        // println!("result: {}", &r);
-        // println!("errors: {}", &errstr);
-        assert!(!r.is_empty());
-        assert!(errstr.is_empty());
-        (r, errstr)
-    }
-
     #[test]
     #[serial]
     fn test_cannot_find_experiment_result_dirs() {
@@ -571,6 +402,7 @@ mod tests {
 
     #[test]
     #[serial]
+    #[allow(unused_variables)]
     fn test_parse_fittest_file() {
         let (r, errstr) = exec_default();
         println!("{}", r);
@@ -587,26 +419,4 @@ mod tests {
 
         std::fs::remove_dir_all("experiments").unwrap();
     }
-
-    #[test]
-    #[serial]
-    fn test_optimize() {
-
-        let mut fittest = None;
-        for pop_size in [12,25,36,50,60,75,90,100,120,180,360,420,540,680,800,1200,1600,3200] {
-            let (r, errstr) = exec(pop_size);
-            let f = parse_fittest();
-            if let Some(o) = f {
-                if o.info.fitness > 0.9999 {
-                    fittest = Some((pop_size,o));
-                    break;
-                }
-            }
-        }
-        if let Some((pop_size, fittest)) = fittest {
-            println!("With population size: {}, the winner is {}", pop_size, fittest);
-        }
-    }
-
-
 }
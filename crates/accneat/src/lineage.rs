@@ -0,0 +1,527 @@
+//! Reader for `lineage.log`, which runs write next to the `fittest_*` files
+//! with `-P lineage_log=1` (see src/util/lineagelog.h), and the ancestries it
+//! records.
+//!
+//! Each generation starts with `g <generation>`, followed by a line per
+//! organism in population order:
+//!
+//! * `<operation> <parents> <mutations> <innovations>`: how the organism was
+//!   bred, e.g. `m 3 9 wL 1043` for `mate_multipoint` of organisms 3 and 9 of
+//!   the previous generation, then `mutate_link_weights` and a
+//!   `mutate_add_link` which created innovation 1043
+//! * `f <index>`: after the organisms, if one of them is the new fittest of
+//!   the run
+//!
+//! See `Operation` and `Mutation` for the codes. Innovations are `<innovation>`
+//! for a new link and `<node_id>:<innovation>:<innovation>` for a new node.
+//! Organisms making the same structural mutation in a generation share its
+//! innovation numbers.
+
+use std::collections::BTreeMap;
+use std::fmt::Formatter;
+use std::path::Path;
+use std::str::FromStr;
+
+pub const FILENAME: &str = "lineage.log";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    NoGeneration,
+    GenerationSyntax,
+    GenerationOrder { expected: usize, found: usize },
+    UnknownOperation(String),
+    FieldCount { expected: usize, found: usize },
+    Parent(String),
+    UnknownMutation(char),
+    Innovation(String),
+    Fittest(String),
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::NoGeneration => write!(f, "expecting a g <generation> line first"),
+            ErrorKind::GenerationSyntax => write!(f, "expecting: g <generation>"),
+            ErrorKind::GenerationOrder { expected, found } => {
+                write!(f, "expecting generation {}, found {}", expected, found)
+            }
+            ErrorKind::UnknownOperation(op) => write!(f, "unknown operation: {}", op),
+            ErrorKind::FieldCount { expected, found } => write!(f, "expecting {} fields, found {}", expected, found),
+            ErrorKind::Parent(p) => write!(f, "no such parent in the previous generation: {}", p),
+            ErrorKind::UnknownMutation(m) => write!(f, "unknown mutation: {}", m),
+            ErrorKind::Innovation(i) => write!(f, "invalid innovation: {}", i),
+            ErrorKind::Fittest(i) => write!(f, "no such organism in the generation: {}", i),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse { line: usize, kind: ErrorKind },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse { line, kind } => write!(f, "line {}: {}", line, kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An organism of a run. Generations count from 1, as for `fittest_*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OrganismId {
+    pub generation: usize,
+    pub index: usize,
+}
+
+impl std::fmt::Display for OrganismId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.generation, self.index)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// `s`, an organism of the first generation.
+    Seed,
+    /// `c`, a copy of one parent. Phased searches also clone the fitter parent
+    /// when mating isn't allowed.
+    Clone,
+    /// `m`
+    MateMultipoint,
+    /// `a`
+    MateMultipointAvg,
+}
+
+impl Operation {
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "s" => Some(Operation::Seed),
+            "c" => Some(Operation::Clone),
+            "m" => Some(Operation::MateMultipoint),
+            "a" => Some(Operation::MateMultipointAvg),
+            _ => None,
+        }
+    }
+
+    pub fn nparents(&self) -> usize {
+        match self {
+            Operation::Seed => 0,
+            Operation::Clone => 1,
+            Operation::MateMultipoint | Operation::MateMultipointAvg => 2,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Seed => "seed",
+            Operation::Clone => "clone",
+            Operation::MateMultipoint => "mate_multipoint",
+            Operation::MateMultipointAvg => "mate_multipoint_avg",
+        }
+    }
+}
+
+/// The `InnovGenome` mutations, with their codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mutation {
+    /// `N`
+    AddNode,
+    /// `L`
+    AddLink,
+    /// `D`
+    DeleteNode,
+    /// `X`
+    DeleteLink,
+    /// `w`
+    LinkWeights,
+    /// `r`
+    RandomTrait,
+    /// `l`
+    LinkTrait,
+    /// `n`
+    NodeTrait,
+    /// `a`
+    Activation,
+    /// `t`
+    ToggleEnable,
+    /// `e`
+    GeneReenable,
+}
+
+impl Mutation {
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            'N' => Some(Mutation::AddNode),
+            'L' => Some(Mutation::AddLink),
+            'D' => Some(Mutation::DeleteNode),
+            'X' => Some(Mutation::DeleteLink),
+            'w' => Some(Mutation::LinkWeights),
+            'r' => Some(Mutation::RandomTrait),
+            'l' => Some(Mutation::LinkTrait),
+            'n' => Some(Mutation::NodeTrait),
+            'a' => Some(Mutation::Activation),
+            't' => Some(Mutation::ToggleEnable),
+            'e' => Some(Mutation::GeneReenable),
+            _ => None,
+        }
+    }
+
+    /// Name of the `InnovGenome` method, e.g. `mutate_add_node`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutation::AddNode => "mutate_add_node",
+            Mutation::AddLink => "mutate_add_link",
+            Mutation::DeleteNode => "mutate_delete_node",
+            Mutation::DeleteLink => "mutate_delete_link",
+            Mutation::LinkWeights => "mutate_link_weights",
+            Mutation::RandomTrait => "mutate_random_trait",
+            Mutation::LinkTrait => "mutate_link_trait",
+            Mutation::NodeTrait => "mutate_node_trait",
+            Mutation::Activation => "mutate_activation",
+            Mutation::ToggleEnable => "mutate_toggle_enable",
+            Mutation::GeneReenable => "mutate_gene_reenable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Innovation {
+    Link { innovation_num: usize },
+    /// A node splitting a link, and the links into and out of it.
+    Node { node_id: usize, innovation_nums: [usize; 2] },
+}
+
+impl FromStr for Innovation {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ErrorKind::Innovation(s.to_string());
+        let nums = s.split(':').map(usize::from_str).collect::<Result<Vec<_>, _>>().map_err(|_| err())?;
+        match nums[..] {
+            [innovation_num] => Ok(Innovation::Link { innovation_num }),
+            [node_id, a, b] => Ok(Innovation::Node { node_id, innovation_nums: [a, b] }),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// How an organism was bred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Birth {
+    pub operation: Operation,
+    /// Population indices in the previous generation.
+    pub parents: Vec<usize>,
+    /// In the order applied.
+    pub mutations: Vec<Mutation>,
+    pub innovations: Vec<Innovation>,
+}
+
+impl Birth {
+    fn parse(line: &str, nprev: usize) -> Result<Self, ErrorKind> {
+        let f: Vec<&str> = line.split_whitespace().collect();
+        let operation = Operation::from_code(f[0]).ok_or_else(|| ErrorKind::UnknownOperation(f[0].to_string()))?;
+        let nparents = operation.nparents();
+        if f.len() != nparents + 3 {
+            return Err(ErrorKind::FieldCount {
+                expected: nparents + 3,
+                found: f.len(),
+            });
+        }
+        let parents = f[1..=nparents]
+            .iter()
+            .map(|p| match usize::from_str(p) {
+                Ok(i) if i < nprev => Ok(i),
+                _ => Err(ErrorKind::Parent(p.to_string())),
+            })
+            .collect::<Result<_, _>>()?;
+        let mutations = match f[nparents + 1] {
+            "-" => vec![],
+            m => m
+                .chars()
+                .map(|c| Mutation::from_code(c).ok_or(ErrorKind::UnknownMutation(c)))
+                .collect::<Result<_, _>>()?,
+        };
+        let innovations = match f[nparents + 2] {
+            "-" => vec![],
+            i => i.split(',').map(Innovation::from_str).collect::<Result<_, _>>()?,
+        };
+        Ok(Self {
+            operation,
+            parents,
+            mutations,
+            innovations,
+        })
+    }
+}
+
+/// The ancestors of an organism, back to the seeds. An ancestor reached
+/// through several lines of descent is only listed once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ancestry {
+    pub organism: OrganismId,
+    /// The organism and each of its ancestors, with their parents.
+    pub parents: BTreeMap<OrganismId, Vec<OrganismId>>,
+}
+
+impl Ancestry {
+    /// Ancestors in the first generation.
+    pub fn seeds(&self) -> impl Iterator<Item = OrganismId> + '_ {
+        self.parents.keys().copied().filter(|id| id.generation == 1)
+    }
+
+    /// Number of ancestors in each generation, oldest first.
+    pub fn generation_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.organism.generation];
+        for id in self.parents.keys() {
+            sizes[id.generation - 1] += 1;
+        }
+        sizes
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineageLog {
+    generations: Vec<Vec<Birth>>,
+    fittest: Vec<OrganismId>,
+}
+
+impl FromStr for LineageLog {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut log = LineageLog::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            log.parse_line(line).map_err(|kind| Error::Parse { line: i + 1, kind })?;
+        }
+        Ok(log)
+    }
+}
+
+impl LineageLog {
+    fn parse_line(&mut self, line: &str) -> Result<(), ErrorKind> {
+        let mut f = line.split_whitespace();
+        match f.next() {
+            Some("g") => {
+                let generation = match (f.next().map(usize::from_str), f.next()) {
+                    (Some(Ok(g)), None) => g,
+                    _ => return Err(ErrorKind::GenerationSyntax),
+                };
+                let expected = self.generations.len() + 1;
+                if generation != expected {
+                    return Err(ErrorKind::GenerationOrder { expected, found: generation });
+                }
+                self.generations.push(vec![]);
+            }
+            Some("f") => {
+                let generation = self.generations.len();
+                let size = self.generations.last().ok_or(ErrorKind::NoGeneration)?.len();
+                let index = f.next().unwrap_or_default();
+                match (usize::from_str(index), f.next()) {
+                    (Ok(index), None) if index < size => self.fittest.push(OrganismId { generation, index }),
+                    _ => return Err(ErrorKind::Fittest(index.to_string())),
+                }
+            }
+            _ => {
+                let n = self.generations.len();
+                let nprev = if n >= 2 { self.generations[n - 2].len() } else { 0 };
+                let birth = Birth::parse(line, nprev)?;
+                self.generations.last_mut().ok_or(ErrorKind::NoGeneration)?.push(birth);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn ngenerations(&self) -> usize {
+        self.generations.len()
+    }
+
+    /// The organisms of a generation, in population order.
+    pub fn generation(&self, generation: usize) -> Option<&[Birth]> {
+        self.generations.get(generation.checked_sub(1)?).map(|g| &g[..])
+    }
+
+    pub fn birth(&self, id: OrganismId) -> Option<&Birth> {
+        self.generation(id.generation)?.get(id.index)
+    }
+
+    pub fn parents(&self, id: OrganismId) -> Option<Vec<OrganismId>> {
+        let birth = self.birth(id)?;
+        Some(
+            birth
+                .parents
+                .iter()
+                .map(|&index| OrganismId {
+                    generation: id.generation - 1,
+                    index,
+                })
+                .collect(),
+        )
+    }
+
+    /// Each new fittest organism of the run, in order. The last one is written
+    /// to the final `fittest_*` file.
+    pub fn fittest(&self) -> &[OrganismId] {
+        &self.fittest
+    }
+
+    /// None if the log has no such organism.
+    pub fn ancestry(&self, organism: OrganismId) -> Option<Ancestry> {
+        self.birth(organism)?;
+        let mut parents = BTreeMap::new();
+        let mut pending = vec![organism];
+        while let Some(id) = pending.pop() {
+            if parents.contains_key(&id) {
+                continue;
+            }
+            let p = self.parents(id).unwrap();
+            pending.extend(&p);
+            parents.insert(id, p);
+        }
+        Some(Ancestry { organism, parents })
+    }
+}
+
+pub fn parse_lineage_log<P: AsRef<Path>>(path: P) -> Result<LineageLog, Error> {
+    std::fs::read_to_string(path).map_err(Error::Io)?.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::parse_fittest_file;
+    use crate::{execute_in, AccNeatArgs};
+    use serial_test::serial;
+    use std::collections::BTreeSet;
+
+    const LOG: &str = "g 1
+s - -
+s - -
+s - -
+f 1
+g 2
+c 1 - -
+m 0 1 wL 12
+a 2 2 N 7:13:14
+f 1
+g 3
+c 0 - -
+a 1 1 w 15,8:16:17
+c 2 X -
+";
+
+    fn id(generation: usize, index: usize) -> OrganismId {
+        OrganismId { generation, index }
+    }
+
+    #[test]
+    fn test_parse() {
+        let log: LineageLog = LOG.parse().unwrap();
+        assert_eq!(log.ngenerations(), 3);
+        assert_eq!(log.fittest(), &[id(1, 1), id(2, 1)]);
+        assert_eq!(
+            log.birth(id(2, 1)),
+            Some(&Birth {
+                operation: Operation::MateMultipoint,
+                parents: vec![0, 1],
+                mutations: vec![Mutation::LinkWeights, Mutation::AddLink],
+                innovations: vec![Innovation::Link { innovation_num: 12 }],
+            })
+        );
+        assert_eq!(
+            log.birth(id(3, 1)).unwrap().innovations,
+            vec![
+                Innovation::Link { innovation_num: 15 },
+                Innovation::Node { node_id: 8, innovation_nums: [16, 17] }
+            ]
+        );
+        assert_eq!(log.birth(id(3, 3)), None);
+        assert_eq!(log.birth(id(0, 0)), None);
+
+        let ancestry = log.ancestry(id(3, 1)).unwrap();
+        assert_eq!(ancestry.parents[&id(3, 1)], vec![id(2, 1), id(2, 1)]);
+        assert_eq!(ancestry.parents[&id(2, 1)], vec![id(1, 0), id(1, 1)]);
+        assert_eq!(ancestry.seeds().collect::<Vec<_>>(), vec![id(1, 0), id(1, 1)]);
+        assert_eq!(ancestry.generation_sizes(), vec![2, 1, 1]);
+        assert_eq!(log.ancestry(id(1, 2)).unwrap().generation_sizes(), vec![1]);
+
+        let err = |text: &str| match text.parse::<LineageLog>() {
+            Err(Error::Parse { line, kind }) => (line, kind),
+            r => panic!("unexpected result: {:?}", r),
+        };
+        assert_eq!(err("s - -\n"), (1, ErrorKind::NoGeneration));
+        assert_eq!(err("g 2\n"), (1, ErrorKind::GenerationOrder { expected: 1, found: 2 }));
+        assert_eq!(err("g 1\nc 0 - -\n"), (2, ErrorKind::Parent("0".to_string())));
+        assert_eq!(err(&LOG.replace("c 2 X", "c 3 X")), (14, ErrorKind::Parent("3".to_string())));
+        assert_eq!(err(&LOG.replace("c 2 X", "m 2 X")), (14, ErrorKind::FieldCount { expected: 5, found: 4 }));
+        assert_eq!(err(&LOG.replace("wL", "wQ")), (8, ErrorKind::UnknownMutation('Q')));
+        assert_eq!(err(&LOG.replace("7:13:14", "7:13")), (9, ErrorKind::Innovation("7:13".to_string())));
+        assert_eq!(err(&LOG.replace("f 1\ng 2", "f 3\ng 2")), (5, ErrorKind::Fittest("3".to_string())));
+        assert_eq!(err("g 1\nx - -\n"), (2, ErrorKind::UnknownOperation("x".to_string())));
+    }
+
+    /// The genes of the run's fittest organism must come from the seeds or from
+    /// innovations of its ancestry.
+    #[test]
+    #[serial]
+    fn test_lineage_of_fittest() {
+        let dir = std::env::temp_dir().join(format!("accneat-lineage-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let args = AccNeatArgs {
+            force_delete: true,
+            pop_size: 100,
+            maxgens: 30,
+            search_type: crate::SearchType::Complexify,
+            params: vec![("lineage_log".to_string(), "1".to_string())],
+            ..Default::default()
+        };
+        execute_in(args, &dir).unwrap();
+
+        let run_dir = dir.join("experiments").join("experiment_1");
+        let log = parse_lineage_log(run_dir.join(FILENAME)).unwrap();
+        for g in 1..=log.ngenerations() {
+            assert_eq!(log.generation(g).unwrap().len(), 100);
+        }
+        assert!(log.generation(1).unwrap().iter().all(|b| b.operation == Operation::Seed));
+
+        let fittest = *log.fittest().last().unwrap();
+        let ancestry = log.ancestry(fittest).unwrap();
+        assert!(ancestry.seeds().count() >= 1);
+        assert!(ancestry.generation_sizes().iter().all(|&n| n >= 1));
+
+        let organism = parse_fittest_file(run_dir.join(format!("fittest_{}", log.ngenerations()))).unwrap();
+        assert_eq!(organism.info.id, fittest.index);
+
+        let all = (1..=log.ngenerations()).flat_map(|g| log.generation(g).unwrap()).flat_map(|b| &b.innovations);
+        let first_new = all.map(|i| match i {
+            Innovation::Link { innovation_num } => *innovation_num,
+            Innovation::Node { innovation_nums, .. } => innovation_nums[0],
+        });
+        let first_new = first_new.min().expect("no structural mutations");
+        let mut inherited = BTreeSet::new();
+        for id in ancestry.parents.keys() {
+            for innovation in &log.birth(*id).unwrap().innovations {
+                match innovation {
+                    Innovation::Link { innovation_num } => {
+                        inherited.insert(*innovation_num);
+                    }
+                    Innovation::Node { innovation_nums, .. } => inherited.extend(innovation_nums),
+                }
+            }
+        }
+        for gene in &organism.genes.0 {
+            assert!(gene.innovation_num < first_new || inherited.contains(&gene.innovation_num), "{:?}", gene);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,602 @@
+//! A port of the map resource parser (src/util/map.cpp) used by the maze
+//! experiment, plus a writer for the same format.
+//!
+//! A map file has four sections:
+//!
+//! * `[glyphs]`: `type = chars`, e.g. `wall = | ~`
+//! * `[glyphs-attrs]`: `char name = value`, e.g. `> dir = east`
+//! * `[objects]`: a `+` followed by one label per column, then one line per
+//!   row starting with its label
+//! * `[objects-attrs]`: `row,col char name = value`, e.g. `H,M f seq = l`
+//!
+//! Text after `#` is ignored and lines are trimmed.
+
+use std::collections::BTreeMap;
+use std::fmt::Formatter;
+use std::path::Path;
+use std::str::FromStr;
+
+pub const SECTIONS: [&str; 4] = ["glyphs", "glyphs-attrs", "objects", "objects-attrs"];
+
+/// Labels used by `LocationTranslator::with_default_labels`.
+pub const DEFAULT_LABELS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    ExpectedClosingBracket,
+    EmptySectionName,
+    InvalidSection(String),
+    DuplicateSection(String),
+    NoSectionHeader,
+    NoSections,
+    MissingSection(String),
+    ExpectedEquals,
+    GlyphAttrSyntax,
+    DuplicateGlyph(char),
+    UndefinedGlyph(char),
+    DuplicateAttr(String),
+    NoColumnLabels,
+    ColumnLabelsStart,
+    UnexpectedWhitespace,
+    UnexpectedComma,
+    DuplicateColumnLabel(char),
+    DuplicateRowLabel(char),
+    ColumnsExceeded,
+    Tab,
+    ObjectAttrSyntax(String),
+    UnknownRowLabel(String),
+    UnknownColumnLabel(String),
+    NoObject,
+    GlyphMismatch { expected: char, found: char },
+    DuplicateAgent { row: char, col: char },
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::ExpectedClosingBracket => write!(f, "expecting ']' as final character"),
+            ErrorKind::EmptySectionName => write!(f, "section name cannot be empty"),
+            ErrorKind::InvalidSection(s) => write!(f, "invalid section name '{}'", s),
+            ErrorKind::DuplicateSection(s) => write!(f, "duplicate section '{}'", s),
+            ErrorKind::NoSectionHeader => write!(f, "no section header"),
+            ErrorKind::NoSections => write!(f, "expected to find at least one section"),
+            ErrorKind::MissingSection(s) => write!(f, "missing section {}", s),
+            ErrorKind::ExpectedEquals => write!(f, "expecting '='"),
+            ErrorKind::GlyphAttrSyntax => write!(f, "expecting: char name = value (e.g. '> dir = right')"),
+            ErrorKind::DuplicateGlyph(c) => write!(f, "duplicate glyph '{}'", c),
+            ErrorKind::UndefinedGlyph(c) => write!(f, "undefined glyph '{}'", c),
+            ErrorKind::DuplicateAttr(name) => write!(f, "duplicate definition of attr '{}'", name),
+            ErrorKind::NoColumnLabels => write!(f, "objects section has no column labels line"),
+            ErrorKind::ColumnLabelsStart => write!(f, "column labels line must start with '+'"),
+            ErrorKind::UnexpectedWhitespace => write!(f, "unexpected whitespace"),
+            ErrorKind::UnexpectedComma => write!(f, "unexpected comma"),
+            ErrorKind::DuplicateColumnLabel(c) => write!(f, "duplicate column label '{}'", c),
+            ErrorKind::DuplicateRowLabel(c) => write!(f, "duplicate row label '{}'", c),
+            ErrorKind::ColumnsExceeded => write!(f, "columns exceeded"),
+            ErrorKind::Tab => write!(f, "tabs not allowed"),
+            ErrorKind::ObjectAttrSyntax(details) => write!(
+                f,
+                "{}; expecting: row,col glyph name = value (e.g. 'A,C f seq = llr')",
+                details
+            ),
+            ErrorKind::UnknownRowLabel(l) => write!(f, "unknown row label '{}'", l),
+            ErrorKind::UnknownColumnLabel(l) => write!(f, "unknown column label '{}'", l),
+            ErrorKind::NoObject => write!(f, "no object at coordinate"),
+            ErrorKind::GlyphMismatch { expected, found } => write!(
+                f,
+                "specified glyph '{}' doesn't match '{}' found at coordinate",
+                found, expected
+            ),
+            ErrorKind::DuplicateAgent { row, col } => write!(f, "duplicate agent; first agent is at {},{}", row, col),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse { line: usize, kind: ErrorKind },
+}
+
+impl Error {
+    fn at(line: usize, kind: ErrorKind) -> Self {
+        Error::Parse { line, kind }
+    }
+
+    pub fn kind(&self) -> Option<&ErrorKind> {
+        match self {
+            Error::Parse { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse { line, kind } => write!(f, "line {}: {}", line, kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    pub type_: String,
+    pub character: char,
+    pub attrs: BTreeMap<String, String>,
+}
+
+/// Zero-based grid position, ordered by row then column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Location {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LocationTranslator {
+    pub row_labels: Vec<char>,
+    pub col_labels: Vec<char>,
+}
+
+impl LocationTranslator {
+    /// Labels rows and columns A-Z, then a-z, then 0-9.
+    pub fn with_default_labels(height: usize, width: usize) -> Self {
+        assert!(height <= DEFAULT_LABELS.len() && width <= DEFAULT_LABELS.len());
+        Self {
+            row_labels: DEFAULT_LABELS.chars().take(height).collect(),
+            col_labels: DEFAULT_LABELS.chars().take(width).collect(),
+        }
+    }
+
+    pub fn find(&self, row: char, col: char) -> Option<Location> {
+        let row = self.row_labels.iter().position(|&c| c == row)?;
+        let col = self.col_labels.iter().position(|&c| c == col)?;
+        Some(Location { row, col })
+    }
+
+    pub fn labels(&self, loc: Location) -> (char, char) {
+        (self.row_labels[loc.row], self.col_labels[loc.col])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    pub loc: Location,
+    pub glyph: Glyph,
+    pub attrs: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    pub glyphs: BTreeMap<char, Glyph>,
+    pub loc_trans: LocationTranslator,
+    pub width: usize,
+    pub height: usize,
+    pub objects: BTreeMap<Location, Object>,
+}
+
+struct Line<'a> {
+    lineno: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn err(&self, kind: ErrorKind) -> Error {
+        Error::at(self.lineno, kind)
+    }
+
+    fn parse_equals(&self) -> Result<(&'a str, &'a str), Error> {
+        let pos = self.text.find('=').ok_or_else(|| self.err(ErrorKind::ExpectedEquals))?;
+        Ok((self.text[..pos].trim(), self.text[pos + 1..].trim()))
+    }
+}
+
+fn parse_sections(text: &str) -> Result<BTreeMap<&str, (usize, Vec<Line<'_>>)>, Error> {
+    let mut result = BTreeMap::new();
+    let mut section: Option<(&str, usize, Vec<Line>)> = None;
+    let mut lineno = 0;
+
+    for raw in text.lines() {
+        lineno += 1;
+        let text = match raw.find('#') {
+            Some(pos) => &raw[..pos],
+            None => raw,
+        }
+        .trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = text.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| Error::at(lineno, ErrorKind::ExpectedClosingBracket))?
+                .trim();
+            if name.is_empty() {
+                return Err(Error::at(lineno, ErrorKind::EmptySectionName));
+            } else if !SECTIONS.contains(&name) {
+                return Err(Error::at(lineno, ErrorKind::InvalidSection(name.to_string())));
+            } else if result.contains_key(name) || section.as_ref().is_some_and(|s| s.0 == name) {
+                return Err(Error::at(lineno, ErrorKind::DuplicateSection(name.to_string())));
+            }
+            if let Some((name, start, lines)) = section.take() {
+                result.insert(name, (start, lines));
+            }
+            section = Some((name, lineno, vec![]));
+        } else {
+            match &mut section {
+                Some((_, _, lines)) => lines.push(Line { lineno, text }),
+                None => return Err(Error::at(lineno, ErrorKind::NoSectionHeader)),
+            }
+        }
+    }
+
+    match section {
+        Some((name, start, lines)) => {
+            result.insert(name, (start, lines));
+        }
+        None => return Err(Error::at(lineno, ErrorKind::NoSections)),
+    }
+    for name in SECTIONS {
+        if !result.contains_key(name) {
+            return Err(Error::at(lineno, ErrorKind::MissingSection(name.to_string())));
+        }
+    }
+    Ok(result)
+}
+
+fn parse_glyphs(lines: &[Line], attrs: &[Line]) -> Result<BTreeMap<char, Glyph>, Error> {
+    let mut glyphs = BTreeMap::new();
+    for line in lines {
+        let (type_, chars) = line.parse_equals()?;
+        for c in chars.chars().filter(|c| !c.is_whitespace()) {
+            if glyphs.contains_key(&c) {
+                return Err(line.err(ErrorKind::DuplicateGlyph(c)));
+            }
+            glyphs.insert(
+                c,
+                Glyph {
+                    type_: type_.to_string(),
+                    character: c,
+                    attrs: BTreeMap::new(),
+                },
+            );
+        }
+    }
+
+    for line in attrs {
+        let (lhs, value) = line.parse_equals()?;
+        let tokens: Vec<&str> = lhs.split_whitespace().collect();
+        if tokens.len() != 2 || tokens[0].chars().count() != 1 {
+            return Err(line.err(ErrorKind::GlyphAttrSyntax));
+        }
+        let c = tokens[0].chars().next().unwrap();
+        let glyph = glyphs.get_mut(&c).ok_or_else(|| line.err(ErrorKind::UndefinedGlyph(c)))?;
+        if glyph.attrs.contains_key(tokens[1]) {
+            return Err(line.err(ErrorKind::DuplicateAttr(tokens[1].to_string())));
+        }
+        glyph.attrs.insert(tokens[1].to_string(), value.to_string());
+    }
+    Ok(glyphs)
+}
+
+fn parse_objects(section_line: usize, lines: &[Line], glyphs: BTreeMap<char, Glyph>) -> Result<Map, Error> {
+    let header = lines.first().ok_or_else(|| Error::at(section_line, ErrorKind::NoColumnLabels))?;
+    let labels = header
+        .text
+        .strip_prefix('+')
+        .ok_or_else(|| header.err(ErrorKind::ColumnLabelsStart))?;
+    let mut loc_trans = LocationTranslator::default();
+    for c in labels.chars() {
+        if c.is_whitespace() {
+            return Err(header.err(ErrorKind::UnexpectedWhitespace));
+        } else if c == ',' {
+            return Err(header.err(ErrorKind::UnexpectedComma));
+        } else if loc_trans.col_labels.contains(&c) {
+            return Err(header.err(ErrorKind::DuplicateColumnLabel(c)));
+        }
+        loc_trans.col_labels.push(c);
+    }
+
+    let mut objects = BTreeMap::new();
+    let mut agent: Option<Location> = None;
+    for (row, line) in lines[1..].iter().enumerate() {
+        let mut chars = line.text.chars();
+        let label = chars.next().unwrap();
+        if loc_trans.row_labels.contains(&label) {
+            return Err(line.err(ErrorKind::DuplicateRowLabel(label)));
+        }
+        loc_trans.row_labels.push(label);
+
+        let content: Vec<char> = chars.collect();
+        if content.len() > loc_trans.col_labels.len() {
+            return Err(line.err(ErrorKind::ColumnsExceeded));
+        }
+        for (col, &c) in content.iter().enumerate() {
+            if c == '\t' {
+                return Err(line.err(ErrorKind::Tab));
+            } else if c != ' ' {
+                let glyph = glyphs.get(&c).ok_or_else(|| line.err(ErrorKind::UndefinedGlyph(c)))?;
+                let loc = Location { row, col };
+                if glyph.type_ == "agent" {
+                    if let Some(first) = agent {
+                        let (row, col) = loc_trans.labels(first);
+                        return Err(line.err(ErrorKind::DuplicateAgent { row, col }));
+                    }
+                    agent = Some(loc);
+                }
+                objects.insert(
+                    loc,
+                    Object {
+                        loc,
+                        glyph: glyph.clone(),
+                        attrs: BTreeMap::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(Map {
+        width: loc_trans.col_labels.len(),
+        height: loc_trans.row_labels.len(),
+        glyphs,
+        loc_trans,
+        objects,
+    })
+}
+
+fn parse_objects_attrs(lines: &[Line], map: &mut Map) -> Result<(), Error> {
+    for line in lines {
+        let (lhs, value) = line.parse_equals()?;
+        let syntax = |details: String| line.err(ErrorKind::ObjectAttrSyntax(details));
+
+        let tokens: Vec<&str> = lhs.split_whitespace().collect();
+        if tokens.len() != 3 {
+            return Err(syntax(format!("expected 3 tokens on lhs, found {}", tokens.len())));
+        }
+        let coord: Vec<&str> = tokens[0].split(',').collect();
+        if coord.len() != 2 {
+            return Err(syntax(format!("coordinate is malformed: '{}'", tokens[0])));
+        }
+        if tokens[1].chars().count() != 1 {
+            return Err(syntax(format!("glyph should be only 1 character, found ({})", tokens[1])));
+        }
+
+        let label = |s: &str, labels: &[char]| {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if labels.contains(&c) => Some(c),
+                _ => None,
+            }
+        };
+        let row = label(coord[0], &map.loc_trans.row_labels)
+            .ok_or_else(|| line.err(ErrorKind::UnknownRowLabel(coord[0].to_string())))?;
+        let col = label(coord[1], &map.loc_trans.col_labels)
+            .ok_or_else(|| line.err(ErrorKind::UnknownColumnLabel(coord[1].to_string())))?;
+        let loc = map.loc_trans.find(row, col).unwrap();
+        let obj = map.objects.get_mut(&loc).ok_or_else(|| line.err(ErrorKind::NoObject))?;
+
+        let c = tokens[1].chars().next().unwrap();
+        if c != obj.glyph.character {
+            return Err(line.err(ErrorKind::GlyphMismatch {
+                expected: obj.glyph.character,
+                found: c,
+            }));
+        }
+        if obj.attrs.contains_key(tokens[2]) {
+            return Err(line.err(ErrorKind::DuplicateAttr(tokens[2].to_string())));
+        }
+        obj.attrs.insert(tokens[2].to_string(), value.to_string());
+    }
+    Ok(())
+}
+
+impl FromStr for Map {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sections = parse_sections(s)?;
+        let glyphs = parse_glyphs(&sections["glyphs"].1, &sections["glyphs-attrs"].1)?;
+        let (start, lines) = &sections["objects"];
+        let mut map = parse_objects(*start, lines, glyphs)?;
+        parse_objects_attrs(&sections["objects-attrs"].1, &mut map)?;
+        Ok(map)
+    }
+}
+
+pub fn parse_map<P: AsRef<Path>>(path: P) -> Result<Map, Error> {
+    std::fs::read_to_string(path).map_err(Error::Io)?.parse()
+}
+
+impl Map {
+    /// An empty map with default labels.
+    pub fn new(glyphs: BTreeMap<char, Glyph>, height: usize, width: usize) -> Self {
+        Self {
+            glyphs,
+            loc_trans: LocationTranslator::with_default_labels(height, width),
+            width,
+            height,
+            objects: BTreeMap::new(),
+        }
+    }
+
+    /// Places the glyph `c`, which must be defined, at `loc`.
+    pub fn set_object(&mut self, loc: Location, c: char) -> &mut Object {
+        let glyph = self.glyphs[&c].clone();
+        self.objects.insert(
+            loc,
+            Object {
+                loc,
+                glyph,
+                attrs: BTreeMap::new(),
+            },
+        );
+        self.objects.get_mut(&loc).unwrap()
+    }
+
+    pub fn objects_of_type<'a>(&'a self, type_: &'a str) -> impl Iterator<Item = &'a Object> + 'a {
+        self.objects.values().filter(move |o| o.glyph.type_ == type_)
+    }
+
+    pub fn agent(&self) -> Option<&Object> {
+        self.objects_of_type("agent").next()
+    }
+}
+
+const SEPARATOR: &str = "################################################################################";
+
+impl std::fmt::Display for Map {
+    /// Writes the map in the format read by `parse_map`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}\n[glyphs]\n", SEPARATOR)?;
+        let mut types: Vec<&str> = vec![];
+        for g in self.glyphs.values() {
+            if !types.contains(&g.type_.as_str()) {
+                types.push(&g.type_);
+            }
+        }
+        let width = types.iter().map(|t| t.len()).max().unwrap_or(0);
+        for t in types {
+            let chars: Vec<String> = self
+                .glyphs
+                .values()
+                .filter(|g| g.type_ == t)
+                .map(|g| g.character.to_string())
+                .collect();
+            writeln!(f, "{:width$} = {}", t, chars.join(" "), width = width)?;
+        }
+
+        writeln!(f, "\n{}\n[glyphs-attrs]\n", SEPARATOR)?;
+        for g in self.glyphs.values() {
+            for (name, value) in &g.attrs {
+                writeln!(f, "{} {} = {}", g.character, name, value)?;
+            }
+        }
+
+        writeln!(f, "\n{}\n[objects]\n", SEPARATOR)?;
+        writeln!(f, "+{}", self.loc_trans.col_labels.iter().collect::<String>())?;
+        for row in 0..self.height {
+            let mut line: Vec<char> = vec![' '; self.width];
+            for obj in self.objects.range(Location::new(row, 0)..Location::new(row + 1, 0)).map(|(_, o)| o) {
+                line[obj.loc.col] = obj.glyph.character;
+            }
+            let line: String = line.into_iter().collect();
+            writeln!(f, "{}{}", self.loc_trans.row_labels[row], line.trim_end())?;
+        }
+
+        writeln!(f, "\n{}\n[objects-attrs]\n", SEPARATOR)?;
+        for obj in self.objects.values() {
+            let (row, col) = self.loc_trans.labels(obj.loc);
+            for (name, value) in &obj.attrs {
+                writeln!(f, "{},{} {} {} = {}", row, col, obj.glyph.character, name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn maze_map_path() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../res/maze.map")
+    }
+
+    fn parse_err(text: &str) -> (usize, ErrorKind) {
+        match text.parse::<Map>() {
+            Err(Error::Parse { line, kind }) => (line, kind),
+            r => panic!("expected a parse error, got {:?}", r.map(|m| m.objects.len())),
+        }
+    }
+
+    const SMALL: &str = "[glyphs]
+agent = > ^
+food = f
+wall = |
+[glyphs-attrs]
+> dir = east
+^ dir = north
+[objects]
++ABC
+A|>f
+B| f
+[objects-attrs]
+A,C f seq = l
+";
+
+    #[test]
+    fn test_parse_maze_map() {
+        let map = parse_map(maze_map_path()).unwrap();
+        assert_eq!((map.width, map.height), (26, 26));
+
+        let agent = map.agent().unwrap();
+        assert_eq!(agent.loc, Location::new(12, 12));
+        assert_eq!(agent.glyph.character, '>');
+        assert_eq!(agent.glyph.attrs["dir"], "east");
+
+        let loc = map.loc_trans.find('H', 'M').unwrap();
+        assert_eq!(loc, Location::new(7, 12));
+        assert_eq!(map.objects[&loc].glyph.type_, "food");
+        assert_eq!(map.objects[&loc].attrs["seq"], "l");
+
+        let foods: Vec<&Object> = map.objects_of_type("food").collect();
+        assert_eq!(foods.len(), 14);
+        assert!(foods.iter().all(|o| o.attrs.contains_key("seq")));
+    }
+
+    #[test]
+    fn test_write_roundtrip() {
+        let map = parse_map(maze_map_path()).unwrap();
+        let text = map.to_string();
+        let reparsed: Map = text.parse().unwrap();
+        assert_eq!(map, reparsed);
+        assert_eq!(text, reparsed.to_string());
+
+        let small: Map = SMALL.parse().unwrap();
+        assert_eq!(small, small.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_err(&SMALL.replace("A|>f", "A|>x")), (10, ErrorKind::UndefinedGlyph('x')));
+        assert_eq!(
+            parse_err(&SMALL.replace("A,C f", "C,C f")),
+            (13, ErrorKind::UnknownRowLabel("C".to_string()))
+        );
+        assert_eq!(
+            parse_err(&SMALL.replace("A,C f", "A,D f")),
+            (13, ErrorKind::UnknownColumnLabel("D".to_string()))
+        );
+        assert_eq!(
+            parse_err(&SMALL.replace("B| f", "B|^f")),
+            (11, ErrorKind::DuplicateAgent { row: 'A', col: 'B' })
+        );
+        assert_eq!(
+            parse_err(&SMALL.replace("A,C f", "A,C |")),
+            (13, ErrorKind::GlyphMismatch { expected: 'f', found: '|' })
+        );
+        assert_eq!(parse_err(&SMALL.replace("A,C f", "B,B f")), (13, ErrorKind::NoObject));
+        assert_eq!(parse_err(&SMALL.replace("food = f", "food = |")), (4, ErrorKind::DuplicateGlyph('|')));
+        assert_eq!(parse_err(&SMALL.replace("B| f", "B| f |")), (11, ErrorKind::ColumnsExceeded));
+        assert_eq!(
+            parse_err(&SMALL.replace("[objects-attrs]\nA,C f seq = l\n", "")),
+            (11, ErrorKind::MissingSection("objects-attrs".to_string()))
+        );
+        assert_eq!(
+            parse_err(&SMALL.replace("[objects]", "[things]")),
+            (8, ErrorKind::InvalidSection("things".to_string()))
+        );
+    }
+}
@@ -0,0 +1,797 @@
+//! A port of `CpuNetwork` (src/network/cpu/cpunetwork.cpp) and of the
+//! genome-to-network translation done by `InnovGenome::init_phenotype`.
+
+use std::str::FromStr;
+
+use crate::genome::{NodeType, ParsedOrganism};
+
+pub type Real = f64;
+
+/// Same as `NACTIVATES_PER_INPUT` in src/neattypes.h.
+pub const NACTIVATES_PER_INPUT: usize = 10;
+
+/// How `CpuNetwork::activate_scheduled` activates a network for each input,
+/// like `-P activation_schedule=` does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActivationSchedule {
+    /// A fixed number of cycles, `activation_cycles`.
+    Cycles(usize),
+    /// Cycles until no hidden or output node changes by `epsilon` or more, at
+    /// most `max_cycles`.
+    Settle { epsilon: Real, max_cycles: usize },
+    /// A single pass in topological order, or `fallback_cycles` cycles for
+    /// networks with cycles.
+    FeedForward { fallback_cycles: usize },
+}
+
+impl Default for ActivationSchedule {
+    fn default() -> Self {
+        ActivationSchedule::Cycles(NACTIVATES_PER_INPUT)
+    }
+}
+
+impl ActivationSchedule {
+    /// The NeatEnv parameters selecting it, for `AccNeatArgs::params`.
+    pub fn params(&self) -> Vec<(String, String)> {
+        let param = |name: &str, value: String| (name.to_string(), value);
+        match *self {
+            ActivationSchedule::Cycles(n) => vec![
+                param("activation_schedule", "cycles".to_string()),
+                param("activation_cycles", n.to_string()),
+            ],
+            ActivationSchedule::Settle { epsilon, max_cycles } => vec![
+                param("activation_schedule", "settle".to_string()),
+                param("settle_epsilon", epsilon.to_string()),
+                param("settle_max_cycles", max_cycles.to_string()),
+            ],
+            ActivationSchedule::FeedForward { fallback_cycles } => vec![
+                param("activation_schedule", "feedforward".to_string()),
+                param("activation_cycles", fallback_cycles.to_string()),
+            ],
+        }
+    }
+}
+
+pub const SIGMOID_SLOPE: Real = 4.924273;
+pub const SIGMOID_CONSTANT: Real = 2.4621365;
+
+/// Non-shifted steepened sigmoid, see `fsigmoid` in src/neat.h.
+#[inline]
+pub fn fsigmoid(activesum: Real, slope: Real, _constant: Real) -> Real {
+    1.0 / (1.0 + (-(slope * activesum)).exp())
+}
+
+/// Same as `functype` in src/neattypes.h, whose values are those of the
+/// optional last column of `node` lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ActivationFunction {
+    #[default]
+    Sigmoid = 0,
+    Tanh = 1,
+    Relu = 2,
+    Gaussian = 3,
+    Sine = 4,
+    Step = 5,
+    Identity = 6,
+    Abs = 7,
+}
+
+#[derive(Debug)]
+pub struct ActivationFunctionParseError;
+
+impl ActivationFunction {
+    pub const ALL: [ActivationFunction; 8] = [
+        ActivationFunction::Sigmoid,
+        ActivationFunction::Tanh,
+        ActivationFunction::Relu,
+        ActivationFunction::Gaussian,
+        ActivationFunction::Sine,
+        ActivationFunction::Step,
+        ActivationFunction::Identity,
+        ActivationFunction::Abs,
+    ];
+
+    pub fn from_index(i: usize) -> Option<Self> {
+        Self::ALL.get(i).copied()
+    }
+
+    /// The name taken by `-P activation=<name>`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ActivationFunction::Sigmoid => "sigmoid",
+            ActivationFunction::Tanh => "tanh",
+            ActivationFunction::Relu => "relu",
+            ActivationFunction::Gaussian => "gaussian",
+            ActivationFunction::Sine => "sine",
+            ActivationFunction::Step => "step",
+            ActivationFunction::Identity => "identity",
+            ActivationFunction::Abs => "abs",
+        }
+    }
+
+    /// Same as `activation_function` in src/neat.h.
+    #[inline]
+    pub fn apply(self, activesum: Real) -> Real {
+        match self {
+            ActivationFunction::Sigmoid => fsigmoid(activesum, SIGMOID_SLOPE, SIGMOID_CONSTANT),
+            ActivationFunction::Tanh => activesum.tanh(),
+            ActivationFunction::Relu => if activesum > 0.0 { activesum } else { 0.0 },
+            ActivationFunction::Gaussian => (-(activesum * activesum)).exp(),
+            ActivationFunction::Sine => activesum.sin(),
+            ActivationFunction::Step => if activesum > 0.0 { 1.0 } else { 0.0 },
+            ActivationFunction::Identity => activesum,
+            ActivationFunction::Abs => activesum.abs(),
+        }
+    }
+}
+
+impl FromStr for ActivationFunction {
+    type Err = ActivationFunctionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter().copied().find(|f| f.name() == s).ok_or(ActivationFunctionParseError)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NodeCounts {
+    pub bias: usize,
+    pub sensor: usize,
+    pub output: usize,
+    pub hidden: usize,
+
+    pub all: usize,
+    pub input: usize,
+    pub noninput: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NetDims {
+    pub nnodes: NodeCounts,
+    pub nlinks: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetLink {
+    pub weight: Real,
+    pub in_node_index: usize,
+    pub out_node_index: usize,
+    /// Learning rates of `hebbian`, from the first params of the link's trait.
+    pub hebb_rate: Real,
+    pub pre_rate: Real,
+    pub post_rate: Real,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NetNode {
+    pub incoming_start: usize,
+    pub incoming_end: usize,
+    pub function: ActivationFunction,
+}
+
+/// Same as `hebbian` in src/neat.cpp: the Floreano & Urzelai rule giving the
+/// new weight of a link between activations `active_in` and `active_out`.
+pub fn hebbian(
+    weight: Real,
+    maxweight: Real,
+    active_in: Real,
+    active_out: Real,
+    hebb_rate: Real,
+    pre_rate: Real,
+    _post_rate: Real,
+) -> Real {
+    let maxweight = maxweight.max(5.0);
+    let mut weight = weight.clamp(-maxweight, maxweight);
+    let neg = weight < 0.0;
+    if neg {
+        weight = -weight;
+    }
+    let topweight = (weight + 2.0).min(maxweight);
+
+    if !neg {
+        let delta = hebb_rate * (maxweight - weight) * active_in * active_out
+            + pre_rate * topweight * active_in * (active_out - 1.0);
+        weight + delta
+    } else {
+        // In the inhibitory case, the synapse strengthens when the output is low
+        // and the input is high.
+        let delta = pre_rate * (maxweight - weight) * active_in * (1.0 - active_out)
+            - hebb_rate * (topweight + 2.0) * active_in * active_out;
+        -(weight + delta)
+    }
+}
+
+/// State of a network whose weights adapt as it activates, see `CpuNetwork::set_plastic`.
+#[derive(Debug, Clone)]
+struct Plasticity {
+    evolved_weights: Vec<Real>,
+    maxweight: Real,
+    reset: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OrganismEvaluation {
+    pub fitness: Real,
+    pub error: Real,
+}
+
+/// Requires nodes to be sorted by type: BIAS, SENSOR, OUTPUT, HIDDEN.
+#[derive(Debug, Clone)]
+pub struct CpuNetwork {
+    dims: NetDims,
+    nodes: Vec<NetNode>,
+    links: Vec<NetLink>,
+    activations: Vec<Real>,
+    plasticity: Option<Plasticity>,
+    schedule: ActivationSchedule,
+    /// Noninput nodes, each after its sources, for `FeedForward` schedules of
+    /// networks without cycles.
+    feedforward_order: Option<Vec<usize>>,
+}
+
+impl CpuNetwork {
+    pub fn configure(dims: NetDims, nodes: Vec<NetNode>, links: Vec<NetLink>) -> Self {
+        assert_eq!(nodes.len(), dims.nnodes.all);
+        assert_eq!(links.len(), dims.nlinks);
+
+        let mut activations = vec![0.0; dims.nnodes.all];
+        for a in activations.iter_mut().take(dims.nnodes.bias) {
+            *a = 1.0;
+        }
+        Self {
+            dims,
+            nodes,
+            links,
+            activations,
+            plasticity: None,
+            schedule: ActivationSchedule::default(),
+            feedforward_order: None,
+        }
+    }
+
+    /// Sets how `activate_scheduled` activates, `Cycles(NACTIVATES_PER_INPUT)`
+    /// by default.
+    pub fn set_schedule(&mut self, schedule: ActivationSchedule) {
+        self.feedforward_order = match schedule {
+            ActivationSchedule::FeedForward { .. } => topological_order(&self.dims, &self.links),
+            _ => None,
+        };
+        self.schedule = schedule;
+    }
+
+    pub fn schedule(&self) -> ActivationSchedule {
+        self.schedule
+    }
+
+    /// Noninput nodes in the order a `FeedForward` schedule activates them, or
+    /// None when it falls back to cycles.
+    pub fn feedforward_order(&self) -> Option<&[usize]> {
+        self.feedforward_order.as_deref()
+    }
+
+    pub fn is_plastic(&self) -> bool {
+        self.plasticity.is_some()
+    }
+
+    /// Makes link weights adapt with `hebbian` after every `activate`, like
+    /// `-P plastic=1` does. With `reset`, as with the default `plastic_reset`,
+    /// `clear_noninput` restores the evolved weights.
+    pub fn set_plastic(&mut self, reset: bool) {
+        let evolved_weights: Vec<Real> = self.links.iter().map(|l| l.weight).collect();
+        let maxweight = evolved_weights.iter().fold(0.0, |m: Real, w| m.max(w.abs()));
+        self.plasticity = Some(Plasticity { evolved_weights, maxweight, reset });
+    }
+
+    /// Restores the evolved weights of a plastic network.
+    pub fn reset_weights(&mut self) {
+        if let Some(p) = &self.plasticity {
+            for (link, &w) in self.links.iter_mut().zip(&p.evolved_weights) {
+                link.weight = w;
+            }
+        }
+    }
+
+    pub fn from_genome(genome: &ParsedOrganism) -> Self {
+        let (dims, nodes, links) = init_phenotype(genome);
+        Self::configure(dims, nodes, links)
+    }
+
+    pub fn dims(&self) -> NetDims {
+        self.dims
+    }
+
+    pub fn nodes(&self) -> &[NetNode] {
+        &self.nodes
+    }
+
+    pub fn links(&self) -> &[NetLink] {
+        &self.links
+    }
+
+    pub fn clear_noninput(&mut self) {
+        for a in &mut self.activations[self.dims.nnodes.input..] {
+            *a = 0.0;
+        }
+        if self.plasticity.as_ref().is_some_and(|p| p.reset) {
+            self.reset_weights();
+        }
+    }
+
+    pub fn load_sensor(&mut self, isensor: usize, activation: Real) {
+        self.activations[self.dims.nnodes.bias + isensor] = activation;
+    }
+
+    pub fn outputs(&self) -> &[Real] {
+        let start = self.dims.nnodes.input;
+        &self.activations[start..start + self.dims.nnodes.output]
+    }
+
+    pub fn activations(&self) -> &[Real] {
+        &self.activations
+    }
+
+    pub fn set_activations(&mut self, newacts: &[Real]) {
+        self.activations.copy_from_slice(newacts);
+    }
+
+    pub fn activate(&mut self, ncycles: usize) {
+        self.cycle(ncycles, 0.0);
+        self.adapt();
+    }
+
+    /// Activates as the schedule says, like `CpuNetworkExecutor::execute` does.
+    pub fn activate_scheduled(&mut self) {
+        match self.schedule {
+            ActivationSchedule::Cycles(n) => self.cycle(n, 0.0),
+            ActivationSchedule::Settle { epsilon, max_cycles } => self.cycle(max_cycles, epsilon),
+            ActivationSchedule::FeedForward { fallback_cycles } => {
+                if self.feedforward_order.is_some() {
+                    self.single_pass();
+                } else {
+                    self.cycle(fallback_cycles, 0.0);
+                }
+            }
+        }
+        self.adapt();
+    }
+
+    /// Runs `max_cycles` cycles, or stops after the first in which no noninput
+    /// node changed by `epsilon` or more. Hidden nodes count, since without
+    /// clearing between inputs a new input may only have reached them so far.
+    fn cycle(&mut self, max_cycles: usize, epsilon: Real) {
+        let input = self.dims.nnodes.input;
+        let mut act_other = self.activations.clone();
+        let (mut act_curr, mut act_new) = (&mut self.activations, &mut act_other);
+        let mut ncycles = 0;
+
+        while ncycles < max_cycles {
+            for (i, node) in self.nodes.iter().enumerate().skip(input) {
+                let mut sum = 0.0;
+                for link in &self.links[node.incoming_start..node.incoming_end] {
+                    sum += link.weight * act_curr[link.in_node_index];
+                }
+
+                act_new[i] = node.function.apply(sum);
+            }
+
+            let change = (input..self.dims.nnodes.all).fold(0.0, |m: Real, i| m.max((act_new[i] - act_curr[i]).abs()));
+            std::mem::swap(&mut act_curr, &mut act_new);
+            ncycles += 1;
+            if change < epsilon {
+                break;
+            }
+        }
+
+        if ncycles % 2 == 1 {
+            // If an odd number of cycles, the latest activations are in act_other.
+            self.activations[input..].copy_from_slice(&act_other[input..]);
+        }
+    }
+
+    /// Activates each node once, after its sources, which is what enough
+    /// cycles would settle to.
+    fn single_pass(&mut self) {
+        for &i in self.feedforward_order.as_ref().unwrap() {
+            let node = &self.nodes[i];
+            let mut sum = 0.0;
+            for link in &self.links[node.incoming_start..node.incoming_end] {
+                sum += link.weight * self.activations[link.in_node_index];
+            }
+            self.activations[i] = node.function.apply(sum);
+        }
+    }
+
+    fn adapt(&mut self) {
+        if let Some(p) = &self.plasticity {
+            for link in &mut self.links {
+                link.weight = hebbian(
+                    link.weight,
+                    p.maxweight,
+                    self.activations[link.in_node_index],
+                    self.activations[link.out_node_index],
+                    link.hebb_rate,
+                    link.pre_rate,
+                    link.post_rate,
+                );
+            }
+        }
+    }
+}
+
+/// Converts a genome into the flat node/link layout expected by
+/// `CpuNetwork::configure`: links are grouped by output node, in gene order.
+pub fn init_phenotype(genome: &ParsedOrganism) -> (NetDims, Vec<NetNode>, Vec<NetLink>) {
+    let nnodes = genome.nodes.0.len();
+
+    let mut dims = NetDims::default();
+    for node in &genome.nodes.0 {
+        match node.type_ {
+            NodeType::Bias => dims.nnodes.bias += 1,
+            NodeType::Sensor => dims.nnodes.sensor += 1,
+            NodeType::Output => dims.nnodes.output += 1,
+            NodeType::Hidden => dims.nnodes.hidden += 1,
+        }
+    }
+    dims.nnodes.all = nnodes;
+    dims.nnodes.input = dims.nnodes.bias + dims.nnodes.sensor;
+    dims.nnodes.noninput = dims.nnodes.output + dims.nnodes.hidden;
+
+    let mut netlinks = vec![];
+    let mut node_nlinks = vec![0; nnodes];
+    for gene in genome.genes.0.iter().filter(|g| g.enable) {
+        let params = &genome.traits.0[gene.trait_id - 1].params;
+        let netlink = NetLink {
+            weight: gene.weight,
+            in_node_index: genome.node_index(gene.in_node_id).expect("gene references missing node"),
+            out_node_index: genome.node_index(gene.out_node_id).expect("gene references missing node"),
+            hebb_rate: params[0],
+            pre_rate: params[1],
+            post_rate: params[2],
+        };
+        node_nlinks[netlink.out_node_index] += 1;
+        netlinks.push(netlink);
+    }
+    dims.nlinks = netlinks.len();
+
+    let mut netnodes = Vec::with_capacity(nnodes);
+    let mut start = 0;
+    for (n, node) in node_nlinks.iter().zip(&genome.nodes.0) {
+        netnodes.push(NetNode { incoming_start: start, incoming_end: start + n, function: node.function });
+        start += n;
+    }
+
+    let mut placed = vec![0; nnodes];
+    let mut sorted: Vec<Option<NetLink>> = vec![None; netlinks.len()];
+    for netlink in netlinks {
+        let inode = netlink.out_node_index;
+        sorted[netnodes[inode].incoming_start + placed[inode]] = Some(netlink);
+        placed[inode] += 1;
+    }
+
+    (dims, netnodes, sorted.into_iter().map(Option::unwrap).collect())
+}
+
+/// Non-input node indices ordered so that every node comes after all of its
+/// sources, or None if the links contain a cycle.
+pub fn topological_order(dims: &NetDims, links: &[NetLink]) -> Option<Vec<usize>> {
+    let input = dims.nnodes.input;
+    let mut nincoming = vec![0; dims.nnodes.all];
+    let mut outgoing = vec![vec![]; dims.nnodes.all];
+    for link in links {
+        if link.in_node_index >= input {
+            nincoming[link.out_node_index] += 1;
+            outgoing[link.in_node_index].push(link.out_node_index);
+        }
+    }
+
+    let mut ready: Vec<usize> = (input..dims.nnodes.all).filter(|&i| nincoming[i] == 0).rev().collect();
+    let mut order = Vec::with_capacity(dims.nnodes.noninput);
+    while let Some(i) = ready.pop() {
+        order.push(i);
+        for &j in outgoing[i].iter().rev() {
+            nincoming[j] -= 1;
+            if nincoming[j] == 0 {
+                ready.push(j);
+            }
+        }
+    }
+
+    if order.len() == dims.nnodes.noninput {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::sequence::SequenceExperiment;
+    use crate::experiments::xor;
+    use crate::genome::tests::{XOR_CHAMPION, XOR_FEEDFORWARD_CHAMPION};
+    use crate::static_evaluator::evaluate;
+    use crate::{cmd_line, for_each_fittest, AccNeatArgs};
+    use serial_test::serial;
+
+    #[test]
+    fn test_init_phenotype() {
+        let genome: ParsedOrganism = XOR_CHAMPION.parse().unwrap();
+        let net = CpuNetwork::from_genome(&genome);
+        let dims = net.dims();
+        assert_eq!(dims.nnodes.bias, 1);
+        assert_eq!(dims.nnodes.sensor, 2);
+        assert_eq!(dims.nnodes.output, 1);
+        assert_eq!(dims.nnodes.hidden, 3);
+        assert_eq!(dims.nlinks, 13);
+        for (i, node) in net.nodes().iter().enumerate() {
+            for link in &net.links()[node.incoming_start..node.incoming_end] {
+                assert_eq!(link.out_node_index, i);
+            }
+        }
+        assert_eq!(net.activations()[0], 1.0);
+    }
+
+    #[test]
+    fn test_activation_schedules() {
+        let xor_inputs = [(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)];
+        let run = |net: &mut CpuNetwork| -> Vec<Real> {
+            xor_inputs
+                .iter()
+                .map(|&(a, b)| {
+                    net.clear_noninput();
+                    net.load_sensor(0, a);
+                    net.load_sensor(1, b);
+                    net.activate_scheduled();
+                    net.outputs()[0]
+                })
+                .collect()
+        };
+
+        let genome: ParsedOrganism = XOR_FEEDFORWARD_CHAMPION.parse().unwrap();
+        let cycles = run(&mut CpuNetwork::from_genome(&genome));
+        let mut net = CpuNetwork::from_genome(&genome);
+        net.set_schedule(ActivationSchedule::FeedForward { fallback_cycles: 1 });
+        assert_eq!(run(&mut net), cycles);
+        net.set_schedule(ActivationSchedule::Settle { epsilon: 1e-9, max_cycles: 100 });
+        for (settled, expected) in run(&mut net).into_iter().zip(&cycles) {
+            assert!((settled - expected).abs() < 1e-8);
+        }
+        net.set_schedule(ActivationSchedule::Settle { epsilon: 1e-9, max_cycles: 1 });
+        let one_cycle = run(&mut net);
+        net.set_schedule(ActivationSchedule::Cycles(1));
+        assert_eq!(run(&mut net), one_cycle);
+        assert_ne!(one_cycle, cycles);
+
+        // Recurrent networks fall back to cycles.
+        let genome: ParsedOrganism = XOR_CHAMPION.parse().unwrap();
+        let mut net = CpuNetwork::from_genome(&genome);
+        net.set_schedule(ActivationSchedule::Cycles(3));
+        let expected = run(&mut net);
+        net.set_schedule(ActivationSchedule::FeedForward { fallback_cycles: 3 });
+        assert_eq!(run(&mut net), expected);
+    }
+
+    /// Without clearing between inputs, as in sequence and environment tasks, a
+    /// new input only reaches the output through the hidden node in cycle 2.
+    #[test]
+    fn test_settle_through_hidden_node() {
+        let mut dims = NetDims::default();
+        dims.nnodes.bias = 1;
+        dims.nnodes.sensor = 1;
+        dims.nnodes.output = 1;
+        dims.nnodes.hidden = 1;
+        dims.nnodes.input = 2;
+        dims.nnodes.noninput = 2;
+        dims.nnodes.all = 4;
+        dims.nlinks = 2;
+        let link = |in_node_index, out_node_index, weight| NetLink {
+            weight,
+            in_node_index,
+            out_node_index,
+            hebb_rate: 0.0,
+            pre_rate: 0.0,
+            post_rate: 0.0,
+        };
+        let node = |incoming_start, incoming_end| NetNode {
+            incoming_start,
+            incoming_end,
+            function: ActivationFunction::Sigmoid,
+        };
+        // Sensor 1 -> hidden 3 -> output 2.
+        let net = CpuNetwork::configure(dims, vec![node(0, 0), node(0, 0), node(0, 1), node(1, 2)], vec![link(3, 2, 0.5), link(1, 3, 1.0)]);
+
+        let run = |schedule: ActivationSchedule| -> Vec<Real> {
+            let mut net = net.clone();
+            net.set_schedule(schedule);
+            [0.0, 1.0, 0.0, 1.0]
+                .iter()
+                .map(|&x| {
+                    net.load_sensor(0, x);
+                    net.activate_scheduled();
+                    net.outputs()[0]
+                })
+                .collect()
+        };
+        let expected = run(ActivationSchedule::Cycles(50));
+        let settled = run(ActivationSchedule::Settle { epsilon: 1e-9, max_cycles: 50 });
+        for (s, e) in settled.iter().zip(&expected) {
+            assert!((s - e).abs() < 1e-8, "{:?} != {:?}", settled, expected);
+        }
+        assert!((expected[1] - expected[0]).abs() > 0.1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_activation_schedules_match_cpp() {
+        let schedules = [
+            (ActivationSchedule::FeedForward { fallback_cycles: 10 }, "xor"),
+            (ActivationSchedule::Settle { epsilon: 0.001, max_cycles: 30 }, "seq-1bit-3el"),
+            (ActivationSchedule::Cycles(4), "seq-1bit-3el"),
+        ];
+        for (schedule, experiment) in schedules {
+            let args = AccNeatArgs {
+                force_delete: true,
+                pop_size: 200,
+                maxgens: 20,
+                params: schedule.params(),
+                experiment: experiment.to_string(),
+                ..Default::default()
+            };
+            let tests = match experiment {
+                "xor" => xor(),
+                _ => SequenceExperiment { len: 3, ..Default::default() }.tests().unwrap(),
+            };
+            for_each_fittest("schedule", |_| cmd_line(args), |genome| {
+                let mut net = CpuNetwork::from_genome(genome);
+                net.set_schedule(schedule);
+                let eval = evaluate(&mut net, &tests);
+                assert!(
+                    (eval.fitness - genome.info.fitness as Real).abs() < 1e-4,
+                    "{:?}: {} != {}",
+                    schedule,
+                    eval.fitness,
+                    genome.info.fitness
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let genome: ParsedOrganism = XOR_CHAMPION.parse().unwrap();
+        let net = CpuNetwork::from_genome(&genome);
+        assert_eq!(topological_order(&net.dims(), net.links()), None);
+
+        let genome: ParsedOrganism = XOR_FEEDFORWARD_CHAMPION.parse().unwrap();
+        let net = CpuNetwork::from_genome(&genome);
+        let order = topological_order(&net.dims(), net.links()).unwrap();
+        assert_eq!(order.len(), 3);
+        let pos = |i| order.iter().position(|&x| x == i).unwrap();
+        for link in net.links() {
+            if link.in_node_index >= net.dims().nnodes.input {
+                assert!(pos(link.in_node_index) < pos(link.out_node_index));
+            }
+        }
+    }
+
+    #[test]
+    fn test_activation_functions() {
+        use ActivationFunction::*;
+        assert_eq!(Sigmoid.apply(0.0), 0.5);
+        assert_eq!(Tanh.apply(0.5), 0.5f64.tanh());
+        assert_eq!(Relu.apply(-2.0), 0.0);
+        assert_eq!(Relu.apply(2.0), 2.0);
+        assert_eq!(Gaussian.apply(0.0), 1.0);
+        assert_eq!(Gaussian.apply(-1.0), (-1.0f64).exp());
+        assert_eq!(Sine.apply(1.0), 1.0f64.sin());
+        assert_eq!(Step.apply(0.0), 0.0);
+        assert_eq!(Step.apply(0.1), 1.0);
+        assert_eq!(Identity.apply(-3.0), -3.0);
+        assert_eq!(Abs.apply(-3.0), 3.0);
+        for (i, f) in ActivationFunction::ALL.iter().enumerate() {
+            assert_eq!(*f as usize, i);
+            assert_eq!(ActivationFunction::from_index(i), Some(*f));
+            assert_eq!(f.name().parse::<ActivationFunction>().unwrap(), *f);
+        }
+        assert!("softmax".parse::<ActivationFunction>().is_err());
+    }
+
+    #[test]
+    fn test_hebbian() {
+        // Excitatory: strengthened by correlated activity, weakened by input without output.
+        assert_eq!(hebbian(1.0, 2.0, 1.0, 1.0, 0.1, 0.0, 0.0), 1.0 + 0.1 * 4.0);
+        assert_eq!(hebbian(1.0, 2.0, 1.0, 0.0, 0.0, 0.1, 0.0), 1.0 - 0.1 * 3.0);
+        // Inhibitory: weakened by correlated activity.
+        assert_eq!(hebbian(-1.0, 2.0, 1.0, 1.0, 0.1, 0.0, 0.0), -(1.0 - 0.1 * 5.0));
+        // Clamped to maxweight, which is at least 5.
+        assert_eq!(hebbian(9.0, 2.0, 0.0, 0.0, 1.0, 1.0, 1.0), 5.0);
+    }
+
+    #[test]
+    fn test_plastic_reset() {
+        let genome: ParsedOrganism = XOR_FEEDFORWARD_CHAMPION.parse().unwrap();
+        let mut net = CpuNetwork::from_genome(&genome);
+        let evolved: Vec<Real> = net.links().iter().map(|l| l.weight).collect();
+        net.set_plastic(true);
+        net.load_sensor(0, 1.0);
+        net.activate(NACTIVATES_PER_INPUT);
+        assert_ne!(net.links().iter().map(|l| l.weight).collect::<Vec<_>>(), evolved);
+        net.clear_noninput();
+        assert_eq!(net.links().iter().map(|l| l.weight).collect::<Vec<_>>(), evolved);
+
+        net.set_plastic(false);
+        net.activate(NACTIVATES_PER_INPUT);
+        net.clear_noninput();
+        assert_ne!(net.links().iter().map(|l| l.weight).collect::<Vec<_>>(), evolved);
+        net.reset_weights();
+        assert_eq!(net.links().iter().map(|l| l.weight).collect::<Vec<_>>(), evolved);
+    }
+
+    #[test]
+    #[serial]
+    fn test_plastic_matches_cpp() {
+        let exp = SequenceExperiment { len: 3, ..Default::default() };
+        for reset in [true, false] {
+            let args = AccNeatArgs {
+                force_delete: true,
+                pop_size: 100,
+                maxgens: 10,
+                params: vec![
+                    ("plastic".to_string(), "1".to_string()),
+                    ("plastic_reset".to_string(), reset.to_string()),
+                ],
+                experiment: "seq-1bit-3el".to_string(),
+                ..Default::default()
+            };
+            for_each_fittest("plastic", |_| cmd_line(args), |genome| {
+                let mut net = CpuNetwork::from_genome(genome);
+                net.set_plastic(reset);
+                let eval = evaluate(&mut net, &exp.tests().unwrap());
+                assert!((eval.fitness - genome.info.fitness as Real).abs() < 1e-4, "{} != {}", eval.fitness, genome.info.fitness);
+            });
+        }
+    }
+
+    #[test]
+    fn test_activate_xor() {
+        let genome: ParsedOrganism = XOR_CHAMPION.parse().unwrap();
+        let mut net = CpuNetwork::from_genome(&genome);
+        for (a, b, expected) in [(0.0, 0.0, 0.0), (0.0, 1.0, 1.0), (1.0, 0.0, 1.0), (1.0, 1.0, 0.0)] {
+            net.clear_noninput();
+            net.load_sensor(0, a);
+            net.load_sensor(1, b);
+            net.activate(NACTIVATES_PER_INPUT);
+            assert!((net.outputs()[0] - expected).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_activation_functions_match_cpp() {
+        let args = AccNeatArgs {
+            force_delete: true,
+            pop_size: 200,
+            maxgens: 30,
+            params: vec![
+                ("activation".to_string(), "tanh".to_string()),
+                ("mutate_activation_prob".to_string(), "0.3".to_string()),
+                ("mutate_add_node_prob".to_string(), "0.1".to_string()),
+            ],
+            experiment: "xor".to_string(),
+            ..Default::default()
+        };
+        let mut functions = std::collections::HashSet::new();
+        for_each_fittest("activation", |_| cmd_line(args), |genome| {
+            for node in &genome.nodes.0 {
+                if node.type_ == NodeType::Output {
+                    assert_eq!(node.function, ActivationFunction::Tanh);
+                }
+                functions.insert(node.function);
+            }
+            let eval = evaluate(&mut CpuNetwork::from_genome(genome), &xor());
+            assert!((eval.fitness - genome.info.fitness as Real).abs() < 1e-4);
+        });
+        assert!(functions.contains(&ActivationFunction::Tanh));
+        assert!(functions.len() > 2, "{:?}", functions);
+    }
+}
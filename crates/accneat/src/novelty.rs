@@ -0,0 +1,133 @@
+//! Novelty search, as done by `-S novelty` and `-S blended`
+//! (src/experiments/novelty.cpp). Evaluators that characterize behavior give
+//! each organism a vector, e.g. where the maze agent ended each trial, and an
+//! organism's novelty is the mean distance from its behavior to the k nearest
+//! of the rest of the generation and the archive of past novel behaviors.
+
+use crate::network::Real;
+
+/// Behaviors of past generations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoveltyArchive {
+    pub behaviors: Vec<Vec<Real>>,
+}
+
+fn distance(a: &[Real], b: &[Real]) -> Real {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<Real>().sqrt()
+}
+
+/// Scales values to [0, 1], or all 1 if they're all the same.
+fn normalize(values: &[Real]) -> Vec<Real> {
+    let lo = values.iter().copied().fold(Real::INFINITY, Real::min);
+    let hi = values.iter().copied().fold(Real::NEG_INFINITY, Real::max);
+    if hi > lo {
+        values.iter().map(|v| (v - lo) / (hi - lo)).collect()
+    } else {
+        vec![1.0; values.len()]
+    }
+}
+
+impl NoveltyArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Novelty of each behavior: the mean distance to its `k` nearest
+    /// neighbors among the other behaviors and the archive.
+    pub fn sparseness(&self, behaviors: &[Vec<Real>], k: usize) -> Vec<Real> {
+        behaviors
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let mut dists: Vec<Real> = behaviors
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .chain(self.behaviors.iter().enumerate())
+                    .map(|(_, other)| distance(b, other))
+                    .collect();
+                let n = dists.len().min(k);
+                if n == 0 {
+                    return 0.0;
+                }
+                dists.sort_by(|a, b| a.total_cmp(b));
+                dists[..n].iter().sum::<Real>() / n as Real
+            })
+            .collect()
+    }
+
+    /// Archives the `nadd` behaviors with the highest novelty, earlier ones
+    /// first on ties.
+    pub fn add_most_novel(&mut self, behaviors: &[Vec<Real>], novelty: &[Real], nadd: usize) {
+        let mut order: Vec<usize> = (0..novelty.len()).collect();
+        order.sort_by(|&a, &b| novelty[b].total_cmp(&novelty[a]));
+        for &i in order.iter().take(nadd) {
+            self.behaviors.push(behaviors[i].clone());
+        }
+    }
+}
+
+/// Selection scores weighing fitness against novelty, each scaled to [0, 1]
+/// across the population. `-S novelty` is a weight of 1.
+pub fn blend_scores(fitness: &[Real], novelty: &[Real], novelty_weight: Real) -> Vec<Real> {
+    normalize(fitness)
+        .iter()
+        .zip(normalize(novelty))
+        .map(|(f, n)| (1.0 - novelty_weight) * f + novelty_weight * n)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::maze::simulator::{self, MazeConfig};
+    use crate::for_each_fittest;
+    use crate::map::{parse_map, tests::maze_map_path};
+    use crate::network::CpuNetwork;
+    use serial_test::serial;
+
+    #[test]
+    fn test_sparseness() {
+        let behaviors = vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![0.0, 3.0]];
+        let mut archive = NoveltyArchive::new();
+        assert_eq!(archive.sparseness(&behaviors, 1), vec![1.0, 1.0, 2.0]);
+        assert_eq!(archive.sparseness(&behaviors, 15), vec![2.0, 1.5, 2.5]);
+
+        archive.add_most_novel(&behaviors, &[2.0, 1.5, 2.5], 2);
+        assert_eq!(archive.behaviors, vec![vec![0.0, 3.0], vec![0.0, 0.0]]);
+        // Each behavior is now also 0 from its archived copy, if it has one.
+        assert_eq!(archive.sparseness(&behaviors, 1), vec![0.0, 1.0, 0.0]);
+        assert_eq!(archive.sparseness(&[vec![4.0, 3.0]], 2), vec![4.5]);
+        assert_eq!(NoveltyArchive::new().sparseness(&[vec![1.0]], 3), vec![0.0]);
+    }
+
+    #[test]
+    fn test_blend_scores() {
+        let fitness = [1.0, 3.0, 2.0];
+        let novelty = [0.5, 0.0, 1.0];
+        assert_eq!(blend_scores(&fitness, &novelty, 0.0), vec![0.0, 1.0, 0.5]);
+        assert_eq!(blend_scores(&fitness, &novelty, 1.0), vec![0.5, 0.0, 1.0]);
+        assert_eq!(blend_scores(&fitness, &novelty, 0.5), vec![0.25, 0.5, 0.75]);
+        assert_eq!(blend_scores(&[2.0, 2.0], &[1.0, 1.0], 0.5), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_novelty_maze_keeps_objective_fitness() {
+        let map_path = maze_map_path().canonicalize().unwrap();
+        let args = [
+            "-f", "-x", "5", "-n", "100", "-S", "novelty", "-P", "novelty_k=5", "-m", map_path.to_str().unwrap(), "maze",
+        ]
+        .map(String::from);
+
+        // Champions are chosen and written by objective fitness, not novelty.
+        let config = MazeConfig::from_map(&parse_map(maze_map_path()).unwrap()).unwrap();
+        let (stdout, stderr) = for_each_fittest("novelty", |_| args.to_vec(), |genome| {
+            let (eval, trajectories) = simulator::run(&mut CpuNetwork::from_genome(genome), &config);
+            let expected = genome.info.fitness as Real;
+            assert!((eval.fitness - expected).abs() <= 1e-4 * expected.abs().max(1.0), "{} != {}", eval.fitness, expected);
+            assert_eq!(simulator::behavior(&config, &trajectories).len(), 2 * config.trials.len());
+        });
+        assert!(stdout.contains("novelty: best="), "{}{}", stdout, stderr);
+    }
+}
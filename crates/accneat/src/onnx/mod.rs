@@ -0,0 +1,258 @@
+//! ONNX export of feed-forward genomes.
+//!
+//! Every hidden and output node becomes `Sigmoid(slope * (x · w))`, with the
+//! steepened slope used by `fsigmoid`. Nodes are emitted in topological order,
+//! so the model computes the settled output of the network in a single pass.
+//! That is what `CpuNetwork::activate(NACTIVATES_PER_INPUT)` computes as long
+//! as the network is no deeper than `NACTIVATES_PER_INPUT`. Genomes with
+//! nodes of other activation functions are rejected.
+//!
+//! The model takes a `[1, nsensors]` float tensor named `input` and produces a
+//! `[1, noutputs]` float tensor named `output`. Only the Split, Concat, MatMul,
+//! Mul and Sigmoid operators from the default domain (opset 13) are used.
+
+mod proto;
+pub mod reference;
+
+use std::fmt::Formatter;
+use std::path::Path;
+
+use crate::genome::{NodeType, ParsedOrganism};
+use crate::network::{
+    fsigmoid, init_phenotype, topological_order, ActivationFunction, SIGMOID_CONSTANT, SIGMOID_SLOPE,
+};
+use proto::Message;
+
+pub const IR_VERSION: i64 = 7;
+pub const OPSET_VERSION: i64 = 13;
+
+// TensorProto.DataType
+const FLOAT: i64 = 1;
+// AttributeProto.AttributeType
+const ATTR_INT: i64 = 2;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The genome has an enabled recurrent gene, identified by its innovation number.
+    RecurrentGene(usize),
+    /// The enabled genes form a cycle even though none is marked recurrent.
+    Cycle,
+    Io(std::io::Error),
+    Decode(String),
+    Unsupported(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::RecurrentGene(innov) => write!(
+                f,
+                "genome has a recurrent gene (innovation {}); only feed-forward genomes can be exported to ONNX",
+                innov
+            ),
+            Error::Cycle => write!(f, "genome links form a cycle; only feed-forward genomes can be exported to ONNX"),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Decode(e) => write!(f, "malformed ONNX model: {}", e),
+            Error::Unsupported(e) => write!(f, "unsupported ONNX model: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn node_name(genome: &ParsedOrganism, index: usize) -> String {
+    format!("node_{}", genome.nodes.0[index].id)
+}
+
+fn tensor(name: &str, dims: &[i64], data: &[f32]) -> Message {
+    let mut t = Message::new();
+    for &d in dims {
+        t.int64(1, d);
+    }
+    t.int64(2, FLOAT);
+    t.string(8, name);
+    let raw: Vec<u8> = data.iter().flat_map(|x| x.to_le_bytes()).collect();
+    t.bytes(9, &raw);
+    t
+}
+
+fn value_info(name: &str, dims: &[i64]) -> Message {
+    let mut shape = Message::new();
+    for &d in dims {
+        let mut dim = Message::new();
+        dim.int64(1, d);
+        shape.message(1, &dim);
+    }
+    let mut tensor_type = Message::new();
+    tensor_type.int64(1, FLOAT);
+    tensor_type.message(2, &shape);
+    let mut type_ = Message::new();
+    type_.message(1, &tensor_type);
+
+    let mut v = Message::new();
+    v.string(1, name);
+    v.message(2, &type_);
+    v
+}
+
+fn node(op_type: &str, name: &str, inputs: &[&str], outputs: &[&str], axis: Option<i64>) -> Message {
+    let mut n = Message::new();
+    for i in inputs {
+        n.string(1, i);
+    }
+    for o in outputs {
+        n.string(2, o);
+    }
+    n.string(3, name);
+    n.string(4, op_type);
+    if let Some(axis) = axis {
+        let mut a = Message::new();
+        a.string(1, "axis");
+        a.int64(3, axis);
+        a.int64(20, ATTR_INT);
+        n.message(5, &a);
+    }
+    n
+}
+
+/// Serializes the genome as an ONNX `ModelProto`.
+pub fn export(genome: &ParsedOrganism) -> Result<Vec<u8>, Error> {
+    if let Some(g) = genome.genes.0.iter().find(|g| g.enable && g.is_recurrent) {
+        return Err(Error::RecurrentGene(g.innovation_num));
+    }
+    let (dims, nodes, links) = init_phenotype(genome);
+    if let Some(node) = nodes[dims.nnodes.input..].iter().find(|n| n.function != ActivationFunction::Sigmoid) {
+        return Err(Error::Unsupported(format!("{} activation, only sigmoid nodes can be exported", node.function.name())));
+    }
+    let order = topological_order(&dims, &links).ok_or(Error::Cycle)?;
+    let n = dims.nnodes;
+
+    let mut graph = Message::new();
+    let mut graph_nodes = vec![];
+    let mut initializers = vec![];
+
+    initializers.push(tensor("slope", &[1], &[SIGMOID_SLOPE as f32]));
+
+    let names: Vec<String> = (0..n.all).map(|i| node_name(genome, i)).collect();
+    for (i, name) in names.iter().enumerate().take(n.input) {
+        if genome.nodes.0[i].type_ == NodeType::Bias {
+            initializers.push(tensor(name, &[1, 1], &[1.0]));
+        }
+    }
+    let sensors: Vec<&str> = (0..n.input)
+        .filter(|&i| genome.nodes.0[i].type_ == NodeType::Sensor)
+        .map(|i| names[i].as_str())
+        .collect();
+    graph_nodes.push(node("Split", "split_input", &["input"], &sensors, Some(1)));
+
+    for i in order {
+        let name = &names[i];
+        let incoming = &links[nodes[i].incoming_start..nodes[i].incoming_end];
+        if incoming.is_empty() {
+            let resting = fsigmoid(0.0, SIGMOID_SLOPE, SIGMOID_CONSTANT) as f32;
+            initializers.push(tensor(name, &[1, 1], &[resting]));
+            continue;
+        }
+
+        let sources: Vec<&str> = incoming.iter().map(|l| names[l.in_node_index].as_str()).collect();
+        let weights: Vec<f32> = incoming.iter().map(|l| l.weight as f32).collect();
+        let (x, w, sum, z) = (format!("{}_x", name), format!("{}_w", name), format!("{}_sum", name), format!("{}_z", name));
+
+        graph_nodes.push(node("Concat", &x, &sources, &[&x], Some(1)));
+        initializers.push(tensor(&w, &[weights.len() as i64, 1], &weights));
+        graph_nodes.push(node("MatMul", &sum, &[&x, &w], &[&sum], None));
+        graph_nodes.push(node("Mul", &z, &[&sum, "slope"], &[&z], None));
+        graph_nodes.push(node("Sigmoid", name, &[&z], &[name], None));
+    }
+
+    let outputs: Vec<&str> = (n.input..n.input + n.output).map(|i| names[i].as_str()).collect();
+    graph_nodes.push(node("Concat", "concat_output", &outputs, &["output"], Some(1)));
+
+    for gn in &graph_nodes {
+        graph.message(1, gn);
+    }
+    graph.string(2, &format!("accneat_organism_{}", genome.info.id));
+    for init in &initializers {
+        graph.message(5, init);
+    }
+    graph.message(11, &value_info("input", &[1, n.sensor as i64]));
+    graph.message(12, &value_info("output", &[1, n.output as i64]));
+
+    let mut opset = Message::new();
+    opset.string(1, "");
+    opset.int64(2, OPSET_VERSION);
+
+    let mut model = Message::new();
+    model.int64(1, IR_VERSION);
+    model.string(2, "accneat");
+    model.string(3, env!("CARGO_PKG_VERSION"));
+    model.message(7, &graph);
+    model.message(8, &opset);
+    Ok(model.into_bytes())
+}
+
+pub fn write_model<P: AsRef<Path>>(genome: &ParsedOrganism, path: P) -> Result<(), Error> {
+    let bytes = export(genome)?;
+    std::fs::write(path, bytes).map_err(Error::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments;
+    use crate::genome::tests::{XOR_CHAMPION, XOR_FEEDFORWARD_CHAMPION};
+    use crate::network::CpuNetwork;
+    use crate::static_evaluator::{run_tests, step_error};
+    use reference::{Model, Tensor};
+
+    #[test]
+    fn test_export_matches_interpreter() {
+        let genome: ParsedOrganism = XOR_FEEDFORWARD_CHAMPION.parse().unwrap();
+        let model = Model::parse(&export(&genome).unwrap()).unwrap();
+
+        let tests = experiments::xor();
+        let mut net = CpuNetwork::from_genome(&genome);
+        let mut nsteps = 0;
+        run_tests(&mut net, &tests, |step, expected| {
+            let input = Tensor::new(vec![1, step.input.len()], step.input.iter().map(|&x| x as f32).collect());
+            let output = model.run(&[("input", input)]).unwrap().remove(0);
+            assert_eq!(output.dims, vec![1, expected.len()]);
+            for (a, e) in output.data.iter().zip(expected) {
+                assert!((*a as f64 - e).abs() < 1e-5, "{} != {}", a, e);
+            }
+            let actual: Vec<f64> = output.data.iter().map(|&x| x as f64).collect();
+            assert_eq!(step_error(&actual, &step.output), 0.0);
+            nsteps += 1;
+        });
+        assert_eq!(nsteps, 4);
+    }
+
+    #[test]
+    fn test_export_rejects_recurrent() {
+        let genome: ParsedOrganism = XOR_CHAMPION.parse().unwrap();
+        match export(&genome) {
+            Err(Error::RecurrentGene(143)) => {}
+            r => panic!("unexpected result: {:?}", r.map(|b| b.len())),
+        }
+    }
+
+    #[test]
+    fn test_export_rejects_other_activations() {
+        let genome: ParsedOrganism = XOR_FEEDFORWARD_CHAMPION.replace("node 6 1 3\n", "node 6 1 3 2\n").parse().unwrap();
+        match export(&genome) {
+            Err(Error::Unsupported(e)) => assert!(e.starts_with("relu"), "{}", e),
+            r => panic!("unexpected result: {:?}", r.map(|b| b.len())),
+        }
+    }
+
+    #[test]
+    fn test_write_model() {
+        let genome: ParsedOrganism = XOR_FEEDFORWARD_CHAMPION.parse().unwrap();
+        let path = std::env::temp_dir().join(format!("accneat-xor-{}.onnx", std::process::id()));
+        write_model(&genome, &path).unwrap();
+        let model = Model::load(&path).unwrap();
+        assert_eq!(model.inputs, vec![("input".to_string(), vec![1, 2])]);
+        assert_eq!(model.outputs, vec!["output".to_string()]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
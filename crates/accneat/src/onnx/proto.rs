@@ -0,0 +1,156 @@
+//! Just enough of the protobuf wire format to write and read ONNX models.
+
+#[derive(Debug, Clone, Default)]
+pub struct Message {
+    buf: Vec<u8>,
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_FIXED64: u32 = 1;
+const WIRE_BYTES: u32 = 2;
+const WIRE_FIXED32: u32 = 5;
+
+impl Message {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(&mut self, field: u32, wire: u32) {
+        put_varint(&mut self.buf, ((field << 3) | wire) as u64);
+    }
+
+    pub fn int64(&mut self, field: u32, v: i64) -> &mut Self {
+        self.key(field, WIRE_VARINT);
+        put_varint(&mut self.buf, v as u64);
+        self
+    }
+
+    pub fn bytes(&mut self, field: u32, v: &[u8]) -> &mut Self {
+        self.key(field, WIRE_BYTES);
+        put_varint(&mut self.buf, v.len() as u64);
+        self.buf.extend_from_slice(v);
+        self
+    }
+
+    pub fn string(&mut self, field: u32, v: &str) -> &mut Self {
+        self.bytes(field, v.as_bytes())
+    }
+
+    pub fn message(&mut self, field: u32, v: &Message) -> &mut Self {
+        self.bytes(field, &v.buf)
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> Value<'a> {
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Varint(v) => Some(v as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match *self {
+            Value::Bytes(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        self.as_bytes().and_then(|b| std::str::from_utf8(b).ok())
+    }
+}
+
+fn get_varint(buf: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let b = *buf.get(*pos).ok_or("truncated varint")?;
+        *pos += 1;
+        if shift >= 64 {
+            return Err("varint too long".to_string());
+        }
+        result |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok(result);
+        }
+        shift += 7;
+    }
+}
+
+fn take<'a>(buf: &'a [u8], pos: &mut usize, n: usize) -> Result<&'a [u8], String> {
+    let end = pos.checked_add(n).filter(|&end| end <= buf.len()).ok_or("truncated field")?;
+    let result = &buf[*pos..end];
+    *pos = end;
+    Ok(result)
+}
+
+/// Splits a message into its (field number, value) pairs, in wire order.
+pub fn decode(buf: &[u8]) -> Result<Vec<(u32, Value<'_>)>, String> {
+    let mut fields = vec![];
+    let mut pos = 0;
+    while pos < buf.len() {
+        let key = get_varint(buf, &mut pos)?;
+        let field = (key >> 3) as u32;
+        let value = match (key & 7) as u32 {
+            WIRE_VARINT => Value::Varint(get_varint(buf, &mut pos)?),
+            WIRE_FIXED64 => Value::Fixed64(u64::from_le_bytes(take(buf, &mut pos, 8)?.try_into().unwrap())),
+            WIRE_BYTES => {
+                let n = get_varint(buf, &mut pos)? as usize;
+                Value::Bytes(take(buf, &mut pos, n)?)
+            }
+            WIRE_FIXED32 => Value::Fixed32(u32::from_le_bytes(take(buf, &mut pos, 4)?.try_into().unwrap())),
+            wire => return Err(format!("unsupported wire type {} for field {}", wire, field)),
+        };
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut inner = Message::new();
+        inner.string(1, "x");
+        let mut m = Message::new();
+        m.int64(1, 300).int64(2, -1).message(3, &inner);
+        let bytes = m.into_bytes();
+
+        let fields = decode(&bytes).unwrap();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0], (1, Value::Varint(300)));
+        assert_eq!(fields[1].1.as_i64(), Some(-1));
+        assert_eq!(decode(fields[2].1.as_bytes().unwrap()).unwrap()[0].1.as_str(), Some("x"));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut m = Message::new();
+        m.string(1, "hello");
+        let bytes = m.into_bytes();
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! A small pure-Rust evaluator for the ONNX models written by `export`, used
+//! to check them without an ONNX runtime. It understands float tensors and
+//! the handful of operators the exporter emits.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::proto::{decode, Value};
+use super::{Error, FLOAT};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tensor {
+    pub dims: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+impl Tensor {
+    pub fn new(dims: Vec<usize>, data: Vec<f32>) -> Self {
+        assert_eq!(dims.iter().product::<usize>(), data.len());
+        Self { dims, data }
+    }
+
+    fn matrix_dims(&self) -> Result<(usize, usize), Error> {
+        match self.dims[..] {
+            [rows, cols] => Ok((rows, cols)),
+            _ => Err(Error::Unsupported(format!("expected a 2-d tensor, found dims {:?}", self.dims))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub op_type: String,
+    pub name: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub int_attrs: HashMap<String, i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Model {
+    pub nodes: Vec<Node>,
+    pub initializers: HashMap<String, Tensor>,
+    pub inputs: Vec<(String, Vec<usize>)>,
+    pub outputs: Vec<String>,
+}
+
+fn decode_err(e: String) -> Error {
+    Error::Decode(e)
+}
+
+fn string(v: &Value) -> Result<String, Error> {
+    v.as_str().map(str::to_string).ok_or_else(|| Error::Decode("expected a string".to_string()))
+}
+
+fn message<'a>(v: &Value<'a>) -> Result<&'a [u8], Error> {
+    v.as_bytes().ok_or_else(|| Error::Decode("expected a message".to_string()))
+}
+
+fn int(v: &Value) -> Result<i64, Error> {
+    v.as_i64().ok_or_else(|| Error::Decode("expected an integer".to_string()))
+}
+
+fn parse_tensor(buf: &[u8]) -> Result<(String, Tensor), Error> {
+    let mut name = String::new();
+    let mut dims = vec![];
+    let mut data_type = 0;
+    let mut raw = None;
+    let mut float_data = vec![];
+    for (field, v) in decode(buf).map_err(decode_err)? {
+        match field {
+            1 => dims.push(int(&v)? as usize),
+            2 => data_type = int(&v)?,
+            4 => match v {
+                Value::Fixed32(bits) => float_data.push(f32::from_bits(bits)),
+                Value::Bytes(b) => float_data.extend(b.chunks_exact(4).map(|c| f32::from_le_bytes(c.try_into().unwrap()))),
+                _ => return Err(Error::Decode("bad float_data".to_string())),
+            },
+            8 => name = string(&v)?,
+            9 => raw = Some(message(&v)?),
+            _ => {}
+        }
+    }
+    if data_type != FLOAT {
+        return Err(Error::Unsupported(format!("tensor {} has data type {}", name, data_type)));
+    }
+    let data = match raw {
+        Some(raw) => raw.chunks_exact(4).map(|c| f32::from_le_bytes(c.try_into().unwrap())).collect(),
+        None => float_data,
+    };
+    if dims.iter().product::<usize>() != data.len() {
+        return Err(Error::Decode(format!("tensor {} has {} values for dims {:?}", name, data.len(), dims)));
+    }
+    Ok((name, Tensor { dims, data }))
+}
+
+/// The payloads of every occurrence of `field` in `buf`.
+fn submessages(buf: &[u8], field: u32) -> Result<Vec<&[u8]>, Error> {
+    decode(buf)
+        .map_err(decode_err)?
+        .into_iter()
+        .filter(|(f, _)| *f == field)
+        .map(|(_, v)| message(&v))
+        .collect()
+}
+
+fn parse_value_info(buf: &[u8]) -> Result<(String, Vec<usize>), Error> {
+    let mut name = String::new();
+    let mut dims = vec![];
+    for (field, v) in decode(buf).map_err(decode_err)? {
+        match field {
+            1 => name = string(&v)?,
+            // TypeProto.tensor_type.shape.dim[].dim_value
+            2 => {
+                for tensor_type in submessages(message(&v)?, 1)? {
+                    for shape in submessages(tensor_type, 2)? {
+                        for dim in submessages(shape, 1)? {
+                            for (field, v) in decode(dim).map_err(decode_err)? {
+                                if field == 1 {
+                                    dims.push(int(&v)? as usize);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((name, dims))
+}
+
+fn parse_node(buf: &[u8]) -> Result<Node, Error> {
+    let mut node = Node {
+        op_type: String::new(),
+        name: String::new(),
+        inputs: vec![],
+        outputs: vec![],
+        int_attrs: HashMap::new(),
+    };
+    for (field, v) in decode(buf).map_err(decode_err)? {
+        match field {
+            1 => node.inputs.push(string(&v)?),
+            2 => node.outputs.push(string(&v)?),
+            3 => node.name = string(&v)?,
+            4 => node.op_type = string(&v)?,
+            5 => {
+                let mut name = String::new();
+                let mut i = None;
+                for (field, v) in decode(message(&v)?).map_err(decode_err)? {
+                    match field {
+                        1 => name = string(&v)?,
+                        3 => i = Some(int(&v)?),
+                        _ => {}
+                    }
+                }
+                if let Some(i) = i {
+                    node.int_attrs.insert(name, i);
+                }
+            }
+            7 => {
+                let domain = string(&v)?;
+                if !domain.is_empty() && domain != "ai.onnx" {
+                    return Err(Error::Unsupported(format!("operator domain {}", domain)));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(node)
+}
+
+impl Model {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::parse(&std::fs::read(path).map_err(Error::Io)?)
+    }
+
+    pub fn parse(buf: &[u8]) -> Result<Self, Error> {
+        let graph = decode(buf)
+            .map_err(decode_err)?
+            .into_iter()
+            .find(|(field, _)| *field == 7)
+            .ok_or_else(|| Error::Decode("model has no graph".to_string()))?
+            .1;
+
+        let mut model = Model {
+            nodes: vec![],
+            initializers: HashMap::new(),
+            inputs: vec![],
+            outputs: vec![],
+        };
+        for (field, v) in decode(message(&graph)?).map_err(decode_err)? {
+            match field {
+                1 => model.nodes.push(parse_node(message(&v)?)?),
+                5 => {
+                    let (name, t) = parse_tensor(message(&v)?)?;
+                    model.initializers.insert(name, t);
+                }
+                11 => model.inputs.push(parse_value_info(message(&v)?)?),
+                12 => model.outputs.push(parse_value_info(message(&v)?)?.0),
+                _ => {}
+            }
+        }
+        Ok(model)
+    }
+
+    /// Evaluates the graph. Nodes must already be in topological order, as
+    /// the ONNX spec requires.
+    pub fn run(&self, inputs: &[(&str, Tensor)]) -> Result<Vec<Tensor>, Error> {
+        let mut values: HashMap<&str, Tensor> = HashMap::new();
+        for (name, t) in &self.initializers {
+            values.insert(name, t.clone());
+        }
+        for (name, t) in inputs {
+            values.insert(name, t.clone());
+        }
+
+        for node in &self.nodes {
+            let args = node
+                .inputs
+                .iter()
+                .map(|i| values.get(i.as_str()).ok_or_else(|| Error::Decode(format!("{} reads undefined value {}", node.name, i))))
+                .collect::<Result<Vec<&Tensor>, Error>>()?;
+            let results = eval_node(node, &args)?;
+            for (name, t) in node.outputs.iter().zip(results) {
+                values.insert(name, t);
+            }
+        }
+
+        self.outputs
+            .iter()
+            .map(|o| values.remove(o.as_str()).ok_or_else(|| Error::Decode(format!("output {} was never computed", o))))
+            .collect()
+    }
+}
+
+fn axis(node: &Node, rank: usize) -> Result<usize, Error> {
+    let a = node.int_attrs.get("axis").copied().unwrap_or(0);
+    let a = if a < 0 { a + rank as i64 } else { a };
+    if a == 1 && rank == 2 {
+        Ok(1)
+    } else {
+        Err(Error::Unsupported(format!("{} along axis {} of a rank {} tensor", node.op_type, a, rank)))
+    }
+}
+
+fn eval_node(node: &Node, args: &[&Tensor]) -> Result<Vec<Tensor>, Error> {
+    match node.op_type.as_str() {
+        "Split" => {
+            let x = args[0];
+            axis(node, x.dims.len())?;
+            let (rows, cols) = x.matrix_dims()?;
+            let n = node.outputs.len();
+            if rows != 1 || cols % n != 0 {
+                return Err(Error::Unsupported(format!("Split of {:?} into {} parts", x.dims, n)));
+            }
+            let width = cols / n;
+            Ok(x.data.chunks(width).map(|c| Tensor::new(vec![1, width], c.to_vec())).collect())
+        }
+        "Concat" => {
+            let mut data = vec![];
+            let mut cols = 0;
+            for x in args {
+                axis(node, x.dims.len())?;
+                let (rows, c) = x.matrix_dims()?;
+                if rows != 1 {
+                    return Err(Error::Unsupported(format!("Concat of {:?}", x.dims)));
+                }
+                cols += c;
+                data.extend_from_slice(&x.data);
+            }
+            Ok(vec![Tensor::new(vec![1, cols], data)])
+        }
+        "MatMul" => {
+            let (a, b) = (args[0], args[1]);
+            let (n, k) = a.matrix_dims()?;
+            let (k2, m) = b.matrix_dims()?;
+            if k != k2 {
+                return Err(Error::Decode(format!("MatMul of {:?} and {:?}", a.dims, b.dims)));
+            }
+            let mut data = vec![0.0; n * m];
+            for i in 0..n {
+                for j in 0..m {
+                    let mut sum = 0.0;
+                    for l in 0..k {
+                        sum += a.data[i * k + l] * b.data[l * m + j];
+                    }
+                    data[i * m + j] = sum;
+                }
+            }
+            Ok(vec![Tensor::new(vec![n, m], data)])
+        }
+        "Mul" => {
+            let (a, b) = (args[0], args[1]);
+            let data = if a.dims == b.dims {
+                a.data.iter().zip(&b.data).map(|(x, y)| x * y).collect()
+            } else if b.data.len() == 1 {
+                a.data.iter().map(|x| x * b.data[0]).collect()
+            } else {
+                return Err(Error::Unsupported(format!("Mul broadcast of {:?} and {:?}", a.dims, b.dims)));
+            };
+            Ok(vec![Tensor::new(a.dims.clone(), data)])
+        }
+        "Sigmoid" => {
+            let x = args[0];
+            let data = x.data.iter().map(|v| 1.0 / (1.0 + (-v).exp())).collect();
+            Ok(vec![Tensor::new(x.dims.clone(), data)])
+        }
+        op => Err(Error::Unsupported(format!("operator {}", op))),
+    }
+}
@@ -0,0 +1,195 @@
+//! Multi-objective selection, as done by `-S pareto` (src/pareto), and the
+//! Pareto fronts it writes. Instead of `fittest_N`, each run writes a
+//! `pareto_N` directory of `organism_K` files in the fittest format: the
+//! non-dominated champions over fitness, nnodes and nlinks found so far, by
+//! decreasing fitness.
+
+use std::path::Path;
+
+use crate::genome::{parse_fittest_file, Error, ParsedOrganism};
+use crate::network::Real;
+
+/// Fitness is maximized, network size minimized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Objectives {
+    pub fitness: Real,
+    pub nnodes: usize,
+    pub nlinks: usize,
+}
+
+impl Objectives {
+    /// As `Genome::get_stats` counts them, disabled links included.
+    pub fn of(org: &ParsedOrganism) -> Self {
+        Self {
+            fitness: org.info.fitness as Real,
+            nnodes: org.nodes.0.len(),
+            nlinks: org.genes.0.len(),
+        }
+    }
+
+    /// Whether `self` is at least as good as `other` in every objective and
+    /// better in one.
+    pub fn dominates(&self, other: &Objectives) -> bool {
+        if self.fitness < other.fitness || self.nnodes > other.nnodes || self.nlinks > other.nlinks {
+            return false;
+        }
+        self.fitness > other.fitness || self.nnodes < other.nnodes || self.nlinks < other.nlinks
+    }
+}
+
+/// NSGA-II non-dominated sorting: 0 for the Pareto front, 1 for the front once
+/// those are removed, and so on.
+pub fn nondominated_ranks(objs: &[Objectives]) -> Vec<usize> {
+    let mut ranks = vec![usize::MAX; objs.len()];
+    let mut remaining: Vec<usize> = (0..objs.len()).collect();
+    let mut rank = 0;
+    while !remaining.is_empty() {
+        let front: Vec<usize> = remaining
+            .iter()
+            .copied()
+            .filter(|&i| !remaining.iter().any(|&j| objs[j].dominates(&objs[i])))
+            .collect();
+        for &i in &front {
+            ranks[i] = rank;
+        }
+        remaining.retain(|i| !front.contains(i));
+        rank += 1;
+    }
+    ranks
+}
+
+/// NSGA-II crowding distance within each rank: the sum over objectives of the
+/// normalized gap between neighbors, infinite at the extremes of a front.
+pub fn crowding_distances(objs: &[Objectives], ranks: &[usize]) -> Vec<Real> {
+    let mut result = vec![0.0; objs.len()];
+    let objective: [fn(&Objectives) -> Real; 3] =
+        [|o| o.fitness, |o| o.nnodes as Real, |o| o.nlinks as Real];
+    let maxrank = ranks.iter().copied().max().unwrap_or(0);
+    for rank in 0..=maxrank {
+        let mut front: Vec<usize> = (0..objs.len()).filter(|&i| ranks[i] == rank).collect();
+        if front.is_empty() {
+            continue;
+        }
+        for f in &objective {
+            front.sort_by(|&a, &b| f(&objs[a]).total_cmp(&f(&objs[b])));
+            let lo = f(&objs[front[0]]);
+            let hi = f(&objs[*front.last().unwrap()]);
+            result[front[0]] = Real::INFINITY;
+            result[*front.last().unwrap()] = Real::INFINITY;
+            if hi == lo {
+                continue;
+            }
+            for k in 1..front.len().saturating_sub(1) {
+                result[front[k]] += (f(&objs[front[k + 1]]) - f(&objs[front[k - 1]])) / (hi - lo);
+            }
+        }
+    }
+    result
+}
+
+/// Reads the `organism_K` files of a `pareto_N` directory, in order.
+pub fn parse_pareto_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<ParsedOrganism>, Error> {
+    let mut front = vec![];
+    for k in 0.. {
+        let path = dir.as_ref().join(format!("organism_{}", k));
+        if !path.exists() {
+            break;
+        }
+        front.push(parse_fittest_file(path)?);
+    }
+    Ok(front)
+}
+
+/// The member of a front with the fewest links, then nodes, among those with
+/// at least `min_fitness`.
+pub fn smallest_meeting(front: &[ParsedOrganism], min_fitness: Real) -> Option<&ParsedOrganism> {
+    front
+        .iter()
+        .filter(|org| org.info.fitness as Real >= min_fitness)
+        .min_by_key(|org| {
+            let o = Objectives::of(org);
+            (o.nlinks, o.nnodes)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{RunResult, SUCCESS_ERROR};
+    use crate::experiments::xor;
+    use crate::network::CpuNetwork;
+    use crate::static_evaluator::evaluate;
+    use crate::{execute_in, AccNeatArgs, SelectionType};
+    use serial_test::serial;
+
+    fn obj(fitness: Real, nnodes: usize, nlinks: usize) -> Objectives {
+        Objectives { fitness, nnodes, nlinks }
+    }
+
+    #[test]
+    fn test_ranks_and_crowding() {
+        let objs = [obj(1.0, 6, 10), obj(0.5, 4, 2), obj(1.0, 6, 12), obj(0.8, 5, 6), obj(0.5, 5, 6), obj(0.7, 5, 4)];
+        assert!(objs[0].dominates(&objs[2]));
+        assert!(!objs[0].dominates(&objs[0]));
+        assert!(!objs[0].dominates(&objs[1]));
+        let ranks = nondominated_ranks(&objs);
+        assert_eq!(ranks, vec![0, 0, 1, 0, 1, 0]);
+
+        let crowding = crowding_distances(&objs, &ranks);
+        // Front 0 is 1, 5, 3, 0 in every objective, so 1 and 0 are extremes.
+        assert_eq!(crowding[0], Real::INFINITY);
+        assert_eq!(crowding[1], Real::INFINITY);
+        let expected5 = (0.8 - 0.5) / 0.5 + (5.0 - 4.0) / 2.0 + (6.0 - 2.0) / 8.0;
+        let expected3 = (1.0 - 0.7) / 0.5 + (6.0 - 5.0) / 2.0 + (10.0 - 4.0) / 8.0;
+        assert!((crowding[5] - expected5).abs() < 1e-12, "{}", crowding[5]);
+        assert!((crowding[3] - expected3).abs() < 1e-12, "{}", crowding[3]);
+        assert_eq!(crowding[2], Real::INFINITY);
+        assert_eq!(crowding[4], Real::INFINITY);
+    }
+
+    #[test]
+    #[serial]
+    fn test_pareto_xor() {
+        let dir = std::env::temp_dir().join(format!("accneat-pareto-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let args = AccNeatArgs {
+            force_delete: true,
+            pop_size: 300,
+            maxgens: 100,
+            selection: SelectionType::Pareto,
+            ..Default::default()
+        };
+        let (stdout, stderr) = execute_in(args, &dir).unwrap();
+        assert!(stdout.contains("pareto front:"), "{}{}", stdout, stderr);
+
+        let run_dir = dir.join("experiments").join("experiment_1");
+        let pareto_dir = std::fs::read_dir(&run_dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .find(|p| p.file_name().unwrap().to_str().unwrap().starts_with("pareto_"))
+            .unwrap();
+        let front = parse_pareto_dir(pareto_dir).unwrap();
+        assert!(front.len() > 1);
+
+        let objs: Vec<Objectives> = front.iter().map(Objectives::of).collect();
+        for (i, a) in objs.iter().enumerate() {
+            assert!(objs.iter().all(|b| !b.dominates(a)), "{:?} is dominated", a);
+            if i > 0 {
+                assert!(objs[i - 1].fitness >= a.fitness);
+            }
+        }
+        for org in &front {
+            let eval = evaluate(&mut CpuNetwork::from_genome(org), &xor());
+            assert!((eval.fitness - org.info.fitness as Real).abs() < 1e-4);
+        }
+
+        let smallest = smallest_meeting(&front, 0.0).unwrap();
+        assert_eq!(Objectives::of(smallest).nlinks, objs.iter().map(|o| o.nlinks).min().unwrap());
+        assert!(smallest_meeting(&front, 2.0).is_none());
+
+        // Runs can be analysed like any other.
+        let result = RunResult::from_dir(&run_dir, SUCCESS_ERROR).unwrap();
+        assert_eq!(result.fitness, front[0].info.fitness as f64);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+//! A port of `rng_t` (src/util/rng.h): xoshiro256** seeded through
+//! SplitMix64, with the same distributions, so a seed gives the same
+//! sequence as in the C++ engine.
+//!
+//! Derived rngs are seeded with `integer(i32::MIN, i32::MAX)` of their parent,
+//! see `Rng::derive`. From the `-r` seed, each run's rng is derived, and each
+//! organism's from the run's as `organism_rngs` shows.
+
+use std::f64::consts::PI;
+
+use crate::network::Real;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Default for Rng {
+    /// Seeded with 1, like a default-constructed `rng_t`.
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl Rng {
+    /// An `i32` seed converts with `as u64`, i.e. sign-extended, as in C++.
+    pub fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut state = [0; 4];
+        for s in &mut state {
+            // SplitMix64
+            x = x.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            *s = z ^ (z >> 31);
+        }
+        Self { state }
+    }
+
+    /// An rng seeded from this one, as runs, seed genomes and organisms are.
+    pub fn derive(&mut self) -> Rng {
+        Rng::new(self.integer(i32::MIN, i32::MAX) as u64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Uniform in [0, n), rejecting draws at or above the largest multiple of n.
+    pub fn below(&mut self, n: u64) -> u64 {
+        let limit = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < limit {
+                return x % n;
+            }
+        }
+    }
+
+    /// Uniform in [low, hi].
+    pub fn integer(&mut self, low: i32, hi: i32) -> i32 {
+        let n = (hi as i64 - low as i64) as u64 + 1;
+        (low as i64 + self.below(n) as i64) as i32
+    }
+
+    /// Uniform in [begin, len), as `rng_t::index` picks from a vector.
+    pub fn index(&mut self, len: usize, begin: usize) -> usize {
+        begin + self.below((len - begin) as u64) as usize
+    }
+
+    /// Uniform in [0, 1): the top 53 bits of `next_u64`.
+    pub fn prob(&mut self) -> Real {
+        (self.next_u64() >> 11) as Real * (1.0 / 9007199254740992.0)
+    }
+
+    pub fn under(&mut self, prob: Real) -> bool {
+        prob > 0.0 && self.prob() < prob
+    }
+
+    /// -1 or 1, from the top bit of `next_u64`.
+    pub fn posneg(&mut self) -> i32 {
+        if self.next_u64() >> 63 == 1 {
+            1
+        } else {
+            -1
+        }
+    }
+
+    pub fn boolean(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Standard normal, by Box-Muller from two `prob`.
+    pub fn gauss(&mut self) -> Real {
+        let u1 = 1.0 - self.prob();
+        let u2 = self.prob();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+/// The rngs `OrganismsBuffer` gives the organisms of its two generations,
+/// derived in order from the population's rng: the current generation's `n`,
+/// then the next's.
+pub fn organism_rngs(rng: &mut Rng, n: usize) -> (Vec<Rng>, Vec<Rng>) {
+    let a = (0..n).map(|_| rng.derive()).collect();
+    let b = (0..n).map(|_| rng.derive()).collect();
+    (a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    /// The reference sequences pinned by `rng_t::test` in src/util/rng.cpp.
+    #[test]
+    fn test_reference_sequences() {
+        let mut rng = Rng::new(1);
+        assert_eq!(rng.next_u64(), 12966619160104079557);
+        assert_eq!(rng.next_u64(), 9600361134598540522);
+
+        let mut rng = Rng::new(-7i32 as u64);
+        let integers: Vec<i32> = (0..4).map(|_| rng.integer(i32::MIN, i32::MAX)).collect();
+        assert_eq!(integers, vec![-1151077694, -1213309988, 603072185, -1944330335]);
+        let small: Vec<i32> = (0..4).map(|_| rng.integer(-3, 3)).collect();
+        assert_eq!(small, vec![2, -3, 0, -3]);
+        assert_eq!(rng.prob(), 0.14553387957395914);
+        assert_eq!(rng.prob(), 0.2205774574239152);
+        assert_eq!(rng.prob(), 0.29465762425814934);
+        assert_eq!(rng.gauss(), 0.7709155203050004);
+        assert_eq!(rng.gauss(), -0.5863558202826218);
+        assert_eq!(rng.gauss(), 1.0482122495370934);
+        assert_eq!((0..3).map(|_| rng.posneg()).collect::<Vec<_>>(), vec![-1, -1, 1]);
+        assert_eq!(rng.index(10, 3), 5);
+        assert_eq!(rng.index(10, 3), 3);
+    }
+
+    #[test]
+    fn test_organism_rngs() {
+        let (mut a, mut b) = organism_rngs(&mut Rng::new(42), 3);
+        assert_eq!(a[0].prob(), 0.9133897051023544);
+        assert_eq!(a[2].prob(), 0.8020672399080983);
+        assert_eq!(b[0].prob(), 0.3648434979314298);
+        assert_eq!(Rng::default(), Rng::new(1));
+    }
+
+    #[test]
+    fn test_distributions() {
+        let mut rng = Rng::new(3);
+        let n = 100000;
+        let mut counts = [0; 5];
+        for _ in 0..n {
+            counts[rng.integer(0, 4) as usize] += 1;
+            let x = rng.prob();
+            assert!((0.0..1.0).contains(&x));
+        }
+        for c in counts {
+            assert!((c as Real / n as Real - 0.2).abs() < 0.01, "{:?}", counts);
+        }
+        let mean = (0..n).map(|_| rng.gauss()).sum::<Real>() / n as Real;
+        assert!(mean.abs() < 0.02);
+        assert!(!rng.under(0.0));
+    }
+
+    #[test]
+    #[serial]
+    fn test_cpp_self_test() {
+        let (stdout, stderr) = crate::execute_cmd_line(&["-t".to_string()], &std::env::temp_dir()).unwrap();
+        assert!(stdout.contains("rng test passed"), "{}{}", stdout, stderr);
+    }
+}
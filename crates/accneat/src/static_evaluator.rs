@@ -0,0 +1,288 @@
+//! A port of the static evaluator (src/experiments/static/staticevaluator.cxx)
+//! and the `CpuNetworkExecutor` loop that drives it.
+
+use std::str::FromStr;
+
+use crate::network::{CpuNetwork, OrganismEvaluation, Real};
+
+/// Specifies a set of input activations and an expected set of output activations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub input: Vec<Real>,
+    pub output: Vec<Real>,
+    pub weight: Real,
+}
+
+impl Step {
+    pub fn new(input: Vec<Real>, output: Vec<Real>, weight: Real) -> Self {
+        Self { input, output, weight }
+    }
+}
+
+/// A set of Steps for which the neural net state is expected to begin in its
+/// default state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Test {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+impl Test {
+    pub fn new(name: &str, steps: Vec<Step>) -> Self {
+        Self { name: name.to_string(), steps }
+    }
+}
+
+impl From<Vec<Step>> for Test {
+    fn from(steps: Vec<Step>) -> Self {
+        Self { name: String::new(), steps }
+    }
+}
+
+/// Per-output loss of static experiments, the `static_loss` parameter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Loss {
+    #[default]
+    Absolute,
+    Squared,
+    /// Relative to the expected value's own entropy, so a perfect output
+    /// scores 0 whatever the expected value.
+    CrossEntropy,
+    /// 1 for an output on the other side of 0.5 from the expected value.
+    Accuracy,
+}
+
+/// Outputs are clamped this far from 0 and 1 for cross-entropy.
+const CROSS_ENTROPY_EPSILON: Real = 1e-4;
+
+impl Loss {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            Loss::Absolute => "absolute",
+            Loss::Squared => "squared",
+            Loss::CrossEntropy => "cross_entropy",
+            Loss::Accuracy => "accuracy",
+        }
+    }
+
+    /// Most loss a single output can contribute.
+    pub fn max(&self) -> Real {
+        match self {
+            Loss::CrossEntropy => -CROSS_ENTROPY_EPSILON.ln(),
+            _ => 1.0,
+        }
+    }
+
+    /// Same as `output_loss` in staticevaluator.cxx. Accuracy ignores the
+    /// tolerance, and absolute and squared errors are capped at `max`.
+    pub fn output_loss(&self, tolerance: Real, actual: Real, expected: Real) -> Real {
+        let err = (actual - expected).abs();
+        if *self == Loss::Accuracy {
+            return if err < 0.5 { 0.0 } else { 1.0 };
+        }
+        if err < tolerance {
+            return 0.0;
+        }
+        let err = err.min(1.0);
+        match self {
+            Loss::Squared => err * err,
+            Loss::CrossEntropy => {
+                let p = actual.clamp(CROSS_ENTROPY_EPSILON, 1.0 - CROSS_ENTROPY_EPSILON);
+                let mut result = 0.0;
+                if expected > 0.0 {
+                    result -= expected * (p / expected).ln();
+                }
+                if expected < 1.0 {
+                    result -= (1.0 - expected) * ((1.0 - p) / (1.0 - expected)).ln();
+                }
+                result
+            }
+            _ => err,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLossError;
+
+impl FromStr for Loss {
+    type Err = ParseLossError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "absolute" => Ok(Loss::Absolute),
+            "squared" => Ok(Loss::Squared),
+            "cross_entropy" => Ok(Loss::CrossEntropy),
+            "accuracy" => Ok(Loss::Accuracy),
+            _ => Err(ParseLossError),
+        }
+    }
+}
+
+/// How the static evaluator turns outputs into error and fitness.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scoring {
+    pub loss: Loss,
+    /// `static_tolerance`: per-output error counted as none.
+    pub tolerance: Real,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            loss: Loss::Absolute,
+            tolerance: 0.05,
+        }
+    }
+}
+
+impl Scoring {
+    /// The `-P` parameters selecting this scoring.
+    pub fn params(&self) -> Vec<(String, String)> {
+        vec![
+            ("static_loss".to_string(), self.loss.as_arg().to_string()),
+            ("static_tolerance".to_string(), self.tolerance.to_string()),
+        ]
+    }
+
+    /// Error of an organism with the most loss on every output, i.e. a fitness of 0.
+    pub fn max_err(&self, tests: &[Test]) -> Real {
+        max_err(tests) * self.loss.max()
+    }
+
+    /// Error of a single step before weighting.
+    pub fn step_error(&self, actual: &[Real], expected: &[Real]) -> Real {
+        actual
+            .iter()
+            .zip(expected)
+            .map(|(&a, &e)| self.loss.output_loss(self.tolerance, a, e))
+            .sum()
+    }
+
+    pub fn evaluate(&self, net: &mut CpuNetwork, tests: &[Test]) -> OrganismEvaluation {
+        let mut errorsum = 0.0;
+        run_tests(net, tests, |step, outputs| {
+            errorsum += self.step_error(outputs, &step.output) * step.weight;
+        });
+        self.evaluation(errorsum, tests)
+    }
+
+    /// The evaluation of an organism with `errorsum` weighted error on `tests`.
+    pub fn evaluation(&self, errorsum: Real, tests: &[Test]) -> OrganismEvaluation {
+        OrganismEvaluation {
+            error: errorsum,
+            fitness: 1.0 - errorsum / self.max_err(tests),
+        }
+    }
+}
+
+/// Sum of the weighted output counts; an organism with this much error has a
+/// fitness of 0.
+pub fn max_err(tests: &[Test]) -> Real {
+    tests
+        .iter()
+        .flat_map(|t| t.steps.iter())
+        .map(|s| s.weight * s.output.len() as Real)
+        .sum()
+}
+
+/// Error of a single step before weighting, with the default 0.05 dead-zone
+/// absolute error of `Evaluator::evaluate`.
+pub fn step_error(actual: &[Real], expected: &[Real]) -> Real {
+    Scoring::default().step_error(actual, expected)
+}
+
+/// Runs every test through the network the way `CpuNetworkExecutor::execute`
+/// does, calling `f` with the outputs after each step.
+pub fn run_tests<F>(net: &mut CpuNetwork, tests: &[Test], mut f: F)
+where
+    F: FnMut(&Step, &[Real]),
+{
+    for test in tests {
+        for (j, step) in test.steps.iter().enumerate() {
+            if j == 0 {
+                net.clear_noninput();
+            }
+            for (isensor, &x) in step.input.iter().enumerate() {
+                net.load_sensor(isensor, x);
+            }
+            net.activate_scheduled();
+            f(step, net.outputs());
+        }
+    }
+}
+
+/// Evaluates with the default scoring.
+pub fn evaluate(net: &mut CpuNetwork, tests: &[Test]) -> OrganismEvaluation {
+    Scoring::default().evaluate(net, tests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments;
+    use crate::genome::tests::XOR_CHAMPION;
+    use crate::genome::ParsedOrganism;
+    use crate::{cmd_line, for_each_fittest, AccNeatArgs};
+    use serial_test::serial;
+
+    #[test]
+    fn test_evaluate_xor_champion() {
+        let genome: ParsedOrganism = XOR_CHAMPION.parse().unwrap();
+        let mut net = CpuNetwork::from_genome(&genome);
+        let eval = evaluate(&mut net, &experiments::xor());
+        assert_eq!(eval.error, 0.0);
+        assert_eq!(eval.fitness, 1.0);
+    }
+
+    #[test]
+    fn test_max_err() {
+        assert_eq!(max_err(&experiments::xor()), 4.0);
+    }
+
+    #[test]
+    fn test_losses() {
+        assert_eq!("cross_entropy".parse::<Loss>(), Ok(Loss::CrossEntropy));
+        assert_eq!("hinge".parse::<Loss>(), Err(ParseLossError));
+
+        let tol = 0.05;
+        assert_eq!(Loss::Absolute.output_loss(tol, 0.7, 1.0), 0.30000000000000004);
+        assert_eq!(Loss::Absolute.output_loss(tol, 0.97, 1.0), 0.0);
+        assert!((Loss::Squared.output_loss(tol, 0.7, 1.0) - 0.09).abs() < 1e-12);
+        // Outputs outside [0, 1] lose no more than max.
+        assert_eq!(Loss::Absolute.output_loss(tol, -2.0, 1.0), Loss::Absolute.max());
+        assert_eq!(Loss::Squared.output_loss(tol, 3.0, 0.0), Loss::Squared.max());
+        assert!((Loss::CrossEntropy.output_loss(tol, 0.7, 1.0) + (0.7 as Real).ln()).abs() < 1e-12);
+        assert!((Loss::CrossEntropy.output_loss(0.0, 0.0, 1.0) - Loss::CrossEntropy.max()).abs() < 1e-9);
+        assert_eq!(Loss::CrossEntropy.output_loss(0.0, 0.3, 0.3), 0.0);
+        assert!(Loss::CrossEntropy.output_loss(0.0, 0.4, 0.3) > 0.0);
+        assert_eq!(Loss::Accuracy.output_loss(tol, 0.45, 0.0), 0.0);
+        assert_eq!(Loss::Accuracy.output_loss(tol, 0.55, 0.0), 1.0);
+
+        let scoring = Scoring {
+            loss: Loss::CrossEntropy,
+            tolerance: 0.1,
+        };
+        assert!((scoring.max_err(&experiments::xor()) - 4.0 * Loss::CrossEntropy.max()).abs() < 1e-12);
+    }
+
+    #[test]
+    #[serial]
+    fn test_losses_match_cpp_evaluator() {
+        for (loss, tolerance) in [(Loss::Squared, 0.1), (Loss::CrossEntropy, 0.0), (Loss::Accuracy, 0.05)] {
+            let scoring = Scoring { loss, tolerance };
+            let args = AccNeatArgs {
+                force_delete: true,
+                pop_size: 100,
+                maxgens: 5,
+                params: scoring.params(),
+                experiment: "xor".to_string(),
+                ..Default::default()
+            };
+            for_each_fittest("loss", |_| cmd_line(args), |genome| {
+                let eval = scoring.evaluate(&mut CpuNetwork::from_genome(genome), &experiments::xor());
+                assert!((eval.fitness - genome.info.fitness as Real).abs() < 1e-4);
+            });
+        }
+    }
+}
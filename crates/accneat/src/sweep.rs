@@ -0,0 +1,497 @@
+//! Hyperparameter sweeps: a grid or random search over NeatEnv parameters,
+//! population size and search type. Every point is run for several seeds in
+//! parallel, each in its own directory, and the points are ranked by success
+//! rate and generations-to-solve.
+
+use std::fmt::{Formatter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::Mutex;
+
+use crate::analysis::{self, median, read_runs, success_error, RunSet};
+use crate::rng::Rng;
+use crate::{build_cmd, cmd_line, AccNeatArgs, SearchType};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Analysis(analysis::Error),
+    /// Grid searches need every axis to be a list of values.
+    ContinuousGridAxis(String),
+    /// A value `param` can't take, e.g. a float population size.
+    BadValue { param: String, value: String },
+    /// The accneat binary failed, e.g. on an unknown parameter.
+    Run { dir: String, status: ExitStatus, stderr: String },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Analysis(e) => write!(f, "{}", e),
+            Error::ContinuousGridAxis(name) => write!(f, "grid search axis {} is not a list of values", name),
+            Error::BadValue { param, value } => write!(f, "bad value {} for {}", value, param),
+            Error::Run { dir, status, stderr } => write!(f, "{}: {}: {}", dir, status, stderr.trim()),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Param {
+    PopSize,
+    SearchType,
+    /// A NeatEnv field, passed as `-P name=value`.
+    Env(String),
+}
+
+impl std::fmt::Display for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Param::PopSize => write!(f, "pop_size"),
+            Param::SearchType => write!(f, "search_type"),
+            Param::Env(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Search(SearchType),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(x) => write!(f, "{}", x),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Search(s) => write!(f, "{}", s.as_arg()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Domain {
+    Values(Vec<Value>),
+    /// Inclusive integer range, random search only.
+    IntRange(i64, i64),
+    /// Half-open float range, random search only.
+    Uniform(f64, f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    pub param: Param,
+    pub domain: Domain,
+}
+
+impl Axis {
+    pub fn new(param: Param, domain: Domain) -> Self {
+        Self { param, domain }
+    }
+
+    pub fn env(name: &str, domain: Domain) -> Self {
+        Self::new(Param::Env(name.to_string()), domain)
+    }
+
+    pub fn pop_size(sizes: &[i64]) -> Self {
+        Self::new(Param::PopSize, Domain::Values(sizes.iter().map(|&n| Value::Int(n)).collect()))
+    }
+
+    pub fn search_type(types: &[SearchType]) -> Self {
+        Self::new(Param::SearchType, Domain::Values(types.iter().map(|&t| Value::Search(t)).collect()))
+    }
+
+    /// Checks that every value of the domain suits the parameter.
+    fn check(&self) -> Result<(), Error> {
+        let bad = |value: String| Error::BadValue {
+            param: self.param.to_string(),
+            value,
+        };
+        match &self.domain {
+            Domain::Values(values) => {
+                for v in values {
+                    check_value(&self.param, v)?;
+                }
+                Ok(())
+            }
+            Domain::IntRange(lo, hi) => match self.param {
+                Param::PopSize | Param::Env(_) if lo <= hi => Ok(()),
+                _ => Err(bad(format!("{}..={}", lo, hi))),
+            },
+            Domain::Uniform(lo, hi) => match self.param {
+                Param::Env(_) if lo < hi => Ok(()),
+                _ => Err(bad(format!("{}..{}", lo, hi))),
+            },
+        }
+    }
+
+    fn sample(&self, rng: &mut Rng) -> Value {
+        match &self.domain {
+            Domain::Values(values) => values[rng.index(values.len(), 0)],
+            Domain::IntRange(lo, hi) => Value::Int(lo + rng.below((hi - lo) as u64 + 1) as i64),
+            Domain::Uniform(lo, hi) => Value::Float(lo + rng.prob() * (hi - lo)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    Grid,
+    Random { samples: usize, seed: u64 },
+}
+
+fn check_value(param: &Param, value: &Value) -> Result<(), Error> {
+    match (param, value) {
+        (Param::PopSize, Value::Int(_)) | (Param::SearchType, Value::Search(_)) => Ok(()),
+        (Param::Env(_), Value::Int(_) | Value::Float(_)) => Ok(()),
+        (p, v) => Err(Error::BadValue {
+            param: p.to_string(),
+            value: v.to_string(),
+        }),
+    }
+}
+
+/// One set of parameter values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point(pub Vec<(Param, Value)>);
+
+impl Point {
+    /// Arguments for the run, starting from `base`.
+    pub fn args(&self, base: &AccNeatArgs, rng_seed: usize) -> Result<AccNeatArgs, Error> {
+        let mut args = AccNeatArgs {
+            force_delete: true,
+            num_experiments: 1,
+            rng_seed,
+            ..base.clone()
+        };
+        for (param, value) in &self.0 {
+            check_value(param, value)?;
+            match (param, value) {
+                (Param::PopSize, Value::Int(n)) => args.pop_size = *n as usize,
+                (Param::SearchType, Value::Search(s)) => args.search_type = *s,
+                (Param::Env(name), v) => args.params.push((name.clone(), v.to_string())),
+                _ => unreachable!(),
+            }
+        }
+        Ok(args)
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (param, value)) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={}", param, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    /// Experiment name, max generations and any fixed parameters.
+    pub base: AccNeatArgs,
+    pub axes: Vec<Axis>,
+    pub strategy: Strategy,
+    /// Every point is run once per seed.
+    pub seeds: Vec<usize>,
+    /// Runs are written to `out_dir/point_I/seed_S/experiments`.
+    pub out_dir: PathBuf,
+    /// Number of runs executed at once.
+    pub jobs: usize,
+}
+
+impl Sweep {
+    /// Fails if an axis has values its parameter can't take.
+    pub fn new(
+        base: AccNeatArgs,
+        axes: Vec<Axis>,
+        strategy: Strategy,
+        nseeds: usize,
+        out_dir: &Path,
+    ) -> Result<Self, Error> {
+        for axis in &axes {
+            axis.check()?;
+        }
+        Ok(Self {
+            base,
+            axes,
+            strategy,
+            seeds: (1..=nseeds).collect(),
+            out_dir: out_dir.to_path_buf(),
+            jobs: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        })
+    }
+
+    pub fn points(&self) -> Result<Vec<Point>, Error> {
+        match self.strategy {
+            Strategy::Grid => {
+                let mut points = vec![Point(vec![])];
+                for axis in &self.axes {
+                    let values = match &axis.domain {
+                        Domain::Values(values) => values,
+                        _ => return Err(Error::ContinuousGridAxis(axis.param.to_string())),
+                    };
+                    points = points
+                        .into_iter()
+                        .flat_map(|p| {
+                            values.iter().map(move |v| {
+                                let mut p = p.clone();
+                                p.0.push((axis.param.clone(), *v));
+                                p
+                            })
+                        })
+                        .collect();
+                }
+                Ok(points)
+            }
+            Strategy::Random { samples, seed } => {
+                let mut rng = Rng::new(seed);
+                Ok((0..samples)
+                    .map(|_| Point(self.axes.iter().map(|a| (a.param.clone(), a.sample(&mut rng))).collect()))
+                    .collect())
+            }
+        }
+    }
+
+    fn run_dir(&self, ipoint: usize, seed: usize) -> PathBuf {
+        self.out_dir.join(format!("point_{}", ipoint)).join(format!("seed_{}", seed))
+    }
+
+    /// Runs every point for every seed and returns the results, best first.
+    pub fn run(&self) -> Result<Vec<PointResult>, Error> {
+        let points = self.points()?;
+        let jobs: Vec<(usize, usize)> = (0..points.len())
+            .flat_map(|i| self.seeds.iter().map(move |&s| (i, s)))
+            .collect();
+
+        let next = Mutex::new(jobs.iter());
+        let failure = Mutex::new(None);
+        std::thread::scope(|scope| {
+            for _ in 0..self.jobs.max(1) {
+                scope.spawn(|| loop {
+                    let job = next.lock().unwrap().next();
+                    let Some(&(ipoint, seed)) = job else { break };
+                    if let Err(e) = self.run_one(&points[ipoint], ipoint, seed) {
+                        failure.lock().unwrap().get_or_insert(e);
+                        break;
+                    }
+                });
+            }
+        });
+        if let Some(e) = failure.into_inner().unwrap() {
+            return Err(e);
+        }
+
+        let mut results = vec![];
+        for (ipoint, point) in points.into_iter().enumerate() {
+            let mut runs = vec![];
+            for &seed in &self.seeds {
+                let success_error = success_error(&point.args(&self.base, seed)?).map_err(Error::Analysis)?;
+                let experiments = self.run_dir(ipoint, seed).join("experiments");
+                runs.extend(read_runs(experiments, success_error).map_err(Error::Analysis)?);
+            }
+            results.push(PointResult {
+                runs: RunSet::new(&point.to_string(), runs),
+                point,
+            });
+        }
+        rank(&mut results);
+        Ok(results)
+    }
+
+    fn run_one(&self, point: &Point, ipoint: usize, seed: usize) -> Result<(), Error> {
+        let dir = self.run_dir(ipoint, seed);
+        std::fs::create_dir_all(&dir).map_err(Error::Io)?;
+        let output = build_cmd(&cmd_line(point.args(&self.base, seed)?), &dir).map_err(Error::Io)?;
+        if !output.status.success() {
+            return Err(Error::Run {
+                dir: dir.display().to_string(),
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PointResult {
+    pub point: Point,
+    pub runs: RunSet,
+}
+
+impl PointResult {
+    /// Median generations-to-solve, infinite unless most runs succeeded.
+    pub fn median_generations(&self) -> f64 {
+        let gens: Vec<f64> = self.runs.runs.iter().map(|r| r.generations_to_solve()).collect();
+        median(&gens)
+    }
+}
+
+/// Sorts by success rate, then median generations-to-solve, then median fitness.
+pub fn rank(results: &mut [PointResult]) {
+    results.sort_by(|a, b| {
+        b.runs
+            .success_rate()
+            .total_cmp(&a.runs.success_rate())
+            .then(a.median_generations().total_cmp(&b.median_generations()))
+            .then(b.runs.fitness().median.total_cmp(&a.runs.fitness().median))
+    });
+}
+
+pub fn format_table(results: &[PointResult]) -> String {
+    let mut out = String::new();
+    writeln!(out, "rank  success  95% CI        median gens  median fitness  point").unwrap();
+    for (i, r) in results.iter().enumerate() {
+        let (lo, hi) = r.runs.success_interval();
+        let gens = r.median_generations();
+        let gens = if gens.is_finite() { format!("{:.1}", gens) } else { "-".to_string() };
+        writeln!(
+            out,
+            "{:>4}  {:>6.1}%  {:>5.1}-{:>5.1}%  {:>11}  {:>14.4}  {}",
+            i + 1,
+            r.runs.success_rate() * 100.0,
+            lo * 100.0,
+            hi * 100.0,
+            gens,
+            r.runs.fitness().median,
+            r.point
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::RunResult;
+
+    fn base() -> AccNeatArgs {
+        AccNeatArgs {
+            maxgens: 15,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_grid_points() {
+        let axes = vec![
+            Axis::pop_size(&[50, 100]),
+            Axis::search_type(&[SearchType::Phased, SearchType::Complexify]),
+            Axis::env("weight_mut_power", Domain::Values(vec![Value::Float(1.8), Value::Float(2.5)])),
+        ];
+        let sweep = Sweep::new(base(), axes, Strategy::Grid, 3, Path::new("sweep")).unwrap();
+        let points = sweep.points().unwrap();
+        assert_eq!(points.len(), 8);
+        assert_eq!(points[7].to_string(), "pop_size=100 search_type=complexify weight_mut_power=2.5");
+
+        let args = points[7].args(&sweep.base, 2).unwrap();
+        assert_eq!(args.pop_size, 100);
+        assert_eq!(args.search_type, SearchType::Complexify);
+        assert_eq!(args.params, vec![("weight_mut_power".to_string(), "2.5".to_string())]);
+        assert_eq!(args.rng_seed, 2);
+    }
+
+    #[test]
+    fn test_random_points() {
+        let axes = vec![
+            Axis::new(Param::PopSize, Domain::IntRange(50, 60)),
+            Axis::env("compat_threshold", Domain::Uniform(5.0, 15.0)),
+        ];
+        let strategy = Strategy::Random { samples: 20, seed: 7 };
+        let sweep = Sweep::new(base(), axes.clone(), strategy, 1, Path::new("sweep")).unwrap();
+        let points = sweep.points().unwrap();
+        assert_eq!(points.len(), 20);
+        assert_eq!(points, Sweep::new(base(), axes.clone(), strategy, 1, Path::new("sweep")).unwrap().points().unwrap());
+        for p in &points {
+            assert!(
+                matches!((&p.0[0].1, &p.0[1].1), (Value::Int(n), Value::Float(x))
+                    if (50..=60).contains(n) && (5.0..15.0).contains(x)),
+                "{}",
+                p
+            );
+        }
+
+        let grid = Sweep::new(base(), axes, Strategy::Grid, 1, Path::new("sweep")).unwrap();
+        assert!(matches!(grid.points(), Err(Error::ContinuousGridAxis(_))));
+    }
+
+    #[test]
+    fn test_bad_values() {
+        for axis in [
+            Axis::new(Param::PopSize, Domain::Values(vec![Value::Float(50.5)])),
+            Axis::new(Param::PopSize, Domain::Uniform(50.0, 60.0)),
+            Axis::new(Param::SearchType, Domain::Values(vec![Value::Int(1)])),
+            Axis::env("compat_threshold", Domain::Values(vec![Value::Search(SearchType::Phased)])),
+            Axis::env("compat_threshold", Domain::Uniform(15.0, 5.0)),
+            Axis::new(Param::PopSize, Domain::IntRange(60, 50)),
+        ] {
+            let sweep = Sweep::new(base(), vec![axis], Strategy::Grid, 1, Path::new("sweep"));
+            assert!(matches!(sweep, Err(Error::BadValue { .. })));
+        }
+
+        let point = Point(vec![(Param::PopSize, Value::Search(SearchType::Phased))]);
+        assert!(matches!(point.args(&base(), 1), Err(Error::BadValue { .. })));
+    }
+
+    #[test]
+    fn test_rank() {
+        let run = |success, generations| RunResult {
+            success,
+            generations,
+            fitness: if success { 1.0 } else { 0.9 },
+            error: 0.0,
+            nnodes: 6,
+            nlinks: 9,
+        };
+        let result = |name: &str, runs| PointResult {
+            point: Point(vec![(Param::Env(name.to_string()), Value::Int(0))]),
+            runs: RunSet::new(name, runs),
+        };
+        let mut results = vec![
+            result("slow", vec![run(true, 40), run(true, 50)]),
+            result("failing", vec![run(false, 100), run(true, 10)]),
+            result("fast", vec![run(true, 20), run(true, 30)]),
+        ];
+        rank(&mut results);
+        let names: Vec<String> = results.iter().map(|r| r.runs.name.clone()).collect();
+        assert_eq!(names, vec!["fast", "slow", "failing"]);
+        assert!(format_table(&results).lines().nth(1).unwrap().contains("25.0"));
+    }
+
+    #[test]
+    fn test_sweep_run() {
+        let out_dir = std::env::temp_dir().join(format!("accneat-sweep-{}", std::process::id()));
+        let axes = vec![
+            Axis::pop_size(&[50, 150]),
+            Axis::env("mutate_add_link_prob", Domain::Values(vec![Value::Float(0.3)])),
+        ];
+        let sweep = Sweep::new(base(), axes, Strategy::Grid, 2, &out_dir).unwrap();
+        let results = sweep.run().unwrap();
+        assert_eq!(format_table(&results).lines().count(), 3);
+        assert_eq!(results.len(), 2);
+        for r in &results {
+            assert_eq!(r.runs.runs.len(), 2);
+        }
+        assert!(out_dir.join("point_1").join("seed_2").join("experiments").exists());
+
+        let bad = Sweep::new(
+            base(),
+            vec![Axis::env("no_such_param", Domain::Values(vec![Value::Int(1)]))],
+            Strategy::Grid,
+            1,
+            &out_dir.join("bad"),
+        )
+        .unwrap();
+        assert!(matches!(bad.run(), Err(Error::Run { .. })));
+        std::fs::remove_dir_all(out_dir).unwrap();
+    }
+}
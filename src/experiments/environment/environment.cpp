@@ -0,0 +1,143 @@
+#include "std.h"
+
+#include "environment.h"
+#include "cpunetwork.h"
+#include "evaluatorexperiment.h"
+#include "genomemanager.h"
+#include "neat.h"
+#include "networkexecutor.h"
+#include "rng.h"
+#include "util.h"
+
+using namespace NEAT;
+using namespace std;
+
+namespace NEAT {
+
+    //---
+    //--- CLASS EnvironmentEvaluator
+    //---
+    //--- Drives CpuNetworks directly, since environments are arbitrary host code.
+    //---
+    class EnvironmentEvaluator : public NetworkEvaluator {
+        EnvironmentSpec spec;
+        CreateEnvironmentFunc create_env;
+
+    public:
+        EnvironmentEvaluator(const EnvironmentSpec &spec_,
+                             CreateEnvironmentFunc create_env_)
+            : spec(spec_)
+            , create_env(create_env_) {
+            if(env->env_episodes > 0) {
+                spec.nepisodes = env->env_episodes;
+            }
+        }
+
+        virtual void execute(class Network **nets_,
+                             class OrganismEvaluation *results,
+                             size_t nnets) {
+            execute_with_behavior(nets_, results, nullptr, nnets);
+        }
+
+        // The last observation the network was shown in each episode.
+        virtual size_t get_behavior_size() {
+            return spec.nsensors * spec.nepisodes;
+        }
+
+        virtual void execute_with_behavior(class Network **nets_,
+                                           class OrganismEvaluation *results,
+                                           real_t *behaviors,
+                                           size_t nnets) {
+            CpuNetwork **nets = (CpuNetwork **)nets_;
+
+#pragma omp parallel for
+            for(size_t inet = 0; inet < nnets; inet++) {
+                CpuNetwork *net = nets[inet];
+                unique_ptr<Environment> environment(create_env());
+                real_t total_return = 0.0;
+                real_t shortfall = 0.0;
+
+                for(int episode = 0; episode < spec.nepisodes; episode++) {
+                    vector<real_t> observation = environment->reset(episode);
+                    net->clear_noninput();
+                    real_t episode_return = 0.0;
+
+                    for(;;) {
+                        if(observation.size() != spec.nsensors) {
+                            trap("environment observation has " << observation.size()
+                                 << " values, expected " << spec.nsensors);
+                        }
+                        for(size_t isensor = 0; isensor < spec.nsensors; isensor++) {
+                            net->load_sensor(isensor, observation[isensor]);
+                        }
+                        net->activate();
+                        real_t *outputs = net->get_outputs();
+                        vector<real_t> action(outputs, outputs + spec.noutputs);
+
+                        EnvironmentStep step = environment->step(action);
+                        episode_return += step.reward;
+                        if(step.done) {
+                            if(behaviors) {
+                                copy(observation.begin(), observation.end(),
+                                     behaviors + inet * get_behavior_size() + episode * spec.nsensors);
+                            }
+                            break;
+                        }
+                        observation = step.observation;
+                    }
+
+                    total_return += episode_return;
+                    if(episode_return < spec.max_return) {
+                        shortfall += 1.0 - episode_return / spec.max_return;
+                    }
+                }
+
+                results[inet].fitness = total_return / spec.nepisodes;
+                results[inet].error = shortfall / spec.nepisodes;
+            }
+        }
+    };
+
+    NetworkEvaluator *create_environment_evaluator(const EnvironmentSpec &spec,
+                                                   CreateEnvironmentFunc create_env) {
+        return new EnvironmentEvaluator(spec, create_env);
+    }
+
+    void create_environment_experiment(const char *name,
+                                       const EnvironmentSpec &spec,
+                                       CreateEnvironmentFunc create_env) {
+        create_custom_environment_experiment(name, spec, [spec, create_env] () {
+                return create_environment_evaluator(spec, create_env);
+            });
+    }
+
+    void create_custom_environment_experiment(const char *name,
+                                              const EnvironmentSpec &spec,
+                                              function<NetworkEvaluator *()> create_evaluator) {
+        auto create_seeds = [spec] (rng_t rng_exp) {
+            return
+            env->genome_manager->create_seed_generation(env->pop_size,
+                                                        rng_exp,
+                                                        1,
+                                                        spec.nsensors,
+                                                        spec.noutputs,
+                                                        spec.nsensors);
+        };
+
+        new EvaluatorExperiment(name, create_evaluator, create_seeds);
+    }
+
+    uint64_t splitmix64(uint64_t &state) {
+        uint64_t z = (state += 0x9e3779b97f4a7c15ULL);
+        z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9ULL;
+        z = (z ^ (z >> 27)) * 0x94d049bb133111ebULL;
+        return z ^ (z >> 31);
+    }
+
+    real_t splitmix64_uniform(uint64_t &state, real_t lo, real_t hi) {
+        // 53 random bits, exactly representable in a double.
+        real_t unit = real_t(splitmix64(state) >> 11) / real_t(1ULL << 53);
+        return lo + unit * (hi - lo);
+    }
+
+}
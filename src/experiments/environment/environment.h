@@ -0,0 +1,72 @@
+#pragma once
+
+#ifndef ENVIRONMENT_H__
+#define ENVIRONMENT_H__
+
+#include "neattypes.h"
+
+namespace NEAT {
+
+    // Result of one action in an Environment.
+    struct EnvironmentStep {
+        std::vector<real_t> observation;
+        real_t reward;
+        bool done;
+    };
+
+    //---
+    //--- CLASS Environment
+    //---
+    //--- An episodic task in the style of a Gym environment. The network is
+    //--- shown each observation on its sensors and its outputs are the action.
+    //--- Episodes are seeded so that every organism faces the same ones.
+    //---
+    class Environment {
+    public:
+        virtual ~Environment() {}
+
+        // Starts an episode, returning the first observation.
+        virtual std::vector<real_t> reset(uint64_t seed) = 0;
+
+        virtual EnvironmentStep step(const std::vector<real_t> &action) = 0;
+    };
+
+    typedef std::function<Environment *()> CreateEnvironmentFunc;
+
+    struct EnvironmentSpec {
+        size_t nsensors;
+        size_t noutputs;
+        // Episodes per organism, seeded 0..nepisodes-1. Overridden by env_episodes.
+        int nepisodes;
+        // Episode return that counts as solving it.
+        real_t max_return;
+    };
+
+    // Runs each network for the spec's episodes in its own Environment, in
+    // parallel across networks. Fitness is the mean return. Error is the mean
+    // fraction of max_return an episode fell short by, so it's 0 only when
+    // every episode is solved.
+    class NetworkEvaluator *create_environment_evaluator(const EnvironmentSpec &spec,
+                                                         CreateEnvironmentFunc create_env);
+
+    // Registers an experiment evolving networks for the environment.
+    void create_environment_experiment(const char *name,
+                                       const EnvironmentSpec &spec,
+                                       CreateEnvironmentFunc create_env);
+
+    // Registers an experiment for the spec's sensors and outputs, evaluated by
+    // the evaluators create_evaluator returns.
+    void create_custom_environment_experiment(const char *name,
+                                              const EnvironmentSpec &spec,
+                                              std::function<class NetworkEvaluator *()> create_evaluator);
+
+    // Next value of a SplitMix64 sequence, for environments that derive their
+    // episodes from the seed.
+    uint64_t splitmix64(uint64_t &state);
+
+    // Uniform value in [lo, hi) from a SplitMix64 sequence.
+    real_t splitmix64_uniform(uint64_t &state, real_t lo, real_t hi);
+
+}
+
+#endif // #ifndef ENVIRONMENT_H__
@@ -0,0 +1,352 @@
+#include "std.h"
+
+#include "environment.h"
+#include "cpunetwork.h"
+#include "experiment.h"
+#include "json.h"
+#include "neat.h"
+#include "networkexecutor.h"
+#include "util.h"
+
+#ifndef _WIN32
+#include <netdb.h>
+#include <signal.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <unistd.h>
+#endif
+
+using namespace NEAT;
+using namespace std;
+
+//---
+//--- The ext:<address> experiment family evolves networks for an environment
+//--- hosted by another process, spoken to in line-delimited JSON. <address> is
+//--- tcp:<host>:<port> to connect to a running server, or cmd:<command> to run
+//--- the command under /bin/sh and talk to it over its stdin and stdout. Each
+//--- request is one line, answered by one line:
+//---
+//---   {"op":"hello"}
+//---     -> {"nsensors":N,"noutputs":N,"nepisodes":N,"max_return":R}
+//---   {"op":"reset","episodes":[{"id":I,"seed":S},...]}
+//---     -> {"observations":[[...],...]}
+//---   {"op":"step","actions":[{"id":I,"action":[...]},...]}
+//---     -> {"results":[{"observation":[...],"reward":R,"done":B},...]}
+//---   {"op":"close"}
+//---     -> {}
+//---
+//--- The three counts of the hello reply must be whole numbers from 1 to
+//--- NODES_MAX. Replies list episodes in the order of the request. An id
+//--- names an episode from its reset until it's done, and is the organism's
+//--- index in the batch being evaluated: every organism runs episode k with
+//--- seed k, all in one reset and then one step request per step until
+//--- they're done. A reply of {"error":"..."} stops the run with that message.
+//---
+
+namespace {
+
+    //---
+    //--- CLASS Channel
+    //---
+    class Channel {
+        int in_fd = -1;
+        int out_fd = -1;
+        pid_t child = -1;
+        string buffer;
+
+    public:
+        explicit Channel(const string &address) {
+#ifdef _WIN32
+            error("external environments aren't supported on Windows");
+#else
+            if(address.compare(0, 4, "tcp:") == 0) {
+                connect_tcp(address.substr(4));
+            } else if(address.compare(0, 4, "cmd:") == 0) {
+                spawn(address.substr(4));
+            } else {
+                error("invalid external environment address '" << address
+                      << "', expected tcp:<host>:<port> or cmd:<command>");
+            }
+#endif
+        }
+
+        ~Channel() {
+#ifndef _WIN32
+            close(in_fd);
+            if(out_fd != in_fd) {
+                close(out_fd);
+            }
+            if(child > 0) {
+                waitpid(child, nullptr, 0);
+            }
+#endif
+        }
+
+#ifndef _WIN32
+        void connect_tcp(const string &host_port) {
+            size_t colon = host_port.rfind(':');
+            if(colon == string::npos) {
+                error("invalid tcp address '" << host_port << "', expected <host>:<port>");
+            }
+            string host = host_port.substr(0, colon);
+            string port = host_port.substr(colon + 1);
+
+            addrinfo hints;
+            memset(&hints, 0, sizeof(hints));
+            hints.ai_family = AF_UNSPEC;
+            hints.ai_socktype = SOCK_STREAM;
+            addrinfo *addrs;
+            int rc = getaddrinfo(host.c_str(), port.c_str(), &hints, &addrs);
+            if(rc != 0) {
+                error("can't resolve " << host_port << ": " << gai_strerror(rc));
+            }
+            int fd = -1;
+            for(addrinfo *a = addrs; a; a = a->ai_next) {
+                fd = socket(a->ai_family, a->ai_socktype, a->ai_protocol);
+                if(fd < 0) {
+                    continue;
+                }
+                if(connect(fd, a->ai_addr, a->ai_addrlen) == 0) {
+                    break;
+                }
+                close(fd);
+                fd = -1;
+            }
+            freeaddrinfo(addrs);
+            if(fd < 0) {
+                error("can't connect to " << host_port << ": " << strerror(errno));
+            }
+            in_fd = out_fd = fd;
+        }
+
+        void spawn(const string &command) {
+            int to_child[2], from_child[2];
+            if(pipe(to_child) != 0 || pipe(from_child) != 0) {
+                error("pipe failed: " << strerror(errno));
+            }
+            // A dead environment should fail the write below, not kill us.
+            signal(SIGPIPE, SIG_IGN);
+            child = fork();
+            if(child < 0) {
+                error("fork failed: " << strerror(errno));
+            }
+            if(child == 0) {
+                dup2(to_child[0], STDIN_FILENO);
+                dup2(from_child[1], STDOUT_FILENO);
+                close(to_child[0]);
+                close(to_child[1]);
+                close(from_child[0]);
+                close(from_child[1]);
+                execl("/bin/sh", "sh", "-c", command.c_str(), (char *)nullptr);
+                _exit(127);
+            }
+            close(to_child[0]);
+            close(from_child[1]);
+            out_fd = to_child[1];
+            in_fd = from_child[0];
+        }
+#endif
+
+        JsonValue request(const string &line) {
+#ifndef _WIN32
+            string msg = line + "\n";
+            for(size_t sent = 0; sent < msg.size(); ) {
+                ssize_t n = write(out_fd, msg.data() + sent, msg.size() - sent);
+                if(n <= 0) {
+                    error("external environment closed the connection");
+                }
+                sent += n;
+            }
+
+            size_t newline;
+            while((newline = buffer.find('\n')) == string::npos) {
+                char buf[65536];
+                ssize_t n = read(in_fd, buf, sizeof(buf));
+                if(n <= 0) {
+                    error("external environment closed the connection");
+                }
+                buffer.append(buf, n);
+            }
+            string reply = buffer.substr(0, newline);
+            buffer.erase(0, newline + 1);
+
+            JsonValue v;
+            try {
+                v = JsonValue::parse(reply);
+                if(v.has("error")) {
+                    error("external environment: " << v["error"].as_string());
+                }
+            } catch(const exception &x) {
+                error("bad reply from external environment: " << x.what() << ": " << reply);
+            }
+            return v;
+#else
+            return JsonValue();
+#endif
+        }
+    };
+
+    //---
+    //--- CLASS ExternalEnvironmentEvaluator
+    //---
+    //--- Same scoring as EnvironmentEvaluator, with every organism's episode
+    //--- stepped in one round trip. Networks are activated in parallel between
+    //--- round trips.
+    //---
+    class ExternalEnvironmentEvaluator : public NetworkEvaluator {
+        shared_ptr<Channel> channel;
+        EnvironmentSpec spec;
+
+    public:
+        ExternalEnvironmentEvaluator(shared_ptr<Channel> channel_,
+                                     const EnvironmentSpec &spec_)
+            : channel(channel_)
+            , spec(spec_) {
+            if(env->env_episodes > 0) {
+                spec.nepisodes = env->env_episodes;
+            }
+        }
+
+        virtual ~ExternalEnvironmentEvaluator() {
+            channel->request("{\"op\":\"close\"}");
+        }
+
+        virtual void execute(class Network **nets_,
+                             class OrganismEvaluation *results,
+                             size_t nnets) {
+            CpuNetwork **nets = (CpuNetwork **)nets_;
+            vector<real_t> total_return(nnets, 0.0);
+            vector<real_t> shortfall(nnets, 0.0);
+
+            for(int episode = 0; episode < spec.nepisodes; episode++) {
+                ostringstream reset;
+                reset << "{\"op\":\"reset\",\"episodes\":[";
+                for(size_t i = 0; i < nnets; i++) {
+                    if(i > 0) reset << ",";
+                    reset << "{\"id\":" << i << ",\"seed\":" << episode << "}";
+                }
+                reset << "]}";
+                JsonValue reset_reply = channel->request(reset.str());
+                const vector<JsonValue> &obs = reply_list(reset_reply, "observations", nnets);
+
+                vector<vector<real_t>> observations(nnets);
+                vector<real_t> episode_return(nnets, 0.0);
+                vector<size_t> active;
+                try {
+                    for(size_t i = 0; i < nnets; i++) {
+                        observations[i] = obs[i].as_reals();
+                    }
+                } catch(const exception &x) {
+                    error("bad reset reply from external environment: " << x.what());
+                }
+                for(size_t i = 0; i < nnets; i++) {
+                    nets[i]->clear_noninput();
+                    active.push_back(i);
+                }
+
+                while(!active.empty()) {
+                    vector<vector<real_t>> actions(active.size());
+#pragma omp parallel for
+                    for(size_t j = 0; j < active.size(); j++) {
+                        CpuNetwork *net = nets[active[j]];
+                        const vector<real_t> &o = observations[active[j]];
+                        if(o.size() != spec.nsensors) {
+                            trap("environment observation has " << o.size()
+                                 << " values, expected " << spec.nsensors);
+                        }
+                        for(size_t isensor = 0; isensor < spec.nsensors; isensor++) {
+                            net->load_sensor(isensor, o[isensor]);
+                        }
+                        net->activate();
+                        real_t *outputs = net->get_outputs();
+                        actions[j].assign(outputs, outputs + spec.noutputs);
+                    }
+
+                    ostringstream step;
+                    step << "{\"op\":\"step\",\"actions\":[";
+                    for(size_t j = 0; j < active.size(); j++) {
+                        if(j > 0) step << ",";
+                        step << "{\"id\":" << active[j] << ",\"action\":" << json_reals(actions[j]) << "}";
+                    }
+                    step << "]}";
+                    JsonValue step_reply = channel->request(step.str());
+                    const vector<JsonValue> &steps = reply_list(step_reply, "results", active.size());
+
+                    vector<size_t> still_active;
+                    try {
+                        for(size_t j = 0; j < active.size(); j++) {
+                            size_t i = active[j];
+                            episode_return[i] += steps[j]["reward"].as_number();
+                            if(!steps[j]["done"].as_bool()) {
+                                observations[i] = steps[j]["observation"].as_reals();
+                                still_active.push_back(i);
+                            }
+                        }
+                    } catch(const exception &x) {
+                        error("bad step reply from external environment: " << x.what());
+                    }
+                    active.swap(still_active);
+                }
+
+                for(size_t i = 0; i < nnets; i++) {
+                    total_return[i] += episode_return[i];
+                    if(episode_return[i] < spec.max_return) {
+                        shortfall[i] += 1.0 - episode_return[i] / spec.max_return;
+                    }
+                }
+            }
+
+            for(size_t i = 0; i < nnets; i++) {
+                results[i].fitness = total_return[i] / spec.nepisodes;
+                results[i].error = shortfall[i] / spec.nepisodes;
+            }
+        }
+
+    private:
+        static const vector<JsonValue> &reply_list(const JsonValue &reply, const char *key, size_t n) {
+            try {
+                const vector<JsonValue> &list = reply[key].as_array();
+                if(list.size() != n) {
+                    error("external environment replied with " << list.size() << " " << key
+                          << ", expected " << n);
+                }
+                return list;
+            } catch(const exception &x) {
+                error("bad reply from external environment: " << x.what());
+            }
+        }
+    };
+
+    struct ExternalEnvironmentInit {
+        ExternalEnvironmentInit() {
+            Experiment::register_family("ext", "ext:{tcp:<host>:<port>,cmd:<command>}", [] (const string &address) {
+                    shared_ptr<Channel> channel = make_shared<Channel>(address);
+                    EnvironmentSpec spec;
+                    try {
+                        JsonValue hello = channel->request("{\"op\":\"hello\"}");
+                        // Checked before the cast, which is undefined for negative or huge values.
+                        auto count = [&hello] (const char *key) {
+                            double value = hello[key].as_number();
+                            if(!(value >= 1 && value <= NODES_MAX && value == floor(value))) {
+                                error("bad hello from external environment: " << key << " is " << value
+                                      << ", expected a whole number from 1 to " << NODES_MAX);
+                            }
+                            return size_t(value);
+                        };
+                        spec.nsensors = count("nsensors");
+                        spec.noutputs = count("noutputs");
+                        spec.nepisodes = int(count("nepisodes"));
+                        spec.max_return = hello["max_return"].as_number();
+                    } catch(const exception &x) {
+                        error("bad hello from external environment: " << x.what());
+                    }
+
+                    string name = "ext:" + address;
+                    create_custom_environment_experiment(name.c_str(), spec, [channel, spec] () {
+                            return new ExternalEnvironmentEvaluator(channel, spec);
+                        });
+                });
+        }
+    } init;
+
+}
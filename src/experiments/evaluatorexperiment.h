@@ -6,9 +6,13 @@
 #include "experiment.h"
 
 #if __cplusplus >= 199711L
+#include "generationlog.h"
 #include "genomemanager.h"
+#include "lineagelog.h"
 #include "network.h"
+#include "novelty.h"
 #include "organism.h"
+#include "pareto.h"
 #include "population.h"
 #include "stats.h"
 #include "timer.h"
@@ -43,6 +47,14 @@ namespace NEAT {
             return buf;
         }
 
+        std::string get_pareto_path(int experiment_num, int generation) {
+            char buf[1024];
+            sprintf(buf, "%s/pareto_%d",
+                    get_dir_path(experiment_num).c_str(),
+                    generation);
+            return buf;
+        }
+
     public:
         typedef std::function<NetworkEvaluator *()> CreateEvaluatorFunc;
         typedef std::function< std::vector<std::unique_ptr<Genome>> (rng_t rng)> CreateSeedsFunc;
@@ -63,13 +75,16 @@ namespace NEAT {
         }
 
         virtual bool is_success(Organism *org) {
-            return org->eval.error <= 0.0000001;
+            return org->eval.error <= env->success_error;
         }
 
         virtual void run(class rng_t &rng, int gens) override {
             using namespace std;
 
             network_evaluator = unique_ptr<NetworkEvaluator>(create_evaluator());
+            if( is_novelty_selection() && (network_evaluator->get_behavior_size() == 0) ) {
+                error("This experiment doesn't characterize behavior, so can't select by novelty");
+            }
             
             int nsuccesses = 0;
             vector<int> success_generations;
@@ -86,11 +101,19 @@ namespace NEAT {
                 rng_t rng_exp(rng.integer());
 
                 fittest = nullptr;
+                pareto_front.clear();
                 env->genome_manager = GenomeManager::create();
                 vector<unique_ptr<Genome>> genomes = create_seeds(rng_exp);
 
                 //Spawn the Population
                 pop = Population::create(rng_exp, genomes);
+
+                if(is_novelty_selection()) {
+                    novelty_archive.reset(new NoveltyArchive(network_evaluator->get_behavior_size()));
+                }
+
+                GenerationLog genlog(env->generation_log_format, get_dir_path(expcount));
+                LineageLog lineage_log(env->lineage_log, get_dir_path(expcount));
       
                 bool success = false;
                 int gen;
@@ -103,8 +126,13 @@ namespace NEAT {
                     if(gen != 1) {
                         pop->next_generation();
                     }
+                    lineage_log.write_generation(gen, pop);
 
+                    Organism *prev_fittest = fittest.get();
                     evaluate();
+                    if(fittest.get() != prev_fittest) {
+                        lineage_log.write_fittest(fittest->population_index);
+                    }
 
                     if(is_success(fittest.get())) {
                         success = true;
@@ -114,6 +142,10 @@ namespace NEAT {
                     timer.stop();
                     Timer::report();
 
+                    generation_stats.generation = gen;
+                    generation_stats.epoch_time = timer.get_recent();
+                    genlog.write(generation_stats);
+
                     //Don't print on success because we'll exit the loop and print then.
                     if(!success && (gen % env->print_every == 0))
                         print(expcount, gen);
@@ -135,6 +167,8 @@ namespace NEAT {
                 delete env->genome_manager;
             }
 
+            network_evaluator.reset();
+
             cout << "Failures: " << (env->num_runs - nsuccesses) << " out of " << env->num_runs << " runs" << endl;
             if(success_generations.size() > 0) {
                 cout << "Success generations: " << stats(success_generations) << endl;
@@ -146,14 +180,75 @@ namespace NEAT {
         }
 
     private:
+        static bool is_novelty_selection() {
+            return (env->selection_type == SelectionType::NOVELTY)
+                || (env->selection_type == SelectionType::BLENDED);
+        }
+
         void print(int experiment_num,
                    int generation) {
             using namespace std;
 
+            if(env->selection_type == SelectionType::PARETO) {
+                string dir = get_pareto_path(experiment_num, generation);
+                mkdir(dir);
+                for(size_t i = 0; i < pareto_front.size(); i++) {
+                    ofstream out(dir + "/organism_" + to_string(i));
+                    pareto_front[i]->write(out);
+                }
+            }
             ofstream out(get_fittest_path(experiment_num, generation));
             fittest->write(out);
         }
 
+        // Merges the population's non-dominated organisms into those of past
+        // generations, keeping the earliest of any with equal objectives. The
+        // front is ordered by decreasing fitness.
+        void update_pareto_front() {
+            using namespace std;
+
+            vector<unique_ptr<Organism>> candidates;
+            for(auto &org: pareto_front) {
+                candidates.push_back(move(org));
+            }
+            for(size_t i = 0; i < pop->size(); i++) {
+                candidates.push_back(pop->make_copy(i));
+            }
+
+            vector<Objectives> objs;
+            for(auto &org: candidates) {
+                objs.push_back(Objectives::of(*org));
+            }
+            vector<int> ranks = nondominated_ranks(objs);
+
+            pareto_front.clear();
+            vector<Objectives> kept;
+            for(size_t i = 0; i < candidates.size(); i++) {
+                if(ranks[i] != 0) {
+                    continue;
+                }
+                bool duplicate = false;
+                for(const Objectives &o: kept) {
+                    if( (o.fitness == objs[i].fitness)
+                        && (o.nnodes == objs[i].nnodes)
+                        && (o.nlinks == objs[i].nlinks) ) {
+                        duplicate = true;
+                        break;
+                    }
+                }
+                if(!duplicate) {
+                    kept.push_back(objs[i]);
+                    pareto_front.push_back(move(candidates[i]));
+                }
+            }
+            stable_sort(pareto_front.begin(), pareto_front.end(),
+                        [] (const unique_ptr<Organism> &a, const unique_ptr<Organism> &b) {
+                            return a->eval.fitness > b->eval.fitness;
+                        });
+
+            cout << "pareto front: " << pareto_front.size() << " organisms" << endl;
+        }
+
         void evaluate() {
             using namespace std;
 
@@ -166,22 +261,51 @@ namespace NEAT {
                 nets[i] = pop->get(i)->net.get();
             }
             OrganismEvaluation* evaluations = new OrganismEvaluation[norgs];
+            vector<real_t> behaviors;
 
-            network_evaluator->execute(nets, evaluations, norgs);
+            if(novelty_archive) {
+                behaviors.resize(norgs * network_evaluator->get_behavior_size());
+                network_evaluator->execute_with_behavior(nets, evaluations, behaviors.data(), norgs);
+            } else {
+                network_evaluator->execute(nets, evaluations, norgs);
+            }
             delete [] nets;
 
             Organism *best = nullptr;
+            vector<real_t> fitness;
+            vector<real_t> error;
+            vector<size_t> nnodes;
+            vector<size_t> nlinks;
             for(size_t i = 0; i < norgs; i++) {
                 Organism *org = pop->get(i);
                 org->eval = evaluations[i];
                 if( !best || (org->eval.fitness > best->eval.fitness) ) {
                     best = org;
                 }
+
+                Genome::Stats gstats = org->genome->get_stats();
+                fitness.push_back(org->eval.fitness);
+                error.push_back(org->eval.error);
+                nnodes.push_back(gstats.nnodes);
+                nlinks.push_back(gstats.nlinks);
             }
             delete [] evaluations;
 
             timer.stop();
 
+            GenerationStats &s = generation_stats;
+            s.best_fitness = best->eval.fitness;
+            s.mean_fitness = mean(fitness);
+            s.median_fitness = median(fitness);
+            s.best_error = *min_element(error.begin(), error.end());
+            s.mean_error = mean(error);
+            s.median_error = median(error);
+            s.species_sizes = pop->get_species_sizes();
+            s.mean_nnodes = mean(nnodes);
+            s.mean_nlinks = mean(nlinks);
+            s.search_phase = env->genome_manager->get_search_phase();
+            s.evaluate_time = timer.get_recent();
+
             if(!fittest || (best->eval.fitness > fittest->eval.fitness)) {
                 fittest = pop->make_copy(best->population_index);
             }
@@ -193,10 +317,42 @@ namespace NEAT {
                  << ", nnodes=" << gstats.nnodes
                  << ", nlinks=" << gstats.nlinks
                  << endl;
+
+            if(novelty_archive) {
+                select_by_novelty(behaviors, fitness);
+            }
+            if(env->selection_type == SelectionType::PARETO) {
+                update_pareto_front();
+            }
+        }
+
+        // Replaces each organism's fitness with its selection score, now that
+        // the objective fitness has been recorded.
+        void select_by_novelty(const std::vector<real_t> &behaviors,
+                               const std::vector<real_t> &fitness) {
+            using namespace std;
+
+            size_t norgs = pop->size();
+            vector<real_t> novelty = novelty_archive->sparseness(behaviors.data(), norgs, env->novelty_k);
+            novelty_archive->add_most_novel(behaviors.data(), novelty, env->novelty_archive_add);
+
+            real_t weight = env->selection_type == SelectionType::NOVELTY ? 1.0 : env->novelty_weight;
+            vector<real_t> scores = blend_scores(fitness, novelty, weight);
+            for(size_t i = 0; i < norgs; i++) {
+                pop->get(i)->eval.fitness = scores[i];
+            }
+
+            cout << "novelty: best=" << *max_element(novelty.begin(), novelty.end())
+                 << ", mean=" << mean(novelty)
+                 << ", archive=" << novelty_archive->size()
+                 << endl;
         }
 
         class Population *pop;
         std::unique_ptr<Organism> fittest;
+        std::unique_ptr<NoveltyArchive> novelty_archive;
+        std::vector<std::unique_ptr<Organism>> pareto_front;
+        GenerationStats generation_stats;
     };
 
 }
@@ -11,13 +11,29 @@ using namespace std;
 //---
 //------------------------------
 map<string, Experiment *> *Experiment::experiments = nullptr;
+map<string, Experiment::Family> *Experiment::families = nullptr;
 
 Experiment *Experiment::get(const char *name) {
     if(!experiments) {
         experiments = new map<string, Experiment*>();
     }
     auto it = experiments->find(name);
-    return it == experiments->end() ? nullptr : it->second;
+    if(it != experiments->end()) {
+        return it->second;
+    }
+
+    const char *colon = strchr(name, ':');
+    if(colon && families) {
+        auto family = families->find(string(name, colon));
+        if(family != families->end()) {
+            family->second.create(colon + 1);
+            it = experiments->find(name);
+            if(it != experiments->end()) {
+                return it->second;
+            }
+        }
+    }
+    return nullptr;
 }
 
 vector<string> Experiment::get_names() {
@@ -27,12 +43,32 @@ vector<string> Experiment::get_names() {
             result.push_back(kv.first);
         }
     }
+    if(families) {
+        for(auto &kv: *families) {
+            result.push_back(kv.second.usage);
+        }
+    }
     return result;
 }
 
+void Experiment::register_family(const char *prefix,
+                                 const char *usage,
+                                 CreateFamilyMemberFunc create) {
+    if(!families) {
+        families = new map<string, Family>();
+    }
+    if(families->find(prefix) != families->end()) {
+        trap("Experiment family already registered: " << prefix);
+    }
+    families->insert(make_pair(prefix, Family{usage, create}));
+}
+
 Experiment::Experiment(const char *name) {
     this->name = name;
-    if(get(name) != nullptr) {
+    if(!experiments) {
+        experiments = new map<string, Experiment*>();
+    }
+    if(experiments->find(name) != experiments->end()) {
         trap("Experiment already registered: " << name);
     }
     experiments->insert(make_pair(name, this));
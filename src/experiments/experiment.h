@@ -7,11 +7,27 @@ namespace NEAT {
 
     class Experiment {
     public:
+        // Creates and registers the experiment named "prefix:arg", given arg.
+        typedef std::function<void (const std::string &arg)> CreateFamilyMemberFunc;
+
         static std::vector<std::string> get_names();
         static Experiment *get(const char *name);
 
+        // Registers a parameterized family of experiments whose names are
+        // "prefix:arg", created on first use. usage is shown in the list of
+        // experiment names, e.g. "regex:<pattern>".
+        static void register_family(const char *prefix,
+                                    const char *usage,
+                                    CreateFamilyMemberFunc create);
+
     private:
+        struct Family {
+            std::string usage;
+            CreateFamilyMemberFunc create;
+        };
+
         static std::map<std::string, Experiment*> *experiments;
+        static std::map<std::string, Family> *families;
 
     public:
         virtual ~Experiment();
@@ -24,7 +40,7 @@ namespace NEAT {
     private:
         Experiment() {}
 
-        const char *name;
+        std::string name;
     };
 }
 
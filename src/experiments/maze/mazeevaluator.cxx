@@ -2,15 +2,23 @@
 
 #include "mazeevaluator.h"
 #include "map.h"
+#include "neat.h"
 #include "network.h"
 #include "networkexecutor.h"
 #include "resource.h"
+#include "util.h"
 #include <assert.h>
 
 //#define Truncate_Seq 2
 
-#define Max_Seq_Len 3
-#define Max_Maze_Len 26
+// Limits of the types of Config: positions are chars, sequence lengths and
+// distances uchars, with 0xff meaning unreachable.
+#define Max_Seq_Len 255
+#define Max_Maze_Len 127
+#define Max_Dist 254
+// Bounds the final positions Evaluator keeps for behavior(), which is enough
+// for every sequence of up to 7 turns.
+#define Max_Trials ((2 << 7) - 2)
 
 using namespace std;
 
@@ -85,26 +93,63 @@ namespace NEAT {
         __if(north);
         __if(west);
         __if(south);
-        abort();
+        error("Invalid agent dir '" << str << "', expected east, north, west or south");
 #undef __if
     }
 
+    __net_eval_decl inline size_t align8(size_t n) {
+        return (n + 7) & ~size_t(7);
+    }
+
+    // Sized from the map: the Config is followed by wall[width * height], then
+    // by ntrials Trials of trial_size bytes, each followed by seq[max_seqlen]
+    // and dist_map[width * height].
     struct Config {
         uchar width;
         uchar height;
         position_t agent_pos;
         direction_t agent_dir;
-        bool wall[Max_Maze_Len * Max_Maze_Len];
+        uchar max_seqlen;
+        ushort ntrials;
+        size_t trial_size;
+
         struct Trial {
             position_t food_pos;
             uchar seqlen;
-            real_t seq[Max_Seq_Len];
             ushort max_steps;
             uchar max_dist;
-            uchar dist_map[Max_Maze_Len * Max_Maze_Len];
+
+            __net_eval_decl real_t *seq() {
+                return (real_t *)((char *)this + align8(sizeof(Trial)));
+            }
+            __net_eval_decl const real_t *seq() const {
+                return const_cast<Trial *>(this)->seq();
+            }
+            __net_eval_decl uchar *dist_map(uchar max_seqlen) {
+                return (uchar *)(seq() + max_seqlen);
+            }
+            __net_eval_decl const uchar *dist_map(uchar max_seqlen) const {
+                return const_cast<Trial *>(this)->dist_map(max_seqlen);
+            }
         };
-        ushort ntrials;
-        Trial trials[];
+
+        static size_t get_trial_size(size_t ncells, size_t max_seqlen) {
+            return align8(sizeof(Trial)) + align8(sizeof(real_t) * max_seqlen + ncells);
+        }
+
+        __net_eval_decl bool *wall() {
+            return (bool *)((char *)this + align8(sizeof(Config)));
+        }
+        __net_eval_decl const bool *wall() const {
+            return const_cast<Config *>(this)->wall();
+        }
+        __net_eval_decl Trial &trial(size_t i) {
+            char *trials = (char *)wall() + align8(width * height);
+            return *(Trial *)(trials + i * trial_size);
+        }
+        __net_eval_decl const Trial &trial(size_t i) const {
+            return const_cast<Config *>(this)->trial(i);
+        }
     };
 
     static void create_distance_map(const Config *config,
@@ -150,23 +195,29 @@ namespace NEAT {
             }
         };
 
-        local::_(w, h, config->wall, dist_map, max_dist, from.row, from.col, 0);
+        local::_(w, h, config->wall(), dist_map, max_dist, from.row, from.col, 0);
     }
 
     static void create_config(__out Config *&config_,
                               __out size_t &len_) {
-        Map map = parse_map( find_resource("maze.map") );
+        Map map = parse_map( env->maze_map_path.empty()
+                             ? find_resource("maze.map")
+                             : env->maze_map_path );
+        if(map.width > Max_Maze_Len || map.height > Max_Maze_Len) {
+            error("Maze is " << map.width << "x" << map.height << ", max is " << Max_Maze_Len << "x" << Max_Maze_Len);
+        }
+        const size_t ncells = map.width * map.height;
 
         Config config;
         config.width = map.width;
         config.height = map.height;
-        assert(config.width * config.height <= sizeof(config.wall));
+        vector<bool> wall(ncells, false);
 
-        config.ntrials = 0;
-
-        memset(config.wall, 0, sizeof(bool) * config.width * config.height);
-
-        vector<Config::Trial> trials;
+        struct TrialSpec {
+            position_t food_pos;
+            string seq;
+        };
+        vector<TrialSpec> trials;
 
         for(std::map<Location, Object>::iterator it = map.objects.begin();
             it != map.objects.end();
@@ -177,57 +228,69 @@ namespace NEAT {
             size_t col = obj.loc.index.col;
 
             if(obj.glyph.type == "wall") {
-                config.wall[row * map.width + col] = true;
+                wall[row * map.width + col] = true;
             }  else if(obj.glyph.type == "agent") {
                 config.agent_pos.row = obj.loc.index.row;
                 config.agent_pos.col = obj.loc.index.col;
                 config.agent_dir = parse(obj.glyph.attrs["dir"]);
             } else if(obj.glyph.type == "food") {
                 if( obj.attrs.find("seq") != obj.attrs.end() ) {
-                    Config::Trial trial;
-                    trial.food_pos.row = obj.loc.index.row;
-                    trial.food_pos.col = obj.loc.index.col;
                     string seq = obj.attrs["seq"];
-                    assert(seq.length() > 0 && seq.length() <= Max_Seq_Len);
-            
-                    trial.seqlen = seq.length();
-                    for(size_t i = 0; i < seq.length(); i++) {
-                        char c = seq[i];
-                        if(c == 'l') {
-                            trial.seq[i] = 0.0;
-                        } else if(c == 'r') {
-                            trial.seq[i] = 1.0;
-                        } else {
-                            abort();
-                        }
+                    if(seq.length() == 0 || seq.length() > Max_Seq_Len) {
+                        error("Invalid seq '" << seq << "', max length is " << Max_Seq_Len);
                     }
-
-#ifdef Truncate_Seq
-                    for(size_t i = Truncate_Seq; i < seq.length(); i++) {
-                        trial.seq[i] = 0.5;
+                    for(char c: seq) {
+                        if(c != 'l' && c != 'r') {
+                            error("Invalid seq '" << seq << "', expected only 'l' and 'r'");
+                        }
                     }
-#endif
-
-                    trials.push_back(trial);
+                    trials.push_back({position_t(row, col), seq});
                 }
             } else {
-                abort();
+                error("Unknown maze object type '" << obj.glyph.type << "'");
             }
         }
 
+        if(trials.size() > Max_Trials) {
+            error("Maze has " << trials.size() << " trials, max is " << Max_Trials);
+        }
         config.ntrials = trials.size();
+        config.max_seqlen = 0;
+        for(TrialSpec &t: trials) {
+            config.max_seqlen = max(config.max_seqlen, uchar(t.seq.length()));
+        }
+        config.trial_size = Config::get_trial_size(ncells, config.max_seqlen);
 
-        len_ = sizeof(Config) + sizeof(Config::Trial) * config.ntrials;
-        config_ = (Config *)malloc(len_);
+        len_ = align8(sizeof(Config)) + align8(ncells) + config.trial_size * config.ntrials;
+        config_ = (Config *)calloc(1, len_);
         memcpy(config_, &config, sizeof(Config));
+        for(size_t i = 0; i < ncells; i++) {
+            config_->wall()[i] = wall[i];
+        }
 
         for(size_t i = 0; i < config.ntrials; i++) {
-            Config::Trial &trial = trials[i];
-            create_distance_map(config_, trial.food_pos, trial.dist_map, trial.max_dist);
-            uchar dist = trial.dist_map[config.agent_pos.row * config.width + config.agent_pos.col];
+            TrialSpec &spec = trials[i];
+            Config::Trial &trial = config_->trial(i);
+            trial.food_pos = spec.food_pos;
+            trial.seqlen = spec.seq.length();
+            for(size_t j = 0; j < spec.seq.length(); j++) {
+                trial.seq()[j] = spec.seq[j] == 'r' ? 1.0 : 0.0;
+            }
+
+#ifdef Truncate_Seq
+            for(size_t j = Truncate_Seq; j < spec.seq.length(); j++) {
+                trial.seq()[j] = 0.5;
+            }
+#endif
+
+            uchar *dist_map = trial.dist_map(config.max_seqlen);
+            create_distance_map(config_, trial.food_pos, dist_map, trial.max_dist);
+            uchar dist = dist_map[config.agent_pos.row * config.width + config.agent_pos.col];
+            if(dist == 0xff) {
+                error("Food '" << spec.seq << "' is unreachable or more than " << Max_Dist << " steps from the agent");
+            }
             trial.max_steps = dist + 3 * trial.seqlen;
         }
-        memcpy(config_->trials, trials.data(), sizeof(Config::Trial) * config.ntrials);
     }
 
     struct Evaluator {
@@ -242,8 +305,9 @@ namespace NEAT {
         ushort max_trial_steps;
         const uchar *dist_map;
         uchar best_dist;
-        char iseq;
+        short iseq;
         OrganismEvaluation eval;
+        position_t final_pos[Max_Trials];
 
         __net_eval_decl Evaluator(const Config *config_)
         : config(config_) {
@@ -281,13 +345,13 @@ namespace NEAT {
             if(trial_step == 1) {
                 agent_pos = config->agent_pos;
                 agent_dir = config->agent_dir;
-                food_pos = config->trials[trial].food_pos;
-                max_trial_steps = config->trials[trial].max_steps;
-                dist_map = config->trials[trial].dist_map;
+                food_pos = config->trial(trial).food_pos;
+                max_trial_steps = config->trial(trial).max_steps;
+                dist_map = config->trial(trial).dist_map(config->max_seqlen);
                 best_dist = dist();
                 iseq = 0;
             } else {
-                if(trial_step <= config->trials[trial].seqlen * 2) {
+                if(trial_step <= config->trial(trial).seqlen * 2) {
                     if(trial_step % 2 == 0) {
                         iseq = -1;
                     } else {
@@ -307,7 +371,7 @@ namespace NEAT {
 
         __net_eval_decl real_t obj_sensor(rotation_t rot) {
             position_t look_pos = get_look_pos(agent_pos, agent_dir, rot);
-            return config->wall[int(look_pos.row) * config->width + int(look_pos.col)]
+            return config->wall()[int(look_pos.row) * config->width + int(look_pos.col)]
                 ? 1.0
                 : 0.0;
         }
@@ -323,14 +387,14 @@ namespace NEAT {
             case sensor_sound:
                 return iseq > -1 ? 1.0 : 0.0;
             case sensor_freq:
-                return iseq > -1 ? config->trials[trial].seq[int(iseq)] : 0.0;
+                return iseq > -1 ? config->trial(trial).seq()[iseq] : 0.0;
             case sensor_go:
                 return iseq == -2 ? 1.0 : 0.0;
             default:
 #ifdef ENABLE_CUDA
                 return 0.0;
 #else
-                abort();
+                error("Invalid maze sensor index " << sensor_index);
 #endif
             }
         }
@@ -345,7 +409,7 @@ namespace NEAT {
                 }
                 if(output[output_fwd] > 0.5) {
                     position_t newpos = agent_pos + get_rel_pos(agent_dir);
-                    if(!config->wall[int(newpos.row) * config->width + int(newpos.col)]) {
+                    if(!config->wall()[int(newpos.row) * config->width + int(newpos.col)]) {
                         agent_pos = newpos;
                     }
                 }
@@ -353,11 +417,15 @@ namespace NEAT {
                 if(curr_dist < best_dist)
                     best_dist = curr_dist;
 
+                if(best_dist == 0 || trial_step == max_trial_steps) {
+                    final_pos[trial] = agent_pos;
+                }
+
                 if(best_dist == 0) {
                     eval.fitness += 10.0 + (max_trial_steps - trial_step) / real_t(max_trial_steps);
                 } else if(trial_step == max_trial_steps) {
                     eval.error += 1.0;
-                    uchar max_dist = config->trials[trial].max_dist;
+                    uchar max_dist = config->trial(trial).max_dist;
                     eval.fitness += real_t(max_dist - best_dist) / real_t(max_dist);
                     eval.fitness += real_t(max_dist - curr_dist) / real_t(max_dist);
                 }
@@ -368,10 +436,20 @@ namespace NEAT {
             return eval;
         }
 
+        // Where the agent ended each trial, as fractions of the maze's height
+        // and width.
+        void behavior(real_t *out) {
+            for(int i = 0; i < config->ntrials; i++) {
+                out[2 * i] = real_t(final_pos[i].row) / config->height;
+                out[2 * i + 1] = real_t(final_pos[i].col) / config->width;
+            }
+        }
+
     };
 
     class MazeEvaluator : public NetworkEvaluator {
         NetworkExecutor<Evaluator> *executor;
+        size_t ntrials;
     public:
         MazeEvaluator() {
             executor = NetworkExecutor<Evaluator>::create();
@@ -379,6 +457,7 @@ namespace NEAT {
             size_t configlen;
             create_config(config, configlen);
             executor->configure(config, configlen);
+            ntrials = config->ntrials;
             free(config);
         }
 
@@ -391,6 +470,17 @@ namespace NEAT {
                              size_t nnets) {
             executor->execute(nets_, results, nnets);
         }
+
+        virtual size_t get_behavior_size() {
+            return 2 * ntrials;
+        }
+
+        virtual void execute_with_behavior(class Network **nets_,
+                                           class OrganismEvaluation *results,
+                                           real_t *behaviors,
+                                           size_t nnets) {
+            executor->execute(nets_, results, behaviors, get_behavior_size(), nnets);
+        }
     };
 
     class NetworkEvaluator *create_maze_evaluator() {
@@ -0,0 +1,108 @@
+#include "std.h" // Must be included first. Precompiled header with standard library includes.
+
+#include "novelty.h"
+
+using namespace NEAT;
+using namespace std;
+
+namespace {
+
+    real_t distance(const real_t *a, const real_t *b, size_t len) {
+        real_t sum = 0.0;
+        for(size_t i = 0; i < len; i++) {
+            real_t d = a[i] - b[i];
+            sum += d * d;
+        }
+        return sqrt(sum);
+    }
+
+    // Scales values to [0, 1]. All 1 if they're all the same.
+    vector<real_t> normalize(const vector<real_t> &values) {
+        real_t lo = *min_element(values.begin(), values.end());
+        real_t hi = *max_element(values.begin(), values.end());
+        vector<real_t> result(values.size(), 1.0);
+        if(hi > lo) {
+            for(size_t i = 0; i < values.size(); i++) {
+                result[i] = (values[i] - lo) / (hi - lo);
+            }
+        }
+        return result;
+    }
+
+}
+
+namespace NEAT {
+
+    NoveltyArchive::NoveltyArchive(size_t behavior_size_)
+        : behavior_size(behavior_size_) {
+    }
+
+    size_t NoveltyArchive::size() const {
+        return archive.size() / behavior_size;
+    }
+
+    vector<real_t> NoveltyArchive::sparseness(const real_t *behaviors, size_t n, int k) const {
+        size_t narchived = size();
+        vector<real_t> result(n);
+
+#pragma omp parallel for
+        for(size_t i = 0; i < n; i++) {
+            const real_t *b = behaviors + i * behavior_size;
+            vector<real_t> dists;
+            dists.reserve(n - 1 + narchived);
+            for(size_t j = 0; j < n; j++) {
+                if(j != i) {
+                    dists.push_back(distance(b, behaviors + j * behavior_size, behavior_size));
+                }
+            }
+            for(size_t j = 0; j < narchived; j++) {
+                dists.push_back(distance(b, archive.data() + j * behavior_size, behavior_size));
+            }
+
+            size_t nneighbors = min(dists.size(), size_t(k));
+            if(nneighbors == 0) {
+                result[i] = 0.0;
+                continue;
+            }
+            nth_element(dists.begin(), dists.begin() + (nneighbors - 1), dists.end());
+            real_t sum = 0.0;
+            for(size_t j = 0; j < nneighbors; j++) {
+                sum += dists[j];
+            }
+            result[i] = sum / nneighbors;
+        }
+
+        return result;
+    }
+
+    void NoveltyArchive::add_most_novel(const real_t *behaviors,
+                                        const vector<real_t> &novelty,
+                                        size_t nadd) {
+        vector<size_t> order(novelty.size());
+        for(size_t i = 0; i < order.size(); i++) {
+            order[i] = i;
+        }
+        // Stable, so ties go to the earlier organism on every platform.
+        stable_sort(order.begin(), order.end(), [&novelty] (size_t a, size_t b) {
+                return novelty[a] > novelty[b];
+            });
+
+        for(size_t i = 0; i < min(nadd, order.size()); i++) {
+            const real_t *b = behaviors + order[i] * behavior_size;
+            archive.insert(archive.end(), b, b + behavior_size);
+        }
+    }
+
+    vector<real_t> blend_scores(const vector<real_t> &fitness,
+                                const vector<real_t> &novelty,
+                                real_t novelty_weight) {
+        vector<real_t> f = normalize(fitness);
+        vector<real_t> n = normalize(novelty);
+        vector<real_t> result(f.size());
+        for(size_t i = 0; i < f.size(); i++) {
+            result[i] = (1.0 - novelty_weight) * f[i] + novelty_weight * n[i];
+        }
+        return result;
+    }
+
+}
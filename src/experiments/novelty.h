@@ -0,0 +1,44 @@
+#pragma once
+
+#ifndef NOVELTY_H__
+#define NOVELTY_H__
+
+#include "neattypes.h"
+
+namespace NEAT {
+
+    //---
+    //--- CLASS NoveltyArchive
+    //---
+    //--- Behavior characterizations of past generations, so novelty search
+    //--- keeps rewarding organisms for doing something not seen before rather
+    //--- than just something their contemporaries don't do.
+    //---
+    class NoveltyArchive {
+        size_t behavior_size;
+        std::vector<real_t> archive;
+
+    public:
+        NoveltyArchive(size_t behavior_size);
+
+        size_t size() const;
+
+        // Novelty of each of the n behaviors: the mean Euclidean distance to
+        // its k nearest neighbors among the other behaviors and the archive.
+        std::vector<real_t> sparseness(const real_t *behaviors, size_t n, int k) const;
+
+        // Archives the nadd behaviors with the highest novelty.
+        void add_most_novel(const real_t *behaviors,
+                            const std::vector<real_t> &novelty,
+                            size_t nadd);
+    };
+
+    // Selection score of each organism: its fitness and novelty, each scaled to
+    // [0, 1] across the population, weighted by novelty_weight.
+    std::vector<real_t> blend_scores(const std::vector<real_t> &fitness,
+                                     const std::vector<real_t> &novelty,
+                                     real_t novelty_weight);
+
+}
+
+#endif // #ifndef NOVELTY_H__
@@ -0,0 +1,118 @@
+#pragma once
+
+#ifndef CARTPOLE_H__
+#define CARTPOLE_H__
+
+#include "networkexecutor.h"
+
+namespace NEAT {
+
+    //---
+    //--- CLASS CartPole
+    //---
+    //--- Cart with one or two poles hinged side by side, as in Wieland's
+    //--- double pole benchmark. Integrated with fourth-order Runge-Kutta,
+    //--- two TAU steps per network activation.
+    //---
+    struct CartPole {
+        static constexpr real_t GRAVITY = -9.8;
+        static constexpr real_t MASSCART = 1.0;
+        static constexpr real_t MASSPOLE_1 = 0.1;
+        static constexpr real_t LENGTH_1 = 0.5; // Half the pole's length
+        static constexpr real_t MASSPOLE_2 = 0.01;
+        static constexpr real_t LENGTH_2 = 0.05;
+        static constexpr real_t FORCE_MAG = 10.0;
+        static constexpr real_t TAU = 0.01;
+        static constexpr real_t MUP = 0.000002; // Friction of the hinges
+        static constexpr real_t TRACK_LIMIT = 2.4;
+        static constexpr real_t ONE_DEGREE = 0.0174532;
+        static constexpr real_t INITIAL_ANGLE = 0.07; // About 4 degrees
+
+        int npoles;
+        // x, x_dot, theta_1, theta_1_dot, theta_2, theta_2_dot
+        real_t state[6];
+
+        __net_eval_decl void reset(int npoles_) {
+            npoles = npoles_;
+            for(int i = 0; i < 6; i++) {
+                state[i] = 0.0;
+            }
+            state[2] = INITIAL_ANGLE;
+        }
+
+        __net_eval_decl real_t failure_angle() const {
+            return (npoles == 1 ? 12 : 36) * ONE_DEGREE;
+        }
+
+        __net_eval_decl bool outside_bounds() const {
+            const real_t fa = failure_angle();
+            return state[0] < -TRACK_LIMIT || state[0] > TRACK_LIMIT
+                || state[2] < -fa || state[2] > fa
+                || state[4] < -fa || state[4] > fa;
+        }
+
+        __net_eval_decl void derivs(real_t action, const real_t *st, real_t *d) const {
+            real_t force = (action - 0.5) * FORCE_MAG * 2;
+
+            real_t costheta_1 = cos(st[2]);
+            real_t gsintheta_1 = GRAVITY * sin(st[2]);
+            real_t ml_1 = LENGTH_1 * MASSPOLE_1;
+            real_t temp_1 = MUP * st[3] / ml_1;
+            real_t fi_1 = (ml_1 * st[3] * st[3] * sin(st[2]))
+                + (0.75 * MASSPOLE_1 * costheta_1 * (temp_1 + gsintheta_1));
+            real_t mi_1 = MASSPOLE_1 * (1 - (0.75 * costheta_1 * costheta_1));
+
+            real_t costheta_2 = 0, gsintheta_2 = 0, temp_2 = 0, fi_2 = 0, mi_2 = 0;
+            if(npoles == 2) {
+                costheta_2 = cos(st[4]);
+                gsintheta_2 = GRAVITY * sin(st[4]);
+                real_t ml_2 = LENGTH_2 * MASSPOLE_2;
+                temp_2 = MUP * st[5] / ml_2;
+                fi_2 = (ml_2 * st[5] * st[5] * sin(st[4]))
+                    + (0.75 * MASSPOLE_2 * costheta_2 * (temp_2 + gsintheta_2));
+                mi_2 = MASSPOLE_2 * (1 - (0.75 * costheta_2 * costheta_2));
+            }
+
+            d[0] = st[1];
+            d[1] = (force + fi_1 + fi_2) / (mi_1 + mi_2 + MASSCART);
+            d[2] = st[3];
+            d[3] = -0.75 * (d[1] * costheta_1 + gsintheta_1 + temp_1) / LENGTH_1;
+            if(npoles == 2) {
+                d[4] = st[5];
+                d[5] = -0.75 * (d[1] * costheta_2 + gsintheta_2 + temp_2) / LENGTH_2;
+            } else {
+                d[4] = d[5] = 0.0;
+            }
+        }
+
+        __net_eval_decl void rk4(real_t action) {
+            const real_t hh = TAU * 0.5;
+            const real_t h6 = TAU / 6.0;
+            real_t dydx[6], dym[6], dyt[6], yt[6];
+
+            derivs(action, state, dydx);
+            for(int i = 0; i < 6; i++) yt[i] = state[i] + hh * dydx[i];
+            derivs(action, yt, dyt);
+            for(int i = 0; i < 6; i++) yt[i] = state[i] + hh * dyt[i];
+            derivs(action, yt, dym);
+            for(int i = 0; i < 6; i++) {
+                yt[i] = state[i] + TAU * dym[i];
+                dym[i] += dyt[i];
+            }
+            derivs(action, yt, dyt);
+            for(int i = 0; i < 6; i++) {
+                state[i] = state[i] + h6 * (dydx[i] + dyt[i] + 2.0 * dym[i]);
+            }
+        }
+
+        // action is in [0, 1]: 0 pushes left at full force, 1 right.
+        __net_eval_decl void perform_action(real_t action) {
+            for(int i = 0; i < 2; i++) {
+                rk4(action);
+            }
+        }
+    };
+
+}
+
+#endif // #ifndef CARTPOLE_H__
@@ -0,0 +1,65 @@
+#include "std.h"
+
+#include "cartpole.h"
+#include "environment.h"
+
+using namespace NEAT;
+using namespace std;
+
+// Steps in an episode of the cartpole experiment.
+#define CartPole_Max_Steps 500
+
+//---
+//--- CLASS CartPoleEnvironment
+//---
+//--- Single pole balancing as a Gym-style environment: each episode starts the
+//--- cart and pole in a random state within 0.05 of rest, and pays 1 for each
+//--- step the pole stays up, up to CartPole_Max_Steps.
+//---
+class CartPoleEnvironment : public Environment {
+    CartPole cart;
+    int nsteps;
+
+    vector<real_t> observation() {
+        const real_t *s = cart.state;
+        return {s[0] / 4.8, s[1] / 2.0, s[2] / 0.52, s[3] / 2.0};
+    }
+
+public:
+    virtual vector<real_t> reset(uint64_t seed) {
+        cart.reset(1);
+        for(int i = 0; i < 4; i++) {
+            cart.state[i] = splitmix64_uniform(seed, -0.05, 0.05);
+        }
+        nsteps = 0;
+        return observation();
+    }
+
+    virtual EnvironmentStep step(const vector<real_t> &action) {
+        cart.perform_action(action[0]);
+        EnvironmentStep result;
+        result.observation = observation();
+        if(cart.outside_bounds()) {
+            result.reward = 0.0;
+            result.done = true;
+        } else {
+            result.reward = 1.0;
+            result.done = ++nsteps == CartPole_Max_Steps;
+        }
+        return result;
+    }
+};
+
+static struct CartPoleInit {
+    CartPoleInit() {
+        EnvironmentSpec spec;
+        spec.nsensors = 4;
+        spec.noutputs = 1;
+        spec.nepisodes = 10;
+        spec.max_return = CartPole_Max_Steps;
+
+        create_environment_experiment("cartpole", spec, [] () {
+            return new CartPoleEnvironment();
+        });
+    }
+} init;
@@ -0,0 +1,42 @@
+#include "std.h"
+
+#include "evaluatorexperiment.h"
+#include "genomemanager.h"
+#include "neat.h"
+#include "polebalanceevaluator.h"
+#include "rng.h"
+#include "util.h"
+
+using namespace NEAT;
+
+static void create_pole_balance_experiment(const char *name,
+                                           PoleBalanceVariant variant) {
+    auto create_evaluator = [variant] () {
+        if(env->pole_max_steps <= 0) {
+            error("pole_max_steps must be positive");
+        }
+        return create_pole_balance_evaluator(variant);
+    };
+
+    auto create_seeds = [variant] (rng_t rng_exp) {
+        int nsensors = pole_balance_nsensors(variant);
+        return
+        env->genome_manager->create_seed_generation(env->pop_size,
+                                                    rng_exp,
+                                                    1,
+                                                    nsensors,
+                                                    1,
+                                                    nsensors);
+    };
+
+    new EvaluatorExperiment(name, create_evaluator, create_seeds);
+}
+
+static struct PoleBalanceInit {
+    PoleBalanceInit() {
+        create_pole_balance_experiment("pole1", {1, true, false});
+        create_pole_balance_experiment("pole1-nv", {1, false, true});
+        create_pole_balance_experiment("pole2", {2, true, false});
+        create_pole_balance_experiment("pole2-nv", {2, false, true});
+    }
+} init;
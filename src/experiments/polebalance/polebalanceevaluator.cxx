@@ -0,0 +1,163 @@
+#include "std.h"
+
+#include "cartpole.h"
+#include "polebalanceevaluator.h"
+#include "neat.h"
+#include "network.h"
+#include "networkexecutor.h"
+#include "util.h"
+
+using namespace std;
+
+namespace NEAT {
+
+    struct PoleConfig {
+        PoleBalanceVariant variant;
+        int max_steps;
+    };
+
+    //---
+    //--- CLASS PoleEvaluator
+    //---
+    //--- Variants scored with anti-jiggle fitness first run Gruau_Steps steps,
+    //--- and only go on to the long run if the poles stayed up throughout.
+    //--- Otherwise the only run is the long one, scored by the fraction of
+    //--- max_steps balanced. Error is the fraction of the long run not
+    //--- balanced, so it's 0 only for organisms that balance all of it.
+    //---
+    struct PoleEvaluator {
+        typedef PoleConfig Config;
+
+        const Config *config;
+        CartPole cart;
+        bool long_run;
+        int step;
+        int phase_steps;
+        bool failed;
+        real_t jiggle[Jiggle_Window];
+        OrganismEvaluation eval;
+
+        __net_eval_decl PoleEvaluator(const Config *config_)
+        : config(config_) {
+            long_run = !config->variant.anti_jiggle;
+            eval.fitness = 0.0;
+            eval.error = 1.0;
+            start_phase();
+        }
+
+        __net_eval_decl void start_phase() {
+            cart.reset(config->variant.npoles);
+            step = 0;
+            phase_steps = long_run ? config->max_steps : Gruau_Steps;
+            failed = false;
+        }
+
+        __net_eval_decl void end_phase() {
+            if(long_run) {
+                real_t balanced = real_t(step) / config->max_steps;
+                if(!config->variant.anti_jiggle) {
+                    eval.fitness = balanced;
+                }
+                eval.error = 1.0 - balanced;
+            } else {
+                real_t f1 = real_t(step) / Gruau_Steps;
+                real_t f2 = 0.0;
+                if(step >= Jiggle_Window) {
+                    real_t jiggle_total = 0.0;
+                    for(int i = 0; i < Jiggle_Window; i++) {
+                        jiggle_total += jiggle[i];
+                    }
+                    f2 = 0.75 / jiggle_total;
+                }
+                eval.fitness = 0.1 * f1 + 0.9 * f2;
+            }
+        }
+
+        __net_eval_decl bool next_step() {
+            if(failed || step == phase_steps) {
+                end_phase();
+                if(long_run || failed) {
+                    return false;
+                }
+                long_run = true;
+                start_phase();
+            }
+            return true;
+        }
+
+        __net_eval_decl bool clear_noninput() {
+            return step == 0;
+        }
+
+        __net_eval_decl real_t get_sensor(node_size_t sensor_index) {
+            const real_t *s = cart.state;
+            if(config->variant.velocities) {
+                switch(sensor_index) {
+                case 0: return s[0] / 4.8;
+                case 1: return s[1] / 2.0;
+                case 2: return s[2] / 0.52;
+                case 3: return s[3] / 2.0;
+                case 4: return s[4] / 0.52;
+                case 5: return s[5] / 2.0;
+                }
+            } else {
+                switch(sensor_index) {
+                case 0: return s[0] / 4.8;
+                case 1: return s[2] / 0.52;
+                case 2: return s[4] / 0.52;
+                }
+            }
+#ifdef ENABLE_CUDA
+            return 0.0;
+#else
+            abort();
+#endif
+        }
+
+        __net_eval_decl void evaluate(real_t *output) {
+            cart.perform_action(output[0]);
+            if(cart.outside_bounds()) {
+                failed = true;
+                return;
+            }
+            const real_t *s = cart.state;
+            jiggle[step % Jiggle_Window] = fabs(s[0]) + fabs(s[1]) + fabs(s[2]) + fabs(s[3]);
+            step++;
+        }
+
+        __net_eval_decl OrganismEvaluation result() {
+            return eval;
+        }
+    };
+
+    class PoleBalanceEvaluator : public NetworkEvaluator {
+        NetworkExecutor<PoleEvaluator> *executor;
+    public:
+        PoleBalanceEvaluator(const PoleBalanceVariant &variant) {
+            executor = NetworkExecutor<PoleEvaluator>::create();
+            PoleConfig config;
+            config.variant = variant;
+            config.max_steps = env->pole_max_steps;
+            executor->configure(&config, sizeof(config));
+        }
+
+        ~PoleBalanceEvaluator() {
+            delete executor;
+        }
+
+        virtual void execute(class Network **nets_,
+                             class OrganismEvaluation *results,
+                             size_t nnets) {
+            executor->execute(nets_, results, nnets);
+        }
+    };
+
+    int pole_balance_nsensors(const PoleBalanceVariant &variant) {
+        return (variant.velocities ? 2 : 1) * (1 + variant.npoles);
+    }
+
+    class NetworkEvaluator *create_pole_balance_evaluator(const PoleBalanceVariant &variant) {
+        return new PoleBalanceEvaluator(variant);
+    }
+
+}
@@ -0,0 +1,24 @@
+#pragma once
+
+#ifndef POLEBALANCEEVALUATOR_H__
+#define POLEBALANCEEVALUATOR_H__
+
+namespace NEAT {
+
+    // Length of the run scored with Gruau's anti-jiggle fitness.
+    #define Gruau_Steps 1000
+    // Steps over which the anti-jiggle term sums the cart and pole motion.
+    #define Jiggle_Window 100
+
+    struct PoleBalanceVariant {
+        int npoles; // 1 or 2
+        bool velocities; // Markovian: the network sees velocities
+        bool anti_jiggle; // Score with Gruau's fitness before the long run
+    };
+
+    // Number of sensors of the variant, not counting bias.
+    int pole_balance_nsensors(const PoleBalanceVariant &variant);
+
+    class NetworkEvaluator *create_pole_balance_evaluator(const PoleBalanceVariant &variant);
+}
+#endif // #ifndef POLEBALANCEEVALUATOR_H__
@@ -1,4 +1,5 @@
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
+#include "sentences.h"
 #include "staticexperiment.h"
 #include "util.h"
 #include <assert.h>
@@ -8,6 +9,7 @@ using namespace std;
 
 static vector<Test> create_tests(const vector<string> &sentences,
                                  const vector<bool> &is_grammatical);
+static vector<Test> create_tests_cfg(const string &grammar);
 
 static struct CfgInit {
     CfgInit() {
@@ -39,6 +41,13 @@ static struct CfgInit {
 
                 return ::create_tests(sentences, is_grammatical);
             });
+
+        Experiment::register_family("cfg", "cfg:<productions>", [] (const string &grammar) {
+                string name = "cfg:" + grammar;
+                create_static_experiment(name.c_str(), [grammar] () {
+                        return create_tests_cfg(grammar);
+                    });
+            });
     }
 } init;
 
@@ -115,3 +124,205 @@ static vector<Test> create_tests(const vector<string> &sentences,
     return tests;
 }
 
+
+//---
+//--- CLASS Grammar
+//---
+//--- Productions are written one per line, or separated by ';', as
+//---   S -> a S b | a b
+//--- Upper-case letters are nonterminals and any other character besides
+//--- whitespace, '|', ';' and '#' is a terminal. An empty alternative derives
+//--- the empty sentence, '#' starts a comment, and the first production's left
+//--- side is the start symbol.
+//---
+struct Grammar {
+    struct Production {
+        char lhs;
+        string rhs;
+    };
+
+    char start = 0;
+    vector<Production> productions;
+    set<char> nullable;
+    string terminals;
+
+    static bool is_nonterminal(char c) {
+        return isupper(c);
+    }
+
+    Grammar(const string &text) {
+        set<char> terminal_set;
+        set<char> defined;
+
+        size_t lineno = 0;
+        for(string line: split_lines(text)) {
+            lineno++;
+            line = line.substr(0, line.find('#'));
+            line.erase(remove_if(line.begin(), line.end(), ::isspace), line.end());
+            if(line.empty()) {
+                continue;
+            }
+            size_t arrow = line.find("->");
+            if(arrow != 1 || !is_nonterminal(line[0])) {
+                error("Grammar line " << lineno << ": expected 'X -> ...', got '" << line << "'");
+            }
+            char lhs = line[0];
+            if(!start) {
+                start = lhs;
+            }
+            defined.insert(lhs);
+
+            string alts = line.substr(arrow + 2);
+            size_t begin = 0;
+            while(true) {
+                size_t end = alts.find('|', begin);
+                string rhs = alts.substr(begin, end == string::npos ? string::npos : end - begin);
+                for(char c: rhs) {
+                    if(!is_nonterminal(c)) {
+                        terminal_set.insert(c);
+                    }
+                }
+                productions.push_back({lhs, rhs});
+                if(end == string::npos) {
+                    break;
+                }
+                begin = end + 1;
+            }
+        }
+        if(!start) {
+            error("Grammar has no productions");
+        }
+        for(Production &p: productions) {
+            for(char c: p.rhs) {
+                if(is_nonterminal(c) && !defined.count(c)) {
+                    error("Grammar has no productions for " << c);
+                }
+            }
+        }
+        terminals = string(terminal_set.begin(), terminal_set.end());
+
+        for(bool changed = true; changed; ) {
+            changed = false;
+            for(Production &p: productions) {
+                if(nullable.count(p.lhs)) {
+                    continue;
+                }
+                bool all_nullable = true;
+                for(char c: p.rhs) {
+                    all_nullable = all_nullable && nullable.count(c);
+                }
+                if(all_nullable) {
+                    nullable.insert(p.lhs);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    // Earley recognizer, with Aycock and Horspool's handling of nullable
+    // nonterminals.
+    bool accepts(const string &sentence) const {
+        struct Item {
+            size_t production;
+            size_t dot;
+            size_t origin;
+            bool operator<(const Item &other) const {
+                return tie(production, dot, origin) < tie(other.production, other.dot, other.origin);
+            }
+        };
+
+        const size_t n = sentence.size();
+        vector<vector<Item>> chart(n + 1);
+        vector<set<Item>> seen(n + 1);
+        auto add = [&chart, &seen] (size_t i, Item item) {
+            if(seen[i].insert(item).second) {
+                chart[i].push_back(item);
+            }
+        };
+
+        for(size_t p = 0; p < productions.size(); p++) {
+            if(productions[p].lhs == start) {
+                add(0, {p, 0, 0});
+            }
+        }
+
+        for(size_t i = 0; i <= n; i++) {
+            for(size_t j = 0; j < chart[i].size(); j++) {
+                Item item = chart[i][j];
+                const Production &prod = productions[item.production];
+                if(item.dot < prod.rhs.size()) {
+                    char sym = prod.rhs[item.dot];
+                    if(is_nonterminal(sym)) {
+                        for(size_t p = 0; p < productions.size(); p++) {
+                            if(productions[p].lhs == sym) {
+                                add(i, {p, 0, i});
+                            }
+                        }
+                        if(nullable.count(sym)) {
+                            add(i, {item.production, item.dot + 1, item.origin});
+                        }
+                    } else if(i < n && sentence[i] == sym) {
+                        add(i + 1, {item.production, item.dot + 1, item.origin});
+                    }
+                } else {
+                    for(size_t k = 0; k < chart[item.origin].size(); k++) {
+                        Item parent = chart[item.origin][k];
+                        const string &rhs = productions[parent.production].rhs;
+                        if(parent.dot < rhs.size() && rhs[parent.dot] == prod.lhs) {
+                            add(i, {parent.production, parent.dot + 1, parent.origin});
+                        }
+                    }
+                }
+            }
+        }
+
+        for(const Item &item: chart[n]) {
+            const Production &prod = productions[item.production];
+            if(prod.lhs == start && item.origin == 0 && item.dot == prod.rhs.size()) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+private:
+    // Lines of text, where ';' also ends a line.
+    static vector<string> split_lines(const string &text) {
+        vector<string> lines(1);
+        for(char c: text) {
+            if(c == '\n' || c == ';') {
+                lines.emplace_back();
+            } else {
+                lines.back().push_back(c);
+            }
+        }
+        return lines;
+    }
+};
+
+// grammar is either the path of a file of productions or the productions
+// themselves.
+static vector<Test> create_tests_cfg(const string &grammar) {
+    string text = grammar;
+    ifstream in(grammar);
+    if(in) {
+        stringstream buf;
+        buf << in.rdbuf();
+        text = buf.str();
+    }
+    Grammar g(text);
+
+    vector<string> correct;
+    vector<string> incorrect;
+    create_sentences("cfg:" + grammar,
+                     g.terminals,
+                     env->cfg_max_len,
+                     env->cfg_max_sentences,
+                     [&g] (const string &sentence) {
+                         return g.accepts(sentence);
+                     },
+                     correct,
+                     incorrect);
+
+    return create_sentence_tests(g.terminals, correct, incorrect);
+}
@@ -1,4 +1,5 @@
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
+#include "sentences.h"
 #include "staticexperiment.h"
 #include "util.h"
 #include <assert.h>
@@ -10,6 +11,7 @@ static vector<Test> create_tests_1bit(const char *grammar,
                                       const vector<string> &sentences);
 static vector<Test> create_tests_2bit(const char *grammar,
                                       const vector<string> &sentences);
+static vector<Test> create_tests_regex(const string &pattern);
 
 static struct RegexInit {
     RegexInit() {
@@ -42,6 +44,13 @@ static struct RegexInit {
 
                 return ::create_tests_2bit(grammar, sentences);
             });
+
+        Experiment::register_family("regex", "regex:<pattern>", [] (const string &pattern) {
+                string name = "regex:" + pattern;
+                create_static_experiment(name.c_str(), [pattern] () {
+                        return create_tests_regex(pattern);
+                    });
+            });
     }
 } init;
 
@@ -170,3 +179,130 @@ static vector<Test> create_tests_2bit(const char *grammar,
     return tests;
 }
 
+// Letters and digits in the pattern, with ranges like [a-d] expanded. Counts
+// such as {2,3} are skipped.
+static string regex_alphabet(const string &pattern) {
+    set<char> letters;
+    bool in_class = false;
+    bool in_count = false;
+
+    for(size_t i = 0; i < pattern.size(); i++) {
+        char c = pattern[i];
+        if(c == '\\') {
+            error("Escapes aren't supported in regex experiments: " << pattern);
+        }
+        if(in_count) {
+            if(c == '}')
+                in_count = false;
+        } else if(c == '{' && !in_class) {
+            in_count = true;
+        } else if(c == '[') {
+            in_class = true;
+        } else if(c == ']') {
+            in_class = false;
+        } else if(isalnum(c)) {
+            if(in_class && i + 2 < pattern.size() && pattern[i+1] == '-' && isalnum(pattern[i+2])) {
+                for(char x = c; x <= pattern[i+2]; x++) {
+                    letters.insert(x);
+                }
+                i += 2;
+            } else {
+                letters.insert(c);
+            }
+        }
+    }
+
+    return string(letters.begin(), letters.end());
+}
+
+// Only syntax that std::regex's ECMAScript grammar and the Rust regex crate
+// read the same way, as checked by check_pattern in the accneat crate:
+// letters and digits, ., |, ^, $, groups and (?:), the quantifiers *, +, ?
+// and {n}, {n,}, {n,m}, and classes like [^a-cx] of letters, digits and
+// ranges.
+static void check_regex_pattern(const string &pattern) {
+    auto unsupported = [&pattern] (size_t i) {
+        error("Unsupported regex syntax at " << i << " of '" << pattern << "'");
+    };
+    auto at = [&pattern] (size_t i) {
+        return i < pattern.size() ? pattern[i] : '\0';
+    };
+
+    for(size_t i = 0; i < pattern.size(); i++) {
+        char c = pattern[i];
+        if(c == '\\') {
+            error("Escapes aren't supported in regex experiments: " << pattern);
+        } else if(c == '(' && at(i+1) == '?') {
+            if(at(i+2) != ':')
+                unsupported(i);
+            i += 2;
+        } else if(c == '{') {
+            size_t start = i++;
+            if(!isdigit(at(i)))
+                unsupported(start);
+            while(isdigit(at(i)))
+                i++;
+            if(at(i) == ',') {
+                i++;
+                while(isdigit(at(i)))
+                    i++;
+            }
+            if(at(i) != '}')
+                unsupported(start);
+        } else if(c == '[') {
+            size_t start = i++;
+            if(at(i) == '^')
+                i++;
+            size_t nitems = 0;
+            for(; at(i) != ']'; i++, nitems++) {
+                char x = at(i);
+                if(x == '\\') {
+                    error("Escapes aren't supported in regex experiments: " << pattern);
+                } else if(x == '\0') {
+                    unsupported(start);
+                } else if(!isalnum(x)) {
+                    unsupported(i);
+                } else if(at(i+1) == '-') {
+                    if(!isalnum(at(i+2)) || at(i+2) < x)
+                        unsupported(i+1);
+                    i += 2;
+                }
+            }
+            if(nitems == 0)
+                unsupported(start);
+        } else if(!isalnum(c) && !strchr("().|*+?^$", c)) {
+            unsupported(i);
+        }
+    }
+}
+
+static vector<Test> create_tests_regex(const string &pattern) {
+    check_regex_pattern(pattern);
+    string alphabet = env->regex_alphabet.empty()
+        ? regex_alphabet(pattern)
+        : env->regex_alphabet;
+    if(alphabet.empty()) {
+        error("No symbols in regex: " << pattern);
+    }
+
+    regex regex_grammar;
+    try {
+        regex_grammar = regex(pattern);
+    } catch(const regex_error &e) {
+        error("Invalid regex '" << pattern << "': " << e.what());
+    }
+
+    vector<string> correct;
+    vector<string> incorrect;
+    create_sentences("regex:" + pattern,
+                     alphabet,
+                     env->regex_max_len,
+                     env->regex_max_sentences,
+                     [&regex_grammar] (const string &sentence) {
+                         return regex_match(sentence, regex_grammar);
+                     },
+                     correct,
+                     incorrect);
+
+    return create_sentence_tests(alphabet, correct, incorrect);
+}
@@ -0,0 +1,110 @@
+#include "std.h" // Must be included first. Precompiled header with standard library includes.
+#include "sentences.h"
+#include "util.h"
+
+using namespace NEAT;
+using namespace std;
+
+vector<string> NEAT::subsample(const vector<string> &v, size_t n) {
+    if(n >= v.size()) {
+        return v;
+    }
+    vector<string> result;
+    for(size_t i = 0; i < n; i++) {
+        result.push_back(v[i * v.size() / n]);
+    }
+    return result;
+}
+
+void NEAT::create_sentences(const string &name,
+                            const string &alphabet,
+                            size_t max_len,
+                            size_t max_sentences,
+                            function<bool (const string &)> is_grammatical,
+                            vector<string> &correct,
+                            vector<string> &incorrect) {
+    size_t nenumerated = 0;
+    for(size_t len = 1, n = 1; len <= max_len; len++) {
+        n *= alphabet.size();
+        nenumerated += n;
+        if(nenumerated > Max_Sentences_Enumerated) {
+            error(name << " would enumerate more than " << Max_Sentences_Enumerated << " sentences");
+        }
+    }
+
+    correct.clear();
+    incorrect.clear();
+    for(size_t len = 1; len <= max_len; len++) {
+        for(const string &sentence: permute_repeat(alphabet, len)) {
+            if(is_grammatical(sentence)) {
+                correct.push_back(sentence);
+            } else {
+                incorrect.push_back(sentence);
+            }
+        }
+    }
+    if(correct.empty() || incorrect.empty()) {
+        error(name << " needs both grammatical and ungrammatical sentences up to length " << max_len);
+    }
+
+    size_t ncorrect = min(correct.size(), max_sentences / 2);
+    size_t nincorrect = min(incorrect.size(), max_sentences - ncorrect);
+    ncorrect = min(correct.size(), max_sentences - nincorrect);
+    correct = subsample(correct, ncorrect);
+    incorrect = subsample(incorrect, nincorrect);
+}
+
+vector<Test> NEAT::create_sentence_tests(const string &alphabet,
+                                         const vector<string> &correct,
+                                         const vector<string> &incorrect) {
+    size_t nbits = 1;
+    while((size_t(1) << nbits) < alphabet.size()) {
+        nbits++;
+    }
+
+    const real_t S = 1.0; // Signal
+    const real_t Q = 1.0; // Query
+    const real_t _ = 0.0; // Null
+
+    const real_t weight_seq = 0;
+    const real_t weight_delay = 0;
+    const real_t weight_query_correct = 1.0 / correct.size();
+    const real_t weight_query_incorrect = 1.0 / incorrect.size();
+
+    const size_t ninputs = nbits + 2;
+
+    vector<Test> tests;
+    for(int grammatical = 1; grammatical >= 0; grammatical--) {
+        for(const string &sentence: grammatical ? correct : incorrect) {
+            vector<Step> steps;
+
+            for(char c: sentence) {
+                size_t symbol = alphabet.find(c);
+                vector<real_t> input(ninputs, _);
+                input[0] = S;
+                for(size_t bit = 0; bit < nbits; bit++) {
+                    input[1 + bit] = (symbol >> (nbits - 1 - bit)) & 1 ? 1.0 : 0.0;
+                }
+
+                // Create step providing signal, which has a zero output and weight
+                steps.push_back({input, {_}, weight_seq});
+
+                // Make a step of silence
+                steps.push_back({vector<real_t>(ninputs, _), {_}, weight_delay});
+            }
+
+            // End of sentence
+            vector<real_t> query(ninputs, _);
+            query[ninputs - 1] = Q;
+            if(grammatical) {
+                steps.push_back({query, {1.0}, weight_query_correct});
+            } else {
+                steps.push_back({query, {0.0}, weight_query_incorrect});
+            }
+
+            tests.push_back({sentence, steps});
+        }
+    }
+
+    return tests;
+}
@@ -0,0 +1,37 @@
+#pragma once
+
+#ifndef SENTENCES_H__
+#define SENTENCES_H__
+
+#include "staticevaluator.h"
+
+namespace NEAT {
+
+    // Most sentences a grammar experiment will enumerate before giving up.
+    const size_t Max_Sentences_Enumerated = 1 << 20;
+
+    // Evenly spaced elements of v, at most n of them.
+    std::vector<std::string> subsample(const std::vector<std::string> &v, size_t n);
+
+    // Every sentence over alphabet of 1 to max_len symbols, split by
+    // is_grammatical and subsampled down to max_sentences with as close to
+    // half grammatical as possible. Errors out if either class is empty.
+    void create_sentences(const std::string &name,
+                          const std::string &alphabet,
+                          size_t max_len,
+                          size_t max_sentences,
+                          std::function<bool (const std::string &)> is_grammatical,
+                          std::vector<std::string> &correct,
+                          std::vector<std::string> &incorrect);
+
+    // One test per sentence: a signal step carrying the symbol's bits and a
+    // silent step for each symbol, then a query whose expected output is 1 for
+    // grammatical sentences. Each class of query is weighted by one over its
+    // size. Symbols are encoded with the fewest bits.
+    std::vector<Test> create_sentence_tests(const std::string &alphabet,
+                                            const std::vector<std::string> &correct,
+                                            const std::vector<std::string> &incorrect);
+
+}
+
+#endif // #ifndef SENTENCES_H__
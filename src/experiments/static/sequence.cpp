@@ -1,5 +1,7 @@
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
+#include "sentences.h"
 #include "staticexperiment.h"
+#include "util.h"
 #include <assert.h>
 
 using namespace NEAT;
@@ -7,6 +9,7 @@ using namespace std;
 
 static vector<Test> create_parallel_output_tests(string syms,
                                                  vector<string> &sequences);
+static vector<Test> create_seq_tests(const string &spec);
 
 static struct SequenceInit {
     SequenceInit() {
@@ -33,6 +36,13 @@ static struct SequenceInit {
                 vector<string> seqs = permute_repeat(syms, 5);
                 return create_parallel_output_tests(syms, seqs);
             });
+
+        Experiment::register_family("seq", "seq:alphabet=N,len=N,delay=N,order={parallel,forward,reverse}", [] (const string &spec) {
+                string name = "seq:" + spec;
+                create_static_experiment(name.c_str(), [spec] () {
+                        return create_seq_tests(spec);
+                    });
+            });
     }
 } init;
 
@@ -122,3 +132,174 @@ static vector<Test> create_parallel_output_tests(string syms,
 
     return tests;
 }
+
+// Longest seq delay, so that a typo can't make every test millions of steps long.
+const size_t Max_Seq_Delay = 1000;
+
+enum class RecallOrder {
+    Parallel,
+    Forward,
+    Reverse
+};
+
+// Parameters of seq:<spec>, where spec is a comma-separated list of key=value
+// pairs. Omitted keys keep their defaults, which with len=N give the same
+// tests as seq-1bit-Nel.
+struct SequenceSpec {
+    size_t alphabet = 2; // Number of symbols
+    size_t len = 2; // Symbols per sequence
+    size_t delay = 0; // Silent steps between the sequence and its recall
+    RecallOrder order = RecallOrder::Parallel;
+    size_t samples = 0; // Evenly spaced sequences to keep, or 0 for all
+
+    SequenceSpec(const string &spec) {
+        if(!spec.empty() && spec.back() == ',') {
+            error("Invalid seq parameter '', expected key=value");
+        }
+        stringstream in(spec);
+        string pair;
+        while(getline(in, pair, ',')) {
+            size_t eq = pair.find('=');
+            if(eq == string::npos) {
+                error("Invalid seq parameter '" << pair << "', expected key=value");
+            }
+            string key = pair.substr(0, eq);
+            string value = pair.substr(eq + 1);
+            if(key == "order") {
+                if(value == "parallel") {
+                    order = RecallOrder::Parallel;
+                } else if(value == "forward") {
+                    order = RecallOrder::Forward;
+                } else if(value == "reverse") {
+                    order = RecallOrder::Reverse;
+                } else {
+                    error("Invalid seq order '" << value << "'");
+                }
+            } else if(key == "alphabet") {
+                alphabet = parse_size(key, value);
+            } else if(key == "len") {
+                len = parse_size(key, value);
+            } else if(key == "delay") {
+                delay = parse_size(key, value);
+            } else if(key == "samples") {
+                samples = parse_size(key, value);
+            } else {
+                error("Unknown seq parameter '" << key << "'");
+            }
+        }
+        if(alphabet < 2 || alphabet > 26) {
+            error("seq alphabet must be from 2 to 26 symbols");
+        }
+        if(len < 1) {
+            error("seq len must be at least 1");
+        }
+        if(delay > Max_Seq_Delay) {
+            error("seq delay must be at most " << Max_Seq_Delay);
+        }
+    }
+
+private:
+    // Digits only: stoul would also take a sign, and wrap "-1" to SIZE_MAX.
+    static size_t parse_size(const string &key, const string &value) {
+        size_t len = 0;
+        size_t result = 0;
+        if(!value.empty() && value.find_first_not_of("0123456789") == string::npos) {
+            try {
+                result = stoul(value, &len);
+            } catch(...) {
+            }
+        }
+        if(len == 0 || len != value.size()) {
+            error("Invalid value for seq parameter " << key << ": '" << value << "'");
+        }
+        return result;
+    }
+};
+
+// Presents each symbol followed by a silent step, waits spec.delay silent
+// steps, then asks for the sequence back: all at once in a single query step
+// (parallel), or one symbol per query step, first to last (forward) or last to
+// first (reverse).
+static vector<Test> create_seq_tests(const string &spec_str) {
+    SequenceSpec spec(spec_str);
+
+    string syms;
+    for(size_t i = 0; i < spec.alphabet; i++) {
+        syms.push_back('a' + i);
+    }
+
+    size_t nsequences = 1;
+    for(size_t i = 0; i < spec.len; i++) {
+        nsequences *= spec.alphabet;
+        if(nsequences > Max_Sentences_Enumerated) {
+            error("seq:" << spec_str << " would enumerate more than " << Max_Sentences_Enumerated << " sequences");
+        }
+    }
+
+    vector<string> sequences = permute_repeat(syms, spec.len);
+    if(spec.samples) {
+        sequences = subsample(sequences, spec.samples);
+    }
+    const real_t weight_seq = 5;
+    const real_t weight_query = 50;
+
+    size_t nbits = 1;
+    while((size_t(1) << nbits) < spec.alphabet) {
+        nbits++;
+    }
+    const size_t noutputs = spec.order == RecallOrder::Parallel ? spec.len * nbits : nbits;
+    const real_t weight_recall = spec.order == RecallOrder::Parallel ? weight_query : weight_query / spec.len;
+
+    auto encode = [nbits] (size_t sym, vector<real_t> &v) {
+        for(size_t bit = nbits; bit > 0; bit--) {
+            v.push_back((sym >> (bit - 1)) & 1 ? 1.0 : 0.0);
+        }
+    };
+
+    const real_t _ = 0.0;
+    const real_t X = 1.0;
+
+    vector<Test> tests;
+    for(const string &sequence: sequences) {
+        vector<Step> steps;
+        vector<real_t> silence = {_, _};
+        append(silence, _, nbits);
+        vector<real_t> empty_output(noutputs, _);
+
+        //Present sequence
+        for(char sym: sequence) {
+            vector<real_t> input = {X, _};
+            encode(sym - 'a', input);
+            steps.emplace_back(input, empty_output, weight_seq);
+            steps.emplace_back(silence, empty_output, weight_seq);
+        }
+
+        for(size_t i = 0; i < spec.delay; i++) {
+            steps.emplace_back(silence, empty_output, weight_seq);
+        }
+
+        // Recall
+        vector<real_t> query = {_, X};
+        append(query, _, nbits);
+        if(spec.order == RecallOrder::Parallel) {
+            vector<real_t> output;
+            for(char sym: sequence) {
+                encode(sym - 'a', output);
+            }
+            steps.emplace_back(query, output, weight_recall);
+        } else {
+            for(size_t i = 0; i < spec.len; i++) {
+                char sym = spec.order == RecallOrder::Forward
+                    ? sequence[i]
+                    : sequence[spec.len - 1 - i];
+                vector<real_t> output;
+                encode(sym - 'a', output);
+                steps.emplace_back(query, output, weight_recall);
+            }
+        }
+
+        tests.emplace_back(sequence, steps);
+    }
+
+    return tests;
+}
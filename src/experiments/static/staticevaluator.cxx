@@ -1,5 +1,6 @@
 #include "std.h"
 
+#include "neat.h"
 #include "network.h"
 #include "networkexecutor.h"
 #include "staticevaluator.h"
@@ -29,6 +30,8 @@ struct Config {
     };
 
     real_t max_err;
+    int loss; // StaticLoss
+    real_t tolerance;
     node_size_t ninputs;
     node_size_t noutputs;
     int nsteps;
@@ -56,6 +59,55 @@ struct Config {
     }
 };
 
+// Outputs are clamped this far from 0 and 1 for cross-entropy.
+#define Cross_Entropy_Epsilon 1e-4
+
+//---
+//--- FUNC max_loss
+//---
+//--- Most loss a single output can contribute.
+//---
+__net_eval_decl static real_t max_loss(int loss) {
+    if(loss == int(StaticLoss::CROSS_ENTROPY)) {
+        return -log(Cross_Entropy_Epsilon);
+    }
+    return 1.0;
+}
+
+//---
+//--- FUNC output_loss
+//---
+//--- Cross-entropy is taken relative to the expected value's own entropy, so
+//--- it's 0 for a perfect output whatever the expected value. Accuracy counts
+//--- an output as wrong when it's on the other side of 0.5 from the expected
+//--- value, and ignores the tolerance. Absolute and squared errors are capped
+//--- at 1, as outputs of tanh, relu or identity nodes can leave [0, 1].
+//---
+__net_eval_decl static real_t output_loss(int loss, real_t tolerance,
+                                          real_t actual, real_t expected) {
+    real_t err = actual - expected;
+    if(err < 0) err *= -1;
+    if(loss == int(StaticLoss::ACCURACY)) {
+        return err < 0.5 ? 0.0 : 1.0;
+    }
+    if(err < tolerance) {
+        return 0.0;
+    }
+    if(err > 1) err = 1;
+    if(loss == int(StaticLoss::SQUARED)) {
+        return err * err;
+    }
+    if(loss == int(StaticLoss::CROSS_ENTROPY)) {
+        const real_t eps = Cross_Entropy_Epsilon;
+        real_t p = actual < eps ? eps : (actual > 1 - eps ? 1 - eps : actual);
+        real_t result = 0.0;
+        if(expected > 0) result -= expected * log(p / expected);
+        if(expected < 1) result -= (1 - expected) * log((1 - p) / (1 - expected));
+        return result;
+    }
+    return err;
+}
+
 //---
 //--- CLASS Evaluator
 //---
@@ -92,12 +144,7 @@ struct Evaluator {
         real_t result = 0.0;
 
         for(size_t i = 0; i < config->noutputs; i++) {
-            real_t err = actual[i] - expected[i];
-            if(err < 0) err *= -1;
-            if(err < 0.05) {
-                err = 0.0;
-            }
-            result += err;
+            result += output_loss(config->loss, config->tolerance, actual[i], expected[i]);
         }
 
         errorsum += result * config->parms(istep)->weight;
@@ -149,7 +196,7 @@ static void create_config(const std::vector<Test> &tests,
         nsteps += test.steps.size();
         for(size_t j = 0; j < test.steps.size(); j++) {
             const Step &step = test.steps[j];
-            max_err += step.weight * step.output.size();
+            max_err += step.weight * step.output.size() * max_loss(int(env->static_loss));
         }
     }
 
@@ -159,6 +206,8 @@ static void create_config(const std::vector<Test> &tests,
     Config *config = (Config *)malloc(len);
 
     config->max_err = max_err;
+    config->loss = int(env->static_loss);
+    config->tolerance = env->static_tolerance;
     config->ninputs = ninputs;
     config->noutputs = noutputs;
     config->nsteps = nsteps;
@@ -22,11 +22,58 @@
 
 namespace NEAT {
 
+    // How the latest reproduction made a genome, as LineageLog writes it.
+    struct Lineage {
+        enum Operation {
+            SEED = 's',
+            CLONE = 'c',
+            MATE_MULTIPOINT = 'm',
+            MATE_MULTIPOINT_AVG = 'a'
+        };
+
+        enum Mutation {
+            ADD_NODE = 'N',
+            ADD_LINK = 'L',
+            DELETE_NODE = 'D',
+            DELETE_LINK = 'X',
+            LINK_WEIGHTS = 'w',
+            RANDOM_TRAIT = 'r',
+            LINK_TRAIT = 'l',
+            NODE_TRAIT = 'n',
+            ACTIVATION = 'a',
+            TOGGLE_ENABLE = 't',
+            GENE_REENABLE = 'e'
+        };
+
+        // A new node and its two links, or a new link if node_id is -1.
+        struct NewInnovation {
+            int node_id;
+            int innovation_num1;
+            int innovation_num2;
+        };
+
+        Operation operation = SEED;
+        // genome_id of the parents in the previous generation, which is their
+        // population index: OrganismsBuffer sets genome_id = population_index
+        // and it never changes.
+        std::vector<int> parents;
+        std::string mutations; // Mutation codes in the order applied
+        std::vector<NewInnovation> innovations;
+
+        void reset(Operation operation_, std::vector<int> parents_) {
+            operation = operation_;
+            parents = parents_;
+            mutations.clear();
+            innovations.clear();
+        }
+    };
+
     class Genome {
     public:
         rng_t rng;
 		int genome_id;
-        
+        Lineage lineage;
+
         virtual ~Genome() {}
 
         virtual Genome &operator=(const Genome &other) = 0;
@@ -1,5 +1,7 @@
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
 #include "genomemanager.h"
+#include "genome.h"
+#include "hyperneatgenomemanager.h"
 #include "innovgenomemanager.h"
 #include "util.h"
 
@@ -9,7 +11,13 @@ GenomeManager *GenomeManager::create() {
     switch(env->genome_type) {
     case GenomeType::INNOV:
         return new InnovGenomeManager();
+    case GenomeType::HYPERNEAT:
+        return new HyperNeatGenomeManager();
     default:
         panic();
     }
 }
+
+void GenomeManager::init_phenotype(Genome &genome, Network &net) {
+    genome.init_phenotype(net);
+}
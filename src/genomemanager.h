@@ -48,6 +48,13 @@ namespace NEAT {
                             MutationOperation op = MUTATE_OP_ANY) = 0;
 
         virtual void finalize_generation(bool new_fittest) = 0;
+
+        // Configures the network a genome encodes, by default with
+        // genome.init_phenotype().
+        virtual void init_phenotype(Genome &genome, class Network &net);
+
+        // Name of the current phase of the search, e.g. COMPLEXIFY or PRUNE.
+        virtual const char *get_search_phase() = 0;
     };
 
 }
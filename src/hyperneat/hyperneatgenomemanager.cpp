@@ -0,0 +1,59 @@
+#include "std.h" // Must be included first. Precompiled header with standard library includes.
+
+#include "hyperneatgenomemanager.h"
+#include "cpunetwork.h"
+#include "innovgenome.h"
+#include "util.h"
+
+using namespace NEAT;
+using namespace std;
+
+#define CPPN_NHIDDEN 4
+
+HyperNeatGenomeManager::HyperNeatGenomeManager()
+    : substrate(env->substrate_path) {
+}
+
+HyperNeatGenomeManager::~HyperNeatGenomeManager() {
+}
+
+vector<unique_ptr<Genome>> HyperNeatGenomeManager::create_seed_generation(size_t ngenomes,
+                                                                          rng_t rng,
+                                                                          size_t ntraits,
+                                                                          size_t ninputs,
+                                                                          size_t noutputs,
+                                                                          size_t nhidden) {
+    if( (ninputs != substrate.get_ninputs()) || (noutputs != substrate.get_noutputs()) ) {
+        error("Substrate has " << substrate.get_ninputs() << " inputs and "
+              << substrate.get_noutputs() << " outputs, but the experiment has "
+              << ninputs << " inputs and " << noutputs << " outputs");
+    }
+
+    vector<unique_ptr<Genome>> genomes =
+        InnovGenomeManager::create_seed_generation(ngenomes,
+                                                   rng,
+                                                   ntraits,
+                                                   Substrate::Cppn_Ninputs,
+                                                   Substrate::Cppn_Noutputs,
+                                                   CPPN_NHIDDEN);
+
+    for(auto &g: genomes) {
+        InnovGenome *cppn = dynamic_cast<InnovGenome *>(g.get());
+        for(InnovNodeGene &node: cppn->nodes) {
+            if(node.type == NT_HIDDEN) {
+                node.function = (functype)cppn->rng.integer(0, NUM_FUNCTYPES - 1);
+            } else if(node.type == NT_OUTPUT) {
+                // Weights have a sign.
+                node.function = FT_TANH;
+            }
+        }
+    }
+
+    return genomes;
+}
+
+void HyperNeatGenomeManager::init_phenotype(Genome &genome, Network &net) {
+    CpuNetwork cppn;
+    genome.init_phenotype(cppn);
+    substrate.develop(cppn, net);
+}
@@ -0,0 +1,40 @@
+#pragma once
+
+#ifndef HYPERNEATGENOMEMANAGER_H__
+#define HYPERNEATGENOMEMANAGER_H__
+
+#include "innovgenomemanager.h"
+#include "substrate.h"
+
+namespace NEAT {
+
+    //---
+    //--- CLASS HyperNeatGenomeManager
+    //---
+    //--- Evolves InnovGenomes as CPPNs, whose phenotypes are the networks they
+    //--- paint over env->substrate_path rather than the CPPNs themselves.
+    //---
+    class HyperNeatGenomeManager : public InnovGenomeManager {
+        friend class GenomeManager;
+        HyperNeatGenomeManager();
+    public:
+        virtual ~HyperNeatGenomeManager();
+
+        // ninputs and noutputs are those of the experiment, which must match the
+        // substrate. The seeds are CPPNs with hidden nodes of random functions.
+        virtual std::vector<std::unique_ptr<Genome>> create_seed_generation(size_t ngenomes,
+                                                                            class rng_t rng,
+                                                                            size_t ntraits,
+                                                                            size_t ninputs,
+                                                                            size_t noutputs,
+                                                                            size_t nhidden) override;
+
+        virtual void init_phenotype(Genome &genome, Network &net) override;
+
+    private:
+        Substrate substrate;
+    };
+
+}
+
+#endif // #ifndef HYPERNEATGENOMEMANAGER_H__
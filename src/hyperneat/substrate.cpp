@@ -0,0 +1,215 @@
+#include "std.h" // Must be included first. Precompiled header with standard library includes.
+
+#include "substrate.h"
+#include "cpunetwork.h"
+#include "networkexecutor.h"
+#include "util.h"
+
+using namespace NEAT;
+using namespace std;
+
+// Position of the i-th of n points spread evenly over [-1, 1].
+static real_t grid_coord(size_t i, size_t n) {
+    return n == 1 ? 0.0 : -1.0 + 2.0 * i / (n - 1);
+}
+
+Substrate::Substrate(const string &path)
+    : threshold(0.2)
+    , max_weight(3.0) {
+
+    ifstream in(path);
+    if(!in.good()) {
+        error("Failed opening " << path);
+    }
+
+    size_t lineno = 0;
+    auto err = [&lineno, &path] (const string &msg) {
+        error(path << ":" << lineno << ": " << msg);
+    };
+    auto find_layer = [this, &err] (const string &name) {
+        for(size_t i = 0; i < layers.size(); i++) {
+            if(layers[i].name == name) {
+                return i;
+            }
+        }
+        err("No such layer: " + name);
+        return size_t(0);
+    };
+
+    string line;
+    while(getline(in, line)) {
+        lineno++;
+        size_t pos = line.find_first_of('#');
+        if(pos != string::npos) {
+            line.resize(pos);
+        }
+        istringstream ss(line);
+        string cmd;
+        if(!(ss >> cmd)) {
+            continue;
+        }
+
+        if(cmd == "layer") {
+            Layer layer;
+            string type;
+            if(!(ss >> layer.name >> type >> layer.columns >> layer.rows >> layer.z)) {
+                err("Expecting: layer <name> <input|hidden|output> <columns> <rows> <z>");
+            }
+            if(type == "input") {
+                layer.type = NT_SENSOR;
+            } else if(type == "hidden") {
+                layer.type = NT_HIDDEN;
+            } else if(type == "output") {
+                layer.type = NT_OUTPUT;
+            } else {
+                err("Invalid layer type: " + type);
+            }
+            if(layer.columns == 0 || layer.rows == 0) {
+                err("Empty layer: " + layer.name);
+            }
+            for(Layer &other: layers) {
+                if(other.name == layer.name) {
+                    err("Duplicate layer: " + layer.name);
+                }
+            }
+            layers.push_back(layer);
+        } else if(cmd == "connect") {
+            string from, to;
+            if(!(ss >> from >> to)) {
+                err("Expecting: connect <from layer> <to layer>");
+            }
+            size_t ifrom = find_layer(from), ito = find_layer(to);
+            if(layers[ito].type == NT_SENSOR) {
+                err("Can't connect into input layer " + to);
+            }
+            connections.emplace_back(ifrom, ito);
+        } else if(cmd == "threshold") {
+            if(!(ss >> threshold) || threshold < 0 || threshold >= 1) {
+                err("Expecting: threshold <t>, with 0 <= t < 1");
+            }
+        } else if(cmd == "max_weight") {
+            if(!(ss >> max_weight)) {
+                err("Expecting: max_weight <w>");
+            }
+        } else {
+            err("Unknown command: " + cmd);
+        }
+        string extra;
+        if(ss >> extra) {
+            err("Unexpected: " + extra);
+        }
+    }
+
+    // Lay nodes out as CpuNetwork requires: bias, sensors, outputs, hidden.
+    memset(&dims, 0, sizeof(dims));
+    points.push_back({0.0, 0.0, 0.0});
+    dims.nnodes.bias = 1;
+    size_t nnodes = 1;
+    for(nodetype type: {NT_SENSOR, NT_OUTPUT, NT_HIDDEN}) {
+        for(Layer &layer: layers) {
+            if(layer.type != type) {
+                continue;
+            }
+            layer.first = nnodes;
+            for(size_t row = 0; row < layer.rows; row++) {
+                for(size_t col = 0; col < layer.columns; col++) {
+                    points.push_back({grid_coord(col, layer.columns),
+                                      grid_coord(row, layer.rows),
+                                      layer.z});
+                }
+            }
+            nnodes += layer.columns * layer.rows;
+            if(nnodes > NODES_MAX) {
+                error(path << ": more than " << NODES_MAX << " nodes");
+            }
+            switch(type) {
+            case NT_SENSOR: dims.nnodes.sensor += layer.columns * layer.rows; break;
+            case NT_OUTPUT: dims.nnodes.output += layer.columns * layer.rows; break;
+            case NT_HIDDEN: dims.nnodes.hidden += layer.columns * layer.rows; break;
+            default: panic();
+            }
+        }
+    }
+    dims.nnodes.all = nnodes;
+    dims.nnodes.input = dims.nnodes.bias + dims.nnodes.sensor;
+    dims.nnodes.noninput = dims.nnodes.output + dims.nnodes.hidden;
+
+    if(dims.nnodes.sensor == 0 || dims.nnodes.output == 0) {
+        error(path << ": needs an input and an output layer");
+    }
+
+    // Bias links, plus every link a connection may have.
+    size_t max_links = dims.nnodes.noninput;
+    for(auto &c: connections) {
+        const Layer &from = layers[c.first], &to = layers[c.second];
+        max_links += from.columns * from.rows * to.columns * to.rows;
+    }
+    if(max_links > LINKS_MAX) {
+        error(path << ": connections have up to " << max_links << " links, max is " << LINKS_MAX);
+    }
+}
+
+real_t Substrate::query(CpuNetwork &cppn,
+                        const Point &from,
+                        const Point &to,
+                        size_t ioutput) const {
+    real_t inputs[Cppn_Ninputs] = {from.x, from.y, from.z, to.x, to.y, to.z};
+
+    cppn.clear_noninput();
+    for(size_t i = 0; i < Cppn_Ninputs; i++) {
+        cppn.load_sensor(i, inputs[i]);
+    }
+    cppn.activate(NACTIVATES_PER_INPUT);
+    return cppn.get_outputs()[ioutput];
+}
+
+bool Substrate::paint(real_t output, real_t &weight) const {
+    real_t magnitude = min(fabs(output), 1.0);
+    if(magnitude <= threshold) {
+        return false;
+    }
+    weight = (output < 0 ? -1 : 1) * max_weight * (magnitude - threshold) / (1.0 - threshold);
+    return true;
+}
+
+void Substrate::develop(CpuNetwork &cppn, Network &net) const {
+    vector<vector<NetLink>> incoming(dims.nnodes.all);
+    auto add_link = [&incoming] (size_t in, size_t out, real_t weight) {
+        NetLink link;
+        link.weight = weight;
+        link.in_node_index = in;
+        link.out_node_index = out;
+        link.hebb_rate = link.pre_rate = link.post_rate = 0.0;
+        incoming[out].push_back(link);
+    };
+
+    real_t weight;
+    for(size_t i = dims.nnodes.input; i < dims.nnodes.all; i++) {
+        if(paint(query(cppn, points[0], points[i], 1), weight)) {
+            add_link(0, i, weight);
+        }
+    }
+    for(auto &c: connections) {
+        const Layer &from = layers[c.first], &to = layers[c.second];
+        for(size_t j = to.first; j < to.first + to.columns * to.rows; j++) {
+            for(size_t i = from.first; i < from.first + from.columns * from.rows; i++) {
+                if(paint(query(cppn, points[i], points[j], 0), weight)) {
+                    add_link(i, j, weight);
+                }
+            }
+        }
+    }
+
+    NetDims netdims = dims;
+    vector<NetNode> nodes(dims.nnodes.all);
+    vector<NetLink> links;
+    for(size_t i = 0; i < dims.nnodes.all; i++) {
+        nodes[i].incoming_start = links.size();
+        append(links, incoming[i]);
+        nodes[i].incoming_end = links.size();
+        nodes[i].function = FT_SIGMOID;
+    }
+    netdims.nlinks = links.size();
+
+    net.configure(netdims, nodes.data(), links.data());
+}
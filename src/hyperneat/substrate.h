@@ -0,0 +1,69 @@
+#pragma once
+
+#ifndef SUBSTRATE_H__
+#define SUBSTRATE_H__
+
+#include "network.h"
+
+namespace NEAT {
+
+    class CpuNetwork;
+
+    //---
+    //--- CLASS Substrate
+    //---
+    //--- Geometry of the networks HyperNEAT CPPNs paint, read from a file of:
+    //---
+    //---   layer <name> <input|hidden|output> <columns> <rows> <z>
+    //---   connect <from layer> <to layer>
+    //---   threshold <t>     # |CPPN output| at or below which there's no link (default 0.2)
+    //---   max_weight <w>    # Weight of a CPPN output of 1 (default 3.0)
+    //---
+    //--- A layer is a grid spanning [-1, 1] in x and y at depth z, with its nodes
+    //--- in row-major order. Sensors are the nodes of the input layers and
+    //--- outputs those of the output layers, in file order.
+    //---
+    //--- The CPPN takes x1 y1 z1 x2 y2 z2 and outputs the weight of the link from
+    //--- (x1, y1, z1) to (x2, y2, z2) and the weight of the bias link of a node,
+    //--- queried with the node as (x2, y2, z2) and the origin as the source.
+    //---
+    class Substrate {
+    public:
+        static const size_t Cppn_Ninputs = 6;
+        static const size_t Cppn_Noutputs = 2;
+
+        Substrate(const std::string &path);
+
+        size_t get_ninputs() const { return dims.nnodes.sensor; }
+        size_t get_noutputs() const { return dims.nnodes.output; }
+
+        // Configures net with the weights cppn paints over the substrate.
+        void develop(CpuNetwork &cppn, Network &net) const;
+
+    private:
+        struct Point {
+            real_t x, y, z;
+        };
+        struct Layer {
+            std::string name;
+            nodetype type;
+            size_t columns;
+            size_t rows;
+            real_t z;
+            size_t first; // Index of its first node in the network
+        };
+
+        real_t query(CpuNetwork &cppn, const Point &from, const Point &to, size_t ioutput) const;
+        bool paint(real_t output, real_t &weight) const;
+
+        std::vector<Layer> layers;
+        std::vector<std::pair<size_t, size_t>> connections;
+        std::vector<Point> points; // Of each node, by index in the network
+        NetDims dims;
+        real_t threshold;
+        real_t max_weight;
+    };
+
+}
+
+#endif // #ifndef SUBSTRATE_H__
@@ -168,6 +168,9 @@ Genome::Stats InnovGenome::get_stats() {
 }
 
 void InnovGenome::print(std::ostream &out) {
+    // Round-trip precision, so that a genome read back evaluates the same.
+    std::streamsize precision = out.precision(17);
+
     out<<"genomestart "<<genome_id<<std::endl;
 
 	//Output the traits
@@ -183,6 +186,8 @@ void InnovGenome::print(std::ostream &out) {
         g.print_to_file(out);
 
     out << "genomeend " << genome_id << std::endl;
+
+    out.precision(precision);
 }
 
 int InnovGenome::get_last_node_id() {
@@ -205,6 +210,7 @@ InnovGenome &InnovGenome::operator=(const InnovGenome &other) {
     traits = other.traits;
     nodes = other.nodes;
     links = other.links;
+    lineage = other.lineage;
     return *this;
 }
 
@@ -238,6 +244,29 @@ void InnovGenome::mutate_node_trait(int times) {
     //mutation number
 }
 
+bool InnovGenome::mutate_activation() {
+    size_t first_hidden;
+    for(first_hidden = 0; first_hidden < nodes.size(); first_hidden++) {
+        if( nodes[first_hidden].type == NT_HIDDEN ) {
+            break;
+        }
+    }
+    if(first_hidden == nodes.size()) {
+        return false;
+    }
+
+    InnovNodeGene &node = nodes[rng.index(nodes, first_hidden)];
+    if(node.frozen) {
+        return false;
+    }
+    int function = rng.integer(0, NUM_FUNCTYPES - 2);
+    if(function >= node.function) {
+        function++;
+    }
+    node.function = (functype)function;
+    return true;
+}
+
 void InnovGenome::mutate_link_weights(real_t power,real_t rate,mutator mut_type) {
 	//Go through all the InnovLinkGenes and perturb their link's weights
 
@@ -311,12 +340,14 @@ void InnovGenome::mutate_link_weights(real_t power,real_t rate,mutator mut_type)
 	} //end for loop
 }
 
-void InnovGenome::mutate_toggle_enable(int times) {
+bool InnovGenome::mutate_toggle_enable(int times) {
+    bool toggled = false;
     for(int i = 0; i < times; i++) {
         InnovLinkGene &gene = rng.element(links);
 
         if(!gene.enable) {
             gene.enable = true;
+            toggled = true;
         } else {
 			//We need to make sure that another gene connects out of the in-node
 			//Because if not a section of network will break off and become isolated
@@ -331,20 +362,24 @@ void InnovGenome::mutate_toggle_enable(int times) {
             }
 
 			//Disable the gene if it's safe to do so
-			if(found)
+			if(found) {
 				gene.enable = false;
+				toggled = true;
+			}
         }
     }
+    return toggled;
 }
 
-void InnovGenome::mutate_gene_reenable() {
+bool InnovGenome::mutate_gene_reenable() {
 	//Search for a disabled gene
     for(InnovLinkGene &g: links) {
         if(!g.enable) {
             g.enable = true;
-            break;
+            return true;
         }
     }
+    return false;
 }
 
 bool InnovGenome::mutate_add_node(CreateInnovationFunc create_innov,
@@ -405,7 +440,7 @@ bool InnovGenome::mutate_add_node(CreateInnovationFunc create_innov,
 	return true;
 }
 
-void InnovGenome::mutate_delete_node() {
+bool InnovGenome::mutate_delete_node() {
     size_t first_non_io;
     for(first_non_io = 0; first_non_io < nodes.size(); first_non_io++) {
         if( nodes[first_non_io].type == NT_HIDDEN ) {
@@ -415,7 +450,7 @@ void InnovGenome::mutate_delete_node() {
 
     //Don't delete if only 0 or 1 hidden nodes
     if(first_non_io >= (nodes.size()-1)) {
-        return;
+        return false;
     }
 
     size_t node_index = rng.index(nodes, first_non_io);
@@ -432,11 +467,12 @@ void InnovGenome::mutate_delete_node() {
                                  });
 
     links.resize(it_end - links.begin());
+    return true;
 }
 
-void InnovGenome::mutate_delete_link() {
+bool InnovGenome::mutate_delete_link() {
     if(links.size() <= 1)
-        return;
+        return false;
 
     size_t link_index = rng.index(links);
     InnovLinkGene link = links[link_index];
@@ -444,6 +480,7 @@ void InnovGenome::mutate_delete_link() {
 
     delete_if_orphaned_hidden_node(link.in_node_id());
     delete_if_orphaned_hidden_node(link.out_node_id());
+    return true;
 }
 
 bool InnovGenome::mutate_add_link(CreateInnovationFunc create_innov,
@@ -454,8 +491,10 @@ bool InnovGenome::mutate_add_link(CreateInnovationFunc create_innov,
 	InnovNodeGene *in_node = nullptr; //Pointers to the nodes
 	InnovNodeGene *out_node = nullptr; //Pointers to the nodes
 
-	//Decide whether to make this recurrent
-	bool do_recur = rng.prob() < env->recur_only_prob;
+	//Decide whether to make this recurrent. Feedforward networks only get
+	//links that don't close a loop.
+	bool do_recur = (rng.prob() < env->recur_only_prob)
+	    && (env->activation_schedule != ActivationSchedule::FEEDFORWARD);
 
     // Try to find nodes for link.
     {
@@ -559,12 +598,16 @@ void InnovGenome::mate(InnovGenome *genome1,
 
     //Perform mating based on probabilities of differrent mating types
     if( offspring->rng.prob() < env->mate_multipoint_prob ) { 
+        offspring->lineage.reset(Lineage::MATE_MULTIPOINT,
+                                 {genome1->genome_id, genome2->genome_id});
         InnovGenome::mate_multipoint(genome1,
                                      genome2,
                                      offspring,
                                      fitness1,
                                      fitness2);
     } else {
+        offspring->lineage.reset(Lineage::MATE_MULTIPOINT_AVG,
+                                 {genome1->genome_id, genome2->genome_id});
         InnovGenome::mate_multipoint_avg(genome1,
                                          genome2,
                                          offspring,
@@ -1235,6 +1278,10 @@ void InnovGenome::init_phenotype(Network &net) {
             netlink.weight = link.weight();
             netlink.in_node_index = get_node_index(link.in_node_id());
             netlink.out_node_index = get_node_index(link.out_node_id());
+            Trait &trait = get_trait(link);
+            netlink.hebb_rate = trait.params[0];
+            netlink.pre_rate = trait.params[1];
+            netlink.post_rate = trait.params[2];
 
             node_nlinks[netlink.out_node_index]++;
 		}
@@ -1249,12 +1296,14 @@ void InnovGenome::init_phenotype(Network &net) {
     NetNode* netnodes = new NetNode[nnodes];
     netnodes[0].incoming_start = 0;
     netnodes[0].incoming_end = node_nlinks[0];
+    netnodes[0].function = nodes[0].function;
     for(size_t i = 1; i < nnodes; i++) {
         NetNode &prev = netnodes[i-1];
         NetNode &curr = netnodes[i];
 
         curr.incoming_start = prev.incoming_end;
         curr.incoming_end = curr.incoming_start + node_nlinks[i];
+        curr.function = nodes[i].function;
     }
     assert(netnodes[nnodes - 1].incoming_end == nlinks);
     
@@ -71,14 +71,18 @@ namespace NEAT {
 		// Change random node's trait times times 
 		void mutate_node_trait(int times);
 
+		// Give a random hidden node a different activation function, returning
+		// false if there was no unfrozen one to pick
+		bool mutate_activation();
+
 		// Add Gaussian noise to linkweights either GAUSSIAN or COLDGAUSSIAN (from zero)
 		void mutate_link_weights(real_t power,real_t rate,mutator mut_type);
 
-		// toggle links on or off 
-		void mutate_toggle_enable(int times);
+		// toggle links on or off, returning false if none changed
+		bool mutate_toggle_enable(int times);
 
-		// Find first disabled gene and enable it 
-		void mutate_gene_reenable();
+		// Find first disabled gene and enable it, returning false if there's none
+		bool mutate_gene_reenable();
 
 		// These last kinds of mutations return false if they fail
 		//   They can fail under certain conditions,  being unable
@@ -89,9 +93,9 @@ namespace NEAT {
 		bool mutate_add_node(CreateInnovationFunc create_innov,
                              bool delete_split_link);
 
-		void mutate_delete_node();
+		bool mutate_delete_node();
 
-		void mutate_delete_link();
+		bool mutate_delete_link();
 
 		// Mutate the genome by adding a new link between 2 random InnovNodeGenes 
 		bool mutate_add_link(CreateInnovationFunc create_innov,
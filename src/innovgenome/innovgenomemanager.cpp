@@ -82,6 +82,7 @@ bool InnovGenomeManager::are_compatible(Genome &genome1,
 void InnovGenomeManager::clone(Genome &orig,
                                Genome &clone) {
     to_innov(orig)->duplicate_into(to_innov(clone));
+    clone.lineage.reset(Lineage::CLONE, {orig.genome_id});
 }
 
 void InnovGenomeManager::mate(Genome &genome1,
@@ -126,56 +127,83 @@ void InnovGenomeManager::mutate(Genome &genome_,
         genome->mutate_link_weights(env->weight_mut_power,
                                     1.0,
                                     GAUSSIAN);
+        genome->lineage.mutations += Lineage::LINK_WEIGHTS;
         break;
     case MUTATE_OP_STRUCTURE: {
         if(!allow_add && !allow_del) {
             mutate(genome_, MUTATE_OP_WEIGHTS);
         } else {
             if(!allow_del || genome_.rng.boolean()) {
-                genome->mutate_add_link(create_innov_func(genome_),
-                                        env->newlink_tries);
+                if(genome->mutate_add_link(create_innov_func(genome_),
+                                           env->newlink_tries)) {
+                    genome->lineage.mutations += Lineage::ADD_LINK;
+                }
             } else {
-                genome->mutate_delete_link();
+                if(genome->mutate_delete_link()) {
+                    genome->lineage.mutations += Lineage::DELETE_LINK;
+                }
             }
         }
     } break;
     case MUTATE_OP_ANY: {
         rng_t &rng = genome->rng;
         rng_t::prob_switch_t op = rng.prob_switch();
+        string &mutations = genome->lineage.mutations;
 
         if( allow_add && op.prob_case(env->mutate_add_node_prob) ) {
             bool delete_split_link = env->search_type != GeneticSearchType::COMPLEXIFY;
-            genome->mutate_add_node(create_innov_func(genome_), delete_split_link);
+            if(genome->mutate_add_node(create_innov_func(genome_), delete_split_link)) {
+                mutations += Lineage::ADD_NODE;
+            }
         } else if( allow_add && op.prob_case(env->mutate_add_link_prob) ) {
-            genome->mutate_add_link(create_innov_func(genome_),
-                                    env->newlink_tries);
+            if(genome->mutate_add_link(create_innov_func(genome_),
+                                       env->newlink_tries)) {
+                mutations += Lineage::ADD_LINK;
+            }
         } else if( allow_del && op.prob_case(env->mutate_delete_link_prob) ) {
-            genome->mutate_delete_link();
+            if(genome->mutate_delete_link()) {
+                mutations += Lineage::DELETE_LINK;
+            }
         } else if( allow_del && op.prob_case(env->mutate_delete_node_prob) ) {
-            genome->mutate_delete_node();
+            if(genome->mutate_delete_node()) {
+                mutations += Lineage::DELETE_NODE;
+            }
         } else {
             //Only do other mutations when not doing sturctural mutations
             if( rng.under(env->mutate_random_trait_prob) ) {
                 genome->mutate_random_trait();
+                mutations += Lineage::RANDOM_TRAIT;
             }
             if( rng.under(env->mutate_link_trait_prob) ) {
                 genome->mutate_link_trait(1);
+                mutations += Lineage::LINK_TRAIT;
             }
             if( rng.under(env->mutate_node_trait_prob) ) {
                 genome->mutate_node_trait(1);
+                mutations += Lineage::NODE_TRAIT;
+            }
+            if( rng.under(env->mutate_activation_prob) ) {
+                if(genome->mutate_activation()) {
+                    mutations += Lineage::ACTIVATION;
+                }
             }
             if( rng.under(env->mutate_link_weights_prob) ) {
                 genome->mutate_link_weights(env->weight_mut_power,
                                             1.0,
                                             GAUSSIAN);
+                mutations += Lineage::LINK_WEIGHTS;
             }
 
             if(env->search_type == GeneticSearchType::COMPLEXIFY) {
                 if( rng.under(env->mutate_toggle_enable_prob) ) {
-                    genome->mutate_toggle_enable(1);
+                    if(genome->mutate_toggle_enable(1)) {
+                        mutations += Lineage::TOGGLE_ENABLE;
+                    }
                 }
                 if (rng.under(env->mutate_gene_reenable_prob) ) {
-                    genome->mutate_gene_reenable(); 
+                    if(genome->mutate_gene_reenable()) {
+                        mutations += Lineage::GENE_REENABLE;
+                    }
                 }
             }
         }
@@ -185,8 +213,10 @@ void InnovGenomeManager::mutate(Genome &genome_,
     }
 
     if(genome->links.size() == 0) {
-        genome->mutate_add_link(create_innov_func(genome_),
-                                env->newlink_tries);
+        if(genome->mutate_add_link(create_innov_func(genome_),
+                                   env->newlink_tries)) {
+            genome->lineage.mutations += Lineage::ADD_LINK;
+        }
     }
 }
 
@@ -220,11 +250,35 @@ void InnovGenomeManager::finalize_generation(bool new_fittest) {
     }
 }
 
+const char *InnovGenomeManager::get_search_phase() {
+    switch(env->search_type) {
+    case GeneticSearchType::PHASED:
+        return search_phase == PRUNE ? "PRUNE" : "COMPLEXIFY";
+    case GeneticSearchType::BLENDED:
+        return "BLENDED";
+    case GeneticSearchType::COMPLEXIFY:
+        return "COMPLEXIFY";
+    default:
+        panic();
+    }
+}
+
 CreateInnovationFunc InnovGenomeManager::create_innov_func(Genome &g) {
     return [this, &g] (InnovationId id,
                        InnovationParms parms,
                        IndividualInnovation::ApplyFunc apply) {
-        innovations.add(IndividualInnovation(g.genome_id, id, parms, apply));
+        // Innovation numbers are only known once finalize_generation() applies them.
+        auto apply_and_record = [&g, apply] (const Innovation *innov) {
+            apply(innov);
+            if(innov->id.innovation_type == NEWNODE) {
+                g.lineage.innovations.push_back({innov->newnode_id,
+                                                 innov->innovation_num1,
+                                                 innov->innovation_num2});
+            } else {
+                g.lineage.innovations.push_back({-1, innov->innovation_num1, -1});
+            }
+        };
+        innovations.add(IndividualInnovation(g.genome_id, id, parms, apply_and_record));
     };
 }
 
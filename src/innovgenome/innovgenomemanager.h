@@ -10,6 +10,7 @@ namespace NEAT {
 
     class InnovGenomeManager : public GenomeManager {
         friend class GenomeManager;
+    protected:
         InnovGenomeManager();
     public:
         virtual ~InnovGenomeManager();
@@ -40,6 +41,8 @@ namespace NEAT {
 
         virtual void finalize_generation(bool new_fittest) override;
 
+        virtual const char *get_search_phase() override;
+
     private:
         CreateInnovationFunc create_innov_func(Genome &g);
         bool is_mate_allowed();
@@ -15,10 +15,12 @@
 */
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
 #include "innovnodegene.h"
+#include "util.h"
 using namespace NEAT;
 
 InnovNodeGene::InnovNodeGene(nodetype ntype,int nodeid) {
 	type=ntype; //NEURON or SENSOR type
+	function = is_input(ntype) ? FT_SIGMOID : env->activation;
 	node_id=nodeid;
 	frozen=false;
 	trait_id=1;
@@ -26,9 +28,18 @@ InnovNodeGene::InnovNodeGene(nodetype ntype,int nodeid) {
 
 InnovNodeGene::InnovNodeGene (const char *argline) {
     std::stringstream ss(argline);
-    int nodety, nodepl;
-    ss >> node_id >> trait_id >> nodety >> nodepl;
+    int nodety, func;
+    ss >> node_id >> trait_id >> nodety;
     type = (nodetype)nodety;
+    // The function column is optional, for genomes written before there were others.
+    if(ss >> func) {
+        if(func < 0 || func >= NUM_FUNCTYPES) {
+            error("Node " << node_id << ": unknown activation function " << func);
+        }
+        function = (functype)func;
+    } else {
+        function = FT_SIGMOID;
+    }
 
     if(trait_id == 0)
         trait_id = 1;
@@ -44,5 +55,8 @@ InnovNodeGene::~InnovNodeGene() {
 void InnovNodeGene::print_to_file(std::ostream &outFile) {
     outFile<<"node "<<node_id<<" ";
     outFile<<trait_id<<" ";
-    outFile<<(int)type<<std::endl;
+    outFile<<(int)type;
+    if(function != FT_SIGMOID)
+        outFile<<" "<<(int)function;
+    outFile<<std::endl;
 }
@@ -28,6 +28,7 @@ namespace NEAT {
 	public:
 		bool frozen; // When frozen, cannot be mutated (meaning its trait pointer is fixed)
 		nodetype type;
+		functype function; // Always FT_SIGMOID for inputs, which don't activate
 		int node_id;  // A node can be given an identification number for saving in files
 
         // Construct InnovNodeGene with invalid state.
@@ -127,6 +127,12 @@ void usage() {
     cerr << "  -n population_size   (default=" << env->pop_size << ")" << endl;
     cerr << "  -x max_generations   (default=" << DEFAULT_MAX_GENS << ")" << endl;
     cerr << "  -s search_type       {phased, blended, complexify} (default=phased)" << endl;
+    cerr << "  -S selection         {fitness, novelty, blended, pareto} (default=fitness)" << endl;
+    cerr << "  -l generation_log    {csv, jsonl, none} (default=csv)" << endl;
+    cerr << "  -P name=value        Set a NeatEnv parameter, e.g. -P weight_mut_power=2.5" << endl;
+    cerr << "  -m maze_map          Map file for the maze experiment (default=res/maze.map)" << endl;
+    cerr << "  -H substrate         Evolve HyperNEAT CPPNs that paint the weights of this substrate" << endl;
+    cerr << "  -t                   Run the rng self test and exit" << endl;
 
 
     exit(1);
@@ -158,7 +164,7 @@ int main(int argc, char *argv[]) {
 //#ifdef __linux__
     {
         int opt;
-        while( (opt = getopt(argc, argv, "fc:r:p:g:n:x:s:")) != -1) {
+        while( (opt = getopt(argc, argv, "fc:r:p:g:n:x:s:S:l:P:m:H:t")) != -1) {
             switch(opt) {
             case 'f':
                 force_delete = true;
@@ -182,6 +188,39 @@ int main(int argc, char *argv[]) {
                         {"complexify", GeneticSearchType::COMPLEXIFY}
                     });
                 break;
+            case 'S':
+                env->selection_type = parse_enum<SelectionType>("-S", optarg, {
+                        {"fitness", SelectionType::FITNESS},
+                        {"novelty", SelectionType::NOVELTY},
+                        {"blended", SelectionType::BLENDED},
+                        {"pareto", SelectionType::PARETO}
+                    });
+                break;
+            case 'l':
+                env->generation_log_format = parse_enum<GenerationLogFormat>("-l", optarg, {
+                        {"csv", GenerationLogFormat::CSV},
+                        {"jsonl", GenerationLogFormat::JSONL},
+                        {"none", GenerationLogFormat::NONE}
+                    });
+                break;
+            case 'm':
+                env->maze_map_path = optarg;
+                break;
+            case 'H':
+                env->substrate_path = optarg;
+                env->genome_type = GenomeType::HYPERNEAT;
+                break;
+            case 't':
+                rng_t::test();
+                return 0;
+            case 'P': {
+                string param = optarg;
+                size_t eq = param.find('=');
+                if( (eq == string::npos)
+                    || !env->set_param(param.substr(0, eq), param.substr(eq + 1)) ) {
+                    error("Invalid value for -P: " << param);
+                }
+            } break;
             default:
                 error("Invalid option: -" << (char)opt);
             }
@@ -209,8 +248,8 @@ int main(int argc, char *argv[]) {
 //            sh("powershell.exe -Command {del -Force -Recurse experiment_*}")
 //            sh("del -Force -Recurse experiment_*")
 //            _sleep(1000);
-        }
 #endif
+        }
     } else if(exists("experiments")) {
         error("Already exists: experiments.\nMove your experiment directories or use -f to delete them automatically.")
     }
@@ -220,6 +259,23 @@ int main(int argc, char *argv[]) {
         env->mutate_delete_link_prob *= 0.1;
     }
 
+    if(env->selection_type == SelectionType::PARETO) {
+        env->population_type = PopulationType::PARETO;
+    }
+
+    if( (env->genome_type == GenomeType::HYPERNEAT) && env->plastic ) {
+        error("Plastic networks aren't supported with HyperNEAT");
+    }
+
+#ifdef ENABLE_CUDA
+    if(env->plastic) {
+        error("Plastic networks aren't supported on Cuda");
+    }
+    if(env->activation_schedule != ActivationSchedule::CYCLES) {
+        error("Only the cycles activation schedule is supported on Cuda");
+    }
+#endif
+
     const char *experiment_name = argv[optind++];
 
     Experiment *exp = Experiment::get(experiment_name);
@@ -289,7 +345,7 @@ int main(int argc, char *argv[]) {
 //    }
 //#endif
 
-    rng_t rng{rng_seed};
+    rng_t rng(rng_seed);
     exp->run(rng, maxgens);
 
 //#ifdef __linux__
@@ -31,6 +31,145 @@ static NeatEnv sEnv;
 NeatEnv* NEAT::env = &sEnv;
 //NeatEnv *NEAT::env = new NeatEnv();
 
+bool NeatEnv::set_param(const std::string &name, const std::string &value) {
+#define REAL_PARAM(field) if(name == #field) { field = std::stod(value, &len); return len == value.size(); }
+#define INT_PARAM(field) if(name == #field) { field = std::stoi(value, &len); return len == value.size(); }
+    size_t len = 0;
+    try {
+        REAL_PARAM(trait_param_mut_prob);
+        REAL_PARAM(trait_mutation_power);
+        REAL_PARAM(linktrait_mut_sig);
+        REAL_PARAM(nodetrait_mut_sig);
+        REAL_PARAM(weight_mut_power);
+        REAL_PARAM(recur_prob);
+        REAL_PARAM(disjoint_coeff);
+        REAL_PARAM(excess_coeff);
+        REAL_PARAM(mutdiff_coeff);
+        REAL_PARAM(compat_threshold);
+        REAL_PARAM(age_significance);
+        REAL_PARAM(survival_thresh);
+        REAL_PARAM(mutate_only_prob);
+        REAL_PARAM(mutate_random_trait_prob);
+        REAL_PARAM(mutate_link_trait_prob);
+        REAL_PARAM(mutate_node_trait_prob);
+        REAL_PARAM(mutate_link_weights_prob);
+        REAL_PARAM(mutate_toggle_enable_prob);
+        REAL_PARAM(mutate_gene_reenable_prob);
+        REAL_PARAM(mutate_add_node_prob);
+        REAL_PARAM(mutate_delete_node_prob);
+        REAL_PARAM(mutate_add_link_prob);
+        REAL_PARAM(mutate_delete_link_prob);
+        REAL_PARAM(interspecies_mate_rate);
+        REAL_PARAM(mate_multipoint_prob);
+        REAL_PARAM(mate_only_prob);
+        REAL_PARAM(recur_only_prob);
+        REAL_PARAM(static_tolerance);
+        REAL_PARAM(success_error);
+        REAL_PARAM(novelty_weight);
+        REAL_PARAM(mutate_activation_prob);
+        REAL_PARAM(settle_epsilon);
+        INT_PARAM(pop_size);
+        INT_PARAM(dropoff_age);
+        INT_PARAM(newlink_tries);
+        INT_PARAM(print_every);
+        INT_PARAM(num_runs);
+        INT_PARAM(regex_max_len);
+        INT_PARAM(regex_max_sentences);
+        INT_PARAM(cfg_max_len);
+        INT_PARAM(cfg_max_sentences);
+        INT_PARAM(pole_max_steps);
+        INT_PARAM(env_episodes);
+        INT_PARAM(novelty_k);
+        INT_PARAM(novelty_archive_add);
+        INT_PARAM(activation_cycles);
+        INT_PARAM(settle_max_cycles);
+    } catch(...) {
+        return false;
+    }
+#undef REAL_PARAM
+#undef INT_PARAM
+
+    auto bool_param = [&value] (bool &field) {
+        if(value == "true" || value == "1") {
+            field = true;
+        } else if(value == "false" || value == "0") {
+            field = false;
+        } else {
+            return false;
+        }
+        return true;
+    };
+    if(name == "mutate_add_link_reenables") {
+        return bool_param(mutate_add_link_reenables);
+    }
+    if(name == "plastic") {
+        return bool_param(plastic);
+    }
+    if(name == "plastic_reset") {
+        return bool_param(plastic_reset);
+    }
+    if(name == "lineage_log") {
+        return bool_param(lineage_log);
+    }
+
+    if(name == "static_loss") {
+        if(value == "absolute") {
+            static_loss = StaticLoss::ABSOLUTE;
+        } else if(value == "squared") {
+            static_loss = StaticLoss::SQUARED;
+        } else if(value == "cross_entropy") {
+            static_loss = StaticLoss::CROSS_ENTROPY;
+        } else if(value == "accuracy") {
+            static_loss = StaticLoss::ACCURACY;
+        } else {
+            return false;
+        }
+        return true;
+    }
+
+    if(name == "activation_schedule") {
+        if(value == "cycles") {
+            activation_schedule = ActivationSchedule::CYCLES;
+        } else if(value == "settle") {
+            activation_schedule = ActivationSchedule::SETTLE;
+        } else if(value == "feedforward") {
+            activation_schedule = ActivationSchedule::FEEDFORWARD;
+        } else {
+            return false;
+        }
+        return true;
+    }
+
+    if(name == "activation") {
+        return parse_functype(value, activation);
+    }
+
+    if(name == "regex_alphabet") {
+        regex_alphabet = value;
+        return true;
+    }
+
+    return false;
+}
+
+static const char *functype_names[NEAT::NUM_FUNCTYPES] = {
+    "sigmoid", "tanh", "relu", "gaussian", "sine", "step", "identity", "abs"
+};
+
+const char *NEAT::functype_name(NEAT::functype function) {
+    return functype_names[function];
+}
+
+bool NEAT::parse_functype(const std::string &name, NEAT::functype &function) {
+    for(int i = 0; i < NEAT::NUM_FUNCTYPES; i++) {
+        if(name == functype_names[i]) {
+            function = (NEAT::functype)i;
+            return true;
+        }
+    }
+    return false;
+}
+
 int NEAT::getUnitCount(const char *string, const char *set)
 {
 	int count = 0;
@@ -20,18 +20,18 @@
 
 namespace NEAT {
 
-	enum functype {
-		FT_SIGMOID = 0
-	};
-
 	enum mutator {
 		GAUSSIAN = 0,
 		COLDGAUSSIAN = 1
 	};
 
     enum class GeneticSearchType {PHASED, BLENDED, COMPLEXIFY};
-    enum class PopulationType {SPECIES};
-    enum class GenomeType {INNOV};
+    enum class PopulationType {SPECIES, PARETO};
+    enum class GenomeType {INNOV, HYPERNEAT};
+    enum class GenerationLogFormat {NONE, CSV, JSONL};
+    enum class StaticLoss {ABSOLUTE, SQUARED, CROSS_ENTROPY, ACCURACY};
+    enum class SelectionType {FITNESS, NOVELTY, BLENDED, PARETO};
+    enum class ActivationSchedule {CYCLES, SETTLE, FEEDFORWARD};
 
 //    extern const std::vector<nodetype> nodetypes;
 
@@ -41,6 +41,7 @@ namespace NEAT {
 
     struct NeatEnv {
         GeneticSearchType search_type = GeneticSearchType::PHASED;
+        SelectionType selection_type = SelectionType::FITNESS;
         PopulationType population_type = PopulationType::SPECIES;
         GenomeType genome_type = GenomeType::INNOV;
 
@@ -92,6 +93,35 @@ namespace NEAT {
         int newlink_tries = 20;  // Number of tries mutate_add_link will attempt to find an open link 
         int print_every = 1000; // Tells to print population to file every n generations 
         int num_runs = 1;
+        GenerationLogFormat generation_log_format = GenerationLogFormat::CSV; // Per-generation statistics written next to fittest_*
+        bool lineage_log = false; // Write how each organism was bred to lineage.log next to fittest_*, see LineageLog
+        std::string maze_map_path; // Map used by the maze experiment. Empty for res/maze.map
+        std::string substrate_path; // Substrate of HyperNEAT, see Substrate
+        int regex_max_len = 6; // Longest sentence generated by regex:<pattern> experiments
+        int regex_max_sentences = 256; // Sentences kept by regex:<pattern> experiments, half of them matching if possible
+        std::string regex_alphabet; // Symbols of regex:<pattern> sentences. Empty for those in the pattern
+        int cfg_max_len = 8; // Longest sentence generated by cfg:<productions> experiments
+        int cfg_max_sentences = 256; // Sentences kept by cfg:<productions> experiments, half of them grammatical if possible
+        int pole_max_steps = 100000; // Steps a pole balancing run must last to succeed
+        int env_episodes = 0; // Episodes per organism in environment experiments. 0 for the experiment's default
+        StaticLoss static_loss = StaticLoss::ABSOLUTE; // Per-output loss of static experiments
+        real_t static_tolerance = 0.05; // Per-output error static experiments count as none
+        real_t success_error = 0.0000001; // Error at or below which an organism solves the experiment
+        int novelty_k = 15; // Nearest neighbors whose mean distance is a behavior's novelty
+        int novelty_archive_add = 5; // Most novel behaviors of each generation added to the archive
+        real_t novelty_weight = 0.5; // Weight of novelty against fitness in blended selection
+        functype activation = FT_SIGMOID; // Activation function of new hidden and output nodes
+        real_t mutate_activation_prob = 0.0; // Prob. of changing the activation function of a hidden node
+        bool plastic = false; // Adapt link weights with hebbian() as networks activate, at the rates of the link traits
+        bool plastic_reset = true; // Restore the evolved weights of plastic networks at the start of each Test or episode
+        ActivationSchedule activation_schedule = ActivationSchedule::CYCLES; // How networks activate for each input, see CpuNetwork::activate()
+        int activation_cycles = NACTIVATES_PER_INPUT; // Cycles per input of the cycles schedule, and of recurrent networks under feedforward
+        real_t settle_epsilon = 0.0001; // Change of every noninput node under which the settle schedule stops
+        int settle_max_cycles = 100; // Most cycles per input of the settle schedule
+
+        // Sets a numeric, boolean or string parameter by its field name. Returns false if
+        // there is no such parameter or the value can't be parsed.
+        bool set_param(const std::string &name, const std::string &value);
     };
     extern NeatEnv *env;
 
@@ -121,6 +151,26 @@ namespace NEAT {
         return (1/(1+(exp(-(slope*activesum))))); //Compressed
     }
 
+    // Output of a node of the given function for the sum of its inputs.
+    inline real_t activation_function(functype function, real_t activesum) {
+        switch(function) {
+        case FT_SIGMOID: return fsigmoid(activesum, 4.924273, 2.4621365);
+        case FT_TANH: return tanh(activesum);
+        case FT_RELU: return activesum > 0 ? activesum : 0;
+        case FT_GAUSSIAN: return exp(-(activesum*activesum));
+        case FT_SINE: return sin(activesum);
+        case FT_STEP: return activesum > 0 ? 1 : 0;
+        case FT_IDENTITY: return activesum;
+        case FT_ABS: return fabs(activesum);
+        default: abort();
+        }
+    }
+
+    // Name of a function as the activation parameter takes it, e.g. "tanh".
+    const char *functype_name(functype function);
+    // Returns false if there's no function of that name.
+    bool parse_functype(const std::string &name, functype &function);
+
 	// Hebbian Adaptation Function
 	// Based on equations in Floreano & Urzelai 2000
 	// Takes the current weight, the maximum weight in the containing network,
@@ -16,6 +16,9 @@ namespace NEAT {
     #define NODES_MAX USHRT_MAX
     #define LINKS_MAX USHRT_MAX
 
+    // Default activation cycles per input, see NeatEnv::activation_cycles
+    #define NACTIVATES_PER_INPUT 10
+
     typedef unsigned short node_size_t;
     typedef unsigned short link_size_t;
 
@@ -26,6 +29,19 @@ namespace NEAT {
 		NT_HIDDEN = 3
 	};
 
+    // Activation function of a node. The values are those of the genome files.
+	enum functype {
+		FT_SIGMOID = 0,
+		FT_TANH = 1,
+		FT_RELU = 2,
+		FT_GAUSSIAN = 3,
+		FT_SINE = 4,
+		FT_STEP = 5,
+		FT_IDENTITY = 6,
+		FT_ABS = 7,
+		NUM_FUNCTYPES = 8
+	};
+
 #if __cplusplus < 199711L
     class OrganismEvaluation {
     public:
@@ -13,8 +13,6 @@
    See the License for the specific language governing permissions and
    limitations under the License.
 */
-#ifndef ENABLE_CUDA
-
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
 #include "cpunetwork.h"
 #include "neat.h"
@@ -25,9 +23,48 @@ using namespace NEAT;
 using namespace std;
 
 
+// With Cuda, CpuNetwork is still used for CPPNs and environments, but
+// Network::create() is in cudanetwork.cu.
+#ifndef ENABLE_CUDA
 Network *Network::create() {
     return new CpuNetwork();
 }
+#endif // ENABLE_CUDA
+
+// Orders the noninput nodes so each comes after the nodes linking into it.
+// Returns false if the links have a cycle.
+static bool topological_order(const NetDims &dims,
+                              const vector<NetLink> &links,
+                              vector<node_size_t> &order) {
+    vector<size_t> nincoming(dims.nnodes.all, 0);
+    vector<vector<node_size_t>> outgoing(dims.nnodes.all);
+    for(const NetLink &link: links) {
+        if(link.in_node_index >= dims.nnodes.input) {
+            nincoming[link.out_node_index]++;
+            outgoing[link.in_node_index].push_back(link.out_node_index);
+        }
+    }
+
+    vector<node_size_t> ready;
+    for(size_t i = dims.nnodes.all; i > dims.nnodes.input; i--) {
+        if(nincoming[i - 1] == 0) {
+            ready.push_back(i - 1);
+        }
+    }
+    order.clear();
+    while(!ready.empty()) {
+        node_size_t i = ready.back();
+        ready.pop_back();
+        order.push_back(i);
+        for(size_t k = outgoing[i].size(); k > 0; k--) {
+            node_size_t j = outgoing[i][k - 1];
+            if(--nincoming[j] == 0) {
+                ready.push_back(j);
+            }
+        }
+    }
+    return order.size() == dims.nnodes.noninput;
+}
 
 // Requires nodes to be sorted by type: BIAS, SENSOR, OUTPUT, HIDDEN
 void CpuNetwork::configure(const NetDims &dims_,
@@ -52,12 +89,38 @@ void CpuNetwork::configure(const NetDims &dims_,
     for(size_t i = dims.nnodes.bias; i < dims.nnodes.all; i++) {
         activations[i] = 0.0;
     }
+
+    feedforward = false;
+    if(env->activation_schedule == ActivationSchedule::FEEDFORWARD) {
+        feedforward = topological_order(dims, links, feedforward_order);
+    }
+
+    plastic = env->plastic;
+    if(plastic) {
+        evolved_weights.resize(dims.nlinks);
+        maxweight = 0.0;
+        for(size_t i = 0; i < dims.nlinks; i++) {
+            evolved_weights[i] = links[i].weight;
+            maxweight = max(maxweight, fabs(links[i].weight));
+        }
+    }
+}
+
+void CpuNetwork::reset_weights() {
+    if(plastic) {
+        for(size_t i = 0; i < dims.nlinks; i++) {
+            links[i].weight = evolved_weights[i];
+        }
+    }
 }
 
 void CpuNetwork::clear_noninput() {
     memset(activations.data() + dims.nnodes.input,
            0,
            sizeof(real_t) * (dims.nnodes.all - dims.nnodes.input));
+    if(env->plastic_reset) {
+        reset_weights();
+    }
 }
 
 void CpuNetwork::load_sensor(size_t isensor,
@@ -70,6 +133,41 @@ real_t *CpuNetwork::get_outputs() {
 }
 
 void CpuNetwork::activate(size_t ncycles) {
+    cycle(ncycles, 0.0);
+
+    if(plastic) {
+        adapt();
+    }
+}
+
+void CpuNetwork::activate() {
+    switch(env->activation_schedule) {
+    case ActivationSchedule::CYCLES:
+        cycle(env->activation_cycles, 0.0);
+        break;
+    case ActivationSchedule::SETTLE:
+        cycle(env->settle_max_cycles, env->settle_epsilon);
+        break;
+    case ActivationSchedule::FEEDFORWARD:
+        if(feedforward) {
+            single_pass();
+        } else {
+            cycle(env->activation_cycles, 0.0);
+        }
+        break;
+    default:
+        panic();
+    }
+
+    if(plastic) {
+        adapt();
+    }
+}
+
+// Runs max_cycles cycles, or stops after the first in which no noninput node
+// changed by epsilon or more. Hidden nodes count, since without clearing
+// between inputs a new input may only have reached them so far.
+void CpuNetwork::cycle(size_t max_cycles, real_t epsilon) {
     real_t* act_other = new real_t[dims.nnodes.all];
 
     //Copy only input activation state.
@@ -79,7 +177,7 @@ void CpuNetwork::activate(size_t ncycles) {
 
     real_t *act_curr = activations.data(), *act_new = act_other;
 
-    for(size_t icycle = 0; icycle < ncycles; icycle++) {
+    for(size_t icycle = 0; icycle < max_cycles; icycle++) {
 
         for(size_t i = dims.nnodes.input; i < dims.nnodes.all; i++) {
             NetNode &node = nodes[i];
@@ -91,12 +189,19 @@ void CpuNetwork::activate(size_t ncycles) {
 //                cout << "[ACT] from=" << (link.in_node_index+1) << ", to=" << (i+1) << ", weight=" << link.weight << ", act[from]=" << act_curr[link.in_node_index] << ", partial=" << link.weight * act_curr[link.in_node_index] << ", sum=" << sum << endl;
             }
 
-            act_new[i] = NEAT::fsigmoid(sum,
-                                        4.924273,
-                                        2.4621365);  //Sigmoidal activation- see comments under fsigmoid
+            act_new[i] = NEAT::activation_function(node.function, sum);
+        }
+
+        real_t change = 0.0;
+        for(size_t i = dims.nnodes.input; i < dims.nnodes.all; i++) {
+            change = max(change, fabs(act_new[i] - act_curr[i]));
         }
 
         std::swap(act_curr, act_new);
+
+        if(change < epsilon) {
+            break;
+        }
     }
 
     if(act_curr != activations.data()) {
@@ -109,6 +214,37 @@ void CpuNetwork::activate(size_t ncycles) {
     delete [] act_other;
 }
 
+// Activates each node once, after its sources, which is what enough cycles
+// would settle to.
+void CpuNetwork::single_pass() {
+    for(node_size_t i: feedforward_order) {
+        NetNode &node = nodes[i];
+
+        real_t sum = 0.0;
+        for(size_t j = node.incoming_start; j < node.incoming_end; j++) {
+            NetLink &link = links[j];
+            sum += link.weight * activations[link.in_node_index];
+        }
+
+        activations[i] = NEAT::activation_function(node.function, sum);
+    }
+}
+
+// Hebbian learning on every link, from the activations it connects once the
+// network has been activated.
+void CpuNetwork::adapt() {
+    for(size_t i = 0; i < dims.nlinks; i++) {
+        NetLink &link = links[i];
+        link.weight = NEAT::hebbian(link.weight,
+                                    maxweight,
+                                    activations[link.in_node_index],
+                                    activations[link.out_node_index],
+                                    link.hebb_rate,
+                                    link.pre_rate,
+                                    link.post_rate);
+    }
+}
+
 vector<real_t> &CpuNetwork::get_activations(__out vector<real_t> &result) {
     return result = activations;
 }
@@ -116,5 +252,3 @@ vector<real_t> &CpuNetwork::get_activations(__out vector<real_t> &result) {
 void CpuNetwork::set_activations(__in vector<real_t> &newacts) {
     activations = newacts;
 }
-
-#endif // ENABLE_CUDA
@@ -16,15 +16,34 @@ namespace NEAT {
 		std::vector<NetNode> nodes;
 		std::vector<NetLink> links;
         std::vector<real_t> activations;
+        // Set when env->plastic
+        bool plastic;
+        std::vector<real_t> evolved_weights;
+        real_t maxweight;
+        // Noninput nodes, each after its sources, when env->activation_schedule is
+        // FEEDFORWARD and the network has no cycles
+        std::vector<node_size_t> feedforward_order;
+        bool feedforward;
+
+        void cycle(size_t max_cycles, real_t epsilon);
+        void single_pass();
+        void adapt();
 
     public:
         CpuNetwork() {}
 		virtual ~CpuNetwork() {}
 
 		void activate(size_t ncycles);
+        // Activates as env->activation_schedule says: activation_cycles cycles,
+        // cycles until no noninput node changes by settle_epsilon (at most
+        // settle_max_cycles), or a single pass in topological order.
+        void activate();
+        // Restores the evolved weights of a plastic network.
+        void reset_weights();
         std::vector<real_t> &get_activations(__out std::vector<real_t> &result);
         void set_activations(__in std::vector<real_t> &newacts);
 
+        // Also resets the weights of plastic networks unless !env->plastic_reset.
         void clear_noninput();
         void load_sensor(size_t isensor, real_t activation);
         real_t *get_outputs();
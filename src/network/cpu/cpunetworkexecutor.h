@@ -9,6 +9,16 @@ namespace NEAT {
     //Don't need any special qualifiers for CPU
     #define __net_eval_decl
 
+    // Calls eval.behavior(out) for Evaluators that characterize behavior.
+    template<typename Evaluator>
+    inline auto write_behavior(Evaluator &eval, real_t *out, int)
+        -> decltype(eval.behavior(out), void()) {
+        eval.behavior(out);
+    }
+    template<typename Evaluator>
+    inline void write_behavior(Evaluator &, real_t *, long) {
+    }
+
     //---
     //--- CLASS CpuNetworkExecutor
     //---
@@ -35,6 +45,14 @@ namespace NEAT {
         virtual void execute(class Network **nets_,
                              OrganismEvaluation *results,
                              size_t nnets) {
+            execute(nets_, results, nullptr, 0, nnets);
+        }
+
+        virtual void execute(class Network **nets_,
+                             OrganismEvaluation *results,
+                             real_t *behaviors,
+                             size_t behavior_size,
+                             size_t nnets) {
 
             CpuNetwork **nets = (CpuNetwork **)nets_;
             node_size_t nsensors = nets[0]->get_dims().nnodes.sensor;
@@ -51,11 +69,14 @@ namespace NEAT {
                     for(node_size_t isensor = 0; isensor < nsensors; isensor++) {
                         net->load_sensor(isensor, eval.get_sensor(isensor));
                     }
-                    net->activate(NACTIVATES_PER_INPUT);
+                    net->activate();
                     eval.evaluate(net->get_outputs());
                 }
 
                 results[inet] = eval.result();
+                if(behaviors) {
+                    write_behavior(eval, behaviors + inet * behavior_size, 0);
+                }
             }
         }
         
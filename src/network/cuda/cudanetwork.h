@@ -133,6 +133,9 @@ namespace NEAT {
             static_cast<NetDims &>(dims) = dims_;
 
             require(dims.nlinks < Max_Links);
+            for(node_size_t i = dims.nnodes.input; i < dims.nnodes.all; i++) {
+                errif(nodes[i].function != FT_SIGMOID, "Only sigmoid activation is supported on Cuda");
+            }
 
             partitions.clear();
             gpu_links.resize(dims.nlinks);
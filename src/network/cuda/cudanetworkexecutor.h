@@ -4,6 +4,7 @@
 #define CUDANETWORKEXECUTOR_H__
 
 #include "networkexecutor.h"
+#include "neat.h"
 #include "cudanetworkbatch.h"
 
 namespace NEAT {
@@ -55,10 +56,19 @@ namespace NEAT {
                 batches[ibatch]->activate(nets + inet,
                                           results + inet,
                                           n,
-                                          NACTIVATES_PER_INPUT);
+                                          env->activation_cycles);
             }
             
         }
+
+        virtual void execute(class Network **nets_,
+                             OrganismEvaluation *results,
+                             real_t *behaviors,
+                             size_t behavior_size,
+                             size_t nnets) {
+            errif(behaviors, "Behavior characterization isn't supported on Cuda");
+            execute(nets_, results, nnets);
+        }
         
     };
 
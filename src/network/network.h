@@ -47,6 +47,10 @@ namespace NEAT {
 		real_t weight; // Weight of connection
         node_size_t in_node_index; // NetNode inputting into the link
         node_size_t out_node_index; // NetNode gaining energy from the link
+        // Learning rates of hebbian(), from the first params of the link's trait
+        real_t hebb_rate;
+        real_t pre_rate;
+        real_t post_rate;
 	};
 
     //---
@@ -55,6 +59,7 @@ namespace NEAT {
 	struct NetNode {
         link_size_t incoming_start;
         link_size_t incoming_end;
+        functype function;
 	};
 
     //---
@@ -85,6 +90,21 @@ namespace NEAT {
         virtual void execute(class Network **nets_,
                              class OrganismEvaluation *results,
                              size_t nnets) = 0;
+
+        // Number of values in each network's behavior characterization, or 0
+        // if the evaluator doesn't characterize behavior.
+        virtual size_t get_behavior_size() {
+            return 0;
+        }
+
+        // Like execute, also writing each network's behavior characterization
+        // to behaviors + inet * get_behavior_size().
+        virtual void execute_with_behavior(class Network **nets_,
+                                           class OrganismEvaluation *results,
+                                           real_t *behaviors,
+                                           size_t nnets) {
+            execute(nets_, results, nnets);
+        }
     };
 
 } // namespace NEAT
@@ -3,9 +3,9 @@
 #ifndef NETWORKEXECUTOR_H__
 #define NETWORKEXECUTOR_H__
 
-namespace NEAT {
+#include "neattypes.h"
 
-#define NACTIVATES_PER_INPUT 10
+namespace NEAT {
 
     //---
     //--- CLASS NetworkExecutor<>
@@ -23,6 +23,15 @@ namespace NEAT {
         virtual void execute(class Network **nets_,
                              class OrganismEvaluation *results,
                              size_t nnets) = 0;
+
+        // Like execute, also having each network's Evaluator write its behavior
+        // characterization to behaviors + inet * behavior_size. Only for
+        // Evaluators with a behavior(real_t *) method.
+        virtual void execute(class Network **nets_,
+                             class OrganismEvaluation *results,
+                             real_t *behaviors,
+                             size_t behavior_size,
+                             size_t nnets) = 0;
     };
 
 }
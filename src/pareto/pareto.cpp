@@ -0,0 +1,116 @@
+#include "std.h" // Must be included first. Precompiled header with standard library includes.
+
+#include "pareto.h"
+#include "organism.h"
+
+using namespace NEAT;
+using namespace std;
+
+namespace NEAT {
+
+    Objectives Objectives::of(Organism &org) {
+        Genome::Stats stats = org.genome->get_stats();
+        return {org.eval.fitness, stats.nnodes, stats.nlinks};
+    }
+
+    bool dominates(const Objectives &a, const Objectives &b) {
+        if( (a.fitness < b.fitness) || (a.nnodes > b.nnodes) || (a.nlinks > b.nlinks) ) {
+            return false;
+        }
+        return (a.fitness > b.fitness) || (a.nnodes < b.nnodes) || (a.nlinks < b.nlinks);
+    }
+
+    vector<int> nondominated_ranks(const vector<Objectives> &objs) {
+        size_t n = objs.size();
+        vector<vector<size_t>> dominated(n);
+        vector<size_t> ndominators(n, 0);
+
+        for(size_t i = 0; i < n; i++) {
+            for(size_t j = 0; j < n; j++) {
+                if(dominates(objs[i], objs[j])) {
+                    dominated[i].push_back(j);
+                } else if(dominates(objs[j], objs[i])) {
+                    ndominators[i]++;
+                }
+            }
+        }
+
+        vector<int> ranks(n, -1);
+        vector<size_t> front;
+        for(size_t i = 0; i < n; i++) {
+            if(ndominators[i] == 0) {
+                front.push_back(i);
+            }
+        }
+        for(int rank = 0; !front.empty(); rank++) {
+            vector<size_t> next;
+            for(size_t i: front) {
+                ranks[i] = rank;
+                for(size_t j: dominated[i]) {
+                    if(--ndominators[j] == 0) {
+                        next.push_back(j);
+                    }
+                }
+            }
+            front.swap(next);
+        }
+        return ranks;
+    }
+
+    vector<real_t> crowding_distances(const vector<Objectives> &objs,
+                                      const vector<int> &ranks) {
+        size_t n = objs.size();
+        vector<real_t> result(n, 0.0);
+        if(n == 0) {
+            return result;
+        }
+
+        function<real_t (size_t)> objective[] = {
+            [&objs] (size_t i) { return objs[i].fitness; },
+            [&objs] (size_t i) { return real_t(objs[i].nnodes); },
+            [&objs] (size_t i) { return real_t(objs[i].nlinks); }
+        };
+
+        int maxrank = *max_element(ranks.begin(), ranks.end());
+        for(int rank = 0; rank <= maxrank; rank++) {
+            vector<size_t> front;
+            for(size_t i = 0; i < n; i++) {
+                if(ranks[i] == rank) {
+                    front.push_back(i);
+                }
+            }
+
+            for(auto &f: objective) {
+                stable_sort(front.begin(), front.end(), [&f] (size_t a, size_t b) {
+                        return f(a) < f(b);
+                    });
+                real_t lo = f(front.front());
+                real_t hi = f(front.back());
+                result[front.front()] = result[front.back()] = numeric_limits<real_t>::infinity();
+                if(hi == lo) {
+                    continue;
+                }
+                for(size_t k = 1; k + 1 < front.size(); k++) {
+                    result[front[k]] += (f(front[k + 1]) - f(front[k - 1])) / (hi - lo);
+                }
+            }
+        }
+        return result;
+    }
+
+    vector<size_t> nsga2_order(const vector<int> &ranks,
+                               const vector<real_t> &crowding) {
+        vector<size_t> order(ranks.size());
+        for(size_t i = 0; i < order.size(); i++) {
+            order[i] = i;
+        }
+        stable_sort(order.begin(), order.end(), [&] (size_t a, size_t b) {
+                if(ranks[a] != ranks[b]) {
+                    return ranks[a] < ranks[b];
+                }
+                return crowding[a] > crowding[b];
+            });
+        return order;
+    }
+
+}
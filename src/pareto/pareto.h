@@ -0,0 +1,40 @@
+#pragma once
+
+#ifndef PARETO_H__
+#define PARETO_H__
+
+#include "neattypes.h"
+
+namespace NEAT {
+
+    // What multi-objective selection trades off: fitness is maximized,
+    // network size minimized.
+    struct Objectives {
+        real_t fitness;
+        size_t nnodes;
+        size_t nlinks;
+
+        static Objectives of(class Organism &org);
+    };
+
+    // Whether a is at least as good as b in every objective and better in one.
+    bool dominates(const Objectives &a, const Objectives &b);
+
+    // Rank of each in NSGA-II non-dominated sorting: 0 for the Pareto front,
+    // 1 for the front once those are removed, and so on.
+    std::vector<int> nondominated_ranks(const std::vector<Objectives> &objs);
+
+    // NSGA-II crowding distance of each within its rank: the sum over
+    // objectives of the normalized gap between its neighbors, infinite at the
+    // extremes of a front.
+    std::vector<real_t> crowding_distances(const std::vector<Objectives> &objs,
+                                           const std::vector<int> &ranks);
+
+    // Indices sorted best first: by rank, then by decreasing crowding distance,
+    // then by index.
+    std::vector<size_t> nsga2_order(const std::vector<int> &ranks,
+                                    const std::vector<real_t> &crowding);
+
+}
+
+#endif // #ifndef PARETO_H__
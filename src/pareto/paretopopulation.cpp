@@ -0,0 +1,117 @@
+#include "std.h" // Must be included first. Precompiled header with standard library includes.
+
+#include "paretopopulation.h"
+#include "genomemanager.h"
+#include "pareto.h"
+#include "timer.h"
+#include "util.h"
+
+using namespace NEAT;
+using namespace std;
+
+ParetoPopulation::ParetoPopulation(rng_t rng,
+                                   vector<unique_ptr<Genome>> &seeds)
+    : norgs(seeds.size())
+    , generation(0)
+    , orgs(rng, seeds, seeds.size())
+    , highest_fitness(0.0) {
+
+    orgs.init_phenotypes();
+}
+
+ParetoPopulation::~ParetoPopulation() {
+}
+
+size_t ParetoPopulation::size() {
+    return norgs;
+}
+
+Organism *ParetoPopulation::get(size_t index) {
+    return &orgs.curr()[index];
+}
+
+unique_ptr<Organism> ParetoPopulation::make_copy(size_t index) {
+    return unique_ptr<Organism>(new Organism(*get(index)));
+}
+
+void ParetoPopulation::verify() {
+    for(auto &org: orgs.curr())
+        org.genome->verify();
+}
+
+vector<size_t> ParetoPopulation::get_species_sizes() {
+    return {norgs};
+}
+
+void ParetoPopulation::write(std::ostream& out) {
+    for(auto &org: orgs.curr())
+        org.write(out);
+}
+
+void ParetoPopulation::next_generation() {
+    generation++;
+
+    vector<Objectives> objs;
+    for(Organism &org: orgs.curr()) {
+        objs.push_back(Objectives::of(org));
+    }
+    vector<int> ranks = nondominated_ranks(objs);
+    vector<real_t> crowding = crowding_distances(objs, ranks);
+    vector<size_t> order = nsga2_order(ranks, crowding);
+
+    bool new_highest_fitness = false;
+    for(const Objectives &o: objs) {
+        if(o.fitness > highest_fitness) {
+            highest_fitness = o.fitness;
+            new_highest_fitness = true;
+        }
+    }
+
+    size_t nsurvivors = max(size_t(1), norgs / 2);
+    size_t nfront = count(ranks.begin(), ranks.end(), 0);
+    cout << "Generation " << generation << ": pareto front of " << nfront << endl;
+
+    orgs.next_generation(generation);
+
+    {
+        static Timer timer("reproduce");
+        timer.start();
+
+        vector<Organism> &parents = orgs.prev();
+
+        // Of two random survivors, the one NSGA-II ranks higher.
+        auto tournament = [&] (rng_t &rng) -> Organism & {
+            size_t a = rng.integer(0, int(nsurvivors) - 1);
+            size_t b = rng.integer(0, int(nsurvivors) - 1);
+            return parents[order[min(a, b)]];
+        };
+
+#pragma omp parallel for
+        for(size_t iorg = 0; iorg < norgs; iorg++) {
+            Organism &baby = orgs.curr()[iorg];
+            Genome &new_genome = *baby.genome;
+            rng_t &rng = baby.genome->rng;
+
+            if(iorg < nsurvivors) {
+                env->genome_manager->clone(*parents[order[iorg]].genome, new_genome);
+            } else if(rng.prob() < env->mutate_only_prob) {
+                env->genome_manager->clone(*tournament(rng).genome, new_genome);
+                env->genome_manager->mutate(new_genome);
+            } else {
+                Organism &mom = tournament(rng);
+                Organism &dad = tournament(rng);
+                env->genome_manager->mate(*mom.genome,
+                                          *dad.genome,
+                                          new_genome,
+                                          mom.eval.fitness,
+                                          dad.eval.fitness);
+            }
+        }
+
+        env->genome_manager->finalize_generation(new_highest_fitness);
+
+        timer.stop();
+    }
+
+    orgs.init_phenotypes();
+}
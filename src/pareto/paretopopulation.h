@@ -0,0 +1,45 @@
+#pragma once
+
+#ifndef PARETOPOPULATION_H__
+#define PARETOPOPULATION_H__
+
+#include "organismsbuffer.h"
+#include "population.h"
+
+namespace NEAT {
+
+    //---
+    //--- CLASS ParetoPopulation
+    //---
+    //--- NSGA-II selection on fitness, nnodes and nlinks instead of speciation.
+    //--- The better half of each generation by non-dominated rank and crowding
+    //--- distance survives unchanged, and the other half are their offspring,
+    //--- with parents picked by binary tournament.
+    //---
+    class ParetoPopulation : public Population {
+    public:
+        ParetoPopulation(rng_t rng,
+                         std::vector<std::unique_ptr<Genome>> &seeds);
+        virtual ~ParetoPopulation();
+
+        virtual size_t size() override;
+        virtual class Organism *get(size_t index) override;
+        virtual std::unique_ptr<Organism> make_copy(size_t index) override;
+
+        virtual void next_generation() override;
+        virtual void verify() override;
+
+        virtual std::vector<size_t> get_species_sizes() override;
+
+        virtual void write(std::ostream& out) override;
+
+    private:
+        size_t norgs;
+        int generation;
+        OrganismsBuffer<Organism> orgs;
+        real_t highest_fitness;
+    };
+
+} // namespace NEAT
+
+#endif // #ifndef PARETOPOPULATION_H__
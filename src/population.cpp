@@ -14,6 +14,7 @@
    limitations under the License.
 */
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
+#include "paretopopulation.h"
 #include "population.h"
 #include "speciespopulation.h"
 #include "util.h"
@@ -31,6 +32,9 @@ Population *Population::create(rng_t rng,
     case PopulationType::SPECIES:
         result = new SpeciesPopulation(rng, seeds);
         break;
+    case PopulationType::PARETO:
+        result = new ParetoPopulation(rng, seeds);
+        break;
     default:
         panic();
     }
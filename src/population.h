@@ -38,6 +38,10 @@ namespace NEAT {
 		virtual void next_generation() = 0;
 		virtual void verify() = 0;
 
+        // Number of organisms in each species, or a single entry for populations
+        // that aren't speciated.
+        virtual std::vector<size_t> get_species_sizes() = 0;
+
 		virtual void write(std::ostream& out) = 0;
     };
 
@@ -69,6 +69,13 @@ void SpeciesPopulation::verify() {
         org.genome->verify();
 } 
 
+vector<size_t> SpeciesPopulation::get_species_sizes() {
+    vector<size_t> result;
+    for(Species *s: species)
+        result.push_back(s->organisms.size());
+    return result;
+}
+
 void SpeciesPopulation::spawn() {
     orgs.init_phenotypes();
 
@@ -39,6 +39,8 @@ namespace NEAT {
 		virtual void next_generation() override;
 		virtual void verify() override;
 
+        virtual std::vector<size_t> get_species_sizes() override;
+
 		virtual void write(std::ostream& out) override;
 
     private:
@@ -0,0 +1,112 @@
+#pragma once
+
+#ifndef UTIL_GENERATIONLOG_H__
+#define UTIL_GENERATIONLOG_H__
+
+#include "neat.h"
+
+namespace NEAT {
+
+    // Population statistics for a single generation of a run.
+    struct GenerationStats {
+        int generation;
+        real_t best_fitness;
+        real_t mean_fitness;
+        real_t median_fitness;
+        real_t best_error;
+        real_t mean_error;
+        real_t median_error;
+        std::vector<size_t> species_sizes;
+        double mean_nnodes;
+        double mean_nlinks;
+        std::string search_phase;
+        double epoch_time;
+        double evaluate_time;
+    };
+
+    // Writes one line per generation to generations.csv or generations.jsonl.
+    class GenerationLog {
+        GenerationLogFormat format;
+        std::ofstream out;
+
+    public:
+        static const char *get_filename(GenerationLogFormat format) {
+            switch(format) {
+            case GenerationLogFormat::CSV:
+                return "generations.csv";
+            case GenerationLogFormat::JSONL:
+                return "generations.jsonl";
+            default:
+                return nullptr;
+            }
+        }
+
+        GenerationLog(GenerationLogFormat format_, const std::string &dir_path)
+            : format(format_) {
+            const char *filename = get_filename(format);
+            if(!filename)
+                return;
+
+            out.open(dir_path + "/" + filename);
+            out.precision(17);
+            if(format == GenerationLogFormat::CSV) {
+                out << "generation,best_fitness,mean_fitness,median_fitness,"
+                    << "best_error,mean_error,median_error,"
+                    << "nspecies,species_sizes,mean_nnodes,mean_nlinks,"
+                    << "search_phase,epoch_time,evaluate_time" << std::endl;
+            }
+        }
+
+        void write(const GenerationStats &s) {
+            switch(format) {
+            case GenerationLogFormat::CSV:
+                out << s.generation
+                    << "," << s.best_fitness
+                    << "," << s.mean_fitness
+                    << "," << s.median_fitness
+                    << "," << s.best_error
+                    << "," << s.mean_error
+                    << "," << s.median_error
+                    << "," << s.species_sizes.size()
+                    << ",";
+                for(size_t i = 0; i < s.species_sizes.size(); i++) {
+                    if(i != 0) out << ";";
+                    out << s.species_sizes[i];
+                }
+                out << "," << s.mean_nnodes
+                    << "," << s.mean_nlinks
+                    << "," << s.search_phase
+                    << "," << s.epoch_time
+                    << "," << s.evaluate_time
+                    << std::endl;
+                break;
+            case GenerationLogFormat::JSONL:
+                out << "{\"generation\":" << s.generation
+                    << ",\"best_fitness\":" << s.best_fitness
+                    << ",\"mean_fitness\":" << s.mean_fitness
+                    << ",\"median_fitness\":" << s.median_fitness
+                    << ",\"best_error\":" << s.best_error
+                    << ",\"mean_error\":" << s.mean_error
+                    << ",\"median_error\":" << s.median_error
+                    << ",\"nspecies\":" << s.species_sizes.size()
+                    << ",\"species_sizes\":[";
+                for(size_t i = 0; i < s.species_sizes.size(); i++) {
+                    if(i != 0) out << ",";
+                    out << s.species_sizes[i];
+                }
+                out << "],\"mean_nnodes\":" << s.mean_nnodes
+                    << ",\"mean_nlinks\":" << s.mean_nlinks
+                    << ",\"search_phase\":\"" << s.search_phase << "\""
+                    << ",\"epoch_time\":" << s.epoch_time
+                    << ",\"evaluate_time\":" << s.evaluate_time
+                    << "}" << std::endl;
+                break;
+            default:
+                break;
+            }
+        }
+    };
+
+}
+
+#endif // #ifndef UTIL_GENERATIONLOG_H__
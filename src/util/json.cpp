@@ -0,0 +1,245 @@
+#include "std.h" // Must be included first. Precompiled header with standard library includes.
+
+#include "json.h"
+
+#include <iomanip>
+
+using namespace NEAT;
+using namespace std;
+
+namespace {
+
+    class Parser {
+        const string &text;
+        size_t pos = 0;
+
+        [[noreturn]] void fail(const string &what) {
+            throw runtime_error("invalid JSON at offset " + to_string(pos) + ": " + what);
+        }
+
+        void skip_ws() {
+            while(pos < text.size() && isspace((unsigned char)text[pos])) {
+                pos++;
+            }
+        }
+
+        bool consume(const char *literal) {
+            size_t len = strlen(literal);
+            if(text.compare(pos, len, literal) == 0) {
+                pos += len;
+                return true;
+            }
+            return false;
+        }
+
+        void expect(char c) {
+            skip_ws();
+            if(pos >= text.size() || text[pos] != c) {
+                fail(string("expected '") + c + "'");
+            }
+            pos++;
+        }
+
+        string parse_string() {
+            expect('"');
+            string s;
+            while(pos < text.size() && text[pos] != '"') {
+                char c = text[pos++];
+                if(c != '\\') {
+                    s += c;
+                    continue;
+                }
+                if(pos >= text.size()) {
+                    break;
+                }
+                c = text[pos++];
+                switch(c) {
+                case 'n': s += '\n'; break;
+                case 't': s += '\t'; break;
+                case 'r': s += '\r'; break;
+                case 'b': s += '\b'; break;
+                case 'f': s += '\f'; break;
+                case 'u': {
+                    if(pos + 4 > text.size()) {
+                        fail("truncated \\u escape");
+                    }
+                    unsigned code = stoul(text.substr(pos, 4), nullptr, 16);
+                    pos += 4;
+                    // Only the escapes we'd write ourselves, i.e. control characters.
+                    if(code > 0x7f) {
+                        fail("non-ASCII \\u escape");
+                    }
+                    s += char(code);
+                } break;
+                default: s += c; break;
+                }
+            }
+            expect('"');
+            return s;
+        }
+
+    public:
+        Parser(const string &text_) : text(text_) {}
+
+        JsonValue parse_value() {
+            JsonValue v;
+            skip_ws();
+            if(pos >= text.size()) {
+                fail("unexpected end");
+            }
+            char c = text[pos];
+            if(c == '{') {
+                v.type = JsonValue::Object;
+                pos++;
+                skip_ws();
+                if(pos < text.size() && text[pos] == '}') {
+                    pos++;
+                    return v;
+                }
+                for(;;) {
+                    string key = parse_string();
+                    expect(':');
+                    v.object[key] = parse_value();
+                    skip_ws();
+                    if(pos < text.size() && text[pos] == ',') {
+                        pos++;
+                        continue;
+                    }
+                    expect('}');
+                    return v;
+                }
+            } else if(c == '[') {
+                v.type = JsonValue::Array;
+                pos++;
+                skip_ws();
+                if(pos < text.size() && text[pos] == ']') {
+                    pos++;
+                    return v;
+                }
+                for(;;) {
+                    v.array.push_back(parse_value());
+                    skip_ws();
+                    if(pos < text.size() && text[pos] == ',') {
+                        pos++;
+                        continue;
+                    }
+                    expect(']');
+                    return v;
+                }
+            } else if(c == '"') {
+                v.type = JsonValue::String;
+                v.str = parse_string();
+            } else if(consume("true")) {
+                v.type = JsonValue::Bool;
+                v.boolean = true;
+            } else if(consume("false")) {
+                v.type = JsonValue::Bool;
+            } else if(consume("null")) {
+                v.type = JsonValue::Null;
+            } else {
+                const char *start = text.c_str() + pos;
+                char *end;
+                v.type = JsonValue::Number;
+                v.number = strtod(start, &end);
+                if(end == start) {
+                    fail("unexpected character");
+                }
+                pos += end - start;
+            }
+            return v;
+        }
+
+        void finish() {
+            skip_ws();
+            if(pos != text.size()) {
+                fail("trailing characters");
+            }
+        }
+    };
+
+    [[noreturn]] void type_error(const char *expected) {
+        throw runtime_error(string("JSON value isn't ") + expected);
+    }
+
+}
+
+namespace NEAT {
+
+    JsonValue JsonValue::parse(const string &text) {
+        Parser parser(text);
+        JsonValue v = parser.parse_value();
+        parser.finish();
+        return v;
+    }
+
+    bool JsonValue::as_bool() const {
+        if(type != Bool) type_error("a boolean");
+        return boolean;
+    }
+
+    double JsonValue::as_number() const {
+        if(type != Number) type_error("a number");
+        return number;
+    }
+
+    const string &JsonValue::as_string() const {
+        if(type != String) type_error("a string");
+        return str;
+    }
+
+    const vector<JsonValue> &JsonValue::as_array() const {
+        if(type != Array) type_error("an array");
+        return array;
+    }
+
+    const JsonValue &JsonValue::operator[](const std::string &key) const {
+        if(type != Object) type_error("an object");
+        auto it = object.find(key);
+        if(it == object.end()) {
+            throw runtime_error("JSON object has no \"" + key + "\"");
+        }
+        return it->second;
+    }
+
+    bool JsonValue::has(const std::string &key) const {
+        return type == Object && object.find(key) != object.end();
+    }
+
+    vector<real_t> JsonValue::as_reals() const {
+        vector<real_t> result;
+        for(const JsonValue &v: as_array()) {
+            result.push_back(v.as_number());
+        }
+        return result;
+    }
+
+    string json_quote(const string &s) {
+        ostringstream out;
+        out << '"';
+        for(char c: s) {
+            if(c == '"' || c == '\\') {
+                out << '\\' << c;
+            } else if((unsigned char)c < 0x20) {
+                char buf[8];
+                sprintf(buf, "\\u%04x", c);
+                out << buf;
+            } else {
+                out << c;
+            }
+        }
+        out << '"';
+        return out.str();
+    }
+
+    string json_reals(const vector<real_t> &values) {
+        ostringstream out;
+        out << setprecision(17) << '[';
+        for(size_t i = 0; i < values.size(); i++) {
+            if(i > 0) out << ',';
+            out << values[i];
+        }
+        out << ']';
+        return out.str();
+    }
+
+}
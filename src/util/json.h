@@ -0,0 +1,48 @@
+#pragma once
+
+#ifndef UTIL_JSON_H__
+#define UTIL_JSON_H__
+
+#include "neattypes.h"
+
+namespace NEAT {
+
+    //---
+    //--- CLASS JsonValue
+    //---
+    //--- Just enough JSON for line-delimited protocols: parsing throws
+    //--- std::runtime_error on malformed input, and the accessors throw on a
+    //--- value of the wrong type or a missing key.
+    //---
+    class JsonValue {
+    public:
+        enum Type { Null, Bool, Number, String, Array, Object };
+
+        Type type = Null;
+        bool boolean = false;
+        double number = 0.0;
+        std::string str;
+        std::vector<JsonValue> array;
+        std::map<std::string, JsonValue> object;
+
+        static JsonValue parse(const std::string &text);
+
+        bool as_bool() const;
+        double as_number() const;
+        const std::string &as_string() const;
+        const std::vector<JsonValue> &as_array() const;
+        const JsonValue &operator[](const std::string &key) const;
+        bool has(const std::string &key) const;
+
+        std::vector<real_t> as_reals() const;
+    };
+
+    // JSON string literal for s, quoted and escaped.
+    std::string json_quote(const std::string &s);
+
+    // JSON array of the values, printed so they parse back exactly.
+    std::string json_reals(const std::vector<real_t> &values);
+
+}
+
+#endif // #ifndef UTIL_JSON_H__
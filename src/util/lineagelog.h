@@ -0,0 +1,74 @@
+#pragma once
+
+#ifndef UTIL_LINEAGELOG_H__
+#define UTIL_LINEAGELOG_H__
+
+#include "genome.h"
+#include "organism.h"
+#include "population.h"
+
+namespace NEAT {
+
+    // Writes lineage.log, which records how every organism of a run was bred.
+    // Each generation starts with "g <generation>", followed by a line per
+    // organism in population order:
+    //
+    //   <operation> <parents> <mutations> <innovations>
+    //
+    // operation is a Lineage::Operation code, followed by the population
+    // indices of the parents in the previous generation (Lineage::parents):
+    // none for seeds (s), one for clones (c), two for matings (m, a).
+    // mutations are the Lineage::Mutation codes in the order applied, e.g.
+    // "wL", and innovations are comma-separated, "<innovation_num>" for a new
+    // link and "<node_id>:<innovation_num1>:<innovation_num2>" for a new node.
+    // Either is "-" if empty. Once a generation is evaluated, "f <index>" follows if it
+    // holds the new fittest organism of the run.
+    class LineageLog {
+        std::ofstream out;
+
+    public:
+        LineageLog(bool enabled, const std::string &dir_path) {
+            if(enabled) {
+                out.open(dir_path + "/lineage.log");
+            }
+        }
+
+        void write_generation(int generation, Population *pop) {
+            if(!out.is_open())
+                return;
+
+            out << "g " << generation << "\n";
+            for(size_t i = 0; i < pop->size(); i++) {
+                const Lineage &l = pop->get(i)->genome->lineage;
+                out << char(l.operation);
+                for(int parent: l.parents) {
+                    out << " " << parent;
+                }
+                out << " " << (l.mutations.empty() ? "-" : l.mutations) << " ";
+                if(l.innovations.empty()) {
+                    out << "-";
+                }
+                for(size_t j = 0; j < l.innovations.size(); j++) {
+                    const Lineage::NewInnovation &innov = l.innovations[j];
+                    if(j != 0) out << ",";
+                    if(innov.node_id == -1) {
+                        out << innov.innovation_num1;
+                    } else {
+                        out << innov.node_id << ":" << innov.innovation_num1 << ":" << innov.innovation_num2;
+                    }
+                }
+                out << "\n";
+            }
+        }
+
+        void write_fittest(size_t population_index) {
+            if(!out.is_open())
+                return;
+
+            out << "f " << population_index << std::endl;
+        }
+    };
+
+}
+
+#endif // #ifndef UTIL_LINEAGELOG_H__
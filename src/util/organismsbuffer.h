@@ -3,6 +3,7 @@
 #ifndef ORGANISMSBUFFER_H__
 #define ORGANISMSBUFFER_H__
 
+#include "genomemanager.h"
 #include "organism.h"
 #include "rng.h"
 #include <assert.h>
@@ -49,7 +50,7 @@ namespace NEAT {
 #pragma omp parallel for
             for(size_t i = 0; i < _n; i++) {
                 Organism &org = curr()[i];
-                org.genome->init_phenotype(*org.net);
+                env->genome_manager->init_phenotype(*org.genome, *org.net);
             }
         }
 
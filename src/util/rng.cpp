@@ -1,6 +1,5 @@
 #include "std.h" // Must be included first. Precompiled header with standard library includes.
 #include "rng.h"
-#include <assert.h>
 
 using namespace NEAT;
 using namespace std;
@@ -9,6 +8,13 @@ static bool equals(real_t x, real_t y) {
     return abs((x) - (y)) / real_t(y) < 0.01;
 }
 
+// Unlike assert(), also checks in NDEBUG builds.
+#define assert_true(x)                                                  \
+    if( !(x) ) {                                                        \
+        cerr << __FILE__ << ":" << __LINE__ << ": " << #x << " failed" << endl; \
+        exit(1);                                                        \
+    }
+
 #define assert_equals(x, y)                                             \
     if( !equals(x,y) ) {                                                \
         cerr << __FILE__ << ":" << __LINE__ << ": " << x << " != " << y << endl; \
@@ -30,6 +36,40 @@ static bool equals(real_t x, real_t y) {
     }
 
 void rng_t::test() {
+    // reference sequences, also pinned by crates/accneat/src/rng.rs
+    {
+        rng_t rng(1);
+        assert_true(rng.next() == 12966619160104079557ULL);
+        assert_true(rng.next() == 9600361134598540522ULL);
+
+        rng.seed(-7);
+        int integers[] = {-1151077694, -1213309988, 603072185, -1944330335};
+        for(int x: integers) {
+            assert_true(rng.integer() == x);
+        }
+        int small[] = {2, -3, 0, -3};
+        for(int x: small) {
+            assert_true(rng.integer(-3, 3) == x);
+        }
+        assert_true(rng.prob() == 0.14553387957395914);
+        assert_true(rng.prob() == 0.2205774574239152);
+        assert_true(rng.prob() == 0.29465762425814934);
+        assert_true(rng.gauss() == 0.7709155203050004);
+        assert_true(rng.gauss() == -0.5863558202826218);
+        assert_true(rng.gauss() == 1.0482122495370934);
+        assert_true(rng.posneg() == -1);
+        assert_true(rng.posneg() == -1);
+        assert_true(rng.posneg() == 1);
+        vector<int> v(10);
+        assert_true(rng.index(v, 3) == 5);
+        assert_true(rng.index(v, 3) == 3);
+
+        // How OrganismsBuffer seeds organisms
+        rng_t pop(42), org;
+        org.seed(pop.integer());
+        assert_true(org.prob() == 0.9133897051023544);
+    }
+
     // seed
     {
         rng_t rng1;
@@ -83,7 +123,7 @@ void rng_t::test() {
         for(size_t i = 0; i < N; i++) {
             real_t x = rng.prob();
             
-            assert(x >= 0.0 && x <= 1.0);
+            assert_true(x >= 0.0 && x < 1.0);
 
             size_t bin = min(x * NBINS, real_t(NBINS - 1));
 
@@ -105,7 +145,7 @@ void rng_t::test() {
         for(size_t i = 0; i < N; i++) {
             int x = rng.posneg();
 
-            assert(x == 1 || x == -1);
+            assert_true(x == 1 || x == -1);
 
             count[(x + 1) / 2]++;
         }
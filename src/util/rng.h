@@ -10,25 +10,70 @@
 
 namespace NEAT {
 
+    //---
+    //--- CLASS rng_t
+    //---
+    //--- xoshiro256** seeded through SplitMix64, with distributions spelled out
+    //--- below rather than taken from <random>, whose engines and distributions
+    //--- differ between standard libraries. crates/accneat/src/rng.rs does the
+    //--- same, so a seed gives the same sequence on every platform and in Rust.
+    //---
+    //--- Derived rngs (of runs, seed genomes and organisms) are seeded with
+    //--- integer() of their parent.
+    //---
     class rng_t {
-        std::default_random_engine engine;
+        uint64_t state[4];
+
+        static uint64_t rotl(uint64_t x, int k) {
+            return (x << k) | (x >> (64 - k));
+        }
 
     public:
         rng_t() {
+            seed(1);
         }
 
-        rng_t(int seedval) {
+        rng_t(uint64_t seedval) {
             seed(seedval);
         }
 
-        void seed(int seedval) {
-            engine.seed(seedval);
+        // An int seed is sign-extended, e.g. -1 is 0xffffffffffffffff.
+        void seed(uint64_t seedval) {
+            uint64_t x = seedval;
+            for(uint64_t &s: state) {
+                // SplitMix64
+                uint64_t z = (x += 0x9e3779b97f4a7c15);
+                z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9;
+                z = (z ^ (z >> 27)) * 0x94d049bb133111eb;
+                s = z ^ (z >> 31);
+            }
+        }
+
+        uint64_t next() {
+            uint64_t result = rotl(state[1] * 5, 7) * 9;
+            uint64_t t = state[1] << 17;
+            state[2] ^= state[0];
+            state[3] ^= state[1];
+            state[1] ^= state[2];
+            state[0] ^= state[3];
+            state[2] ^= t;
+            state[3] = rotl(state[3], 45);
+            return result;
+        }
+
+        // Uniform in [0, n), rejecting draws at or above the largest multiple of n.
+        uint64_t below(uint64_t n) {
+            uint64_t limit = UINT64_MAX - UINT64_MAX % n;
+            uint64_t x;
+            do {
+                x = next();
+            } while(x >= limit);
+            return x % n;
         }
 
         template<typename T>
             size_t index(std::vector<T> &v, size_t begin = 0) {
-            std::uniform_int_distribution<int> dist(begin, v.size() - 1);
-            return dist(engine);
+            return begin + below(v.size() - begin);
         }
 
         template<typename T>
@@ -36,15 +81,14 @@ namespace NEAT {
             return v[index(v, begin)];
         }
 
+        // value in [low, hi]
         int integer(int low = INT_MIN, int hi = INT_MAX) {
-            std::uniform_int_distribution<int> dist(low, hi);
-            return dist(engine);
+            return int(int64_t(low) + int64_t(below(uint64_t(int64_t(hi) - low) + 1)));
         }
 
-        // value in [0,1] from uniform distribution
+        // value in [0,1) from uniform distribution: the top 53 bits of next()
         real_t prob() {
-            std::uniform_real_distribution<real_t> dist(0, 1);
-            return dist(engine);
+            return (next() >> 11) * (1.0 / 9007199254740992.0);
         }
 
         bool under(real_t prob) {
@@ -70,21 +114,20 @@ namespace NEAT {
             return prob_switch_t(prob());
         }
 
-        // -1 or 1
+        // -1 or 1, from the top bit of next()
         int posneg() {
-            std::uniform_int_distribution<int> dist(0, 1);
-            return dist(engine) * 2 - 1;
+            return (next() >> 63) ? 1 : -1;
         }
 
         bool boolean() {
-            std::uniform_int_distribution<int> dist(0, 1);
-            return dist(engine) == 1;
+            return (next() >> 63) == 1;
         }
 
-        // value from z distribution
+        // value from z distribution, by Box-Muller from two prob()
         real_t gauss() {
-            std::normal_distribution<real_t> dist;
-            return dist(engine);
+            real_t u1 = 1.0 - prob();
+            real_t u2 = prob();
+            return sqrt(-2.0 * log(u1)) * cos(2.0 * M_PI * u2);
         }
 
         static void test();
@@ -37,6 +37,18 @@ namespace NEAT {
         return maxval;
     }
 
+    template<typename T>
+    double median(std::vector<T> vec) {
+        if(vec.size() == 0) return NAN;
+        std::sort(vec.begin(), vec.end());
+        size_t mid = vec.size() / 2;
+        if(vec.size() % 2) {
+            return vec[mid];
+        } else {
+            return (double(vec[mid - 1]) + double(vec[mid])) / 2;
+        }
+    }
+
     struct stats_t {
         size_t n;
         double min;
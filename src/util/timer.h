@@ -21,6 +21,8 @@ namespace NEAT {
         void start();
         void stop();
 
+        double get_recent() const { return _recent; }
+
         static void report();
     };
 }